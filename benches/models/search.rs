@@ -0,0 +1,36 @@
+extern crate amp;
+#[macro_use]
+extern crate criterion;
+extern crate scribe;
+
+#[path = "../support/mod.rs"]
+mod support;
+
+use amp::bench::{LineIndex, SearchMode};
+use criterion::Criterion;
+use scribe::Buffer;
+
+fn large_buffer_search(c: &mut Criterion) {
+    let mut buffer = Buffer::new();
+    buffer.insert(support::source(2_000_000));
+    let mut mode = SearchMode::new(Some(String::from("scrollOffset")));
+    let mut line_index = LineIndex::new();
+
+    c.bench_function("large buffer search", move |b| b.iter(|| {
+        mode.search(&buffer, &mut line_index).unwrap()
+    }));
+}
+
+fn large_buffer_search_without_matches(c: &mut Criterion) {
+    let mut buffer = Buffer::new();
+    buffer.insert(support::source(2_000_000));
+    let mut mode = SearchMode::new(Some(String::from("amp")));
+    let mut line_index = LineIndex::new();
+
+    c.bench_function("large buffer search without matches", move |b| b.iter(|| {
+        mode.search(&buffer, &mut line_index).unwrap()
+    }));
+}
+
+criterion_group!(benches, large_buffer_search, large_buffer_search_without_matches);
+criterion_main!(benches);
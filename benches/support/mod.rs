@@ -0,0 +1,26 @@
+/// Builds at least `size` bytes of Rust-like source code, so that
+/// benchmarks can exercise large buffers without checking them in.
+pub fn source(size: usize) -> String {
+    let lines = [
+        "/// Renders the visible portion of the buffer to the screen.",
+        "pub fn render_buffer(buffer: &Buffer, view: &mut View) -> Result<()> {",
+        "    let scrollOffset = view.get_region(buffer)?.line_offset();",
+        "    for (index, line) in buffer.data().lines().enumerate() {",
+        "        render_line(line, index + scrollOffset, MAX_LINE_LENGTH)?;",
+        "    }",
+        "",
+        "    Ok(())",
+        "}",
+        "",
+    ];
+    let mut data = String::with_capacity(size);
+
+    while data.len() < size {
+        for line in lines.iter() {
+            data.push_str(line);
+            data.push('\n');
+        }
+    }
+
+    data
+}
@@ -0,0 +1,29 @@
+extern crate amp;
+#[macro_use]
+extern crate criterion;
+
+#[path = "../support/mod.rs"]
+mod support;
+
+use amp::bench::movement_lexer;
+use criterion::Criterion;
+
+fn line_lexing(c: &mut Criterion) {
+    let data = support::source(1_000);
+    let line = data.lines().nth(4).unwrap().to_string();
+
+    c.bench_function("movement lexer line lexing", move |b| b.iter(|| {
+        movement_lexer::lex(&line)
+    }));
+}
+
+fn large_buffer_lexing(c: &mut Criterion) {
+    let data = support::source(2_000_000);
+
+    c.bench_function("movement lexer large buffer lexing", move |b| b.iter(|| {
+        movement_lexer::lex(&data)
+    }));
+}
+
+criterion_group!(benches, line_lexing, large_buffer_lexing);
+criterion_main!(benches);
@@ -1,9 +1,16 @@
 extern crate amp;
 #[macro_use]
 extern crate criterion;
+extern crate scribe;
+
+#[path = "../support/mod.rs"]
+mod support;
 
 use amp::Application;
+use amp::bench::JumpMode;
 use criterion::Criterion;
+use scribe::Buffer;
+use scribe::buffer::Position;
 use std::path::PathBuf;
 
 fn buffer_rendering(c: &mut Criterion) {
@@ -50,5 +57,59 @@ fn scrolled_buffer_rendering(c: &mut Criterion) {
     }));
 }
 
-criterion_group!(benches, buffer_rendering, scrolled_buffer_rendering);
+fn large_buffer_rendering(c: &mut Criterion) {
+    let mut app = Application::new(&Vec::new()).unwrap();
+    let mut buffer = Buffer::new();
+    buffer.path = Some(PathBuf::from("large_buffer.rs"));
+    buffer.insert(support::source(5_000_000));
+    app.workspace.add_buffer(buffer);
+    app.view.initialize_buffer(app.workspace.current_buffer().unwrap()).unwrap();
+    let buffer_data = app.workspace.current_buffer().unwrap().data();
+
+    // Scroll to the middle of the buffer.
+    let middle = app.workspace.current_buffer().unwrap().line_count() / 2;
+    app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: middle, offset: 0 });
+    app.view.scroll_to_cursor(app.workspace.current_buffer().unwrap()).unwrap();
+
+    c.bench_function("large buffer rendering", move |b| b.iter(|| {
+        let mut presenter = app.view.build_presenter().unwrap();
+
+        presenter.print_buffer(
+            app.workspace.current_buffer().unwrap(),
+            &buffer_data,
+            None,
+            None
+        ).unwrap()
+    }));
+}
+
+fn jump_mode_rendering(c: &mut Criterion) {
+    let mut app = Application::new(&Vec::new()).unwrap();
+    app.workspace.open_buffer(
+        &PathBuf::from("src/commands/buffer.rs")
+    ).unwrap();
+    app.view.initialize_buffer(app.workspace.current_buffer().unwrap()).unwrap();
+    let buffer_data = app.workspace.current_buffer().unwrap().data();
+    let mut jump_mode = JumpMode::new(0);
+
+    c.bench_function("jump mode rendering", move |b| b.iter(|| {
+        let mut presenter = app.view.build_presenter().unwrap();
+        jump_mode.reset_display();
+
+        presenter.print_buffer(
+            app.workspace.current_buffer().unwrap(),
+            &buffer_data,
+            None,
+            Some(&mut jump_mode)
+        ).unwrap()
+    }));
+}
+
+criterion_group!(
+    benches,
+    buffer_rendering,
+    scrolled_buffer_rendering,
+    large_buffer_rendering,
+    jump_mode_rendering
+);
 criterion_main!(benches);
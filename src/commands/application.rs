@@ -1,24 +1,67 @@
 use crate::errors::*;
 use crate::commands::{self, Result};
-use crate::input::KeyMap;
+use crate::input::{Key, KeyMap};
+use crate::log;
 use scribe::Buffer;
+use scribe::buffer::Range;
+use std::cmp;
 use std::mem;
-use crate::models::application::{Application, Mode};
+use crate::models::application::{local_history, Application, Mode};
 use crate::models::application::modes::*;
 use crate::util;
+use crate::util::{completion, tags, task};
+
+// Upper bound for count prefixes, so that a stray key press can't
+// tie up the editor repeating a command an unreasonable number of times.
+const MAX_COUNT: usize = 10_000;
 
 pub fn handle_input(app: &mut Application) -> Result {
     // Listen for and respond to user input.
+    let mode = app.mode_str();
     let commands = app.view.last_key().as_ref().and_then(|key| {
-        app.mode_str().and_then(|mode| {
+        mode.and_then(|mode| {
             app.preferences.borrow().keymap().commands_for(&mode, &key)
         })
     });
 
+    // Digits without bindings of their own (and zeros that follow them)
+    // build up a count, used to repeat the next key's commands.
+    let digit = match *app.view.last_key() {
+        Some(Key::Char(character)) => character.to_digit(10),
+        _ => None,
+    };
+    if let Some(digit) = digit {
+        let counting = match app.pending_count {
+            Some(_) => true,
+            None => digit > 0 && commands.is_none(),
+        };
+
+        if counting {
+            let count = cmp::min(
+                app.pending_count.unwrap_or(0) * 10 + digit as usize,
+                MAX_COUNT
+            );
+            app.pending_count = Some(count);
+            app.view.pending_input = Some(count.to_string());
+
+            return Ok(());
+        }
+    }
+    let count = app.pending_count.take().unwrap_or(1);
+    app.view.pending_input = None;
+    app.view.record_frame_phase("input");
+
     if let Some(coms) = commands {
         // Run all commands, stopping at the first error encountered, if any.
-        for com in coms {
-            com(app)?;
+        // Counts repeat the whole set, for as long as we're in the same mode.
+        for _ in 0..count {
+            for com in coms.iter() {
+                com(app)?;
+            }
+
+            if app.mode_str() != mode {
+                break;
+            }
         }
     }
 
@@ -33,6 +76,8 @@ pub fn switch_to_normal_mode(app: &mut Application) -> Result {
 }
 
 pub fn switch_to_insert_mode(app: &mut Application) -> Result {
+    app.ensure_writable()?;
+
     if app.workspace.current_buffer().is_some() {
         commands::buffer::start_command_group(app)?;
         app.mode = Mode::Insert;
@@ -44,12 +89,35 @@ pub fn switch_to_insert_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Starts entering a character by its codepoint (e.g. "u00e9").
+pub fn switch_to_codepoint_input_mode(app: &mut Application) -> Result {
+    switch_to_character_input_mode(app, CharacterInputKind::Codepoint)
+}
+
+/// Starts entering a character using a digraph (e.g. "e'").
+pub fn switch_to_digraph_input_mode(app: &mut Application) -> Result {
+    switch_to_character_input_mode(app, CharacterInputKind::Digraph)
+}
+
+fn switch_to_character_input_mode(app: &mut Application, kind: CharacterInputKind) -> Result {
+    if app.workspace.current_buffer().is_none() {
+        bail!(BUFFER_MISSING);
+    }
+    app.ensure_writable()?;
+
+    let mode = CharacterInputMode::new(kind);
+    app.view.pending_input = Some(mode.status());
+    app.mode = Mode::CharacterInput(mode);
+
+    Ok(())
+}
+
 pub fn switch_to_jump_mode(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
     // Initialize a new jump mode and swap
     // it with the current application mode.
-    let jump_mode = Mode::Jump(JumpMode::new(buffer.cursor.line));
+    let jump_mode = Mode::Jump(JumpMode::with_lex_cache(buffer.cursor.line, app.lex_cache.clone()));
     let old_mode = mem::replace(&mut app.mode, jump_mode);
 
     // If we were previously in a select mode, store it
@@ -95,9 +163,24 @@ pub fn switch_to_line_jump_mode(app: &mut Application) -> Result {
 }
 
 pub fn switch_to_open_mode(app: &mut Application) -> Result {
-    let exclusions = app.preferences.borrow().open_mode_exclusions()?;
     let config = app.preferences.borrow().search_select_config();
-    app.mode = Mode::Open(OpenMode::new(app.workspace.path.clone(), exclusions, app.event_channel.clone(), config));
+    if let Some(index) = app.open_mode_index.get() {
+        app.mode = Mode::Open(OpenMode::with_index(index, config));
+    } else {
+        let exclusions = util::workspace_exclusions(app)?;
+        app.mode = Mode::Open(OpenMode::new(app.workspace.path.clone(), exclusions, app.event_channel.clone(), config));
+    }
+
+    // Favour recently opened files when ranking results.
+    let recent_paths = app.recent_files
+        .paths()
+        .iter()
+        .filter_map(|path| path.strip_prefix(&app.workspace.path).ok())
+        .map(|path| path.to_path_buf())
+        .collect();
+    if let Mode::Open(ref mut mode) = app.mode {
+        mode.set_recent_paths(recent_paths);
+    }
     commands::search_select::search(app)?;
 
     Ok(())
@@ -111,6 +194,22 @@ pub fn switch_to_command_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn switch_to_command_line_mode(app: &mut Application) -> Result {
+    let mut mode = CommandLineMode::new();
+
+    // Carry the selection over, so that commands can be scoped to it.
+    if let Some(buffer) = app.workspace.current_buffer() {
+        mode.selection = match app.mode {
+            Mode::Select(ref select_mode) => Some(Range::new(*buffer.cursor, select_mode.anchor)),
+            Mode::SelectLine(ref select_line_mode) => Some(select_line_mode.to_range(&*buffer.cursor)),
+            _ => None,
+        };
+    }
+    app.mode = Mode::CommandLine(mode);
+
+    Ok(())
+}
+
 pub fn switch_to_symbol_jump_mode(app: &mut Application) -> Result {
     if let Some(buf) = app.workspace.current_buffer() {
         let token_set = buf.tokens()
@@ -126,17 +225,113 @@ pub fn switch_to_symbol_jump_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Lists every definition in the workspace's tags file, across all of its files.
+pub fn switch_to_tag_mode(app: &mut Application) -> Result {
+    let tags_path = app.workspace.path.join(tags::FILE_NAME);
+    if !tags_path.exists() {
+        bail!(TAGS_FILE_MISSING);
+    }
+    let definitions = tags::all(&tags_path)?;
+    let config = app.preferences.borrow().search_select_config();
+
+    app.mode = Mode::Tag(TagMode::new(definitions, config));
+    commands::search_select::search(app)
+}
+
+/// Lists the copies of the current buffer's file kept in its local history.
+pub fn switch_to_local_history_mode(app: &mut Application) -> Result {
+    let path = app.workspace
+        .current_buffer()
+        .ok_or(BUFFER_MISSING)?
+        .path
+        .clone()
+        .ok_or(BUFFER_PATH_MISSING)?;
+    let snapshots = local_history::list(&path, &local_history::directory()?);
+    if snapshots.is_empty() {
+        bail!("There are no saved versions of this file in its local history");
+    }
+    let config = app.preferences.borrow().search_select_config();
+
+    app.mode = Mode::LocalHistory(LocalHistoryMode::new(snapshots, config));
+    commands::search_select::search(app)
+}
+
+/// Lists the TODO, FIXME, and HACK comments in the workspace, which
+/// are found in the background, using the content of any open buffers.
+pub fn switch_to_task_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+    let exclusions = util::workspace_exclusions(app)?;
+    let buffers = app.buffer_contents();
+    task::spawn_scan(app.workspace.path.clone(), exclusions, buffers, app.event_channel.clone());
+
+    app.mode = Mode::Task(TaskMode::new(config));
+    commands::search_select::search(app)
+}
+
+pub fn switch_to_recent_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+
+    // Show paths within the workspace relative to it, to keep them short.
+    let paths = app.recent_files
+        .paths()
+        .iter()
+        .map(|path| {
+            path.strip_prefix(&app.workspace.path)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    app.mode = Mode::Recent(recent::new(paths, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+/// Lists the other open workspaces (see the "workspace" command), to switch to one.
+pub fn switch_to_workspace_mode(app: &mut Application) -> Result {
+    let paths: Vec<String> = app
+        .background_workspace_paths()
+        .iter()
+        .map(|path| completion::collapse_home(path))
+        .collect();
+    if paths.is_empty() {
+        bail!("No other workspaces are open; use \"workspace path\" to open one");
+    }
+
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Workspace(workspace::new(paths, config));
+    commands::search_select::search(app)
+}
+
 pub fn switch_to_theme_mode(app: &mut Application) -> Result {
+    // Pick up themes that have been added or changed since startup.
+    app.view.reload_themes()?;
+
     let config = app.preferences.borrow().search_select_config();
+    let original_theme = app.preferences.borrow().theme().to_string();
     app.mode = Mode::Theme(
         ThemeMode::new(
-            app.view.theme_set.themes.keys().map(|k| k.to_string()).collect(),
+            app.view.theme_set()?.themes.keys().map(|k| k.to_string()).collect(),
+            original_theme,
             config
         ),
     );
     commands::search_select::search(app)?;
 
-    Ok(())
+    // Themes that couldn't be loaded are left out of the picker; the last
+    // problem is shown once it's open, and the rest are in the message log.
+    let mut theme_errors = app.view.take_theme_errors();
+    match theme_errors.pop() {
+        Some(error) => {
+            for theme_error in &theme_errors {
+                app.messages.record_error(theme_error);
+            }
+
+            Err(error)
+        },
+        None => Ok(()),
+    }
 }
 
 pub fn switch_to_select_mode(app: &mut Application) -> Result {
@@ -149,6 +344,13 @@ pub fn switch_to_select_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn switch_to_select_block_mode(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.mode = Mode::SelectBlock(SelectBlockMode::new(*buffer.cursor));
+
+    Ok(())
+}
+
 pub fn switch_to_select_line_mode(app: &mut Application) -> Result {
     if let Some(buffer) = app.workspace.current_buffer() {
         app.mode = Mode::SelectLine(SelectLineMode::new(buffer.cursor.line));
@@ -161,9 +363,12 @@ pub fn switch_to_select_line_mode(app: &mut Application) -> Result {
 
 pub fn switch_to_search_mode(app: &mut Application) -> Result {
     if app.workspace.current_buffer().is_some() {
-        app.mode = Mode::Search(
-            SearchMode::new(app.search_query.clone())
+        let mut mode = SearchMode::with_history(
+            app.search_query.clone(),
+            app.search_history.entries().to_vec()
         );
+        mode.options = app.search_options;
+        app.mode = Mode::Search(mode);
     } else {
         bail!(BUFFER_MISSING);
     }
@@ -246,6 +451,38 @@ pub fn display_last_error(app: &mut Application) -> Result {
     util::add_buffer(scope_display_buffer, app)
 }
 
+/// Opens a buffer listing previously reported messages and errors, newest first.
+pub fn display_message_history(app: &mut Application) -> Result {
+    if app.messages.entries().is_empty() {
+        bail!("No messages have been reported");
+    }
+
+    let mut history_buffer = Buffer::new();
+    for entry in app.messages.entries().iter().rev() {
+        history_buffer.insert(format!("[{}] {}\n", entry.severity, entry.content));
+    }
+    util::add_buffer(history_buffer, app)
+}
+
+/// Opens the diagnostic log, to look into problems that weren't reported on-screen.
+pub fn display_log(app: &mut Application) -> Result {
+    let path = log::path()?;
+    if !path.exists() {
+        bail!("Nothing has been logged yet");
+    }
+
+    let buffer = Buffer::from_file(&path).chain_err(|| "Couldn't open the log")?;
+    util::add_buffer(buffer, app)
+}
+
+/// Shows (or hides) a breakdown of how long the last frame took
+/// to handle input, run commands, highlight, and render.
+pub fn toggle_frame_timings(app: &mut Application) -> Result {
+    app.view.toggle_frame_timings();
+
+    Ok(())
+}
+
 pub fn suspend(app: &mut Application) -> Result {
     app.view.suspend();
 
@@ -258,13 +495,103 @@ pub fn exit(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Exits, writing the current buffer's content to standard output, so that
+/// piped input can be edited on its way through (e.g. `cmd | amp - | cmd`).
+pub fn exit_with_buffer(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.output = Some(buffer.data());
+    app.mode = Mode::Exit;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::input::Key;
     use crate::models::Application;
-    use crate::models::application::Mode;
+    use crate::models::application::{Mode, Severity};
+    use crate::models::application::modes::SearchSelectMode;
     use scribe::Buffer;
     use std::path::PathBuf;
 
+    #[test]
+    fn handle_input_repeats_commands_using_count_prefixes() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("\n\n\n\n\n\n\n\n\n\n\n\n");
+        app.workspace.add_buffer(buffer);
+
+        for key in &[Key::Char('1'), Key::Char('0'), Key::Char('j')] {
+            app.view.last_key = Some(key.clone());
+            super::handle_input(&mut app).unwrap();
+        }
+
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 10);
+        assert!(app.pending_count.is_none());
+    }
+
+    #[test]
+    fn handle_input_tracks_pending_counts_for_the_status_line() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+
+        for key in &[Key::Char('1'), Key::Char('2')] {
+            app.view.last_key = Some(key.clone());
+            super::handle_input(&mut app).unwrap();
+        }
+        assert_eq!(app.view.pending_input, Some(String::from("12")));
+        assert!(app.message.is_none());
+
+        app.view.last_key = Some(Key::Char('j'));
+        super::handle_input(&mut app).unwrap();
+        assert!(app.view.pending_input.is_none());
+    }
+
+    #[test]
+    fn display_message_history_lists_messages_newest_first() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.messages.record(Severity::Info, "Created src");
+        app.messages.record(Severity::Error, "Unable to save buffer");
+        super::display_message_history(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "[error] Unable to save buffer\n[info] Created src\n"
+        );
+    }
+
+    #[test]
+    fn toggle_frame_timings_summarizes_the_previous_frame() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        super::toggle_frame_timings(&mut app).unwrap();
+
+        app.view.start_frame();
+        app.view.last_key = Some(Key::Char('j'));
+        super::handle_input(&mut app).unwrap();
+        app.view.finish_frame();
+
+        let summary = app.view.last_frame_timings.clone().unwrap();
+        assert!(summary.starts_with("input "));
+        assert!(summary.contains(" render "));
+
+        super::toggle_frame_timings(&mut app).unwrap();
+        app.view.finish_frame();
+        assert!(app.view.last_frame_timings.is_none());
+    }
+
+    #[test]
+    fn handle_input_runs_commands_bound_to_zero_without_a_count() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.view.last_key = Some(Key::Char('0'));
+        super::handle_input(&mut app).unwrap();
+
+        assert!(app.pending_count.is_none());
+        match app.mode {
+            Mode::Command(_) => (),
+            _ => panic!("Not in command mode"),
+        }
+    }
+
     #[test]
     fn display_available_commands_creates_a_new_buffer() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -353,4 +680,30 @@ mod tests {
 
         assert!(super::switch_to_path_mode(&mut app).is_err());
     }
+
+    #[test]
+    fn switch_to_recent_mode_lists_workspace_paths_relative_to_it() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let path = app.workspace.path.join("Cargo.toml");
+        app.recent_files.record(path);
+
+        super::switch_to_recent_mode(&mut app).unwrap();
+
+        if let Mode::Recent(ref mode) = app.mode {
+            assert_eq!(mode.results().cloned().collect::<Vec<String>>(), vec!["Cargo.toml"]);
+        } else {
+            panic!("Not in recent mode");
+        }
+    }
+
+    #[test]
+    fn switch_to_workspace_mode_raises_error_if_no_other_workspaces_are_open() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+
+        assert!(super::switch_to_workspace_mode(&mut app).is_err());
+        assert!(match app.mode {
+            Mode::Normal => true,
+            _ => false,
+        });
+    }
 }
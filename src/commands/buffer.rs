@@ -1,14 +1,29 @@
+use crate::errors;
 use crate::errors::*;
 use crate::commands::{self, Result};
+use std::cmp;
+use std::io;
 use std::mem;
 use crate::input::Key;
 use crate::util;
+use crate::util::abbreviation::{self, Expansion};
+use crate::util::elevated::Elevation;
+use crate::util::encryption;
+use crate::util::indentation::{self, Indentation};
+use crate::util::lint::Diagnostic;
+use crate::util::markdown;
+use crate::util::movement_lexer;
+use crate::util::remote;
+use crate::util::snapshot;
 use crate::util::token::{Direction, adjacent_token_position};
-use crate::models::application::{Application, ClipboardContent, Mode};
-use crate::models::application::modes::ConfirmMode;
-use scribe::buffer::{Buffer, Position, Range};
+use crate::models::application::{local_history, run_hook, Application, ClipboardContent, Hook, Mode, Paste};
+use crate::models::application::modes::{ConfirmMode, PathAction};
+use luthor::token::Category;
+use scribe::buffer::{Buffer, Distance, LineRange, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn save(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     remove_trailing_whitespace(app)?;
     ensure_trailing_newline(app)?;
 
@@ -16,18 +31,92 @@ pub fn save(app: &mut Application) -> Result {
     // borrowing the buffer for the full scope of this save command. That will
     // allow us to hand the application object to the switch_to_path_mode
     // command, if necessary.
-    let path_set = app
+    let (path_set, new_file) = app
         .workspace
         .current_buffer()
         .ok_or(BUFFER_MISSING)?
-        .path.is_some();
+        .path.as_ref()
+        .map(|path| (true, !path.exists()))
+        .unwrap_or((false, false));
 
     if path_set {
-        app.workspace
-            .current_buffer()
-            .ok_or(BUFFER_MISSING)?
-            .save()
-            .chain_err(|| "Unable to save buffer")
+        // Open mode won't know about the file unless it's re-indexed.
+        if new_file {
+            app.open_mode_index.invalidate();
+        }
+
+        run_hook(app, Hook::BeforeSave)?;
+
+        // Decrypted content is re-encrypted on its way to disk, which may
+        // require prompting for a passphrase; the rest of this is skipped.
+        if app.workspace.current_buffer().map(|buffer| encryption::is_encrypted(buffer)).unwrap_or(false) {
+            return util::save_encrypted_buffer(app);
+        }
+
+        // Remote files are written back over SSH, rather than to disk.
+        if app.workspace.current_buffer().map(|buffer| remote::is_remote(buffer)).unwrap_or(false) {
+            return util::save_remote_buffer(app);
+        }
+
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        if let Err(error) = buffer.save() {
+            // Rather than losing the changes, offer to write
+            // files the user doesn't have access to using sudo.
+            if error.kind() == io::ErrorKind::PermissionDenied && Elevation::detect().is_some() {
+                app.mode = Mode::Confirm(ConfirmMode::with_prompt(
+                    save_with_elevated_privileges,
+                    "Permission denied; save with elevated privileges?",
+                ));
+
+                return Ok(());
+            }
+
+            return Err(error).chain_err(|| "Unable to save buffer");
+        }
+
+        // Keep a copy of what was saved, as a safety net for files outside
+        // of version control. Failing to do so doesn't undo the save.
+        if app.preferences.borrow().local_history() {
+            if let Some(ref path) = buffer.path {
+                let recorded = local_history::directory()
+                    .and_then(|directory| local_history::record(path, &buffer.data(), &directory));
+                if let Err(error) = recorded {
+                    log_warn!("Couldn't add {} to local history: {}", path.to_string_lossy(), error);
+                }
+            }
+        }
+
+        // Apply changes to preference files right away.
+        let preferences = &app.preferences;
+        let preferences_file = buffer.path
+            .as_ref()
+            .map(|path| preferences.borrow().is_preferences_file(path))
+            .unwrap_or(false);
+        let mut preferences_warning = None;
+        if preferences_file {
+            preferences.borrow_mut().reload()?;
+
+            // Typos in preference names aren't errors as far as loading goes,
+            // but they're worth pointing out alongside the lines they're on,
+            // as they'd be ignored otherwise.
+            let unknown_keys = preferences.borrow().unknown_keys();
+            if let Some(ref path) = buffer.path {
+                if unknown_keys.is_empty() {
+                    app.view.diagnostics.remove(path);
+                } else {
+                    let diagnostics = unknown_preference_diagnostics(&buffer.data(), &unknown_keys);
+                    app.view.diagnostics.insert(path.clone(), diagnostics);
+                    preferences_warning = Some(format!("Saved, but found {} unknown preference(s)", unknown_keys.len()));
+                }
+            }
+        }
+
+        app.message = preferences_warning.or_else(|| buffer.path.as_ref().map(|path| {
+            format!("Wrote {} lines to {}", buffer.data().lines().count(), path.to_string_lossy())
+        }));
+
+        start_linter(app)?;
+        run_hook(app, Hook::AfterSave)
     } else {
         commands::application::switch_to_path_mode(app)?;
         if let Mode::Path(ref mut mode) = app.mode {
@@ -38,13 +127,141 @@ pub fn save(app: &mut Application) -> Result {
     }
 }
 
+// Run once writing the buffer with elevated privileges has been confirmed.
+fn save_with_elevated_privileges(app: &mut Application) -> Result {
+    util::save_elevated_buffer(app)
+}
+
+/// Prompts for a path, writing the buffer to it and leaving
+/// the buffer pointed at the new path (and its original file intact).
+pub fn save_as(app: &mut Application) -> Result {
+    commands::application::switch_to_path_mode(app)?;
+    if let Mode::Path(ref mut mode) = app.mode {
+        mode.save_on_accept = true;
+    }
+
+    Ok(())
+}
+
+/// Checks the buffer's file using the linter configured for its type.
+/// Findings are shown alongside the lines they refer to once it's finished.
+pub fn lint(app: &mut Application) -> Result {
+    if !start_linter(app)? {
+        bail!("No linter is configured for this file type");
+    }
+
+    Ok(())
+}
+
+// Runs the buffer type's linter in the background, if it has one.
+fn start_linter(app: &mut Application) -> errors::Result<bool> {
+    let path = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?
+        .path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    let linter = app.preferences.borrow().linter(Some(&path))?;
+
+    match linter {
+        Some(linter) => {
+            linter.spawn(path, app.event_channel.clone());
+            Ok(true)
+        },
+        None => Ok(false),
+    }
+}
+
+/// Prompts for a path, moving the buffer's file there.
+pub fn rename(app: &mut Application) -> Result {
+    let path = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?
+        .path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    if !path.exists() {
+        bail!("The buffer hasn't been saved yet; save it instead");
+    }
+
+    commands::application::switch_to_path_mode(app)?;
+    if let Mode::Path(ref mut mode) = app.mode {
+        mode.action = PathAction::Rename;
+    }
+
+    Ok(())
+}
+
+/// Allows changes to a buffer that was opened read-only.
+pub fn make_writable(app: &mut Application) -> Result {
+    let id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id;
+    if !id.map(|id| app.read_only_buffers.remove(&id)).unwrap_or(false) {
+        bail!("The buffer is already writable");
+    }
+    app.message = Some(String::from("The buffer is now writable"));
+
+    Ok(())
+}
+
+/// Prevents changes to the current buffer until it's made writable again.
+pub fn make_read_only(app: &mut Application) -> Result {
+    let id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id;
+    app.read_only_buffers.extend(id);
+
+    Ok(())
+}
+
+/// Turns the features skipped for large files (e.g. highlighting
+/// content that's been scrolled past) back on for the current buffer.
+pub fn enable_full_features(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.enable_full_features(buffer)?;
+    app.message = Some(String::from("All features enabled for this buffer"));
+
+    Ok(())
+}
+
+/// Bound to keys that would otherwise change a read-only buffer.
+pub fn reject_read_only_edit(_: &mut Application) -> Result {
+    bail!(BUFFER_READ_ONLY)
+}
+
 pub fn reload(app: &mut Application) -> Result {
+    if app.workspace.current_buffer().map(|buffer| encryption::is_encrypted(buffer)).unwrap_or(false) {
+        bail!("Encrypted buffers can't be reloaded; close and reopen them instead");
+    }
+    if app.workspace.current_buffer().map(|buffer| remote::is_remote(buffer)).unwrap_or(false) {
+        bail!("Remote buffers can't be reloaded; close and reopen them instead");
+    }
+
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.reload().chain_err(|| {
         "Unable to reload buffer."
     })
 }
 
+/// Keeps the buffer up to date with content appended to its file (e.g. a
+/// log), like `tail -f`. Running it again stops following the file.
+pub fn toggle_follow(app: &mut Application) -> Result {
+    let path = app.workspace.current_buffer_path().ok_or(BUFFER_PATH_MISSING)?.to_path_buf();
+    if app.unfollow_file(&path) {
+        app.message = Some(format!("Stopped following {}", path.to_string_lossy()));
+        return Ok(());
+    }
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    if encryption::is_encrypted(buffer) || remote::is_remote(buffer) {
+        bail!("Only local, unencrypted files can be followed");
+    }
+    if buffer.modified() {
+        bail!("Save or reload the buffer before following its file");
+    }
+
+    // Start from the file's current content, and at its end. Anything appended
+    // after it's been read is picked up, based on the length of what was read.
+    buffer.reload().chain_err(|| "Unable to reload buffer.")?;
+    let length = buffer.data().len() as u64;
+    buffer.cursor.move_to_last_line();
+    app.view.scroll_to_cursor(buffer)?;
+    app.follow_file(&path, length)?;
+    app.message = Some(format!("Following {}", path.to_string_lossy()));
+
+    Ok(())
+}
+
 pub fn delete(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.delete();
     commands::view::scroll_to_cursor(app)?;
 
@@ -52,10 +269,12 @@ pub fn delete(app: &mut Application) -> Result {
 }
 
 pub fn delete_token(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     let mut subsequent_token_on_line = false;
 
     if let Some(buffer) = app.workspace.current_buffer() {
-        if let Some(position) = adjacent_token_position(buffer, false, Direction::Forward) {
+        let line_index = app.view.line_index(buffer);
+        if let Some(position) = adjacent_token_position(buffer, &mut app.lex_cache.borrow_mut(), &mut line_index.borrow_mut(), false, Direction::Forward) {
             if position.line == buffer.cursor.line {
                 subsequent_token_on_line = true;
             }
@@ -96,6 +315,7 @@ pub fn copy_current_line(app: &mut Application) -> Result {
 }
 
 pub fn merge_next_line(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let current_line = buffer.cursor.line;
     let data = buffer.data();
@@ -129,7 +349,7 @@ pub fn merge_next_line(app: &mut Application) -> Result {
     buffer.start_operation_group();
     let target_position = Position {
         line: current_line,
-        offset: data.lines().nth(current_line).unwrap().len(),
+        offset: data.lines().nth(current_line).unwrap().graphemes(true).count(),
     };
     buffer.delete_range(Range::new(Position {
                                        line: current_line,
@@ -166,10 +386,23 @@ pub fn close(app: &mut Application) -> Result {
         };
 
     if unmodified || empty || confirm_mode {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+        // Remember where we were, so that reopening the file restores it.
+        // That's a convenience; failing to do so doesn't prevent closing.
+        let mut recorded = app.file_states.record(buffer, &mut app.view);
+        if recorded.is_ok() {
+            recorded = app.file_states.save();
+        }
+        if let Err(error) = recorded {
+            log_warn!("Couldn't save the closed file's state: {}", error);
+        }
+
         // Clean up view-related data for the buffer.
-        app.view.forget_buffer(
-            app.workspace.current_buffer().ok_or(BUFFER_MISSING)?
-        )?;
+        app.view.forget_buffer(buffer)?;
+        if let Some(id) = buffer.id {
+            app.snapshots.forget(id);
+        }
         app.workspace.close_current_buffer();
     } else {
         // Display a confirmation prompt before closing a modified buffer.
@@ -207,7 +440,13 @@ pub fn close_others(app: &mut Application) -> Result {
             } else if buf.modified() && !buf.data().is_empty() {
                 modified_buffer = true;
             } else {
+                if let Err(error) = app.file_states.record(buf, &mut app.view) {
+                    log_warn!("Couldn't record the closed file's state: {}", error);
+                }
                 app.view.forget_buffer(buf)?;
+                if let Some(id) = buf.id {
+                    app.snapshots.forget(id);
+                }
             }
         }
 
@@ -223,12 +462,22 @@ pub fn close_others(app: &mut Application) -> Result {
         app.workspace.close_current_buffer();
     }
 
+    if let Err(error) = app.file_states.save() {
+        log_warn!("Couldn't save the closed files' states: {}", error);
+    }
+
     Ok(())
 }
 
 pub fn close_others_confirm(app: &mut Application) -> Result {
     if let Some(buf) = app.workspace.current_buffer() {
+        if let Err(error) = app.file_states.record(buf, &mut app.view) {
+            log_warn!("Couldn't record the closed file's state: {}", error);
+        }
         app.view.forget_buffer(buf)?;
+        if let Some(id) = buf.id {
+            app.snapshots.forget(id);
+        }
     }
     app.workspace.close_current_buffer();
     commands::application::switch_to_normal_mode(app)?;
@@ -237,6 +486,14 @@ pub fn close_others_confirm(app: &mut Application) -> Result {
 }
 
 pub fn backspace(app: &mut Application) -> Result {
+    app.ensure_writable()?;
+    // Backspacing right after an abbreviation's expanded reverts it.
+    if let Some(expansion) = app.last_expansion.take() {
+        if revert_expansion(app, expansion)? {
+            return commands::view::scroll_to_cursor(app);
+        }
+    }
+
     let mut outdent = false;
 
     if let Some(buffer) = app.workspace.current_buffer() {
@@ -268,22 +525,75 @@ pub fn backspace(app: &mut Application) -> Result {
 }
 
 pub fn insert_char(app: &mut Application) -> Result {
-    if let Some(buffer) = app.workspace.current_buffer() {
-        if let Some(Key::Char(character)) = *app.view.last_key() {
-            // TODO: Drop explicit call to to_string().
-            buffer.insert(character.to_string());
-            buffer.cursor.move_right();
-        } else {
-            bail!("No character to insert");
-        }
-    } else {
-        bail!(BUFFER_MISSING);
+    app.ensure_writable()?;
+    app.last_expansion = None;
+    let character = match *app.view.last_key() {
+        Some(Key::Char(character)) => character.to_string(),
+        _ => bail!("No character to insert"),
+    };
+
+    // Characters that end a word expand the abbreviation before them, if there is one.
+    let trigger = character.chars().all(abbreviation::is_trigger);
+    if !(trigger && expand_abbreviation(app, &character)?) {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        util::insert_at_cursor(buffer, &character);
     }
     commands::view::scroll_to_cursor(app)?;
 
     Ok(())
 }
 
+// Replaces the abbreviation ending at the cursor (if there is one) with its
+// expansion, followed by the text that triggered it, as a single change.
+fn expand_abbreviation(app: &mut Application, trigger: &str) -> errors::Result<bool> {
+    let path = app.workspace.current_buffer().and_then(|buffer| buffer.path.clone());
+    let abbreviations = app.preferences.borrow().abbreviations(path.as_ref());
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let preceding_content = util::content_before_cursor(buffer);
+    let (length, expansion) = match abbreviation::find(&preceding_content, &abbreviations) {
+        Some(abbreviation) => abbreviation,
+        None => return Ok(false),
+    };
+
+    let end = *buffer.cursor;
+    let start = Position{ line: end.line, offset: end.offset - length };
+    let abbreviation_range = Range::new(start, end);
+    let typed = format!("{}{}", buffer.read(&abbreviation_range).unwrap_or_default(), trigger);
+    let expanded = format!("{}{}", expansion, trigger);
+    let expanded_end = start + Distance::of_str(&expanded);
+
+    buffer.start_operation_group();
+    buffer.delete_range(abbreviation_range);
+    buffer.cursor.move_to(start);
+    buffer.insert(expanded.as_str());
+    buffer.cursor.move_to(expanded_end);
+    buffer.end_operation_group();
+
+    app.last_expansion = Some(Expansion{ range: Range::new(start, expanded_end), expanded, typed });
+
+    Ok(true)
+}
+
+// Puts back what was typed in place of the abbreviation that was just
+// expanded, provided that nothing's happened to the expansion since.
+fn revert_expansion(app: &mut Application, expansion: Expansion) -> errors::Result<bool> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let start = expansion.range.start();
+    if *buffer.cursor != expansion.range.end() || buffer.read(&expansion.range) != Some(expansion.expanded) {
+        return Ok(false);
+    }
+
+    let typed_end = start + Distance::of_str(&expansion.typed);
+    buffer.start_operation_group();
+    buffer.delete_range(expansion.range);
+    buffer.cursor.move_to(start);
+    buffer.insert(expansion.typed);
+    buffer.cursor.move_to(typed_end);
+    buffer.end_operation_group();
+
+    Ok(true)
+}
+
 pub fn display_current_scope(app: &mut Application) -> Result {
     let scope_display_buffer = {
         let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
@@ -300,11 +610,149 @@ pub fn display_current_scope(app: &mut Application) -> Result {
     util::add_buffer(scope_display_buffer, app)
 }
 
+/// Reports line, word, character, and byte counts for the current
+/// selection (or the entire buffer), along with the cursor's position.
+pub fn display_statistics(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (label, content) = match app.mode {
+        Mode::Select(ref mode) => {
+            let range = Range::new(*buffer.cursor, mode.anchor);
+            let content = buffer.read(&range).ok_or("Couldn't read selected data from buffer")?;
+
+            ("Selection", content)
+        },
+        Mode::SelectLine(ref mode) => {
+            let range = util::inclusive_range(
+                &LineRange::new(mode.anchor, buffer.cursor.line),
+                buffer
+            );
+            let content = buffer.read(&range).ok_or("Couldn't read selected data from buffer")?;
+
+            ("Selection", content)
+        },
+        _ => ("Buffer", buffer.data()),
+    };
+
+    let line_count = buffer.line_count();
+    let current_line = buffer.cursor.line + 1;
+    app.message = Some(format!(
+        "{}: {} | line {} of {} ({}%)",
+        label,
+        content_statistics(&content),
+        current_line,
+        line_count,
+        current_line * 100 / line_count
+    ));
+
+    Ok(())
+}
+
+/// Reports the codepoints, names, and UTF-8 encoding of the character under
+/// the cursor, which is handy for spotting invisible or look-alike characters.
+pub fn inspect_character(app: &mut Application) -> Result {
+    let description = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let data = buffer.data();
+        let lines: Vec<&str> = data.split('\n').collect();
+        let line = lines.get(buffer.cursor.line).ok_or("No character under the cursor")?;
+        let grapheme = match line.graphemes(true).nth(buffer.cursor.offset) {
+            Some(grapheme) => grapheme,
+            None if buffer.cursor.line + 1 < lines.len() => "\n",
+            None => bail!("No character under the cursor"),
+        };
+
+        util::character::describe(grapheme)
+    };
+    app.message = Some(description);
+
+    Ok(())
+}
+
+// Finds the (zero-based) line on which a preference is defined in a config
+// file. Nested names (e.g. "types.rs.tab_width") are found one level at a time.
+fn line_defining(config: &str, key: &str) -> Option<usize> {
+    let lines: Vec<&str> = config.lines().collect();
+    let mut line = None;
+
+    for name in key.split('.') {
+        let start = line.map(|line| line + 1).unwrap_or(0);
+        let prefix = format!("{}:", name);
+        line = Some(start + lines[start..]
+            .iter()
+            .position(|content| content.trim_start().starts_with(&prefix))?);
+    }
+
+    line
+}
+
+// Flags unknown preferences on the lines defining them, falling
+// back to the first line for any that can't be found.
+fn unknown_preference_diagnostics(config: &str, keys: &[String]) -> Vec<Diagnostic> {
+    keys.iter().map(|key| {
+        let line = line_defining(config, key).unwrap_or(0);
+        let offset = config.lines().nth(line)
+            .map(|content| content.len() - content.trim_start().len())
+            .unwrap_or(0);
+
+        Diagnostic{ line, offset, message: format!("unknown preference: {}", key) }
+    }).collect()
+}
+
+// Words are counted using the movement lexer, so
+// that they agree with word-based cursor movement.
+fn content_statistics(content: &str) -> String {
+    let word_count = movement_lexer::lex(content)
+        .iter()
+        .filter(|token| {
+            token.category == Category::Text &&
+                token.lexeme.chars().any(|c| c.is_alphanumeric())
+        })
+        .count();
+
+    format!(
+        "{} lines, {} words, {} characters, {} bytes",
+        content.lines().count(),
+        word_count,
+        content.chars().count(),
+        content.len()
+    )
+}
+
 /// Inserts a newline character at the current cursor position.
 /// Also performs automatic indentation, basing the indent off
 /// of the previous line's leading whitespace.
 pub fn insert_newline(app: &mut Application) -> Result {
+    app.ensure_writable()?;
+    expand_abbreviation(app, "")?;
+
     if let Some(buffer) = app.workspace.current_buffer() {
+        // In Markdown, new lines continue the list the cursor is in.
+        let list_item = if is_markdown(buffer) {
+            let before_cursor = util::content_before_cursor(buffer);
+            let item = markdown::list_item(&before_cursor);
+
+            // Hitting enter at the end of an empty item ends the list instead.
+            // The rest of the line is only read once the item's known to be empty.
+            if item.as_ref().map(|item| item.empty).unwrap_or(false) {
+                let data = buffer.data();
+                let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+                if line.trim_end() == before_cursor.trim_end() {
+                    let line_number = buffer.cursor.line;
+                    buffer.delete_range(Range::new(
+                        Position{ line: line_number, offset: 0 },
+                        Position{ line: line_number, offset: line.graphemes(true).count() }
+                    ));
+                    buffer.cursor.move_to(Position{ line: line_number, offset: 0 });
+
+                    return Ok(());
+                }
+            }
+
+            item
+        } else {
+            None
+        };
+
         // Insert the newline character.
         buffer.insert("\n");
 
@@ -325,9 +773,10 @@ pub fn insert_newline(app: &mut Application) -> Result {
 
         // Searching backwards, copy the nearest non-blank line's indent content.
         let nearest_non_blank_line = previous_content.lines().rev().find(|line| !line.is_empty());
-        let indent_content = match nearest_non_blank_line {
-            Some(line) => line.chars().take_while(|&c| c.is_whitespace()).collect(),
-            None => String::new(),
+        let indent_content = match (list_item, nearest_non_blank_line) {
+            (Some(item), _) => item.next_prefix,
+            (None, Some(line)) => line.chars().take_while(|&c| c.is_whitespace()).collect(),
+            (None, None) => String::new(),
         };
 
         // Insert and move to the end of the indent content.
@@ -346,6 +795,7 @@ pub fn insert_newline(app: &mut Application) -> Result {
 }
 
 pub fn indent_line(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
 
@@ -391,6 +841,7 @@ pub fn indent_line(app: &mut Application) -> Result {
 }
 
 pub fn outdent_line(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
 
@@ -468,6 +919,114 @@ pub fn outdent_line(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Converts the buffer's indentation from spaces to tabs, or vice versa,
+/// as a single change. Indentation added from then on follows suit.
+pub fn convert_indentation(app: &mut Application) -> Result {
+    app.ensure_writable()?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (target, width) = {
+        let preferences = app.preferences.borrow();
+        let width = preferences.indent_width(buffer.path.as_ref());
+        let target = match preferences.indentation(buffer.path.as_ref()) {
+            Indentation::Tabs => Indentation::Spaces(width),
+            Indentation::Spaces(_) => Indentation::Tabs,
+        };
+
+        (target, width)
+    };
+
+    let content = indentation::convert(&buffer.data(), target, width);
+    snapshot::restore(buffer, &content);
+    if let Some(ref path) = buffer.path {
+        app.preferences.borrow_mut().set_indentation(path, target);
+    }
+    app.message = Some(format!("Converted indentation to {}", target));
+
+    Ok(())
+}
+
+/// Aligns the columns of the Markdown table under the cursor, as a single change.
+pub fn format_table(app: &mut Application) -> Result {
+    app.ensure_writable()?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let lines: Vec<&str> = data.lines().collect();
+    let cursor_line = buffer.cursor.line;
+    if !lines.get(cursor_line).map(|line| markdown::is_table_row(line)).unwrap_or(false) {
+        bail!("The cursor isn't in a table");
+    }
+
+    let first_line = (0..cursor_line + 1)
+        .rev()
+        .take_while(|&line| markdown::is_table_row(lines[line]))
+        .last()
+        .unwrap_or(cursor_line);
+    let last_line = (cursor_line..lines.len())
+        .take_while(|&line| markdown::is_table_row(lines[line]))
+        .last()
+        .unwrap_or(cursor_line);
+    let table = markdown::format_table(&lines[first_line..last_line + 1]).join("\n");
+    let cursor = *buffer.cursor.clone();
+
+    buffer.start_operation_group();
+    buffer.delete_range(Range::new(
+        Position{ line: first_line, offset: 0 },
+        Position{ line: last_line, offset: lines[last_line].graphemes(true).count() }
+    ));
+    buffer.cursor.move_to(Position{ line: first_line, offset: 0 });
+    buffer.insert(table);
+    buffer.end_operation_group();
+
+    // Stay on the same line, even if the cursor's column is now out of range.
+    if !buffer.cursor.move_to(cursor) {
+        buffer.cursor.move_to(Position{ line: cursor.line, offset: 0 });
+        buffer.cursor.move_to_end_of_line();
+    }
+
+    Ok(())
+}
+
+/// Checks or unchecks the Markdown checkbox on the current line (or on each
+/// selected line), adding checkboxes to list items that don't have one.
+pub fn toggle_checkbox(app: &mut Application) -> Result {
+    app.ensure_writable()?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let lines = match app.mode {
+        Mode::Select(ref mode) => {
+            cmp::min(mode.anchor.line, buffer.cursor.line)..cmp::max(mode.anchor.line, buffer.cursor.line) + 1
+        }
+        Mode::SelectLine(ref mode) => {
+            cmp::min(mode.anchor, buffer.cursor.line)..cmp::max(mode.anchor, buffer.cursor.line) + 1
+        }
+        _ => buffer.cursor.line..buffer.cursor.line + 1,
+    };
+    let data = buffer.data();
+    let cursor = *buffer.cursor.clone();
+
+    buffer.start_operation_group();
+    let mut toggled = false;
+    for line in lines {
+        let content = data.lines().nth(line).unwrap_or("");
+        if let Some(replacement) = markdown::toggle_checkbox(content) {
+            buffer.delete_range(Range::new(
+                Position{ line, offset: 0 },
+                Position{ line, offset: content.graphemes(true).count() }
+            ));
+            buffer.cursor.move_to(Position{ line, offset: 0 });
+            buffer.insert(replacement);
+            toggled = true;
+        }
+    }
+    buffer.end_operation_group();
+    buffer.cursor.move_to(cursor);
+
+    if !toggled {
+        bail!("There are no list items to toggle");
+    }
+
+    Ok(())
+}
+
 pub fn change_token(app: &mut Application) -> Result {
     commands::buffer::delete_token(app)?;
     commands::application::switch_to_insert_mode(app)?;
@@ -476,6 +1035,7 @@ pub fn change_token(app: &mut Application) -> Result {
 }
 
 pub fn delete_rest_of_line(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
     // Create a range extending from the
@@ -521,6 +1081,7 @@ pub fn end_command_group(app: &mut Application) -> Result {
 }
 
 pub fn undo(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.undo();
     commands::view::scroll_to_cursor(app).chain_err(|| {
         "Couldn't scroll to cursor after undoing."
@@ -528,6 +1089,7 @@ pub fn undo(app: &mut Application) -> Result {
 }
 
 pub fn redo(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.redo();
     commands::view::scroll_to_cursor(app).chain_err(|| {
         "Couldn't scroll to cursor after redoing."
@@ -535,8 +1097,9 @@ pub fn redo(app: &mut Application) -> Result {
 }
 
 pub fn paste(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     let insert_below = match app.mode {
-        Mode::Select(_) | Mode::SelectLine(_) | Mode::Search(_) => {
+        Mode::Select(_) | Mode::SelectLine(_) | Mode::SelectBlock(_) | Mode::Search(_) => {
             commands::selection::delete(app).chain_err(|| {
                 "Couldn't delete selection prior to pasting."
             })?;
@@ -545,10 +1108,16 @@ pub fn paste(app: &mut Application) -> Result {
         _ => true,
     };
 
-    // TODO: Clean up duplicate buffer.insert(content.clone()) calls.
     if let Some(buffer) = app.workspace.current_buffer() {
+        // Track where the content ends up, so that it can be
+        // replaced with earlier clipboard entries afterwards.
+        let mut pasted = None;
+
         match *app.clipboard.get_content() {
-            ClipboardContent::Inline(ref content) => buffer.insert(content.clone()),
+            ClipboardContent::Inline(ref content) => {
+                pasted = Some((*buffer.cursor, content.clone()));
+                buffer.insert(content.clone());
+            }
             ClipboardContent::Block(ref content) => {
                 let original_cursor_position = *buffer.cursor.clone();
                 let line = original_cursor_position.line;
@@ -563,26 +1132,47 @@ pub fn paste(app: &mut Application) -> Result {
                         // That didn't work because we're at the last line.
                         // Move to the end of the line to insert the data.
                         if let Some(line_content) = buffer.data().lines().nth(line) {
+                            let start_of_next_line = Position {
+                                line: line + 1,
+                                offset: 0,
+                            };
                             buffer.cursor.move_to(Position {
                                 line,
-                                offset: line_content.len(),
+                                offset: line_content.graphemes(true).count(),
                             });
-                            buffer.insert(format!("\n{}", content));
+                            buffer.start_operation_group();
+                            buffer.insert("\n");
+                            buffer.cursor.move_to(start_of_next_line);
+                            buffer.insert(content.clone());
+                            buffer.end_operation_group();
                             buffer.cursor.move_to(original_cursor_position);
+                            pasted = Some((start_of_next_line, content.clone()));
                         } else {
                             // We're on a trailing newline, which doesn't
                             // have any data; just insert the content here.
+                            pasted = Some((*buffer.cursor, content.clone()));
                             buffer.insert(content.clone());
                         }
                     } else {
+                        pasted = Some((*buffer.cursor, content.clone()));
                         buffer.insert(content.clone());
                     }
                 } else {
+                    pasted = Some((*buffer.cursor, content.clone()));
                     buffer.insert(content.clone());
                 }
             }
+            ClipboardContent::Rectangle(ref content) => paste_rectangle(buffer, content),
             ClipboardContent::None => (),
         }
+
+        app.clipboard.last_paste = pasted.map(|(start, content)| Paste {
+            buffer_id: buffer.id,
+            range: Range::new(start, start + Distance::of_str(&content)),
+            content,
+            cursor: *buffer.cursor,
+            entry: 0,
+        });
     } else {
         bail!(BUFFER_MISSING);
     }
@@ -591,7 +1181,87 @@ pub fn paste(app: &mut Application) -> Result {
     Ok(())
 }
 
+// Whether the buffer is highlighted as Markdown, where lists are continued.
+fn is_markdown(buffer: &Buffer) -> bool {
+    buffer.syntax_definition
+        .as_ref()
+        .map(|syntax| syntax.name == "Markdown")
+        .unwrap_or(false)
+}
+
+// Inserts each row of the content at the cursor's column on successive lines,
+// padding lines that are too short and adding lines past the end of the buffer.
+fn paste_rectangle(buffer: &mut Buffer, content: &str) {
+    let origin = *buffer.cursor;
+    let mut line_lengths: Vec<usize> = buffer
+        .data()
+        .split('\n')
+        .map(|line| line.graphemes(true).count())
+        .collect();
+
+    buffer.start_operation_group();
+    for (index, row) in content.split('\n').enumerate() {
+        let line = origin.line + index;
+        if line >= line_lengths.len() {
+            let last_line = line_lengths.len() - 1;
+            buffer.cursor.move_to(Position{ line: last_line, offset: line_lengths[last_line] });
+            buffer.insert("\n");
+            line_lengths.push(0);
+        }
+
+        let length = line_lengths[line];
+        let padding = origin.offset.saturating_sub(length);
+        buffer.cursor.move_to(Position{ line, offset: cmp::min(origin.offset, length) });
+        buffer.insert(format!("{}{}", " ".repeat(padding), row));
+        line_lengths[line] = length + padding + row.graphemes(true).count();
+    }
+    buffer.end_operation_group();
+    buffer.cursor.move_to(origin);
+}
+
+/// Replaces the content inserted by the last paste with the clipboard entry
+/// that preceded it, cycling back to the current content after the oldest.
+pub fn paste_previous(app: &mut Application) -> Result {
+    app.ensure_writable()?;
+    let paste = app.clipboard.last_paste.clone().ok_or("Nothing has been pasted yet")?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+    // Only cycle if the paste is untouched and we haven't moved away from it.
+    if buffer.id != paste.buffer_id ||
+        *buffer.cursor != paste.cursor ||
+        buffer.read(&paste.range) != Some(paste.content.clone()) {
+        app.clipboard.last_paste = None;
+        bail!("Clipboard entries can only be cycled through immediately after pasting");
+    }
+
+    let entry = (paste.entry + 1) % (app.clipboard.history().len() + 1);
+    let content = app.clipboard
+        .entry(entry)
+        .and_then(|entry| entry.text())
+        .ok_or("There's nothing on the clipboard")?
+        .to_string();
+
+    buffer.start_operation_group();
+    buffer.delete_range(paste.range.clone());
+    buffer.cursor.move_to(paste.range.start());
+    buffer.insert(content.clone());
+    buffer.end_operation_group();
+    buffer.cursor.move_to(paste.cursor);
+
+    let start = paste.range.start();
+    app.clipboard.last_paste = Some(Paste {
+        range: Range::new(start, start + Distance::of_str(&content)),
+        content,
+        entry,
+        ..paste
+    });
+    commands::view::scroll_to_cursor(app)?;
+
+    Ok(())
+}
+
 pub fn paste_above(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
     if let ClipboardContent::Block(ref content) = *app.clipboard.get_content() {
@@ -611,6 +1281,7 @@ pub fn paste_above(app: &mut Application) -> Result {
 }
 
 pub fn remove_trailing_whitespace(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let mut line = 0;
     let mut offset = 0;
@@ -674,6 +1345,7 @@ pub fn remove_trailing_whitespace(app: &mut Application) -> Result {
 }
 
 pub fn ensure_trailing_newline(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
     // Find end of buffer position.
@@ -688,7 +1360,7 @@ pub fn ensure_trailing_newline(app: &mut Application) -> Result {
             let original_position = *buffer.cursor;
             let target_position = Position {
                 line: line_no,
-                offset: line.len(),
+                offset: line.graphemes(true).count(),
             };
 
             if buffer.cursor.move_to(target_position) {
@@ -706,6 +1378,7 @@ pub fn ensure_trailing_newline(app: &mut Application) -> Result {
 }
 
 pub fn insert_tab(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
     let tab_content_width = tab_content.chars().count();
@@ -722,11 +1395,80 @@ pub fn insert_tab(app: &mut Application) -> Result {
 #[cfg(test)]
 mod tests {
     use crate::commands;
+    use crate::input::Key;
     use crate::models::Application;
-    use crate::models::application::{ClipboardContent, Mode};
+    use crate::models::application::{ClipboardContent, Mode, Preferences};
+    use crate::models::application::modes::SelectLineMode;
+    use crate::yaml::yaml::YamlLoader;
+    use crate::util::lint::Diagnostic;
     use scribe::Buffer;
     use scribe::buffer::Position;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn line_defining_finds_nested_preferences() {
+        let config = "tab_width: 2\ntypes:\n  rs:\n    tab_width: 4\n";
+
+        assert_eq!(super::line_defining(config, "tab_width"), Some(0));
+        assert_eq!(super::line_defining(config, "types.rs.tab_width"), Some(3));
+        assert_eq!(super::line_defining(config, "types.py.tab_width"), None);
+    }
+
+    #[test]
+    fn unknown_preference_diagnostics_point_at_the_lines_defining_them() {
+        let config = "tab_widht: 2\ntypes:\n  rs:\n    soft_tab: false\n";
+        let keys = vec![String::from("tab_widht"), String::from("types.rs.soft_tab")];
+
+        assert_eq!(
+            super::unknown_preference_diagnostics(config, &keys),
+            vec![
+                Diagnostic{ line: 0, offset: 0, message: String::from("unknown preference: tab_widht") },
+                Diagnostic{ line: 3, offset: 4, message: String::from("unknown preference: types.rs.soft_tab") },
+            ]
+        );
+    }
+
+    #[test]
+    fn content_statistics_counts_lines_words_characters_and_bytes() {
+        assert_eq!(
+            super::content_statistics("amp editor\nrust_lang é\n"),
+            "2 lines, 5 words, 23 characters, 24 bytes"
+        );
+    }
+
+    #[test]
+    fn display_statistics_reports_on_the_current_selection() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\nbuffer\n");
+        app.workspace.add_buffer(buffer);
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::buffer::display_statistics(&mut app).unwrap();
+
+        assert_eq!(
+            app.message,
+            Some(String::from("Selection: 1 lines, 1 words, 4 characters, 4 bytes | line 1 of 4 (25%)"))
+        );
+    }
+
+    #[test]
+    fn inspect_character_describes_the_grapheme_under_the_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("ae\u{0301}\n");
+        buffer.cursor.move_to(Position{ line: 0, offset: 1 });
+        app.workspace.add_buffer(buffer);
+        commands::buffer::inspect_character(&mut app).unwrap();
+
+        assert_eq!(
+            app.message,
+            Some(String::from("U+0065 LATIN SMALL LETTER E + U+0301 COMBINING ACUTE ACCENT | UTF-8: 65 CC 81"))
+        );
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 2 });
+        commands::buffer::inspect_character(&mut app).unwrap();
+        assert_eq!(app.message, Some(String::from("U+000A LINE FEED | UTF-8: 0A")));
+    }
 
     #[test]
     fn insert_newline_uses_current_line_indentation() {
@@ -794,6 +1536,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn insert_newline_continues_and_ends_markdown_lists() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("notes.md"));
+        buffer.insert("1. [x] amp");
+        buffer.cursor.move_to(Position{ line: 0, offset: 10 });
+        app.workspace.add_buffer(buffer);
+
+        super::insert_newline(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "1. [x] amp\n2. [ ] ");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 7 });
+
+        super::insert_newline(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "1. [x] amp\n");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 0 });
+    }
+
+    #[test]
+    fn format_table_aligns_the_table_under_the_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n|a|editor|\n|-|-|\n|rust|x|\nend");
+        buffer.cursor.move_to(Position{ line: 3, offset: 2 });
+        app.workspace.add_buffer(buffer);
+
+        super::format_table(&mut app).unwrap();
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "amp\n| a    | editor |\n| ---- | ------ |\n| rust | x      |\nend"
+        );
+
+        app.workspace.current_buffer().unwrap().undo();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\n|a|editor|\n|-|-|\n|rust|x|\nend");
+    }
+
+    #[test]
+    fn toggle_checkbox_toggles_each_selected_list_item() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("- [ ] amp\n- [x] editor\nrust");
+        buffer.cursor.move_to(Position{ line: 2, offset: 0 });
+        app.workspace.add_buffer(buffer);
+        app.mode = Mode::SelectLine(SelectLineMode::new(0));
+
+        super::toggle_checkbox(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "- [x] amp\n- [ ] editor\nrust");
+
+        app.mode = Mode::Normal;
+        assert!(super::toggle_checkbox(&mut app).is_err());
+    }
+
     #[test]
     fn change_rest_of_line_removes_content_and_switches_to_insert_mode() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -881,6 +1675,27 @@ mod tests {
         assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor");
     }
 
+    #[test]
+    fn convert_indentation_switches_between_tabs_and_spaces() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n  editor\n    rust");
+        buffer.path = Some(PathBuf::from("convert_indentation.rs"));
+        app.workspace.add_buffer(buffer);
+
+        super::convert_indentation(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\n\teditor\n\t\trust");
+
+        // New indentation follows the converted style.
+        super::indent_line(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "\tamp\n\teditor\n\t\trust");
+
+        // The conversion is undone as a single change.
+        app.workspace.current_buffer().unwrap().undo();
+        app.workspace.current_buffer().unwrap().undo();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\n  editor\n    rust");
+    }
+
     #[test]
     fn indent_line_inserts_two_spaces_at_start_of_line() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1313,6 +2128,52 @@ mod tests {
                    "amp\neditor\n      ");
     }
 
+    #[test]
+    fn insert_char_joins_combining_marks_to_the_previous_grapheme() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("ab");
+        buffer.cursor.move_right();
+        app.workspace.add_buffer(buffer);
+
+        // Add an acute accent to the "a", and then another character.
+        app.view.last_key = Some(Key::Char('\u{301}'));
+        commands::buffer::insert_char(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('c'));
+        commands::buffer::insert_char(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "a\u{301}cb");
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 2 });
+
+        // Backspacing removes the whole of the accented grapheme.
+        commands::buffer::backspace(&mut app).unwrap();
+        commands::buffer::backspace(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "b");
+    }
+
+    #[test]
+    fn insert_char_expands_abbreviations_and_backspace_reverts_them() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("abbreviations:\n  teh: the").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+        app.workspace.add_buffer(Buffer::new());
+
+        for character in "(teh teh)".chars() {
+            app.view.last_key = Some(Key::Char(character));
+            commands::buffer::insert_char(&mut app).unwrap();
+        }
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "(the the)");
+
+        commands::buffer::backspace(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "(the teh)");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 9 });
+
+        // Only the expansion is reverted; backspacing again deletes as usual.
+        commands::buffer::backspace(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "(the teh");
+    }
+
     #[test]
     fn merge_next_line_joins_current_and_next_lines_with_a_space() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1484,6 +2345,73 @@ mod tests {
         // assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp");
     }
 
+    #[test]
+    fn read_only_buffers_refuse_changes_until_made_writable() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("read_only.txt"));
+        app.workspace.add_buffer(buffer);
+        commands::buffer::make_read_only(&mut app).unwrap();
+
+        assert!(commands::application::switch_to_insert_mode(&mut app).is_err());
+        assert!(commands::buffer::save(&mut app).is_err());
+        assert!(commands::buffer::paste(&mut app).is_err());
+        assert!(commands::buffer::toggle_checkbox(&mut app).is_err());
+
+        commands::buffer::make_writable(&mut app).unwrap();
+        assert!(commands::application::switch_to_insert_mode(&mut app).is_ok());
+    }
+
+    #[test]
+    fn paste_with_rectangle_content_inserts_rows_at_the_cursor_column() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nab");
+        buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+        app.workspace.add_buffer(buffer);
+        app.clipboard.set_content(ClipboardContent::Rectangle("12\n34\n56".to_string())).unwrap();
+        commands::buffer::paste(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(),
+                   "amp12 editor\nab 34\n   56");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor,
+                   Position{ line: 0, offset: 3 });
+    }
+
+    #[test]
+    fn paste_previous_cycles_through_clipboard_history_in_place() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(" rust");
+        app.workspace.add_buffer(buffer);
+        app.clipboard.set_content(ClipboardContent::Inline("amp".to_string())).unwrap();
+        app.clipboard.set_content(ClipboardContent::Inline("editor".to_string())).unwrap();
+
+        commands::buffer::paste(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor rust");
+
+        commands::buffer::paste_previous(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp rust");
+
+        commands::buffer::paste_previous(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor rust");
+    }
+
+    #[test]
+    fn paste_previous_requires_an_untouched_paste() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(" rust");
+        app.workspace.add_buffer(buffer);
+        app.clipboard.set_content(ClipboardContent::Inline("amp".to_string())).unwrap();
+        app.clipboard.set_content(ClipboardContent::Inline("editor".to_string())).unwrap();
+
+        commands::buffer::paste(&mut app).unwrap();
+        commands::cursor::move_right(&mut app).unwrap();
+
+        assert!(commands::buffer::paste_previous(&mut app).is_err());
+    }
+
     #[test]
     fn paste_above_inserts_clipboard_contents_on_a_new_line_above() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -0,0 +1,124 @@
+use crate::errors::*;
+use crate::input::Key;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use crate::util;
+
+pub fn push_char(app: &mut Application) -> Result {
+    let character = match *app.view.last_key() {
+        Some(Key::Char(character)) => character,
+        _ => bail!("Last key press wasn't a character"),
+    };
+
+    let digraphs = app.preferences.borrow().digraphs();
+    let resolved = if let Mode::CharacterInput(ref mut mode) = app.mode {
+        mode.input.push(character);
+        app.view.pending_input = Some(mode.status());
+
+        mode.character(&digraphs)
+    } else {
+        bail!("Can't push a character outside of character input mode");
+    };
+
+    match resolved {
+        Ok(Some(character)) => insert(app, character),
+        Ok(None) => Ok(()),
+        Err(error) => {
+            cancel(app)?;
+            Err(error)
+        }
+    }
+}
+
+pub fn pop_char(app: &mut Application) -> Result {
+    if let Mode::CharacterInput(ref mut mode) = app.mode {
+        mode.input.pop();
+        app.view.pending_input = Some(mode.status());
+    } else {
+        bail!("Can't pop a character outside of character input mode");
+    }
+
+    Ok(())
+}
+
+/// Inserts the character described by the input entered so far,
+/// allowing codepoints with fewer digits than usual (e.g. "ue9").
+pub fn accept(app: &mut Application) -> Result {
+    let digraphs = app.preferences.borrow().digraphs();
+    let resolved = if let Mode::CharacterInput(ref mode) = app.mode {
+        mode.accept(&digraphs)
+    } else {
+        bail!("Can't accept a character outside of character input mode");
+    };
+
+    match resolved {
+        Ok(character) => insert(app, character),
+        Err(error) => {
+            cancel(app)?;
+            Err(error)
+        }
+    }
+}
+
+/// Returns to insert mode without inserting anything.
+pub fn cancel(app: &mut Application) -> Result {
+    app.view.pending_input = None;
+    app.mode = Mode::Insert;
+
+    Ok(())
+}
+
+fn insert(app: &mut Application, character: char) -> Result {
+    {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        util::insert_at_cursor(buffer, &character.to_string());
+    }
+    cancel(app)?;
+    commands::view::scroll_to_cursor(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use scribe::Buffer;
+
+    fn type_keys(app: &mut Application, keys: &str) {
+        for character in keys.chars() {
+            app.view.last_key = Some(Key::Char(character));
+            super::push_char(app).unwrap();
+        }
+    }
+
+    #[test]
+    fn push_char_inserts_codepoints_once_complete() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+        commands::application::switch_to_codepoint_input_mode(&mut app).unwrap();
+
+        type_keys(&mut app, "u00e");
+        assert_eq!(app.view.pending_input, Some(String::from("^V u00e")));
+        type_keys(&mut app, "9");
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "é");
+        assert!(app.view.pending_input.is_none());
+        match app.mode {
+            Mode::Insert => (),
+            _ => panic!("Not in insert mode"),
+        }
+    }
+
+    #[test]
+    fn push_char_inserts_digraphs() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+        commands::application::switch_to_digraph_input_mode(&mut app).unwrap();
+        type_keys(&mut app, "a:");
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "ä");
+    }
+}
@@ -0,0 +1,660 @@
+use crate::errors;
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::input::Key;
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{ConfirmMode, DiffMode, DiffSource, LineJumpMode, ReplaceMode};
+use crate::util::{self, commit_message, completion, occurrences, rename, replace, snapshot, workspace_replace};
+use regex::Regex;
+use scribe::buffer::Range;
+use std::time::Instant;
+
+pub fn push_char(app: &mut Application) -> Result {
+    let last_key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
+    if let Key::Char(c) = *last_key {
+        if let Mode::CommandLine(ref mut mode) = app.mode {
+            mode.push_char(c);
+        } else {
+            bail!("Cannot push char outside of command line mode");
+        }
+    } else {
+        bail!("Last key press wasn't a character");
+    }
+    Ok(())
+}
+
+pub fn pop_char(app: &mut Application) -> Result {
+    let empty = if let Mode::CommandLine(ref mut mode) = app.mode {
+        mode.pop_char();
+        mode.input.is_empty()
+    } else {
+        bail!("Cannot pop char outside of command line mode");
+    };
+
+    // Deleting the last character leaves the command line.
+    if empty {
+        commands::application::switch_to_normal_mode(app)?;
+    }
+
+    Ok(())
+}
+
+pub fn complete(app: &mut Application) -> Result {
+    if let Mode::CommandLine(ref mut mode) = app.mode {
+        mode.complete();
+    } else {
+        bail!("Cannot complete input outside of command line mode");
+    }
+
+    Ok(())
+}
+
+pub fn toggle_hidden_completions(app: &mut Application) -> Result {
+    if let Mode::CommandLine(ref mut mode) = app.mode {
+        mode.completions.toggle_hidden();
+    } else {
+        bail!("Cannot toggle hidden completions outside of command line mode");
+    }
+
+    Ok(())
+}
+
+pub fn accept(app: &mut Application) -> Result {
+    let (input, selection) = if let Mode::CommandLine(ref mode) = app.mode {
+        (mode.input.clone(), mode.selection.clone())
+    } else {
+        bail!("Cannot accept input outside of command line mode");
+    };
+
+    // Leave the command line before running anything, so that we don't
+    // clobber modes entered by the command (e.g. path mode when saving).
+    app.mode = Mode::Normal;
+
+    run(app, &input, selection.as_ref())
+}
+
+fn run(app: &mut Application, input: &str, selection: Option<&Range>) -> Result {
+    // Substitutions take raw input, since their arguments can contain spaces.
+    if let Some((query, replacement, flags)) = parse_substitution(input) {
+        return substitute(app, &query, &replacement, &flags, selection);
+    }
+
+    let mut words = input.split_whitespace();
+    let command = match words.next() {
+        Some(command) => command,
+        None => return Ok(()),
+    };
+    let arguments: Vec<&str> = words.collect();
+
+    // Bare line numbers jump to that line.
+    if command.chars().all(|c| c.is_digit(10)) {
+        app.mode = Mode::LineJump(LineJumpMode{ input: command.to_string() });
+        return commands::line_jump::accept_input(app);
+    }
+
+    match command {
+        "w" | "write" => write(app, &arguments),
+        "q" | "quit" => commands::buffer::close(app),
+        "wq" | "x" => {
+            // Git aborts commits with empty messages; make sure that's intended.
+            if arguments.is_empty() && has_empty_commit_message(app) {
+                app.mode = Mode::Confirm(ConfirmMode::with_prompt(
+                    write_and_close,
+                    "The commit message is empty, which aborts the commit; save and close anyway?",
+                ));
+
+                return Ok(());
+            }
+
+            write(app, &arguments)?;
+            close_written_buffer(app)
+        },
+        "e" | "edit" => edit(app, &arguments),
+        "cd" => change_directory(app, &arguments),
+        "workspace" => open_workspace(app, &arguments),
+        "set" => set(app, &arguments),
+        "replace" => match arguments.len() {
+            2 => substitute(app, arguments[0], arguments[1], "", selection),
+            _ => bail!("Please provide a query and its replacement (e.g. replace foo bar)"),
+        },
+        "rename" => rename_identifier(app, &arguments),
+        "earlier" => earlier(app, &arguments),
+        "compare_earlier" => compare_earlier(app, &arguments),
+        _ => {
+            // Anything else is run using the same registry as the command palette.
+            let registered_command = commands::hash_map()
+                .get(command)
+                .cloned()
+                .ok_or_else(|| format!("Unknown command: {}", command))?;
+
+            registered_command(app)
+        },
+    }
+}
+
+fn write_and_close(app: &mut Application) -> Result {
+    write(app, &[])?;
+    close_written_buffer(app)
+}
+
+fn close_written_buffer(app: &mut Application) -> Result {
+    // Saving buffers without a path prompts for one; don't close them.
+    if let Mode::Normal = app.mode {
+        commands::buffer::close(app)?;
+    }
+
+    Ok(())
+}
+
+fn has_empty_commit_message(app: &mut Application) -> bool {
+    app.workspace.current_buffer().map(|buffer| {
+        buffer.path.as_ref().map(|path| commit_message::is_commit_message(path)).unwrap_or(false) &&
+            commit_message::message(&buffer.data()).is_empty()
+    }).unwrap_or(false)
+}
+
+// Saves the current buffer, first pointing it at a new path, if one is given.
+fn write(app: &mut Application, arguments: &[&str]) -> Result {
+    match arguments.len() {
+        0 => (),
+        1 => {
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            buffer.path = Some(completion::expand_home(arguments[0]));
+            app.workspace.update_current_syntax().chain_err(||
+                "Failed to update buffer's syntax definition"
+            )?;
+            util::apply_syntax_preference(&mut app.workspace, &app.preferences.borrow())?;
+        },
+        _ => bail!("Please provide a single path to write to"),
+    }
+
+    commands::buffer::save(app)
+}
+
+fn edit(app: &mut Application, arguments: &[&str]) -> Result {
+    if arguments.len() != 1 {
+        bail!("Please provide a path to edit");
+    }
+    let path = completion::expand_home(arguments[0]);
+
+    util::open_buffer(&path, app)
+}
+
+// Changes the working directory (to the home directory, if none is given).
+fn change_directory(app: &mut Application, arguments: &[&str]) -> Result {
+    let path = match arguments.len() {
+        0 => completion::expand_home("~"),
+        1 => completion::expand_home(arguments[0]),
+        _ => bail!("Please provide a single directory"),
+    };
+    app.change_directory(&path)?;
+    app.message = Some(format!("Changed directory to {}", app.workspace.path.to_string_lossy()));
+
+    Ok(())
+}
+
+// Opens (or switches to) a workspace in another directory.
+fn open_workspace(app: &mut Application, arguments: &[&str]) -> Result {
+    if arguments.len() != 1 {
+        bail!("Please provide the workspace's directory");
+    }
+    app.open_workspace(&completion::expand_home(arguments[0]))?;
+    app.message = Some(format!("Switched to the workspace in {}", app.workspace.path.to_string_lossy()));
+
+    Ok(())
+}
+
+// Replaces matches for the query using the current search options,
+// limited to the selection the command line was opened with, if any.
+// The "p" flag ignores case when matching, and preserves it when replacing.
+// The "w" flag replaces matches throughout the workspace, once reviewed.
+fn substitute(app: &mut Application, query: &str, replacement: &str, flags: &str, selection: Option<&Range>) -> Result {
+    let (mut preserve_case, mut workspace) = (false, false);
+    for flag in flags.chars() {
+        match flag {
+            'p' => preserve_case = true,
+            'w' => workspace = true,
+            _ => bail!("Unknown substitution flags: {}", flags),
+        }
+    }
+    let options = app.search_options;
+    let mut pattern = options.pattern(query)?;
+    if preserve_case {
+        pattern = Regex::new(&format!("(?i){}", pattern.as_str()))
+            .chain_err(|| "Couldn't build a case-insensitive pattern")?;
+    }
+    if workspace {
+        if selection.is_some() {
+            bail!("Workspace-wide substitutions can't be limited to a selection");
+        }
+
+        return substitute_in_workspace(app, query, &pattern, replacement, preserve_case);
+    }
+    app.ensure_writable()?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let count = replace::replace_all(buffer, &pattern, replacement, options.regex, preserve_case, selection);
+
+    app.message = Some(match count {
+        0 => format!("No matches found for \"{}\"", query),
+        1 => String::from("Replaced 1 match"),
+        _ => format!("Replaced {} matches", count),
+    });
+
+    Ok(())
+}
+
+// Finds matches for the pattern throughout the workspace,
+// listing them for review before any of them are replaced.
+fn substitute_in_workspace(app: &mut Application, query: &str, pattern: &Regex, replacement: &str, preserve_case: bool) -> Result {
+    let expand = app.search_options.regex;
+    let exclusions = util::workspace_exclusions(app)?;
+    let buffers = app.buffer_contents();
+    let files = workspace_replace::find(
+        &app.workspace.path,
+        exclusions.as_ref().map(|exclusions| exclusions.as_slice()),
+        &buffers,
+        |content| replace::replacements(content, pattern, replacement, expand, preserve_case)
+    );
+    if files.is_empty() {
+        app.message = Some(format!("No matches found for \"{}\" in the workspace", query));
+        return Ok(());
+    }
+
+    app.mode = Mode::Replace(ReplaceMode::new(query.to_string(), replacement.to_string(), files));
+
+    Ok(())
+}
+
+// Parses vim-style substitutions (e.g. "s/query/replacement/flags"), using
+// the character following the "s" as the delimiter. Delimiters can be escaped
+// using a backslash; other escape sequences are left as-is.
+fn parse_substitution(input: &str) -> Option<(String, String, String)> {
+    let mut characters = input.chars();
+    if characters.next() != Some('s') {
+        return None;
+    }
+    let delimiter = characters.next().filter(|c| !c.is_alphanumeric() && !c.is_whitespace())?;
+
+    let mut fields = vec![String::new()];
+    while let Some(character) = characters.next() {
+        if character == '\\' && characters.clone().next() == Some(delimiter) {
+            fields.last_mut()?.push(delimiter);
+            characters.next();
+        } else if character == delimiter {
+            fields.push(String::new());
+        } else {
+            fields.last_mut()?.push(character);
+        }
+    }
+
+    // The trailing delimiter (and the flags after it) are optional.
+    if fields.len() == 2 {
+        fields.push(String::new());
+    }
+    if fields.len() != 3 || fields[0].is_empty() {
+        return None;
+    }
+    let flags = fields.pop()?;
+    let replacement = fields.pop()?;
+    let query = fields.pop()?;
+
+    Some((query, replacement, flags))
+}
+
+fn set(app: &mut Application, arguments: &[&str]) -> Result {
+    if arguments.len() != 2 {
+        bail!("Please provide a preference and a value (e.g. \"set tab_width 4\")");
+    }
+    let (key, value) = (arguments[0], arguments[1]);
+
+    if key == "theme" && !app.view.theme_set()?.themes.contains_key(value) {
+        bail!("Couldn't find \"{}\" theme", value);
+    }
+
+    app.preferences.borrow_mut().set(key, value)?;
+
+    // Setting indentation explicitly overrides what was detected for the
+    // current buffer's file; other files keep their detected indentation.
+    if key == "soft_tabs" || key == "tab_width" {
+        if let Some(path) = app.workspace.current_buffer_path() {
+            app.preferences.borrow_mut().clear_indentation(path);
+        }
+    }
+
+    Ok(())
+}
+
+// Finds whole-word occurrences of the identifier under the cursor throughout
+// the workspace, listing them for review before any of them are renamed.
+fn rename_identifier(app: &mut Application, arguments: &[&str]) -> Result {
+    if arguments.len() != 1 {
+        bail!("Please provide a new name (e.g. \"rename new_name\")");
+    }
+    let replacement = arguments[0];
+    if !replacement.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        bail!("\"{}\" isn't a valid identifier", replacement);
+    }
+
+    let name = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let data = buffer.data();
+        let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+        occurrences::word_at(line, buffer.cursor.offset).ok_or("There's no identifier under the cursor")?
+    };
+    if name == replacement {
+        bail!("{} already has that name", name);
+    }
+
+    let exclusions = util::workspace_exclusions(app)?;
+    let buffers = app.buffer_contents();
+    let files = rename::find(
+        &app.workspace.path,
+        exclusions.as_ref().map(|exclusions| exclusions.as_slice()),
+        &buffers,
+        &name,
+        replacement
+    );
+    if files.is_empty() {
+        bail!("Couldn't find {} in the workspace", name);
+    }
+
+    app.mode = Mode::Replace(ReplaceMode::rename(name, replacement.to_string(), files));
+
+    Ok(())
+}
+
+// Restores the current buffer to the content it had a while ago
+// (e.g. "earlier 10m"), as a single change that can be undone.
+fn earlier(app: &mut Application, arguments: &[&str]) -> Result {
+    app.ensure_writable()?;
+    let (_, content) = earlier_content(app, arguments)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    snapshot::restore(buffer, &content);
+
+    Ok(())
+}
+
+// Compares the current buffer with the content it had a while ago.
+fn compare_earlier(app: &mut Application, arguments: &[&str]) -> Result {
+    let (time, content) = earlier_content(app, arguments)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let title = buffer.path
+        .as_ref()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|| String::from("[untitled]"));
+
+    app.mode = Mode::Diff(DiffMode::new(
+        DiffSource::Earlier(time),
+        title.clone(),
+        &buffer.data(),
+        format!("{} ({} ago)", title, arguments[0]),
+        &content
+    ));
+
+    Ok(())
+}
+
+// The time the argument refers to, along with the current buffer's content at that time.
+fn earlier_content(app: &mut Application, arguments: &[&str]) -> errors::Result<(Instant, String)> {
+    if arguments.len() != 1 {
+        bail!("Please provide how far back to go (e.g. 10m, 30s, or 2h)");
+    }
+    let duration = snapshot::parse_duration(arguments[0])
+        .ok_or_else(|| format!("Couldn't read \"{}\" as a length of time (e.g. 10m, 30s, or 2h)", arguments[0]))?;
+    let time = Instant::now().checked_sub(duration).ok_or("That's further back than the editor can go")?;
+    let id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id;
+    let content = id
+        .and_then(|id| app.snapshots.content_at(id, time))
+        .ok_or("The buffer's content from that long ago wasn't recorded")?;
+
+    Ok((time, content.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use crate::models::application::modes::SelectLineMode;
+    use crate::util::indentation::Indentation;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+    fn run(app: &mut Application, input: &str) {
+        commands::application::switch_to_command_line_mode(app).unwrap();
+        if let Mode::CommandLine(ref mut mode) = app.mode {
+            mode.input = String::from(input);
+        }
+        super::accept(app).unwrap();
+    }
+
+    #[test]
+    fn accept_jumps_to_line_numbers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\nrust");
+        app.workspace.add_buffer(buffer);
+
+        run(&mut app, "3");
+
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 2);
+        if let Mode::Normal = app.mode {
+        } else {
+            panic!("Not in normal mode");
+        }
+    }
+
+    #[test]
+    fn accept_runs_registered_commands() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        run(&mut app, "workspace::new_buffer");
+
+        assert!(app.workspace.current_buffer().is_some());
+    }
+
+    #[test]
+    fn accept_sets_preferences() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        run(&mut app, "set tab_width 7");
+
+        assert_eq!(app.preferences.borrow().tab_width(None), 7);
+    }
+
+    #[test]
+    fn accept_overrides_detected_indentation_for_the_current_file_only() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("set_indentation.rs"));
+        app.workspace.add_buffer(buffer);
+        let other_path = PathBuf::from("other.rs");
+        app.preferences.borrow_mut().set_indentation(&PathBuf::from("set_indentation.rs"), Indentation::Spaces(3));
+        app.preferences.borrow_mut().set_indentation(&other_path, Indentation::Spaces(3));
+
+        run(&mut app, "set tab_width 7");
+        let preferences = app.preferences.borrow();
+        assert_eq!(preferences.indentation(Some(&PathBuf::from("set_indentation.rs"))), Indentation::Spaces(7));
+        assert_eq!(preferences.indentation(Some(&other_path)), Indentation::Spaces(3));
+    }
+
+    #[test]
+    fn accept_opens_paths() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        run(&mut app, "e Cargo.toml");
+
+        assert!(app.workspace.current_buffer().unwrap().data().contains("[package]"));
+    }
+
+    #[test]
+    fn accept_writes_to_new_paths() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/command_line_write"));
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n");
+        app.workspace.add_buffer(buffer);
+
+        run(&mut app, &format!("w {}", path.to_string_lossy()));
+
+        assert_eq!(app.workspace.current_buffer().unwrap().path, Some(path.clone()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "amp\n");
+    }
+
+    #[test]
+    fn accept_confirms_closing_empty_commit_messages() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("\n# Please enter the commit message for your changes.\n");
+        buffer.path = Some(PathBuf::from(concat!(env!("OUT_DIR"), "/COMMIT_EDITMSG")));
+        app.workspace.add_buffer(buffer);
+
+        run(&mut app, "wq");
+        if let Mode::Confirm(_) = app.mode {
+        } else {
+            panic!("Not in confirm mode");
+        }
+        assert!(app.workspace.current_buffer().is_some());
+
+        commands::confirm::confirm_command(&mut app).unwrap();
+        assert!(app.workspace.current_buffer().is_none());
+    }
+
+    #[test]
+    fn accept_rejects_unknown_commands() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        commands::application::switch_to_command_line_mode(&mut app).unwrap();
+        if let Mode::CommandLine(ref mut mode) = app.mode {
+            mode.input = String::from("nonexistent");
+        }
+
+        assert!(super::accept(&mut app).is_err());
+    }
+
+    #[test]
+    fn accept_restores_earlier_content() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        app.workspace.add_buffer(buffer);
+        let id = app.workspace.current_buffer().unwrap().id.unwrap();
+        let now = Instant::now();
+        app.snapshots.record(id, String::from("amp"), now - Duration::from_secs(120));
+        app.snapshots.record(id, String::from("amp editor"), now - Duration::from_secs(30));
+
+        run(&mut app, "earlier 1m");
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp");
+
+        commands::buffer::undo(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp editor");
+    }
+
+    #[test]
+    fn accept_substitutes_within_the_selection() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\namp amp\namp");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        app.workspace.add_buffer(buffer);
+        app.mode = Mode::SelectLine(SelectLineMode::new(1));
+
+        run(&mut app, "s/amp/editor/");
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor editor\namp");
+        assert_eq!(app.message, Some(String::from("Replaced 2 matches")));
+    }
+
+    #[test]
+    fn accept_refuses_substitutions_in_read_only_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        app.workspace.add_buffer(buffer);
+        app.make_buffers_read_only();
+
+        commands::application::switch_to_command_line_mode(&mut app).unwrap();
+        if let Mode::CommandLine(ref mut mode) = app.mode {
+            mode.input = String::from("s/amp/editor/");
+        }
+
+        assert!(super::accept(&mut app).is_err());
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp");
+    }
+
+    #[test]
+    fn accept_lists_workspace_substitutions_for_review_with_the_w_flag() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/command_line_substitute_workspace"));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.rs"), "amp amp\n").unwrap();
+        fs::write(root.join("b.rs"), "Amp\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.path = root.clone();
+
+        run(&mut app, "s/amp/editor/wp");
+        match app.mode {
+            Mode::Replace(ref mode) => {
+                assert!(!mode.rename);
+                assert_eq!(mode.occurrence_count(), 3);
+                assert_eq!(mode.files[1].occurrences[0].replacement, "Editor");
+            },
+            _ => panic!("Not in replace mode"),
+        }
+
+        commands::replace::apply(&mut app).unwrap();
+        assert_eq!(fs::read_to_string(root.join("a.rs")).unwrap(), "editor editor\n");
+        assert_eq!(fs::read_to_string(root.join("b.rs")).unwrap(), "Editor\n");
+        assert_eq!(app.message, Some(String::from("Replaced 3 occurrence(s) in 2 file(s)")));
+    }
+
+    #[test]
+    fn accept_substitutes_preserving_case_with_the_p_flag() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("foo Foo FOO");
+        app.workspace.add_buffer(buffer);
+
+        run(&mut app, "s/foo/bar/p");
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "bar Bar BAR");
+    }
+
+    #[test]
+    fn accept_lists_identifier_occurrences_for_review_when_renaming() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/command_line_rename"));
+        fs::create_dir_all(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        fs::write(root.join("lib.rs"), "fn amp() {}\nfn ample() { amp() }\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.path = root.clone();
+        app.workspace.open_buffer(&root.join("lib.rs")).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 4 });
+
+        run(&mut app, "rename editor");
+        match app.mode {
+            Mode::Replace(ref mode) => {
+                assert!(mode.rename);
+                assert_eq!(mode.occurrence_count(), 2);
+            },
+            _ => panic!("Not in replace mode"),
+        }
+
+        commands::replace::apply(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "fn editor() {}\nfn ample() { editor() }\n");
+        assert_eq!(app.message, Some(String::from("Renamed 2 occurrence(s) in 1 file(s)")));
+    }
+
+    #[test]
+    fn parse_substitution_handles_escaped_delimiters() {
+        assert_eq!(
+            super::parse_substitution("s/a\\/b/c d"),
+            Some((String::from("a/b"), String::from("c d"), String::new()))
+        );
+        assert_eq!(super::parse_substitution("s|a|"), Some((String::from("a"), String::new(), String::new())));
+        assert_eq!(
+            super::parse_substitution("s/a/b/p"),
+            Some((String::from("a"), String::from("b"), String::from("p")))
+        );
+        assert_eq!(super::parse_substitution("set tab_width 2"), None);
+        assert_eq!(super::parse_substitution("s/a"), None);
+    }
+}
@@ -10,5 +10,36 @@ pub fn confirm_command(app: &mut Application) -> Result {
       };
 
     command(app)?;
-    commands::application::switch_to_normal_mode(app)
+
+    // Commands can lead to other modes (e.g. to prompt for a password).
+    if let Mode::Confirm(_) = app.mode {
+        commands::application::switch_to_normal_mode(app)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::{self, Result};
+    use crate::models::application::{Application, Mode};
+    use crate::models::application::modes::ConfirmMode;
+
+    fn switch_to_insert_mode(app: &mut Application) -> Result {
+        app.mode = Mode::Insert;
+
+        Ok(())
+    }
+
+    #[test]
+    fn confirm_command_keeps_the_mode_entered_by_the_command() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.mode = Mode::Confirm(ConfirmMode::new(switch_to_insert_mode));
+        commands::confirm::confirm_command(&mut app).unwrap();
+
+        assert!(match app.mode {
+            Mode::Insert => true,
+            _ => false,
+        });
+    }
 }
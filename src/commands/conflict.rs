@@ -0,0 +1,111 @@
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::models::application::Application;
+use crate::util::{self, conflict};
+use scribe::buffer::{LineRange, Position};
+
+pub fn keep_ours(app: &mut Application) -> Result {
+    resolve(app, true, false)
+}
+
+pub fn keep_theirs(app: &mut Application) -> Result {
+    resolve(app, false, true)
+}
+
+pub fn keep_both(app: &mut Application) -> Result {
+    resolve(app, true, true)
+}
+
+// Replaces the conflict under the cursor (markers included)
+// with the content of the specified side(s) of the conflict.
+fn resolve(app: &mut Application, ours: bool, theirs: bool) -> Result {
+    app.ensure_writable()?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let conflicts = conflict::conflicts(&data);
+    let conflict = conflict::conflict_at(&conflicts, buffer.cursor.line)
+        .ok_or("No merge conflict found at the cursor")?;
+
+    let lines: Vec<&str> = data.lines().collect();
+    let mut content = String::new();
+    if ours {
+        for line in &lines[conflict.ours()] {
+            content.push_str(line);
+            content.push('\n');
+        }
+    }
+    if theirs {
+        for line in &lines[conflict.theirs()] {
+            content.push_str(line);
+            content.push('\n');
+        }
+    }
+
+    // The conflict may end on the buffer's last line, without a trailing
+    // newline. In that case, don't introduce one with our content.
+    let range = util::inclusive_range(&LineRange::new(conflict.start, conflict.end), buffer);
+    if range.end().line == conflict.end {
+        content.pop();
+    }
+
+    buffer.start_operation_group();
+    buffer.delete_range(range);
+    buffer.cursor.move_to(Position{ line: conflict.start, offset: 0 });
+    if !content.is_empty() {
+        buffer.insert(content);
+    }
+    buffer.end_operation_group();
+
+    commands::view::scroll_to_cursor(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::Application;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    const CONFLICTED: &str = "amp\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\neditor\n";
+
+    fn set_up_application(content: &str) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        buffer.cursor.move_to(Position{ line: 2, offset: 0 });
+        app.workspace.add_buffer(buffer);
+
+        app
+    }
+
+    #[test]
+    fn keep_ours_replaces_the_conflict_with_our_side() {
+        let mut app = set_up_application(CONFLICTED);
+        super::keep_ours(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\nours\neditor\n");
+    }
+
+    #[test]
+    fn keep_theirs_replaces_the_conflict_with_their_side() {
+        let mut app = set_up_application(CONFLICTED);
+        super::keep_theirs(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\ntheirs\neditor\n");
+    }
+
+    #[test]
+    fn keep_both_handles_conflicts_at_the_end_of_the_buffer() {
+        let mut app = set_up_application("amp\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch");
+        super::keep_both(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\nours\ntheirs");
+    }
+
+    #[test]
+    fn resolving_fails_outside_of_a_conflict() {
+        let mut app = set_up_application(CONFLICTED);
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 0 });
+
+        assert!(super::keep_ours(&mut app).is_err());
+    }
+}
@@ -1,20 +1,46 @@
+use crate::errors;
 use crate::errors::*;
 use crate::commands::{self, Result};
+use crate::util::delimiter;
 use crate::util::token::{Direction, adjacent_token_position};
 use crate::models::application::Application;
-use scribe::buffer::Position;
+use scribe::Buffer;
+use scribe::buffer::{Position, Token};
 use super::{application, buffer};
+use std::str::FromStr;
+use syntect::highlighting::ScopeSelectors;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn move_up(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_up();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let origin = *buffer.cursor;
+    buffer.cursor.move_up();
+    app.sticky_line_end = stick_to_line_end(buffer, origin, app.sticky_line_end);
+
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_down(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_down();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let origin = *buffer.cursor;
+    buffer.cursor.move_down();
+    app.sticky_line_end = stick_to_line_end(buffer, origin, app.sticky_line_end);
+
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
+// If the cursor was moved vertically from a position it reached by moving to
+// the end of a line, keep it at the end of the new line, too. Returns the
+// position to use for the next vertical movement, if it should also stick.
+fn stick_to_line_end(buffer: &mut Buffer, origin: Position, sticky_line_end: Option<Position>) -> Option<Position> {
+    if sticky_line_end != Some(origin) {
+        return None;
+    }
+
+    buffer.cursor.move_to_end_of_line();
+    Some(*buffer.cursor)
+}
+
 pub fn move_left(app: &mut Application) -> Result {
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_left();
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
@@ -35,11 +61,12 @@ pub fn move_to_start_of_line(app: &mut Application) -> Result {
 }
 
 pub fn move_to_end_of_line(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer()
-        .ok_or(BUFFER_MISSING)?
-        .cursor
-        .move_to_end_of_line();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    buffer.cursor.move_to_end_of_line();
+
+    // Subsequent vertical movements should stay at the end of the line.
+    app.sticky_line_end = Some(*buffer.cursor);
+
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
@@ -95,6 +122,57 @@ pub fn move_to_first_word_of_line(app: &mut Application) -> Result {
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
+/// Moves the cursor to the first non-whitespace character on the line or,
+/// if it's already there (or the line is blank), the start of the line.
+pub fn move_to_start_of_line_or_first_word(app: &mut Application) -> Result {
+    if let Some(buffer) = app.workspace.current_buffer() {
+        let data = buffer.data();
+        let line = buffer.cursor.line;
+        let offset = match line_content_bounds(&data, line) {
+            Some((start, _)) if buffer.cursor.offset != start => start,
+            _ => 0,
+        };
+
+        buffer.cursor.move_to(Position{ line, offset });
+    } else {
+        bail!(BUFFER_MISSING);
+    }
+
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+/// Moves the cursor past the last non-whitespace character on the line or,
+/// if it's already there (or the line is blank), the end of the line.
+pub fn move_to_end_of_line_or_last_word(app: &mut Application) -> Result {
+    if let Some(buffer) = app.workspace.current_buffer() {
+        let data = buffer.data();
+        let line = buffer.cursor.line;
+
+        match line_content_bounds(&data, line) {
+            Some((_, end)) if buffer.cursor.offset != end => {
+                buffer.cursor.move_to(Position{ line, offset: end });
+            },
+            _ => return move_to_end_of_line(app),
+        }
+    } else {
+        bail!(BUFFER_MISSING);
+    }
+
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+// Finds the grapheme offsets of the first non-whitespace character on the
+// specified line, and the position just after its last non-whitespace
+// character. Returns None if the line is blank or doesn't exist.
+fn line_content_bounds(data: &str, line: usize) -> Option<(usize, usize)> {
+    let graphemes: Vec<&str> = data.lines().nth(line)?.graphemes(true).collect();
+    let is_content = |grapheme: &&str| !grapheme.chars().all(char::is_whitespace);
+    let start = graphemes.iter().position(&is_content)?;
+    let end = graphemes.iter().rposition(&is_content)? + 1;
+
+    Some((start, end))
+}
+
 pub fn insert_at_end_of_line(app: &mut Application) -> Result {
     move_to_end_of_line(app)?;
     application::switch_to_insert_mode(app)?;
@@ -146,8 +224,11 @@ pub fn insert_with_newline_above(app: &mut Application) -> Result {
 
 pub fn move_to_start_of_previous_token(app: &mut Application) -> Result {
     if let Some(buffer) = app.workspace.current_buffer() {
+        let line_index = app.view.line_index(buffer);
         let position = adjacent_token_position(
             buffer,
+            &mut app.lex_cache.borrow_mut(),
+            &mut line_index.borrow_mut(),
             false,
             Direction::Backward
         ).ok_or("Couldn't find previous token")?;
@@ -161,8 +242,11 @@ pub fn move_to_start_of_previous_token(app: &mut Application) -> Result {
 
 pub fn move_to_start_of_next_token(app: &mut Application) -> Result {
     if let Some(buffer) = app.workspace.current_buffer() {
+        let line_index = app.view.line_index(buffer);
         let position = adjacent_token_position(
             buffer,
+            &mut app.lex_cache.borrow_mut(),
+            &mut line_index.borrow_mut(),
             false,
             Direction::Forward
         ).ok_or("Couldn't find next token")?;
@@ -176,8 +260,11 @@ pub fn move_to_start_of_next_token(app: &mut Application) -> Result {
 
 pub fn move_to_end_of_current_token(app: &mut Application) -> Result {
     if let Some(buffer) = app.workspace.current_buffer() {
+        let line_index = app.view.line_index(buffer);
         let position = adjacent_token_position(
             buffer,
+            &mut app.lex_cache.borrow_mut(),
+            &mut line_index.borrow_mut(),
             true,
             Direction::Forward
         ).ok_or("Couldn't find next token")?;
@@ -194,11 +281,73 @@ pub fn append_to_current_token(app: &mut Application) -> Result {
     application::switch_to_insert_mode(app)
 }
 
+/// Moves to the next definition (e.g. a function or a heading), using the
+/// syntax scopes configured for the current buffer's file type.
+pub fn move_to_next_definition(app: &mut Application) -> Result {
+    let positions = definition_positions(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let position = positions
+        .into_iter()
+        .find(|position| *position > *buffer.cursor)
+        .ok_or("Couldn't find a definition after the cursor")?;
+    buffer.cursor.move_to(position);
+
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+/// Moves to the previous definition (e.g. a function or a heading), using
+/// the syntax scopes configured for the current buffer's file type.
+pub fn move_to_previous_definition(app: &mut Application) -> Result {
+    let positions = definition_positions(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let position = positions
+        .into_iter()
+        .rev()
+        .find(|position| *position < *buffer.cursor)
+        .ok_or("Couldn't find a definition before the cursor")?;
+    buffer.cursor.move_to(position);
+
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+/// Moves between a bracket, brace or parenthesis and its counterpart.
+pub fn move_to_matching_delimiter(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let position = delimiter::matching_delimiter(&buffer.data(), &*buffer.cursor)
+        .ok_or("Couldn't find a matching delimiter")?;
+    buffer.cursor.move_to(position);
+
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+// Where each definition in the current buffer starts. Definitions can span
+// several tokens (e.g. a heading's marker and its text), so only the first
+// matching token on each line is used.
+fn definition_positions(app: &mut Application) -> errors::Result<Vec<Position>> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let selectors = ScopeSelectors::from_str(&app.preferences.borrow().definitions(buffer.path.as_ref()))
+        .map_err(|_| "Couldn't parse the definitions preference")?;
+    let tokens = buffer.tokens().chain_err(|| "No tokens available for the current buffer")?;
+
+    let mut positions: Vec<Position> = Vec::new();
+    for token in tokens.iter() {
+        if let Token::Lexeme(lexeme) = token {
+            let new_line = positions.last().map(|position| position.line != lexeme.position.line).unwrap_or(true);
+            if new_line && selectors.does_match(lexeme.scope.as_slice()).is_some() {
+                positions.push(lexeme.position);
+            }
+        }
+    }
+
+    Ok(positions)
+}
+
 #[cfg(test)]
 mod tests {
     use scribe::Buffer;
     use scribe::buffer::Position;
     use crate::models::application::Application;
+    use std::path::PathBuf;
 
     #[test]
     fn move_to_first_word_of_line_works() {
@@ -223,6 +372,32 @@ mod tests {
                    });
     }
 
+    #[test]
+    fn move_to_start_of_line_or_first_word_toggles_between_both() {
+        let mut app = set_up_application("    amp  ");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 6 });
+
+        super::move_to_start_of_line_or_first_word(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 4 });
+
+        super::move_to_start_of_line_or_first_word(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 0 });
+
+        super::move_to_start_of_line_or_first_word(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 4 });
+    }
+
+    #[test]
+    fn move_to_end_of_line_or_last_word_skips_trailing_whitespace_first() {
+        let mut app = set_up_application("    amp  ");
+
+        super::move_to_end_of_line_or_last_word(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 7 });
+
+        super::move_to_end_of_line_or_last_word(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 9 });
+    }
+
     #[test]
     fn move_to_start_of_previous_token_works() {
         // Set up the application.
@@ -267,6 +442,22 @@ mod tests {
                    });
     }
 
+    #[test]
+    fn token_motions_cross_runs_of_blank_lines_longer_than_a_lexed_chunk() {
+        let mut app = set_up_application(&format!("amp  {}  editor", "\n".repeat(250)));
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 250, offset: 2 });
+        super::move_to_start_of_previous_token(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 0 });
+
+        super::move_to_start_of_next_token(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 250, offset: 2 });
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 3 });
+        super::move_to_end_of_current_token(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 250, offset: 2 });
+    }
+
     #[test]
     fn move_to_start_of_next_token_works() {
         // Set up the application.
@@ -373,6 +564,68 @@ mod tests {
         });
     }
 
+    #[test]
+    fn move_down_keeps_the_cursor_at_the_end_of_lines_after_moving_to_one() {
+        let mut app = set_up_application("amp\nx\neditor");
+
+        super::move_to_end_of_line(&mut app).unwrap();
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 1 });
+
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 2, offset: 6 });
+    }
+
+    #[test]
+    fn move_down_restores_the_original_offset_when_not_at_the_end_of_a_line() {
+        let mut app = set_up_application("amp\nx\neditor");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: 2 });
+
+        super::move_down(&mut app).unwrap();
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 2, offset: 2 });
+    }
+
+    #[test]
+    fn move_up_stops_sticking_to_the_end_of_lines_after_other_movements() {
+        let mut app = set_up_application("editor\nx\namp");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 2, offset: 0 });
+
+        super::move_to_end_of_line(&mut app).unwrap();
+        super::move_left(&mut app).unwrap();
+        super::move_up(&mut app).unwrap();
+        super::move_up(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 2 });
+    }
+
+    #[test]
+    fn move_to_next_and_previous_definition_use_syntax_scopes() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fn amp() {}\n\nfn editor() {}\n");
+        buffer.path = Some(PathBuf::from("main.rs"));
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        app.workspace.add_buffer(buffer);
+
+        super::move_to_next_definition(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 2, offset: 3 });
+        assert!(super::move_to_next_definition(&mut app).is_err());
+
+        super::move_to_previous_definition(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 3 });
+    }
+
+    #[test]
+    fn move_to_matching_delimiter_toggles_between_the_pair() {
+        let mut app = set_up_application("amp {\n  (editor)\n}");
+
+        super::move_to_matching_delimiter(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 2, offset: 0 });
+
+        super::move_to_matching_delimiter(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position{ line: 0, offset: 4 });
+    }
+
     fn set_up_application(content: &str) -> Application {
         let mut app = Application::new(&Vec::new()).unwrap();
         let mut buffer = Buffer::new();
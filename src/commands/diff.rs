@@ -0,0 +1,317 @@
+use crate::errors;
+use crate::errors::*;
+use crate::commands::Result;
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{DiffMode, DiffSource};
+use crate::util::snapshot;
+use scribe::Buffer;
+use scribe::buffer::{Position, Range};
+use std::fs;
+use std::ops;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub fn compare_with_disk(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    let disk_data = fs::read_to_string(&path)
+        .chain_err(|| "Couldn't read the buffer's file from disk")?;
+    let title = path.to_string_lossy().into_owned();
+
+    app.mode = Mode::Diff(DiffMode::new(
+        DiffSource::Disk,
+        title.clone(),
+        &buffer.data(),
+        format!("{} (on disk)", title),
+        &disk_data
+    ));
+
+    Ok(())
+}
+
+pub fn compare_with_next_buffer(app: &mut Application) -> Result {
+    let (left_id, left_title, left_data) = buffer_details(app)?;
+    app.workspace.next_buffer();
+    let right = buffer_details(app);
+    app.workspace.previous_buffer();
+    let (right_id, right_title, right_data) = right?;
+
+    if left_id == right_id {
+        bail!("There's no other buffer to compare against");
+    }
+
+    app.mode = Mode::Diff(DiffMode::new(
+        DiffSource::NextBuffer,
+        left_title,
+        &left_data,
+        right_title,
+        &right_data
+    ));
+
+    Ok(())
+}
+
+pub fn next_change(app: &mut Application) -> Result {
+    if let Mode::Diff(ref mut mode) = app.mode {
+        if !mode.next_change() {
+            bail!("No more changes below");
+        }
+    } else {
+        bail!("Can't navigate changes outside of diff mode.");
+    }
+
+    Ok(())
+}
+
+pub fn previous_change(app: &mut Application) -> Result {
+    if let Mode::Diff(ref mut mode) = app.mode {
+        if !mode.previous_change() {
+            bail!("No more changes above");
+        }
+    } else {
+        bail!("Can't navigate changes outside of diff mode.");
+    }
+
+    Ok(())
+}
+
+pub fn scroll_up(app: &mut Application) -> Result {
+    if let Mode::Diff(ref mut mode) = app.mode {
+        mode.scroll_up(1);
+    } else {
+        bail!("Can't scroll outside of diff mode.");
+    }
+
+    Ok(())
+}
+
+pub fn scroll_down(app: &mut Application) -> Result {
+    if let Mode::Diff(ref mut mode) = app.mode {
+        mode.scroll_down(1);
+    } else {
+        bail!("Can't scroll outside of diff mode.");
+    }
+
+    Ok(())
+}
+
+/// Replaces the selected change in the current (left-hand) buffer
+/// with its counterpart from the right-hand side of the diff.
+pub fn copy_change_to_left(app: &mut Application) -> Result {
+    app.ensure_writable()?;
+    let (lines, content) = if let Mode::Diff(ref mode) = app.mode {
+        let hunk = mode.selected_hunk().ok_or("No change selected")?;
+        (hunk.left.clone(), mode.right[hunk.right.clone()].to_vec())
+    } else {
+        bail!("Can't copy changes outside of diff mode.");
+    };
+
+    replace_lines(
+        app.workspace.current_buffer().ok_or(BUFFER_MISSING)?,
+        &lines,
+        &content
+    );
+
+    refresh(app)
+}
+
+/// Replaces the selected change in the right-hand buffer with its
+/// counterpart from the left. On-disk content can't be changed this way.
+pub fn copy_change_to_right(app: &mut Application) -> Result {
+    let (lines, content) = if let Mode::Diff(ref mode) = app.mode {
+        match mode.source {
+            DiffSource::Disk => bail!("Only buffers can be changed; save to update the on-disk version"),
+            DiffSource::Earlier(_) | DiffSource::Snapshot(_) => {
+                bail!("Earlier versions of the buffer can't be changed")
+            },
+            #[cfg(feature = "git")]
+            DiffSource::Revision(..) => bail!("Earlier versions of the buffer can't be changed"),
+            DiffSource::NextBuffer => (),
+        }
+
+        let hunk = mode.selected_hunk().ok_or("No change selected")?;
+        (hunk.right.clone(), mode.left[hunk.left.clone()].to_vec())
+    } else {
+        bail!("Can't copy changes outside of diff mode.");
+    };
+
+    app.workspace.next_buffer();
+    let writable = app.ensure_writable();
+    if let (Ok(_), Some(buffer)) = (&writable, app.workspace.current_buffer()) {
+        replace_lines(buffer, &lines, &content);
+    }
+    app.workspace.previous_buffer();
+    writable?;
+
+    refresh(app)
+}
+
+/// Replaces the current (left-hand) buffer's content with the right-hand
+/// side of the diff, as a single change (e.g. to restore an earlier version).
+pub fn copy_all_changes_to_left(app: &mut Application) -> Result {
+    app.ensure_writable()?;
+    let content = right_content(app)?;
+    snapshot::restore(app.workspace.current_buffer().ok_or(BUFFER_MISSING)?, &content);
+
+    refresh(app)
+}
+
+// Re-reads both sides of the diff and updates the mode with the results.
+fn refresh(app: &mut Application) -> Result {
+    let (_, _, left_data) = buffer_details(app)?;
+    let right_data = right_content(app)?;
+
+    if let Mode::Diff(ref mut mode) = app.mode {
+        mode.update(&left_data, &right_data);
+    }
+
+    Ok(())
+}
+
+// Reads the current content of the right-hand side of the diff.
+fn right_content(app: &mut Application) -> errors::Result<String> {
+    let source = match app.mode {
+        Mode::Diff(ref mode) => mode.source.clone(),
+        _ => bail!("Can't compare changes outside of diff mode."),
+    };
+    let left_id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id;
+    let right_data = match source {
+        DiffSource::Disk => {
+            let path = app.workspace
+                .current_buffer()
+                .ok_or(BUFFER_MISSING)?
+                .path
+                .clone()
+                .ok_or(BUFFER_PATH_MISSING)?;
+            fs::read_to_string(path).chain_err(|| "Couldn't read the buffer's file from disk")?
+        },
+        DiffSource::NextBuffer => {
+            app.workspace.next_buffer();
+            let right = buffer_details(app);
+            app.workspace.previous_buffer();
+            right?.2
+        },
+        DiffSource::Earlier(time) => left_id
+            .and_then(|id| app.snapshots.content_at(id, time))
+            .map(String::from)
+            .ok_or("The buffer's earlier version is no longer available")?,
+        DiffSource::Snapshot(path) => fs::read_to_string(path)
+            .chain_err(|| "Couldn't read the saved version")?,
+        #[cfg(feature = "git")]
+        DiffSource::Revision(_, content) => content,
+    };
+
+    Ok(right_data)
+}
+
+fn buffer_details(app: &mut Application) -> errors::Result<(Option<usize>, String, String)> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let title = buffer.path
+        .as_ref()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|| String::from("[untitled]"));
+
+    Ok((buffer.id, title, buffer.data()))
+}
+
+// Replaces a half-open range of lines with the provided lines.
+fn replace_lines(buffer: &mut Buffer, lines: &ops::Range<usize>, content: &[String]) {
+    let line_count = buffer.line_count();
+    let mut text: String = content.iter().map(|line| format!("{}\n", line)).collect();
+
+    buffer.start_operation_group();
+    if lines.end < line_count {
+        if lines.start < lines.end {
+            buffer.delete_range(Range::new(
+                Position{ line: lines.start, offset: 0 },
+                Position{ line: lines.end, offset: 0 }
+            ));
+        }
+        buffer.cursor.move_to(Position{ line: lines.start, offset: 0 });
+    } else {
+        // The range includes the last line, which doesn't have a trailing
+        // newline; the inserted content shouldn't introduce one, either.
+        let last_line = line_count - 1;
+        let last_line_length = buffer.data()
+            .lines()
+            .nth(last_line)
+            .map(|line| line.graphemes(true).count())
+            .unwrap_or(0);
+        let end_of_buffer = Position{ line: last_line, offset: last_line_length };
+        text.pop();
+
+        if lines.start < lines.end {
+            buffer.delete_range(Range::new(
+                Position{ line: lines.start, offset: 0 },
+                end_of_buffer
+            ));
+            buffer.cursor.move_to(Position{ line: lines.start, offset: 0 });
+        } else {
+            text.insert(0, '\n');
+            buffer.cursor.move_to(end_of_buffer);
+        }
+    }
+    if !text.is_empty() {
+        buffer.insert(text);
+    }
+    buffer.end_operation_group();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::{Application, Mode};
+    use scribe::Buffer;
+
+    fn set_up_application(left: &str, right: &str) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(right);
+        app.workspace.add_buffer(buffer);
+        let mut buffer = Buffer::new();
+        buffer.insert(left);
+        app.workspace.add_buffer(buffer);
+
+        // The left-hand buffer is selected, and wraps around to the right.
+        super::compare_with_next_buffer(&mut app).unwrap();
+
+        app
+    }
+
+    #[test]
+    fn compare_with_next_buffer_switches_to_diff_mode() {
+        let app = set_up_application("amp\neditor\n", "amp\nrust\n");
+
+        match app.mode {
+            Mode::Diff(ref mode) => assert_eq!(mode.hunks.len(), 1),
+            _ => panic!("Not in diff mode"),
+        }
+    }
+
+    #[test]
+    fn copy_change_to_left_updates_the_current_buffer() {
+        let mut app = set_up_application("amp\neditor\ntext\n", "amp\nrust\ntext\n");
+        super::copy_change_to_left(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\nrust\ntext\n");
+        match app.mode {
+            Mode::Diff(ref mode) => assert!(mode.hunks.is_empty()),
+            _ => panic!("Not in diff mode"),
+        }
+    }
+
+    #[test]
+    fn copy_change_to_left_handles_last_lines_without_trailing_newlines() {
+        let mut app = set_up_application("amp\neditor", "amp\nrust\nlang");
+        super::copy_change_to_left(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\nrust\nlang");
+    }
+
+    #[test]
+    fn copy_change_to_right_updates_the_next_buffer() {
+        let mut app = set_up_application("amp\neditor\ntext\n", "amp\nrust\ntext\n");
+        super::copy_change_to_right(&mut app).unwrap();
+        app.workspace.next_buffer();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor\ntext\n");
+    }
+}
@@ -0,0 +1,81 @@
+use crate::commands::Result;
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::HexMode;
+
+pub fn scroll_up(app: &mut Application) -> Result {
+    scroll(app, |mode, _| mode.scroll_up(1))
+}
+
+pub fn scroll_down(app: &mut Application) -> Result {
+    scroll(app, |mode, _| mode.scroll_down(1))
+}
+
+pub fn scroll_page_up(app: &mut Application) -> Result {
+    scroll(app, |mode, page_height| mode.scroll_up(page_height))
+}
+
+pub fn scroll_page_down(app: &mut Application) -> Result {
+    scroll(app, |mode, page_height| mode.scroll_down(page_height))
+}
+
+pub fn scroll_to_start(app: &mut Application) -> Result {
+    scroll(app, |mode, _| mode.scroll_to_start())
+}
+
+pub fn scroll_to_end(app: &mut Application) -> Result {
+    scroll(app, |mode, _| mode.scroll_to_end())
+}
+
+fn scroll<F: FnOnce(&mut HexMode, usize)>(app: &mut Application, f: F) -> Result {
+    let page_height = app.view.page_height();
+    if let Mode::Hex(ref mut mode) = app.mode {
+        f(mode, page_height);
+    } else {
+        bail!("Can't scroll outside of hex mode.");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::{Application, Mode};
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn opening_a_binary_file_shows_it_in_hex_mode() {
+        let path = env::temp_dir().join("amp_hex_view_test.bin");
+        fs::write(&path, vec![0; 64]).unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+
+        assert!(crate::util::open_hex_view(&path, &mut app));
+        assert!(app.workspace.current_buffer().is_none());
+        commands::hex::scroll_to_end(&mut app).unwrap();
+        match app.mode {
+            Mode::Hex(ref mode) => assert_eq!(mode.line_offset, 3),
+            _ => panic!("Not in hex mode"),
+        }
+
+        assert!(!crate::util::open_hex_view(&env::current_dir().unwrap().join("Cargo.toml"), &mut app));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn opening_a_file_with_invalid_utf8_past_its_start_shows_it_in_hex_mode() {
+        let path = env::temp_dir().join("amp_hex_view_late_binary_test.txt");
+        let mut data = vec![b'a'; 10_000];
+        data.push(0xff);
+        fs::write(&path, &data).unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+
+        crate::util::open_buffer(&path, &mut app).unwrap();
+        assert!(app.workspace.current_buffer().is_none());
+        match app.mode {
+            Mode::Hex(ref mode) => assert_eq!(mode.data.len(), 10_001),
+            _ => panic!("Not in hex mode"),
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -3,6 +3,7 @@ use crate::input::Key;
 use crate::commands::{self, Result};
 use crate::models::application::{Application, Mode};
 use scribe::buffer::Position;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn accept_input(app: &mut Application) -> Result {
     if let Mode::LineJump(ref mode) = app.mode {
@@ -37,7 +38,7 @@ pub fn accept_input(app: &mut Application) -> Result {
                     .map(|line| line.to_string())
                     .ok_or("Couldn't find the specified line")?;
 
-                target_position.offset = line_content.len();
+                target_position.offset = line_content.graphemes(true).count();
                 buffer.cursor.move_to(target_position);
             }
         }
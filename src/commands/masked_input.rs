@@ -0,0 +1,88 @@
+use crate::commands::Result;
+use crate::input::Key;
+use crate::models::application::{Application, Mode};
+use std::mem;
+
+/// Leaves the prompt, handing the input to the callback it was opened with.
+pub fn accept(app: &mut Application) -> Result {
+    let mode = match mem::replace(&mut app.mode, Mode::Normal) {
+        Mode::MaskedInput(mode) => mode,
+        mode => {
+            app.mode = mode;
+            bail!("Can't accept input outside of masked input mode.");
+        },
+    };
+
+    (mode.callback)(app, &mode.input)
+}
+
+pub fn push_char(app: &mut Application) -> Result {
+    let key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
+
+    if let Key::Char(c) = *key {
+        if let Mode::MaskedInput(ref mut mode) = app.mode {
+            mode.input.push(c)
+        } else {
+            bail!("Can't push input character outside of masked input mode")
+        }
+    } else {
+        bail!("Last key press wasn't a character")
+    }
+
+    Ok(())
+}
+
+pub fn pop_char(app: &mut Application) -> Result {
+    if let Mode::MaskedInput(ref mut mode) = app.mode {
+        mode.input.pop();
+    } else {
+        bail!("Can't pop input character outside of masked input mode")
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::{Application, Mode};
+    use crate::models::application::modes::MaskedInputMode;
+    use scribe::Buffer;
+    use std::path::PathBuf;
+
+    #[test]
+    fn accept_hands_the_input_to_the_callback() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.mode = Mode::MaskedInput(MaskedInputMode::new(String::from("Token"), |app, input| {
+            app.message = Some(format!("{} characters", input.len()));
+
+            Ok(())
+        }));
+        for c in "amp!".chars() {
+            app.view.last_key = Some(Key::Char(c));
+            commands::masked_input::push_char(&mut app).unwrap();
+        }
+        commands::masked_input::pop_char(&mut app).unwrap();
+        commands::masked_input::accept(&mut app).unwrap();
+
+        assert_eq!(app.message, Some(String::from("3 characters")));
+        assert!(match app.mode { Mode::Normal => true, _ => false });
+    }
+
+    #[test]
+    fn saving_an_encrypted_buffer_prompts_for_a_passphrase() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n");
+        buffer.path = Some(PathBuf::from("/amp/secrets.txt.gpg"));
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::save(&mut app).unwrap();
+        match app.mode {
+            Mode::MaskedInput(ref mode) => assert_eq!(mode.prompt, "Passphrase for secrets.txt.gpg"),
+            _ => panic!("Not in masked input mode"),
+        }
+        assert!(app.workspace.current_buffer().unwrap().modified());
+    }
+}
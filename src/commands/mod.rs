@@ -1,26 +1,38 @@
 use crate::errors;
 use crate::models::application::Application;
+use crate::plugins;
 use std::collections::HashMap;
 
 pub mod application;
 pub mod buffer;
+pub mod character_input;
+pub mod command_line;
 pub mod confirm;
+pub mod conflict;
 pub mod cursor;
-pub mod git;
+pub mod diff;
+pub mod hex;
 pub mod jump;
 pub mod line_jump;
+pub mod masked_input;
 pub mod path;
 pub mod preferences;
+pub mod replace;
 pub mod search;
 pub mod selection;
 pub mod search_select;
+pub mod spelling;
 pub mod view;
 pub mod workspace;
 
 pub type Command = fn(&mut Application) -> Result;
 pub type Result = errors::Result<()>;
 
+/// Built-in commands, along with those registered by plugins.
 pub fn hash_map() -> HashMap<&'static str, Command> {
-    include!(concat!(env!("OUT_DIR"), "/hash_map"))
+    let mut commands: HashMap<&'static str, Command> = include!(concat!(env!("OUT_DIR"), "/hash_map"));
+    commands.extend(plugins::commands());
+
+    commands
 }
 
@@ -1,8 +1,12 @@
 use crate::errors::*;
 use crate::commands::{self, Result};
 use crate::input::Key;
-use crate::models::application::{Application, Mode};
-use std::path::PathBuf;
+use crate::models::application::{run_hook, Application, Hook, Mode};
+use crate::models::application::modes::PathAction;
+use crate::util::{self, completion};
+use scribe::Buffer;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
 
 pub fn push_char(app: &mut Application) -> Result {
     let last_key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
@@ -27,23 +31,65 @@ pub fn pop_char(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn complete(app: &mut Application) -> Result {
+    if let Mode::Path(ref mut mode) = app.mode {
+        mode.complete();
+    } else {
+        bail!("Cannot complete path outside of path mode");
+    }
+    Ok(())
+}
+
+pub fn toggle_hidden_completions(app: &mut Application) -> Result {
+    if let Mode::Path(ref mut mode) = app.mode {
+        mode.completions.toggle_hidden();
+    } else {
+        bail!("Cannot toggle hidden completions outside of path mode");
+    }
+    Ok(())
+}
+
 pub fn accept_path(app: &mut Application) -> Result {
-    let save_on_accept =
-        if let Mode::Path(ref mut mode) = app.mode {
-            let current_buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-            let path_name = mode.input.clone();
-            if path_name.is_empty() {
+    let (path, action, save_on_accept) =
+        if let Mode::Path(ref mode) = app.mode {
+            if mode.input.is_empty() {
                 bail!("Please provide a non-empty path")
             }
-            current_buffer.path = Some(PathBuf::from(path_name));
-            mode.save_on_accept
+            (completion::expand_home(&mode.input), mode.action, mode.save_on_accept)
         } else {
             bail!("Cannot accept path outside of path mode");
         };
 
-    app.workspace.update_current_syntax().chain_err(||
-        "Failed to update buffer's syntax definition"
-    )?;
+    match action {
+        PathAction::SetPath | PathAction::Rename => {
+            let current_buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            if action == PathAction::Rename {
+                rename_file(current_buffer, path)?;
+            } else {
+                current_buffer.path = Some(path);
+            }
+            app.workspace.update_current_syntax().chain_err(||
+                "Failed to update buffer's syntax definition"
+            )?;
+            util::apply_syntax_preference(&mut app.workspace, &app.preferences.borrow())?;
+        },
+        PathAction::CreateFile => {
+            create_file_at(&path)?;
+            app.workspace
+                .open_buffer(&path)
+                .chain_err(|| "Couldn't open a buffer for the new file")?;
+            util::apply_syntax_preference(&mut app.workspace, &app.preferences.borrow())?;
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            app.view.initialize_buffer(buffer)?;
+            util::record_recent_file(app)?;
+            run_hook(app, Hook::BufferOpen)?;
+        },
+        PathAction::CreateDirectory => {
+            create_directory_at(&path)?;
+            app.message = Some(format!("Created {}", path.to_string_lossy()));
+        },
+    }
+    app.open_mode_index.invalidate();
     app.mode = Mode::Normal;
 
     if save_on_accept {
@@ -53,12 +99,74 @@ pub fn accept_path(app: &mut Application) -> Result {
     }
 }
 
+/// Creates the directory named by the prompt's input, without leaving
+/// the prompt, so that a file can then be placed within it.
+pub fn create_directory(app: &mut Application) -> Result {
+    if let Mode::Path(ref mut mode) = app.mode {
+        if mode.input.is_empty() {
+            bail!("Please provide a non-empty path")
+        }
+        let path = completion::expand_home(&mode.input);
+        create_directory_at(&path)?;
+
+        if !mode.input.ends_with('/') {
+            mode.input.push('/');
+        }
+        mode.completions.clear();
+        app.message = Some(format!("Created {}", path.to_string_lossy()));
+    } else {
+        bail!("Cannot create directory outside of path mode");
+    }
+
+    Ok(())
+}
+
+// Moves the buffer's file to the specified path, and points the buffer at it.
+// Existing files are never overwritten, and missing parent directories are
+// reported rather than created, so that typos don't scatter files around.
+fn rename_file(buffer: &mut Buffer, path: PathBuf) -> Result {
+    let current_path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    if path.exists() {
+        bail!("A file already exists at that path");
+    }
+    fs::rename(&current_path, &path).chain_err(|| "Couldn't rename the buffer's file")?;
+    buffer.path = Some(path);
+
+    Ok(())
+}
+
+// Creates an empty file, along with any missing parent directories.
+fn create_file_at(path: &Path) -> Result {
+    if path.exists() {
+        bail!("A file already exists at that path");
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).chain_err(|| "Couldn't create the file's parent directories")?;
+    }
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .chain_err(|| "Couldn't create the file")?;
+
+    Ok(())
+}
+
+// Creates a directory, along with any missing parent directories.
+fn create_directory_at(path: &Path) -> Result {
+    if path.exists() {
+        bail!("Something already exists at that path");
+    }
+    fs::create_dir_all(path).chain_err(|| "Couldn't create the directory")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::commands;
     use crate::models::Application;
     use crate::models::application::Mode;
     use scribe::Buffer;
+    use std::fs;
     use std::path::{PathBuf, Path};
 
     #[test]
@@ -127,6 +235,114 @@ mod tests {
 
     }
 
+    #[test]
+    fn accept_path_moves_the_buffer_file_when_renaming() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let original_path = PathBuf::from(concat!(env!("OUT_DIR"), "/rename_original"));
+        let new_path = PathBuf::from(concat!(env!("OUT_DIR"), "/rename_new"));
+        fs::write(&original_path, "amp").unwrap();
+        fs::remove_file(&new_path).ok();
+        app.workspace.open_buffer(&original_path).unwrap();
+
+        commands::buffer::rename(&mut app).unwrap();
+        if let Mode::Path(ref mut mode) = app.mode {
+            mode.input = new_path.to_string_lossy().into();
+        }
+        super::accept_path(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().path, Some(new_path.clone()));
+        assert!(!original_path.exists());
+        assert_eq!(fs::read_to_string(&new_path).unwrap(), "amp");
+    }
+
+    #[test]
+    fn accept_path_doesnt_overwrite_existing_files_when_renaming() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let original_path = PathBuf::from(concat!(env!("OUT_DIR"), "/rename_conflict_original"));
+        let existing_path = PathBuf::from(concat!(env!("OUT_DIR"), "/rename_conflict_existing"));
+        fs::write(&original_path, "amp").unwrap();
+        fs::write(&existing_path, "editor").unwrap();
+        app.workspace.open_buffer(&original_path).unwrap();
+
+        commands::buffer::rename(&mut app).unwrap();
+        if let Mode::Path(ref mut mode) = app.mode {
+            mode.input = existing_path.to_string_lossy().into();
+        }
+
+        assert!(super::accept_path(&mut app).is_err());
+        assert_eq!(fs::read_to_string(&existing_path).unwrap(), "editor");
+        assert!(original_path.exists());
+    }
+
+    #[test]
+    fn accept_path_creates_and_opens_files_with_missing_directories() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let directory = PathBuf::from(concat!(env!("OUT_DIR"), "/create_file"));
+        let path = directory.join("nested/new_file.rs");
+        fs::remove_dir_all(&directory).ok();
+
+        commands::workspace::create_file(&mut app).unwrap();
+        if let Mode::Path(ref mut mode) = app.mode {
+            mode.input = path.to_string_lossy().into();
+        }
+        super::accept_path(&mut app).unwrap();
+
+        assert!(path.is_file());
+        assert_eq!(app.workspace.current_buffer().unwrap().path, Some(path));
+    }
+
+    #[test]
+    fn accept_path_doesnt_create_files_over_existing_ones() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+
+        commands::workspace::create_file(&mut app).unwrap();
+        if let Mode::Path(ref mut mode) = app.mode {
+            mode.input = String::from("Cargo.toml");
+        }
+
+        assert!(super::accept_path(&mut app).is_err());
+        assert!(app.workspace.current_buffer().is_none());
+    }
+
+    #[test]
+    fn accept_path_creates_directories() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/create_directory/nested"));
+        fs::remove_dir_all(&path).ok();
+
+        commands::workspace::create_directory(&mut app).unwrap();
+        if let Mode::Path(ref mut mode) = app.mode {
+            mode.input = path.to_string_lossy().into();
+        }
+        super::accept_path(&mut app).unwrap();
+
+        assert!(path.is_dir());
+        if let Mode::Normal = app.mode {
+        } else {
+            panic!("Not in normal mode");
+        }
+    }
+
+    #[test]
+    fn create_directory_keeps_the_prompt_open() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/create_directory_from_prompt"));
+        fs::remove_dir_all(&path).ok();
+
+        commands::workspace::create_file(&mut app).unwrap();
+        if let Mode::Path(ref mut mode) = app.mode {
+            mode.input = path.to_string_lossy().into();
+        }
+        super::create_directory(&mut app).unwrap();
+
+        assert!(path.is_dir());
+        if let Mode::Path(ref mode) = app.mode {
+            assert_eq!(mode.input, format!("{}/", path.to_string_lossy()));
+        } else {
+            panic!("Not in path mode");
+        }
+    }
+
     #[test]
     fn accept_path_updates_syntax() {
         let mut app = Application::new(&Vec::new()).unwrap();
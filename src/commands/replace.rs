@@ -0,0 +1,179 @@
+use crate::errors::*;
+use crate::commands::Result;
+use crate::models::application::{Application, Mode};
+use crate::util::workspace_replace::{self, FileOccurrences};
+use std::fs;
+
+pub fn select_next(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        mode.select_next();
+    } else {
+        bail!("Can't change selection outside of replace mode.");
+    }
+
+    Ok(())
+}
+
+pub fn select_previous(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        mode.select_previous();
+    } else {
+        bail!("Can't change selection outside of replace mode.");
+    }
+
+    Ok(())
+}
+
+pub fn toggle_occurrence(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        mode.toggle_selected();
+    } else {
+        bail!("Can't toggle occurrences outside of replace mode.");
+    }
+
+    Ok(())
+}
+
+pub fn toggle_file(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        mode.toggle_file();
+    } else {
+        bail!("Can't toggle occurrences outside of replace mode.");
+    }
+
+    Ok(())
+}
+
+pub fn toggle_preview(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        mode.toggle_preview();
+    } else {
+        bail!("Can't preview changes outside of replace mode.");
+    }
+
+    Ok(())
+}
+
+/// Replaces the selected occurrences, either in all of their files or in none
+/// of them: every file is checked for changes made since its occurrences were
+/// found before anything is written, and files that were already written are
+/// restored if a later one can't be. Open buffers are changed in memory
+/// (as a single undoable operation each), and are left to be saved.
+pub fn apply(app: &mut Application) -> Result {
+    let (rename, files): (bool, Vec<FileOccurrences>) = if let Mode::Replace(ref mode) = app.mode {
+        (mode.rename, mode.files.iter().filter(|file| file.selected_count() > 0).cloned().collect())
+    } else {
+        bail!("Can't apply replacements outside of replace mode.");
+    };
+    if files.is_empty() {
+        bail!("No occurrences are selected");
+    }
+
+    for file in &files {
+        let mut ids = Vec::new();
+        app.with_buffer_at(&file.path, |buffer| ids.extend(buffer.id));
+        if ids.iter().any(|id| app.read_only_buffers.contains(id)) {
+            bail!("{} is open read-only; nothing was changed", file.label);
+        }
+    }
+
+    let buffers = app.buffer_contents();
+    for file in &files {
+        let content = match buffers.get(&file.path) {
+            Some(content) => content.clone(),
+            None => fs::read_to_string(&file.path)
+                .chain_err(|| format!("Couldn't read {}; nothing was changed", file.label))?,
+        };
+        if content != file.content {
+            bail!("{} has changed since its occurrences were found; nothing was changed", file.label);
+        }
+    }
+
+    // Writing files is the only step that can fail, so it's done before
+    // touching any buffers, which can then be changed unconditionally.
+    let mut written: Vec<&FileOccurrences> = Vec::new();
+    for file in files.iter().filter(|file| !buffers.contains_key(&file.path)) {
+        if let Err(error) = fs::write(&file.path, file.replaced()) {
+            for written_file in written {
+                let _ = fs::write(&written_file.path, &written_file.content);
+            }
+            bail!("Couldn't write {} ({}); nothing was changed", file.label, error);
+        }
+        written.push(file);
+    }
+
+    for file in files.iter().filter(|file| buffers.contains_key(&file.path)) {
+        app.with_buffer_at(&file.path, |buffer| workspace_replace::replace_in_buffer(buffer, &file.occurrences));
+    }
+
+    let count: usize = files.iter().map(|file| file.selected_count()).sum();
+    app.mode = Mode::Normal;
+    app.message = Some(format!(
+        "{} {} occurrence(s) in {} file(s)",
+        if rename { "Renamed" } else { "Replaced" },
+        count,
+        files.len()
+    ));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use crate::models::application::modes::ReplaceMode;
+    use crate::util::{replace, workspace_replace};
+    use regex::Regex;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn apply_replaces_selected_occurrences_in_files_and_buffers() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/replace_apply"));
+        fs::create_dir_all(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        fs::write(root.join("closed.rs"), "amp(amp)\n").unwrap();
+        fs::write(root.join("open.rs"), "amp\n").unwrap();
+        let pattern = Regex::new("amp").unwrap();
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.open_buffer(&root.join("open.rs")).unwrap();
+        let files = workspace_replace::find(&root, None, &app.buffer_contents(), |content| {
+            replace::replacements(content, &pattern, "editor", false, false)
+        });
+        let mut mode = ReplaceMode::new(String::from("amp"), String::from("editor"), files);
+        mode.select_next();
+        mode.toggle_selected();
+        app.mode = Mode::Replace(mode);
+
+        commands::replace::apply(&mut app).unwrap();
+
+        assert_eq!(fs::read_to_string(root.join("closed.rs")).unwrap(), "editor(amp)\n");
+        assert_eq!(fs::read_to_string(root.join("open.rs")).unwrap(), "amp\n");
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor\n");
+        assert_eq!(app.message, Some(String::from("Replaced 2 occurrence(s) in 2 file(s)")));
+    }
+
+    #[test]
+    fn apply_leaves_everything_untouched_when_a_file_has_changed() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/replace_apply_stale"));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.rs"), "amp\n").unwrap();
+        fs::write(root.join("b.rs"), "amp\n").unwrap();
+        let pattern = Regex::new("amp").unwrap();
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let files = workspace_replace::find(&root, None, &HashMap::new(), |content| {
+            replace::replacements(content, &pattern, "editor", false, false)
+        });
+        app.mode = Mode::Replace(ReplaceMode::new(String::from("amp"), String::from("editor"), files));
+        fs::write(root.join("b.rs"), "amp amp\n").unwrap();
+
+        assert!(commands::replace::apply(&mut app).is_err());
+        assert_eq!(fs::read_to_string(root.join("a.rs")).unwrap(), "amp\n");
+        assert_eq!(fs::read_to_string(root.join("b.rs")).unwrap(), "amp amp\n");
+    }
+}
@@ -2,6 +2,7 @@ use crate::errors::*;
 use crate::input::Key;
 use crate::commands::{self, Result};
 use crate::models::application::{Application, Mode};
+use crate::models::application::modes::SearchOptions;
 
 pub fn move_to_previous_result(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
@@ -51,6 +52,11 @@ pub fn accept_query(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
         // Disable insert sub-mode.
         mode.insert = false;
+
+        if let Some(ref query) = mode.input {
+            app.search_history.record(query);
+            app.search_history.save()?;
+        }
     } else {
         bail!("Can't accept search query outside of search mode");
     }
@@ -75,6 +81,7 @@ pub fn push_search_char(app: &mut Application) -> Result {
 
     if let Key::Char(c) = *key {
         if let Mode::Search(ref mut mode) = app.mode {
+            mode.reset_history_navigation();
             let query = mode.input.get_or_insert(String::new());
             query.push(c);
             app.search_query = Some(query.clone());
@@ -90,6 +97,7 @@ pub fn push_search_char(app: &mut Application) -> Result {
 
 pub fn pop_search_char(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
+        mode.reset_history_navigation();
         let query = mode.input.as_mut().ok_or(SEARCH_QUERY_MISSING)?;
 
         query.pop();
@@ -101,11 +109,46 @@ pub fn pop_search_char(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn recall_previous_query(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        mode.recall_previous_query();
+        app.search_query = mode.input.clone();
+    } else {
+        bail!("Can't recall search queries outside of search mode");
+    }
+
+    Ok(())
+}
+
+pub fn recall_next_query(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        mode.recall_next_query();
+        app.search_query = mode.input.clone();
+    } else {
+        bail!("Can't recall search queries outside of search mode");
+    }
+
+    Ok(())
+}
+
+pub fn toggle_smart_case(app: &mut Application) -> Result {
+    toggle_option(app, |options| options.smart_case = !options.smart_case)
+}
+
+pub fn toggle_regex(app: &mut Application) -> Result {
+    toggle_option(app, |options| options.regex = !options.regex)
+}
+
+pub fn toggle_whole_word(app: &mut Application) -> Result {
+    toggle_option(app, |options| options.whole_word = !options.whole_word)
+}
+
 pub fn run(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
         // Search the buffer.
         let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-        mode.search(&buffer)?;
+        let line_index = app.view.line_index(buffer);
+        mode.search(&buffer, &mut line_index.borrow_mut())?;
     } else {
         bail!("Can't run search outside of search mode");
     }
@@ -114,6 +157,23 @@ pub fn run(app: &mut Application) -> Result {
     move_to_current_result(app)
 }
 
+// Applies the change to the search options, remembering them for future
+// searches, and refreshing the results if a query has already been run.
+fn toggle_option<F: Fn(&mut SearchOptions)>(app: &mut Application, toggle: F) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        toggle(&mut mode.options);
+        app.search_options = mode.options;
+
+        if mode.insert {
+            return Ok(());
+        }
+    } else {
+        bail!("Can't toggle search options outside of search mode");
+    }
+
+    run(app)
+}
+
 fn select_closest_result(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
         let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
@@ -285,4 +345,26 @@ mod tests {
                        offset: 0,
                    });
     }
+
+    #[test]
+    fn toggle_smart_case_refreshes_results_and_is_remembered() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("Amp amp");
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input = Some(String::from("amp"));
+        }
+        commands::search::accept_query(&mut app).unwrap();
+        commands::search::toggle_smart_case(&mut app).unwrap();
+
+        if let Mode::Search(ref mode) = app.mode {
+            assert_eq!(mode.results.as_ref().unwrap().len(), 2);
+        } else {
+            panic!("Not in search mode");
+        }
+        assert!(app.search_options.smart_case);
+    }
 }
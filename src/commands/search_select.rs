@@ -3,8 +3,12 @@ use crate::commands::{self, application, Result};
 use crate::input::Key;
 use std::mem;
 use crate::models::application::modes::open::DisplayablePath;
-use crate::models::application::{Application, Mode};
-use crate::models::application::modes::SearchSelectMode;
+use crate::models::application::{run_hook, Application, Hook, Mode};
+use crate::models::application::modes::{DiffMode, DiffSource, SearchSelectMode};
+use crate::util::{self, completion};
+use scribe::buffer::Position;
+use std::fs;
+use std::path::PathBuf;
 
 pub fn accept(app: &mut Application) -> Result {
     // Consume the application mode. This is necessary because the selection in
@@ -23,17 +27,94 @@ pub fn accept(app: &mut Application) -> Result {
             let &DisplayablePath(ref path) = mode
                 .selection()
                 .ok_or("Couldn't find a selected path to open")?;
+            if util::open_encrypted_file(path, app)? || util::open_hex_view(path, app) {
+                return Ok(());
+            }
 
+            let already_open = app.workspace.contains_buffer_with_path(&path);
             app.workspace
                 .open_buffer(&path)
                 .chain_err(|| "Couldn't open a buffer for the specified path.")?;
-            app.view.initialize_buffer(app.workspace.current_buffer().unwrap())?;
+            util::apply_syntax_preference(&mut app.workspace, &app.preferences.borrow())?;
+            util::record_recent_file(app)?;
+
+            // Buffers that were already open keep their current state;
+            // newly opened ones pick up where we last left them.
+            if !already_open {
+                util::detect_indentation(&mut app.workspace, &mut app.preferences.borrow_mut());
+                let buffer = app.workspace.current_buffer().unwrap();
+                app.view.initialize_buffer(buffer)?;
+                app.file_states.restore(buffer, &mut app.view)?;
+                run_hook(app, Hook::BufferOpen)?;
+
+                return Ok(());
+            }
 
         },
+        Mode::Recent(ref mut mode) => {
+            let path = PathBuf::from(mode.selection().ok_or("No file selected")?);
+            if util::open_encrypted_file(&path, app)? || util::open_hex_view(&path, app) {
+                return Ok(());
+            }
+            let already_open = app.workspace.contains_buffer_with_path(&path);
+            app.workspace
+                .open_buffer(&path)
+                .chain_err(|| "Couldn't open a buffer for the specified path.")?;
+            util::apply_syntax_preference(&mut app.workspace, &app.preferences.borrow())?;
+            util::record_recent_file(app)?;
+
+            if !already_open {
+                util::detect_indentation(&mut app.workspace, &mut app.preferences.borrow_mut());
+                let buffer = app.workspace.current_buffer().unwrap();
+                app.view.initialize_buffer(buffer)?;
+                app.file_states.restore(buffer, &mut app.view)?;
+                run_hook(app, Hook::BufferOpen)?;
+
+                return Ok(());
+            }
+        },
         Mode::Theme(ref mut mode) => {
             let theme_key = mode.selection().ok_or("No theme selected")?;
             app.preferences.borrow_mut().set_theme(theme_key.as_str());
         },
+        Mode::Spelling(ref mut mode) => {
+            let correction = mode.selection().ok_or("No correction selected")?;
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+            buffer.start_operation_group();
+            buffer.delete_range(mode.word.clone());
+            buffer.cursor.move_to(mode.word.start());
+            buffer.insert(correction.as_str());
+            buffer.end_operation_group();
+        },
+        Mode::Tag(ref mut mode) => {
+            let tag = mode.selection().ok_or("No definition selected")?;
+            util::jump_to_tag(tag, app)?;
+        },
+        Mode::LocalHistory(ref mut mode) => {
+            let snapshot = mode.selection().ok_or("No saved version selected")?;
+            let content = fs::read_to_string(&snapshot.path).chain_err(|| "Couldn't read the saved version")?;
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            let title = buffer.path
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned())
+                .ok_or(BUFFER_PATH_MISSING)?;
+
+            app.mode = Mode::Diff(DiffMode::new(
+                DiffSource::Snapshot(snapshot.path.clone()),
+                title.clone(),
+                &buffer.data(),
+                format!("{} ({})", title, snapshot),
+                &content
+            ));
+        },
+        Mode::Task(ref mut mode) => {
+            let task = mode.selection().ok_or("No task selected")?;
+            util::open_buffer(&task.path, app)?;
+
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            buffer.cursor.move_to(Position{ line: task.line, offset: 0 });
+        },
         Mode::SymbolJump(ref mut mode) => {
             let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
             let position = mode
@@ -45,6 +126,15 @@ pub fn accept(app: &mut Application) -> Result {
                 bail!("Couldn't move to the selected symbol's position");
             }
         },
+        Mode::Workspace(ref mut mode) => {
+            let path = completion::expand_home(mode.selection().ok_or("No workspace selected")?);
+            app.switch_workspace(&path)?;
+        },
+        #[cfg(feature = "git")]
+        Mode::FileHistory(ref mut mode) => {
+            let revision = mode.selection().ok_or("No revision selected")?;
+            (mode.action)(app, &mode.path, revision)?;
+        },
         _ => bail!("Can't accept selection outside of search select mode."),
     }
 
@@ -58,11 +148,19 @@ pub fn search(app: &mut Application) -> Result {
         Mode::Command(ref mut mode) => mode.search(),
         Mode::Open(ref mut mode) => mode.search(),
         Mode::Theme(ref mut mode) => mode.search(),
+        Mode::Recent(ref mut mode) => mode.search(),
+        Mode::Spelling(ref mut mode) => mode.search(),
+        Mode::Tag(ref mut mode) => mode.search(),
+        Mode::LocalHistory(ref mut mode) => mode.search(),
+        #[cfg(feature = "git")]
+        Mode::FileHistory(ref mut mode) => mode.search(),
+        Mode::Task(ref mut mode) => mode.search(),
         Mode::SymbolJump(ref mut mode) => mode.search(),
+        Mode::Workspace(ref mut mode) => mode.search(),
         _ => bail!("Can't search outside of search select mode."),
     };
 
-    Ok(())
+    preview_theme(app)
 }
 
 pub fn select_next(app: &mut Application) -> Result {
@@ -70,11 +168,19 @@ pub fn select_next(app: &mut Application) -> Result {
         Mode::Command(ref mut mode) => mode.select_next(),
         Mode::Open(ref mut mode) => mode.select_next(),
         Mode::Theme(ref mut mode) => mode.select_next(),
+        Mode::Recent(ref mut mode) => mode.select_next(),
+        Mode::Spelling(ref mut mode) => mode.select_next(),
+        Mode::Tag(ref mut mode) => mode.select_next(),
+        Mode::LocalHistory(ref mut mode) => mode.select_next(),
+        #[cfg(feature = "git")]
+        Mode::FileHistory(ref mut mode) => mode.select_next(),
+        Mode::Task(ref mut mode) => mode.select_next(),
         Mode::SymbolJump(ref mut mode) => mode.select_next(),
+        Mode::Workspace(ref mut mode) => mode.select_next(),
         _ => bail!("Can't change selection outside of search select mode."),
     }
 
-    Ok(())
+    preview_theme(app)
 }
 
 pub fn select_previous(app: &mut Application) -> Result {
@@ -82,11 +188,19 @@ pub fn select_previous(app: &mut Application) -> Result {
         Mode::Command(ref mut mode) => mode.select_previous(),
         Mode::Open(ref mut mode) => mode.select_previous(),
         Mode::Theme(ref mut mode) => mode.select_previous(),
+        Mode::Recent(ref mut mode) => mode.select_previous(),
+        Mode::Spelling(ref mut mode) => mode.select_previous(),
+        Mode::Tag(ref mut mode) => mode.select_previous(),
+        Mode::LocalHistory(ref mut mode) => mode.select_previous(),
+        #[cfg(feature = "git")]
+        Mode::FileHistory(ref mut mode) => mode.select_previous(),
+        Mode::Task(ref mut mode) => mode.select_previous(),
         Mode::SymbolJump(ref mut mode) => mode.select_previous(),
+        Mode::Workspace(ref mut mode) => mode.select_previous(),
         _ => bail!("Can't change selection outside of search select mode."),
     }
 
-    Ok(())
+    preview_theme(app)
 }
 
 pub fn enable_insert(app: &mut Application) -> Result {
@@ -94,7 +208,15 @@ pub fn enable_insert(app: &mut Application) -> Result {
         Mode::Command(ref mut mode) => mode.set_insert_mode(true),
         Mode::Open(ref mut mode) => mode.set_insert_mode(true),
         Mode::Theme(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Recent(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Spelling(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Tag(ref mut mode) => mode.set_insert_mode(true),
+        Mode::LocalHistory(ref mut mode) => mode.set_insert_mode(true),
+        #[cfg(feature = "git")]
+        Mode::FileHistory(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Task(ref mut mode) => mode.set_insert_mode(true),
         Mode::SymbolJump(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Workspace(ref mut mode) => mode.set_insert_mode(true),
         _ => bail!("Can't change search insert state outside of search select mode"),
     }
 
@@ -106,7 +228,15 @@ pub fn disable_insert(app: &mut Application) -> Result {
         Mode::Command(ref mut mode) => mode.set_insert_mode(false),
         Mode::Open(ref mut mode) => mode.set_insert_mode(false),
         Mode::Theme(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Recent(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Spelling(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Tag(ref mut mode) => mode.set_insert_mode(false),
+        Mode::LocalHistory(ref mut mode) => mode.set_insert_mode(false),
+        #[cfg(feature = "git")]
+        Mode::FileHistory(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Task(ref mut mode) => mode.set_insert_mode(false),
         Mode::SymbolJump(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Workspace(ref mut mode) => mode.set_insert_mode(false),
         _ => bail!("Can't change search insert state outside of search select mode"),
     }
 
@@ -119,7 +249,15 @@ pub fn push_search_char(app: &mut Application) -> Result {
             Mode::Command(ref mut mode) => mode.push_search_char(c),
             Mode::Open(ref mut mode) => mode.push_search_char(c),
             Mode::Theme(ref mut mode) => mode.push_search_char(c),
+            Mode::Recent(ref mut mode) => mode.push_search_char(c),
+            Mode::Spelling(ref mut mode) => mode.push_search_char(c),
+        Mode::Tag(ref mut mode) => mode.push_search_char(c),
+        Mode::LocalHistory(ref mut mode) => mode.push_search_char(c),
+        #[cfg(feature = "git")]
+        Mode::FileHistory(ref mut mode) => mode.push_search_char(c),
+        Mode::Task(ref mut mode) => mode.push_search_char(c),
             Mode::SymbolJump(ref mut mode) => mode.push_search_char(c),
+            Mode::Workspace(ref mut mode) => mode.push_search_char(c),
             _ => bail!("Can't push search character outside of search select mode"),
         }
     }
@@ -133,7 +271,15 @@ pub fn pop_search_token(app: &mut Application) -> Result {
         Mode::Command(ref mut mode) => mode.pop_search_token(),
         Mode::Open(ref mut mode) => mode.pop_search_token(),
         Mode::Theme(ref mut mode) => mode.pop_search_token(),
+        Mode::Recent(ref mut mode) => mode.pop_search_token(),
+        Mode::Spelling(ref mut mode) => mode.pop_search_token(),
+        Mode::Tag(ref mut mode) => mode.pop_search_token(),
+        Mode::LocalHistory(ref mut mode) => mode.pop_search_token(),
+        #[cfg(feature = "git")]
+        Mode::FileHistory(ref mut mode) => mode.pop_search_token(),
+        Mode::Task(ref mut mode) => mode.pop_search_token(),
         Mode::SymbolJump(ref mut mode) => mode.pop_search_token(),
+        Mode::Workspace(ref mut mode) => mode.pop_search_token(),
         _ => bail!("Can't pop search token outside of search select mode"),
     }
 
@@ -146,13 +292,42 @@ pub fn step_back(app: &mut Application) -> Result {
         Mode::Command(ref mut mode) => mode.results().count(),
         Mode::Open(ref mut mode) => mode.results().count(),
         Mode::Theme(ref mut mode) => mode.results().count(),
+        Mode::Recent(ref mut mode) => mode.results().count(),
+        Mode::Spelling(ref mut mode) => mode.results().count(),
+        Mode::Tag(ref mut mode) => mode.results().count(),
+        Mode::LocalHistory(ref mut mode) => mode.results().count(),
+        #[cfg(feature = "git")]
+        Mode::FileHistory(ref mut mode) => mode.results().count(),
+        Mode::Task(ref mut mode) => mode.results().count(),
         Mode::SymbolJump(ref mut mode) => mode.results().count(),
+        Mode::Workspace(ref mut mode) => mode.results().count(),
         _ => bail!("Can't pop search token outside of search select mode"),
     };
 
     if result_count == 0 {
-        application::switch_to_normal_mode(app)
+        cancel(app)
     } else {
         disable_insert(app)
     }
 }
+
+/// Leaves search/select mode without accepting the selection,
+/// restoring the original theme if one was being previewed.
+pub fn cancel(app: &mut Application) -> Result {
+    if let Mode::Theme(ref mode) = app.mode {
+        app.preferences.borrow_mut().set_theme(mode.original_theme.as_str());
+    }
+
+    application::switch_to_normal_mode(app)
+}
+
+// Applies the selected theme in theme mode, so that it can be previewed.
+fn preview_theme(app: &mut Application) -> Result {
+    if let Mode::Theme(ref mode) = app.mode {
+        if let Some(theme) = mode.selection() {
+            app.preferences.borrow_mut().set_theme(theme.as_str());
+        }
+    }
+
+    Ok(())
+}
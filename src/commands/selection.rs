@@ -6,6 +6,7 @@ use crate::commands::{self, Result};
 use crate::util;
 
 pub fn delete(app: &mut Application) -> Result {
+    app.ensure_writable()?;
     if let Some(buffer) = app.workspace.current_buffer() {
         match app.mode {
             Mode::Select(ref select_mode) => {
@@ -14,6 +15,21 @@ pub fn delete(app: &mut Application) -> Result {
                 buffer.delete_range(delete_range.clone());
                 buffer.cursor.move_to(delete_range.start());
             }
+            Mode::SelectBlock(ref mode) => {
+                let data = buffer.data();
+                let ranges = mode.to_ranges(&*buffer.cursor, &data);
+
+                // Each range is on its own line, so deleting
+                // one doesn't affect where the others are.
+                buffer.start_operation_group();
+                for range in ranges.iter().filter(|range| range.start() != range.end()) {
+                    buffer.delete_range(range.clone());
+                }
+                buffer.end_operation_group();
+                if let Some(range) = ranges.first() {
+                    buffer.cursor.move_to(range.start());
+                }
+            }
             Mode::SelectLine(ref mode) => {
                 let delete_range = mode.to_range(&*buffer.cursor);
                 buffer.delete_range(delete_range.clone());
@@ -80,6 +96,15 @@ fn copy_to_clipboard(app: &mut Application) -> Result {
                 .ok_or("Couldn't read selected data from buffer")?;
             app.clipboard.set_content(ClipboardContent::Inline(data))?;
         }
+        Mode::SelectBlock(ref mode) => {
+            let data = buffer.data();
+            let rows: Vec<String> = mode
+                .to_ranges(&*buffer.cursor, &data)
+                .iter()
+                .map(|range| buffer.read(range).unwrap_or_default())
+                .collect();
+            app.clipboard.set_content(ClipboardContent::Rectangle(rows.join("\n")))?;
+        }
         Mode::SelectLine(ref mode) => {
             let selected_range = util::inclusive_range(
                 &LineRange::new(
@@ -166,6 +191,23 @@ mod tests {
         )
     }
 
+    #[test]
+    fn delete_removes_the_selected_columns_in_select_block_mode() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\nab");
+        buffer.cursor.move_to(Position{ line: 0, offset: 1 });
+        app.workspace.add_buffer(buffer);
+        commands::application::switch_to_select_block_mode(&mut app).unwrap();
+        commands::cursor::move_down(&mut app).unwrap();
+        commands::cursor::move_down(&mut app).unwrap();
+        commands::cursor::move_right(&mut app).unwrap();
+        commands::selection::copy_and_delete(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "a\netor\na");
+        assert_eq!(app.clipboard.get_content().text(), Some("mp\ndi\nb"));
+    }
+
     #[test]
     fn delete_removes_the_selected_line_in_select_line_mode() {
         let mut app = Application::new(&Vec::new()).unwrap();
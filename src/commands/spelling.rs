@@ -0,0 +1,105 @@
+use crate::errors;
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::SpellingMode;
+use crate::util::spelling;
+use scribe::buffer::{Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Enough corrections to be useful, without listing every similar word.
+const MAX_SUGGESTIONS: usize = 20;
+
+/// Lists corrections for the word under the cursor, replacing it with the selected one.
+pub fn suggest(app: &mut Application) -> Result {
+    app.ensure_writable()?;
+    let (range, word) = word_at_cursor(app)?;
+    let suggestions = app.view.dictionary()?.suggestions(&word, MAX_SUGGESTIONS);
+    if suggestions.is_empty() {
+        bail!("No suggestions found for \"{}\"", word);
+    }
+
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Spelling(SpellingMode::new(range, suggestions, config));
+    commands::search_select::search(app)
+}
+
+/// Adds the word under the cursor to the personal dictionary.
+pub fn add_word(app: &mut Application) -> Result {
+    let (_, word) = word_at_cursor(app)?;
+    app.view.dictionary()?.add(&word)?;
+    app.message = Some(format!("Added \"{}\" to the dictionary", word));
+
+    Ok(())
+}
+
+/// Turns spell checking on or off for the rest of the session.
+pub fn toggle(app: &mut Application) -> Result {
+    let path = app.workspace.current_buffer().and_then(|buffer| buffer.path.clone());
+    let enabled = !app.preferences.borrow().spell_check(path.as_ref());
+    app.preferences.borrow_mut().set("spell_check", if enabled { "on" } else { "off" })?;
+
+    // Type-specific preferences take precedence over session values.
+    if app.preferences.borrow().spell_check(path.as_ref()) != enabled {
+        bail!("Spell checking is configured for this file type in the preferences file");
+    }
+    app.message = Some(format!("Spell checking {}", if enabled { "on" } else { "off" }));
+
+    Ok(())
+}
+
+// The word under the cursor, along with the range it covers.
+fn word_at_cursor(app: &mut Application) -> errors::Result<(Range, String)> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let line = buffer.cursor.line;
+    let content = data.lines().nth(line).unwrap_or("");
+    let (offset, word) = spelling::word_at(content, buffer.cursor.offset)
+        .ok_or("There's no word under the cursor")?;
+    let range = Range::new(
+        Position{ line, offset },
+        Position{ line, offset: offset + word.graphemes(true).count() }
+    );
+
+    Ok((range, word))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::{Application, Mode};
+    use crate::models::application::modes::{SearchSelectConfig, SpellingMode};
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn word_at_cursor_returns_the_word_and_its_range() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fix\nthe quikc fox");
+        buffer.cursor.move_to(Position{ line: 1, offset: 6 });
+        app.workspace.add_buffer(buffer);
+
+        let (range, word) = super::word_at_cursor(&mut app).unwrap();
+        assert_eq!(word, "quikc");
+        assert_eq!(range.start(), Position{ line: 1, offset: 4 });
+        assert_eq!(range.end(), Position{ line: 1, offset: 9 });
+    }
+
+    #[test]
+    fn accepting_a_correction_replaces_the_word() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("the quikc fox");
+        buffer.cursor.move_to(Position{ line: 0, offset: 5 });
+        app.workspace.add_buffer(buffer);
+
+        let (range, _) = super::word_at_cursor(&mut app).unwrap();
+        let suggestions = vec![String::from("quick"), String::from("quiche")];
+        app.mode = Mode::Spelling(SpellingMode::new(range, suggestions, SearchSelectConfig::default()));
+        commands::search_select::search(&mut app).unwrap();
+        commands::search_select::accept(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "the quick fox");
+    }
+}
@@ -3,14 +3,56 @@ use crate::commands::Result;
 use crate::models::application::Application;
 
 pub fn scroll_up(app: &mut Application) -> Result {
+    let amount = app.preferences.borrow().scroll_amount();
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-    app.view.scroll_up(buffer, 10)?;
+    app.view.scroll_up(buffer, amount)?;
     Ok(())
 }
 
 pub fn scroll_down(app: &mut Application) -> Result {
+    let amount = app.preferences.borrow().scroll_amount();
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-    app.view.scroll_down(buffer, 10)?;
+    app.view.scroll_down(buffer, amount)?;
+    Ok(())
+}
+
+pub fn scroll_line_up(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_up(buffer, 1)?;
+    Ok(())
+}
+
+pub fn scroll_line_down(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_down(buffer, 1)?;
+    Ok(())
+}
+
+pub fn scroll_half_page_up(app: &mut Application) -> Result {
+    let amount = app.view.page_height() / 2;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_up(buffer, amount)?;
+    Ok(())
+}
+
+pub fn scroll_half_page_down(app: &mut Application) -> Result {
+    let amount = app.view.page_height() / 2;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_down(buffer, amount)?;
+    Ok(())
+}
+
+pub fn scroll_page_up(app: &mut Application) -> Result {
+    let amount = app.view.page_height();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_up(buffer, amount)?;
+    Ok(())
+}
+
+pub fn scroll_page_down(app: &mut Application) -> Result {
+    let amount = app.view.page_height();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_down(buffer, amount)?;
     Ok(())
 }
 
@@ -25,3 +67,25 @@ pub fn scroll_cursor_to_center(app: &mut Application) -> Result {
     app.view.scroll_to_center(buffer)?;
     Ok(())
 }
+
+pub fn scroll_cursor_to_top(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_to_top(buffer)?;
+    Ok(())
+}
+
+pub fn scroll_cursor_to_bottom(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_to_bottom(buffer)?;
+    Ok(())
+}
+
+/// Shows or hides linter messages after the lines they refer to,
+/// for the rest of the session. Their line numbers stay highlighted.
+pub fn toggle_inline_diagnostics(app: &mut Application) -> Result {
+    let enabled = !app.preferences.borrow().inline_diagnostics();
+    app.preferences.borrow_mut().set("inline_diagnostics", if enabled { "on" } else { "off" })?;
+    app.message = Some(format!("Inline diagnostics {}", if enabled { "on" } else { "off" }));
+
+    Ok(())
+}
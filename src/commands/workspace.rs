@@ -1,7 +1,15 @@
-use crate::commands::Result;
+use crate::errors::*;
+use crate::commands::{self, Result};
 use scribe::Buffer;
-use crate::models::application::Application;
+use scribe::buffer::Position;
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{PathAction, PathMode, TagMode};
 use crate::util;
+use crate::util::completion;
+use crate::util::occurrences;
+use crate::util::reference::{self, Reference};
+use crate::util::tags;
+use std::path::PathBuf;
 
 pub fn next_buffer(app: &mut Application) -> Result {
     app.workspace.next_buffer();
@@ -12,3 +20,145 @@ pub fn next_buffer(app: &mut Application) -> Result {
 pub fn new_buffer(app: &mut Application) -> Result {
     util::add_buffer(Buffer::new(), app)
 }
+
+/// Prompts for the path of a new file, which is created (along with any
+/// missing directories) and opened. When used from open mode, the prompt
+/// is seeded with the search query.
+pub fn create_file(app: &mut Application) -> Result {
+    switch_to_creation_prompt(app, PathAction::CreateFile);
+
+    Ok(())
+}
+
+/// Prompts for the path of a new directory, which is created
+/// (along with any missing parent directories).
+pub fn create_directory(app: &mut Application) -> Result {
+    switch_to_creation_prompt(app, PathAction::CreateDirectory);
+
+    Ok(())
+}
+
+/// Opens the URL under the cursor in the browser, or the file path under the
+/// cursor in a buffer, moving to the line number following it (if any).
+pub fn open_reference(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+
+    match reference::reference_at(line, buffer.cursor.offset) {
+        Some(Reference::Url(url)) => reference::open_url(&url),
+        Some(Reference::Path{ path, line }) => {
+            let directory = buffer.path.as_ref().and_then(|path| path.parent()).map(|parent| parent.to_path_buf());
+            let path = resolve_path(&path, directory, app.workspace.path.clone())
+                .ok_or_else(|| format!("Couldn't find {}", path))?;
+            util::open_buffer(&path, app)?;
+
+            if let Some(line) = line {
+                let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+                if !buffer.cursor.move_to(Position{ line: line.saturating_sub(1), offset: 0 }) {
+                    bail!("Line {} is out of range", line);
+                }
+                commands::view::scroll_cursor_to_center(app)?;
+            }
+
+            Ok(())
+        },
+        None => bail!("There's no URL or file path under the cursor"),
+    }
+}
+
+/// Jumps to the definition of the identifier under the cursor, using the tags
+/// file at the root of the workspace. When there's more than one, they're listed.
+pub fn jump_to_definition(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+    let name = occurrences::word_at(line, buffer.cursor.offset)
+        .ok_or("There's no identifier under the cursor")?;
+
+    let tags_path = app.workspace.path.join(tags::FILE_NAME);
+    if !tags_path.exists() {
+        bail!(TAGS_FILE_MISSING);
+    }
+    let mut definitions = tags::find(&tags_path, &name)?;
+    match definitions.len() {
+        0 => bail!("Couldn't find a definition for {}", name),
+        1 => util::jump_to_tag(&definitions.remove(0), app)?,
+        _ => {
+            let config = app.preferences.borrow().search_select_config();
+            app.mode = Mode::Tag(TagMode::new(definitions, config));
+            return commands::search_select::search(app);
+        },
+    }
+
+    commands::view::scroll_cursor_to_center(app)
+}
+
+/// (Re-)generates the workspace's tags file in the background,
+/// using the configured command (`ctags -R`, by default).
+pub fn generate_tags(app: &mut Application) -> Result {
+    let command = app.preferences.borrow().tags_command();
+    tags::spawn_generate(command, app.workspace.path.clone(), app.event_channel.clone());
+    app.message = Some(String::from("Generating the tags file..."));
+
+    Ok(())
+}
+
+// Relative paths are found next to the current buffer's file, or failing
+// that, in the workspace (which is where compiler output is relative to).
+fn resolve_path(path: &str, directory: Option<PathBuf>, workspace: PathBuf) -> Option<PathBuf> {
+    let path = completion::expand_home(path);
+    if path.is_absolute() {
+        return Some(path).filter(|path| path.exists());
+    }
+
+    directory
+        .into_iter()
+        .chain(Some(workspace))
+        .map(|directory| directory.join(&path))
+        .find(|path| path.exists())
+}
+
+fn switch_to_creation_prompt(app: &mut Application, action: PathAction) {
+    // Start in the current buffer's directory, falling back to the workspace.
+    let directory = app.workspace
+        .current_buffer()
+        .and_then(|buffer| buffer.path.as_ref())
+        .and_then(|path| path.parent())
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(|parent| parent.to_path_buf())
+        .unwrap_or_else(|| app.workspace.path.clone());
+    let query = match app.mode {
+        Mode::Open(ref mode) => mode.input.clone(),
+        _ => String::new(),
+    };
+
+    let mut mode = PathMode::new(
+        format!("{}/{}", directory.to_string_lossy().trim_end_matches('/'), query)
+    );
+    mode.action = action;
+    app.mode = Mode::Path(mode);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::Application;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::env;
+
+    #[test]
+    fn open_reference_opens_paths_at_their_line_number() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("see Cargo.toml:3 for details");
+        buffer.cursor.move_to(Position{ line: 0, offset: 6 });
+        app.workspace.add_buffer(buffer);
+
+        commands::workspace::open_reference(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.path, Some(env::current_dir().unwrap().join("Cargo.toml")));
+        assert_eq!(buffer.cursor.line, 2);
+    }
+}
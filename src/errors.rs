@@ -9,5 +9,8 @@ pub static SEARCH_QUERY_MISSING: &'static str = "No search query";
 pub static NO_SEARCH_RESULTS: &'static str = "No search results available";
 pub static BUFFER_MISSING: &'static str = "No buffer available";
 pub static BUFFER_PATH_MISSING: &'static str = "No path found for the current buffer";
+pub static BUFFER_READ_ONLY: &'static str = "The buffer is read-only; run buffer::make_writable to edit it";
 pub static CURRENT_LINE_MISSING: &'static str = "The current line couldn't be found in the buffer";
 pub static SCROLL_TO_CURSOR_FAILED: &'static str = "Failed to scroll to cursor position";
+pub static AGE_IDENTITY_MISSING: &'static str = "Set the age_identity preference to use age-encrypted files";
+pub static TAGS_FILE_MISSING: &'static str = "There's no tags file in the workspace; run workspace::generate_tags to create one";
@@ -0,0 +1,249 @@
+use crate::input::Key;
+use std::str;
+
+const ESCAPE: u8 = 0x1B;
+
+/// Turns raw terminal input into keys. Input is buffered until it forms a
+/// complete key, since a single key's bytes can arrive in separate reads
+/// (e.g. over a slow SSH connection).
+///
+/// An escape byte on its own is ambiguous: it's either the escape key, or
+/// the start of a sequence sent for another key (e.g. an arrow key, or an
+/// alt-chord). It's held back until more input arrives, or the caller
+/// decides it's waited long enough and asks for it to be flushed.
+pub struct KeyDecoder {
+    input: Vec<u8>,
+}
+
+// The result of trying to decode the start of the buffered input.
+enum Decoded {
+    // A key, and the number of bytes it took up.
+    Key(Key, usize),
+    // A recognized sequence that amp doesn't handle (e.g. shift-tab).
+    Ignored(usize),
+    // The start of something that needs more input to be decoded.
+    Incomplete,
+}
+
+impl KeyDecoder {
+    pub fn new() -> KeyDecoder {
+        KeyDecoder { input: Vec::new() }
+    }
+
+    pub fn push(&mut self, data: &[u8]) {
+        self.input.extend_from_slice(data);
+    }
+
+    /// Whether there's input that's waiting on more to be decoded.
+    pub fn is_waiting(&self) -> bool {
+        !self.input.is_empty()
+    }
+
+    /// Decodes the next key from the buffered input, if there's enough of it.
+    pub fn next_key(&mut self) -> Option<Key> {
+        self.decode(false)
+    }
+
+    /// Decodes the next key, giving up on waiting for the rest of an
+    /// incomplete sequence: a lone escape byte becomes the escape key,
+    /// and anything that follows it is decoded on its own.
+    pub fn flush(&mut self) -> Option<Key> {
+        self.decode(true)
+    }
+
+    fn decode(&mut self, flush: bool) -> Option<Key> {
+        while !self.input.is_empty() {
+            match decode(&self.input) {
+                Decoded::Key(key, length) => {
+                    self.input.drain(..length);
+                    return Some(key);
+                },
+                Decoded::Ignored(length) => {
+                    self.input.drain(..length);
+                },
+                Decoded::Incomplete if !flush => return None,
+                Decoded::Incomplete => {
+                    // Anything but an escape is an incomplete character, which is dropped.
+                    if self.input.remove(0) == ESCAPE {
+                        return Some(Key::Esc);
+                    }
+                },
+            }
+        }
+
+        None
+    }
+}
+
+fn decode(input: &[u8]) -> Decoded {
+    match input[0] {
+        ESCAPE => decode_escape(input),
+        b'\n' | b'\r' => Decoded::Key(Key::Enter, 1),
+        b'\t' => Decoded::Key(Key::Tab, 1),
+        0x7F => Decoded::Key(Key::Backspace, 1),
+        byte if byte >= 0x01 && byte <= 0x1A => Decoded::Key(Key::Ctrl((byte - 0x01 + b'a') as char), 1),
+        byte if byte >= 0x1C && byte <= 0x1F => Decoded::Key(Key::Ctrl((byte - 0x1C + b'4') as char), 1),
+        // Sent for ctrl-space, which amp doesn't distinguish.
+        0x00 => Decoded::Ignored(1),
+        byte if byte < 0x80 => Decoded::Key(Key::Char(byte as char), 1),
+        _ => match decode_char(input) {
+            Some((character, length)) => Decoded::Key(Key::Char(character), length),
+            None if input.len() < char_length(input[0]) => Decoded::Incomplete,
+            None => Decoded::Ignored(1),
+        },
+    }
+}
+
+fn decode_escape(input: &[u8]) -> Decoded {
+    match input.get(1) {
+        None => Decoded::Incomplete,
+        Some(&b'[') => decode_csi(input),
+        Some(&b'O') => match input.get(2) {
+            None => Decoded::Incomplete,
+            Some(&byte) => match cursor_key(byte) {
+                Some(key) => Decoded::Key(key, 3),
+                None => Decoded::Ignored(3),
+            },
+        },
+        // A doubled escape is the escape key, followed by whatever's next.
+        Some(&ESCAPE) => Decoded::Key(Key::Esc, 1),
+        Some(&byte) if byte < 0x20 || byte == 0x7F => Decoded::Key(Key::Esc, 1),
+        Some(_) => match decode_char(&input[1..]) {
+            Some((character, length)) => Decoded::Key(Key::Alt(character), length + 1),
+            None if input.len() - 1 < char_length(input[1]) => Decoded::Incomplete,
+            None => Decoded::Key(Key::Esc, 1),
+        },
+    }
+}
+
+// Control sequences are the escape byte and "[", followed by any number of
+// parameter bytes (e.g. "5" for page up), and finished by a single byte.
+fn decode_csi(input: &[u8]) -> Decoded {
+    let parameters = input[2..].iter().take_while(|&&byte| byte >= 0x20 && byte <= 0x3F).count();
+    let end = 2 + parameters;
+    let final_byte = match input.get(end) {
+        Some(&byte) => byte,
+        None => return Decoded::Incomplete,
+    };
+    let parameter = str::from_utf8(&input[2..end])
+        .ok()
+        .and_then(|parameters| parameters.split(';').next())
+        .unwrap_or("");
+
+    let key = match final_byte {
+        b'~' => match parameter {
+            "1" | "7" => Some(Key::Home),
+            "2" => Some(Key::Insert),
+            "3" => Some(Key::Delete),
+            "4" | "8" => Some(Key::End),
+            "5" => Some(Key::PageUp),
+            "6" => Some(Key::PageDown),
+            _ => None,
+        },
+        byte => cursor_key(byte),
+    };
+
+    match key {
+        Some(key) => Decoded::Key(key, end + 1),
+        None => Decoded::Ignored(end + 1),
+    }
+}
+
+fn cursor_key(byte: u8) -> Option<Key> {
+    match byte {
+        b'A' => Some(Key::Up),
+        b'B' => Some(Key::Down),
+        b'C' => Some(Key::Right),
+        b'D' => Some(Key::Left),
+        b'H' => Some(Key::Home),
+        b'F' => Some(Key::End),
+        _ => None,
+    }
+}
+
+// Decodes the UTF-8 character at the start of the input.
+fn decode_char(input: &[u8]) -> Option<(char, usize)> {
+    let length = char_length(input[0]);
+    let bytes = input.get(..length)?;
+
+    str::from_utf8(bytes).ok()?.chars().next().map(|character| (character, length))
+}
+
+// The number of bytes in a UTF-8 character, based on its first byte.
+fn char_length(first_byte: u8) -> usize {
+    if first_byte < 0x80 {
+        1
+    } else if first_byte >= 0xF0 {
+        4
+    } else if first_byte >= 0xE0 {
+        3
+    } else {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::Key;
+    use super::KeyDecoder;
+
+    fn keys(decoder: &mut KeyDecoder) -> Vec<Key> {
+        let mut keys = Vec::new();
+        while let Some(key) = decoder.next_key() {
+            keys.push(key);
+        }
+
+        keys
+    }
+
+    #[test]
+    fn next_key_decodes_characters_and_sequences() {
+        let mut decoder = KeyDecoder::new();
+        decoder.push(b"a\r\x7f\x12\x1b[A\x1b[5~\x1bOH\x1b[Z");
+        decoder.push("é".as_bytes());
+
+        assert_eq!(keys(&mut decoder), vec![
+            Key::Char('a'), Key::Enter, Key::Backspace, Key::Ctrl('r'),
+            Key::Up, Key::PageUp, Key::Home, Key::Char('é')
+        ]);
+        assert!(!decoder.is_waiting());
+    }
+
+    #[test]
+    fn next_key_waits_for_sequences_split_across_reads() {
+        let mut decoder = KeyDecoder::new();
+        decoder.push(b"\x1b");
+        assert_eq!(decoder.next_key(), None);
+        decoder.push(b"[");
+        assert_eq!(decoder.next_key(), None);
+        decoder.push(b"B");
+        assert_eq!(decoder.next_key(), Some(Key::Down));
+
+        decoder.push(&"é".as_bytes()[..1]);
+        assert_eq!(decoder.next_key(), None);
+        decoder.push(&"é".as_bytes()[1..]);
+        assert_eq!(decoder.next_key(), Some(Key::Char('é')));
+    }
+
+    #[test]
+    fn next_key_decodes_alt_chords() {
+        let mut decoder = KeyDecoder::new();
+        decoder.push(b"\x1bj\x1b\x1bk");
+
+        assert_eq!(keys(&mut decoder), vec![Key::Alt('j'), Key::Esc, Key::Alt('k')]);
+    }
+
+    #[test]
+    fn flush_treats_a_lone_escape_as_the_escape_key() {
+        let mut decoder = KeyDecoder::new();
+        decoder.push(b"\x1b");
+        assert_eq!(decoder.flush(), Some(Key::Esc));
+        assert!(!decoder.is_waiting());
+
+        // An escape followed by the start of a sequence that never
+        // finished is the escape key, followed by what was typed.
+        decoder.push(b"\x1b[");
+        assert_eq!(decoder.flush(), Some(Key::Esc));
+        assert_eq!(decoder.next_key(), Some(Key::Char('[')));
+    }
+}
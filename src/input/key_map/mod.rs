@@ -1,12 +1,19 @@
 use crate::commands::{self, Command};
 use crate::errors::*;
 use crate::input::Key;
+use crate::plugins;
 use smallvec::SmallVec;
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use std::convert::Into;
 use crate::yaml::yaml::{Hash, Yaml, YamlLoader};
 
+/// Built-in sets of bindings (by name), which are layered over the defaults.
+const PRESETS: [(&str, &str); 2] = [
+    ("amp", ""),
+    ("vim", include_str!("vim.yml")),
+];
+
 /// Nested HashMap newtype that provides a more ergonomic interface.
 pub struct KeyMap(HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>>);
 
@@ -56,15 +63,49 @@ impl KeyMap {
     }
 
     /// Loads the default keymap from a static
-    /// YAML document injected during the build.
+    /// YAML document injected during the build,
+    /// along with bindings registered by plugins.
     pub fn default() -> Result<KeyMap> {
         let default_keymap_data = YamlLoader::load_from_str(KeyMap::default_data())
             .chain_err(|| "Couldn't parse default keymap")?
             .into_iter()
             .nth(0)
             .ok_or("Couldn't locate a document in the default keymap")?;
+        let mut keymap = KeyMap::from(&default_keymap_data.as_hash().unwrap())?;
+
+        for plugin_data in plugins::key_bindings() {
+            let document = YamlLoader::load_from_str(plugin_data)
+                .chain_err(|| "Couldn't parse plugin key bindings")?
+                .into_iter()
+                .nth(0);
+            if let Some(document) = document {
+                let bindings = document.as_hash().ok_or("Plugin key bindings should contain a set of modes")?;
+                keymap.include(KeyMap::from(bindings)?);
+            }
+        }
 
-        KeyMap::from(&default_keymap_data.as_hash().unwrap())
+        Ok(keymap)
+    }
+
+    /// Loads the default keymap, with the named preset's bindings merged in.
+    pub fn preset(name: &str) -> Result<KeyMap> {
+        let preset_data = PRESETS
+            .iter()
+            .find(|&&(preset, _)| preset == name)
+            .map(|&(_, data)| data)
+            .ok_or_else(|| format!("Keymap preset \"{}\" doesn't exist", name))?;
+        let mut keymap = KeyMap::default()?;
+
+        let document = YamlLoader::load_from_str(preset_data)
+            .chain_err(|| format!("Couldn't parse \"{}\" keymap preset", name))?
+            .into_iter()
+            .nth(0);
+        if let Some(document) = document {
+            let bindings = document.as_hash().ok_or("Keymap presets should contain a set of modes")?;
+            keymap.merge(KeyMap::from(bindings)?);
+        }
+
+        Ok(keymap)
     }
 
     /// Returns the default YAML keymap data as a string.
@@ -105,6 +146,14 @@ impl KeyMap {
             }
         }
     }
+
+    /// Like merge, except that modes that don't exist yet are added, rather
+    /// than discarded (i.e. modes registered by plugins).
+    pub fn include(&mut self, key_map: KeyMap) {
+        for (mode, key_bindings) in key_map.0 {
+            self.entry(mode).or_insert_with(HashMap::new).extend(key_bindings);
+        }
+    }
 }
 
 /// Parses the key bindings for a particular mode.
@@ -171,23 +220,27 @@ fn parse_mode_key_bindings(mode: &Yaml, commands: &HashMap<&str, Command>) -> Re
 /// e.g.
 ///
 ///   ctrl-r becomes Key::Ctrl('r')
+///   alt-j becomes Key::Alt('j')
 ///
-fn parse_key(data: &str) -> Result<Key> {
+pub fn parse_key(data: &str) -> Result<Key> {
     let mut key_components = data.split('-');
     let component = key_components.next().ok_or(
         "A keymap key is an empty string",
     )?;
 
     if let Some(key) = key_components.next() {
-        // We have a modifier-qualified key; get the key.
-        let key_char = key.chars().nth(0).ok_or_else(|| format!(
-            "Keymap key \"{}\" is invalid",
-            key
-        ))?;
+        // We have a modifier-qualified key; get the key, which should be a
+        // single character (anything else would be silently ignored).
+        let mut key_chars = key.chars();
+        let key_char = match (key_chars.next(), key_chars.next(), key_components.next()) {
+            (Some(key_char), None, None) => key_char,
+            _ => bail!(format!("Keymap key \"{}\" is invalid", data)),
+        };
 
         // Find the variant for the specified modifier.
         match component {
             "ctrl" => Ok(Key::Ctrl(key_char)),
+            "alt" => Ok(Key::Alt(key_char)),
             _ => bail!(format!("Keymap modifier \"{}\" is invalid", component)),
         }
     } else {
@@ -209,12 +262,14 @@ fn parse_key(data: &str) -> Result<Key> {
             "tab"       => Key::Tab,
             "enter"     => Key::Enter,
             "_"         => Key::AnyChar,
-            _           => Key::Char(
-                // It's not a keyword; take its first character, if available.
-                component.chars().nth(0).ok_or_else(||
-                    format!("Keymap key \"{}\" is invalid", component)
-                )?
-            ),
+            _           => {
+                // It's not a keyword; it should be a single character.
+                let mut chars = component.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(character), None) => Key::Char(character),
+                    _ => bail!(format!("Keymap key \"{}\" is invalid", component)),
+                }
+            },
         })
     }
 }
@@ -320,6 +375,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn keymap_correctly_parses_yaml_alt_keybindings() {
+        let yaml_data = "normal:\n  alt-j: cursor::move_down";
+        let yaml = YamlLoader::load_from_str(yaml_data).unwrap();
+        let keymap = KeyMap::from(&yaml[0].as_hash().unwrap()).unwrap();
+
+        let command = keymap.commands_for("normal", &Key::Alt('j')).expect(
+            "Keymap doesn't contain command",
+        );
+        assert_eq!(
+            (command[0] as *const usize),
+            (commands::cursor::move_down as *const usize)
+        );
+    }
+
+    #[test]
+    fn keymap_rejects_invalid_key_notation() {
+        for key in &["ctlr-s", "ctrl-sp", "ctrl-a-b", "alt-", "pageup"] {
+            let yaml_data = format!("normal:\n  {}: cursor::move_up", key);
+            let yaml = YamlLoader::load_from_str(&yaml_data).unwrap();
+
+            assert!(KeyMap::from(&yaml[0].as_hash().unwrap()).is_err(), "{} was accepted", key);
+        }
+    }
+
     #[test]
     fn keymap_correctly_parses_yaml_keyword_keybindings() {
         let mappings = vec![
@@ -350,6 +430,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn preset_layers_its_bindings_over_the_defaults() {
+        let keymap = KeyMap::preset("vim").unwrap();
+
+        let command = keymap.commands_for("normal", &Key::Char('G')).unwrap();
+        assert_eq!((command[0] as *const usize), (commands::cursor::move_to_last_line as *const usize));
+        let command = keymap.commands_for("normal", &Key::Char('k')).unwrap();
+        assert_eq!((command[0] as *const usize), (commands::cursor::move_up as *const usize));
+
+        assert!(KeyMap::preset("amp").is_ok());
+        assert!(KeyMap::preset("emacs").is_err());
+    }
+
     #[test]
     fn keymap_correctly_loads_default_keybindings() {
         // Build the keymap
@@ -1,5 +1,7 @@
-pub use self::key_map::KeyMap;
+pub use self::decoder::KeyDecoder;
+pub use self::key_map::{parse_key, KeyMap};
 
+mod decoder;
 mod key_map;
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -21,4 +23,5 @@ pub enum Key {
     AnyChar,
     Char(char),
     Ctrl(char),
+    Alt(char),
 }
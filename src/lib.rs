@@ -5,6 +5,7 @@
 extern crate app_dirs;
 extern crate bloodhound;
 extern crate fragment;
+#[cfg(feature = "git")]
 extern crate git2;
 extern crate luthor;
 extern crate mio;
@@ -24,15 +25,32 @@ extern crate error_chain;
 #[macro_use]
 extern crate lazy_static;
 
+// Logging macros need to be defined before the modules that use them.
+#[macro_use]
+pub mod log;
+
 // Private modules
 mod commands;
 mod errors;
 mod util;
 mod input;
 mod models;
+mod plugins;
 mod presenters;
 mod view;
 
 // External application API
 pub use crate::models::Application;
+pub use crate::models::application::Mode;
+pub use crate::plugins::{register as register_plugin, Plugin, PluginMode, Registry, StatusSegment};
+pub use crate::commands::{Command, Result as CommandResult};
+pub use crate::models::application::{install_crash_reporter, send_to_server};
 pub use crate::errors::Error;
+
+// Internals exercised by the benchmark suite (`cargo bench --features bench`).
+#[cfg(feature = "bench")]
+pub mod bench {
+    pub use crate::models::application::modes::{JumpMode, SearchMode};
+    pub use crate::util::line_index::LineIndex;
+    pub use crate::util::movement_lexer;
+}
@@ -0,0 +1,167 @@
+//! Diagnostic logging, written to a file in the application state directory.
+//! The terminal is reserved for the editor itself, so this is the only place
+//! problems that aren't worth interrupting the user about end up.
+
+use crate::errors::*;
+use crate::models::application::Preferences;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FILE_NAME: &str = "amp.log";
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Level::Trace => "trace",
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        };
+
+        // Padded, so that entries' messages line up.
+        f.pad(name)
+    }
+}
+
+impl FromStr for Level {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Level> {
+        match name {
+            "trace" => Ok(Level::Trace),
+            "debug" => Ok(Level::Debug),
+            "info" => Ok(Level::Info),
+            "warn" => Ok(Level::Warn),
+            "error" => Ok(Level::Error),
+            _ => bail!("Unknown log level \"{}\" (expected trace, debug, info, warn, or error)", name),
+        }
+    }
+}
+
+struct Logger {
+    level: Level,
+    // Opened on the first write, so that quiet sessions don't leave a log behind.
+    file: Option<File>,
+}
+
+lazy_static! {
+    static ref LOGGER: Mutex<Logger> = Mutex::new(Logger{ level: Level::Warn, file: None });
+}
+
+/// Sets the least severe level that will be written to the log.
+pub fn set_level(level: Level) {
+    if let Ok(mut logger) = LOGGER.lock() {
+        logger.level = level;
+    }
+}
+
+/// The location of the log file, which may not exist yet.
+pub fn path() -> Result<PathBuf> {
+    Ok(Preferences::state_path()?.join(FILE_NAME))
+}
+
+/// Appends a message to the log, if its level is enabled. Failing to
+/// write isn't worth reporting, as there's nowhere left to report it.
+pub fn write(level: Level, target: &str, message: fmt::Arguments) {
+    let mut logger = match LOGGER.lock() {
+        Ok(logger) => logger,
+        Err(_) => return,
+    };
+    if level < logger.level {
+        return;
+    }
+
+    if logger.file.is_none() {
+        logger.file = path().ok().and_then(|path| {
+            OpenOptions::new().create(true).append(true).open(path).ok()
+        });
+    }
+
+    if let Some(ref mut file) = logger.file {
+        let _ = writeln!(file, "{}", format_entry(SystemTime::now(), level, target, message));
+    }
+}
+
+fn format_entry(time: SystemTime, level: Level, target: &str, message: fmt::Arguments) -> String {
+    let timestamp = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+    format!(
+        "{}.{:03} {:<5} {}: {}",
+        timestamp.as_secs(),
+        timestamp.subsec_millis(),
+        level,
+        target,
+        message
+    )
+}
+
+#[macro_export]
+macro_rules! log_at {
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log::write($level, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)+) => { log_at!($crate::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)+) => { log_at!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)+) => { log_at!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)+) => { log_at!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! log_trace {
+    ($($arg:tt)+) => { log_at!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Level;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn levels_are_parsed_and_ordered_by_severity() {
+        assert_eq!("debug".parse::<Level>().unwrap(), Level::Debug);
+        assert!("verbose".parse::<Level>().is_err());
+        assert!(Level::Error > Level::Warn);
+        assert!(Level::Trace < Level::Debug);
+    }
+
+    #[test]
+    fn format_entry_includes_the_time_level_and_target() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_500);
+
+        assert_eq!(
+            super::format_entry(time, Level::Warn, "amp::view", format_args!("{} lines", 2)),
+            "1.500 warn  amp::view: 2 lines"
+        );
+    }
+}
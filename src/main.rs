@@ -1,16 +1,224 @@
 extern crate amp;
-use amp::Application;
+extern crate libc;
+use amp::{install_crash_reporter, send_to_server, Application};
 use amp::Error;
+use amp::log::{self, Level};
 use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::{Path, PathBuf};
+use std::process;
 
 fn main() {
+    // Pull out flags, leaving the paths to open.
+    let mut args: Vec<String> = env::args().collect();
+    let print_timings = take_flag(&mut args, "--timings");
+    let batch = take_flag(&mut args, "--batch");
+    let mut batch_commands = Vec::new();
+    while let Some(command) = take_option(&mut args, "-c") {
+        batch_commands.push(command);
+    }
+    let read_only = take_flag(&mut args, "-R");
+    let server = take_flag(&mut args, "--server");
+    let remote = take_flag(&mut args, "--remote");
+    let piped_input = if take_flag(&mut args, "-") {
+        match read_piped_input() {
+            Ok(piped_input) => Some(piped_input),
+            Err(e) => {
+                print_error(&e.into());
+                process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    match take_option(&mut args, "--log-level").map(|level| level.parse::<Level>()) {
+        Some(Ok(level)) => log::set_level(level),
+        Some(Err(e)) => {
+            print_error(&e);
+            process::exit(1);
+        }
+        None => (),
+    }
+    let record_input = take_option(&mut args, "--record-input");
+    let replay_input = take_option(&mut args, "--replay-input");
+    for path in record_input.iter().chain(replay_input.iter()) {
+        if path.is_empty() {
+            print_error(&"Please provide a path for the input recording".into());
+            process::exit(1);
+        }
+    }
+
+    if batch {
+        run_batch(&args, &batch_commands);
+        return;
+    }
+
+    // Without a server to send them to, the files are opened here instead.
+    if remote && open_remotely(&args) {
+        return;
+    }
+
+    // This needs to be in place before the application sets up the terminal,
+    // so that the terminal is restored before the report's location is printed.
+    install_crash_reporter();
+
     // Instantiate, run, and handle errors for the application.
-    if let Some(e) = Application::new(&env::args().collect())
-        .and_then(|mut app| app.run())
-        .err() { handle_error(&e) }
+    let mut app = match Application::new(&args) {
+        Ok(app) => app,
+        Err(e) => {
+            print_error(&e);
+            process::exit(1);
+        }
+    };
+    if let Some((ref content, _)) = piped_input {
+        if let Err(e) = app.open_stdin_buffer(content) {
+            app.view.restore_terminal();
+            print_error(&e);
+            process::exit(1);
+        }
+    }
+    if read_only {
+        app.make_buffers_read_only();
+    }
+    if server {
+        if let Err(e) = app.start_server() {
+            app.view.restore_terminal();
+            print_error(&e);
+            process::exit(1);
+        }
+    }
+    if let Some(ref path) = record_input {
+        if let Err(e) = app.start_recording_input(Path::new(path)) {
+            app.view.restore_terminal();
+            print_error(&e);
+            process::exit(1);
+        }
+    }
+    if let Some(ref path) = replay_input {
+        if let Err(e) = app.replay_input(Path::new(path)) {
+            app.view.restore_terminal();
+            print_error(&e);
+            process::exit(1);
+        }
+    }
+
+    if let Err(e) = app.run() {
+        // Hand the terminal back before reporting the error,
+        // so that we can ask about saving unsaved changes.
+        app.view.restore_terminal();
+        print_error(&e);
+        offer_to_save(&mut app);
+
+        // Exit with an error code.
+        process::exit(1);
+    }
+
+    if let Some(output) = app.output.take() {
+        app.view.restore_terminal();
+        let result = match piped_input {
+            Some((_, mut stdout)) => stdout.write_all(output.as_bytes()),
+            None => io::stdout().write_all(output.as_bytes()),
+        };
+        if let Err(e) = result {
+            print_error(&e.into());
+            process::exit(1);
+        }
+    }
+
+    if print_timings {
+        app.view.restore_terminal();
+        eprintln!("{}", app.startup_timings);
+    }
+}
+
+/// Runs the commands against the files without a UI, reporting
+/// their messages, and exiting with an error code if any fail.
+fn run_batch(args: &Vec<String>, commands: &[String]) {
+    let result = Application::new_headless(args).and_then(|mut app| app.run_batch(commands));
+    match result {
+        Ok(messages) => for message in messages {
+            eprintln!("{}", message);
+        },
+        Err(e) => {
+            print_error(&e);
+            process::exit(1);
+        }
+    }
 }
 
-fn handle_error(error: &Error) {
+/// Sends the files to the instance running with `--server`, waiting
+/// until they've been closed there. Returns false if there isn't one.
+fn open_remotely(args: &Vec<String>) -> bool {
+    let paths: Vec<PathBuf> = match env::current_dir() {
+        // URLs (e.g. for remote files over SSH) are passed along as-is.
+        Ok(directory) => args
+            .iter()
+            .skip(1)
+            .map(|arg| if arg.contains("://") { PathBuf::from(arg) } else { directory.join(arg) })
+            .collect(),
+        Err(e) => {
+            print_error(&e.into());
+            process::exit(1);
+        }
+    };
+    if paths.is_empty() {
+        print_error(&"Please provide files to open using --remote".into());
+        process::exit(1);
+    }
+
+    match send_to_server(&paths) {
+        Ok(sent) => sent,
+        Err(e) => {
+            print_error(&e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Reads everything piped to the editor, and then points stdin and stdout at
+/// the terminal, so that the editor can still be used. The original stdout
+/// is returned along with the input, for writing the result to.
+fn read_piped_input() -> io::Result<(String, File)> {
+    let mut content = String::new();
+    io::stdin().read_to_string(&mut content)?;
+
+    let terminal = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    unsafe {
+        let stdout = libc::dup(libc::STDOUT_FILENO);
+        if stdout < 0 ||
+            libc::dup2(terminal.as_raw_fd(), libc::STDIN_FILENO) < 0 ||
+            libc::dup2(terminal.as_raw_fd(), libc::STDOUT_FILENO) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok((content, File::from_raw_fd(stdout)))
+    }
+}
+
+/// Removes all occurrences of the flag, returning whether it was present.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let count = args.len();
+    args.retain(|arg| arg != flag);
+
+    args.len() != count
+}
+
+/// Removes the option and the value following it, returning the
+/// value (which is empty if the option was the last argument).
+fn take_option(args: &mut Vec<String>, option: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg == option)?;
+    args.remove(index);
+
+    if index < args.len() {
+        Some(args.remove(index))
+    } else {
+        Some(String::new())
+    }
+}
+
+fn print_error(error: &Error) {
     // Print the proximate/contextual error.
     eprintln!("error: {}", error);
 
@@ -23,7 +231,25 @@ fn handle_error(error: &Error) {
     if let Some(backtrace) = error.backtrace() {
         eprintln!("backtrace: {:?}", backtrace);
     }
+}
 
-    // Exit with an error code.
-    ::std::process::exit(1);
+fn offer_to_save(app: &mut Application) {
+    let paths = app.modified_buffer_paths();
+    if paths.is_empty() {
+        return;
+    }
+
+    eprintln!("\nThe following files have unsaved changes:");
+    for path in &paths {
+        eprintln!("  {}", path.to_string_lossy());
+    }
+    eprint!("Save them before exiting? [y/N] ");
+    let _ = io::stderr().flush();
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_ok() && answer.trim().eq_ignore_ascii_case("y") {
+        if let Err(e) = app.save_modified_buffers() {
+            print_error(&e);
+        }
+    }
 }
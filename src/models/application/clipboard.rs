@@ -1,24 +1,59 @@
 use crate::errors::*;
 use clipboard::{ClipboardContext, ClipboardProvider};
+use scribe::buffer::{Position, Range};
+use std::mem;
 
-/// In-app content can be captured in both regular and full-line selection
+/// The number of previous clipboard entries kept around for paste cycling.
+const HISTORY_SIZE: usize = 20;
+
+/// In-app content can be captured in regular, full-line, and block selection
 /// modes. This type describes the structure of said content, based on the
 /// context in which it was captured. When OS-level clipboard contents are
 /// used, they are always represented as inline, as we cannot infer block
 /// style without the copy context.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ClipboardContent {
     Inline(String),
     Block(String),
+    /// The rows of a block selection, separated by newlines,
+    /// which are pasted into the same columns on successive lines.
+    Rectangle(String),
     None,
 }
 
+impl ClipboardContent {
+    pub fn text(&self) -> Option<&str> {
+        match *self {
+            ClipboardContent::Inline(ref content) |
+            ClipboardContent::Block(ref content) |
+            ClipboardContent::Rectangle(ref content) => Some(content),
+            ClipboardContent::None => None,
+        }
+    }
+}
+
+/// Describes the most recent paste, so that it can
+/// be replaced in-place with earlier clipboard entries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Paste {
+    pub buffer_id: Option<usize>,
+    pub range: Range,
+    pub content: String,
+    pub cursor: Position,
+    /// The clipboard entry that was pasted; zero refers
+    /// to the current content, and higher values to the
+    /// corresponding (one-based) history entries.
+    pub entry: usize,
+}
+
 /// Qualifies in-app copy/paste content with structural information, and
 /// synchronizes said content with the OS-level clipboard (preferring it
 /// in scenarios where it differs from the in-app equivalent).
 pub struct Clipboard {
     content: ClipboardContent,
+    history: Vec<ClipboardContent>,
     system_clipboard: Option<ClipboardContext>,
+    pub last_paste: Option<Paste>,
 }
 
 impl Default for Clipboard {
@@ -37,7 +72,9 @@ impl Clipboard {
 
         Clipboard {
             content: ClipboardContent::None,
+            history: Vec::new(),
             system_clipboard,
+            last_paste: None,
         }
     }
 
@@ -56,7 +93,8 @@ impl Clipboard {
                             // There is system clipboard content we can use.
                             match self.content {
                                 ClipboardContent::Inline(ref app_content) |
-                                ClipboardContent::Block(ref app_content) => {
+                                ClipboardContent::Block(ref app_content) |
+                                ClipboardContent::Rectangle(ref app_content) => {
                                     // We have in-app clipboard content, too. Prefer
                                     // the system clipboard content if they differ.
                                     if content != *app_content {
@@ -77,22 +115,37 @@ impl Clipboard {
         };
 
         // Update the in-app clipboard if we've found newer content.
-        if new_content.is_some() {
-            self.content = new_content.unwrap();
+        if let Some(content) = new_content {
+            self.replace_content(content);
         }
 
         &self.content
     }
 
+    /// Previous clipboard entries, from most to least recent.
+    pub fn history(&self) -> &[ClipboardContent] {
+        &self.history
+    }
+
+    /// Returns the current content for index zero, and
+    /// the corresponding history entry for higher values.
+    pub fn entry(&self, index: usize) -> Option<&ClipboardContent> {
+        match index {
+            0 => Some(&self.content),
+            _ => self.history.get(index - 1),
+        }
+    }
+
     // Updates the in-app and system clipboards with the specified content.
     pub fn set_content(&mut self, content: ClipboardContent) -> Result<()> {
         // Update the in-app clipboard.
-        self.content = content;
+        self.replace_content(content);
 
         // Update the system clipboard.
         match self.content {
             ClipboardContent::Inline(ref app_content) |
-            ClipboardContent::Block(ref app_content) => {
+            ClipboardContent::Block(ref app_content) |
+            ClipboardContent::Rectangle(ref app_content) => {
                 if let Some(ref mut clipboard) = self.system_clipboard {
                     return clipboard
                         .set_contents(app_content.clone())
@@ -104,4 +157,36 @@ impl Clipboard {
 
         Ok(())
     }
+
+    // Swaps in new content, moving the previous content into the history.
+    fn replace_content(&mut self, content: ClipboardContent) {
+        let previous = mem::replace(&mut self.content, content);
+
+        if previous != ClipboardContent::None && previous != self.content {
+            let current = &self.content;
+            self.history.retain(|entry| *entry != previous && entry != current);
+            self.history.insert(0, previous);
+            self.history.truncate(HISTORY_SIZE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clipboard, ClipboardContent};
+
+    #[test]
+    fn set_content_moves_previous_content_into_the_history() {
+        let mut clipboard = Clipboard::new();
+        clipboard.set_content(ClipboardContent::Inline(String::from("amp"))).unwrap();
+        clipboard.set_content(ClipboardContent::Block(String::from("editor\n"))).unwrap();
+        clipboard.set_content(ClipboardContent::Inline(String::from("amp"))).unwrap();
+
+        assert_eq!(
+            clipboard.history(),
+            &[
+                ClipboardContent::Block(String::from("editor\n")),
+            ]
+        );
+    }
 }
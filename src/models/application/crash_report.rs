@@ -0,0 +1,148 @@
+use crate::errors::*;
+use crate::input::Key;
+use crate::models::application::Preferences;
+use error_chain::Backtrace;
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::{Mutex, Once};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DIRECTORY_NAME: &str = "crash_reports";
+const MAX_RECENT_KEYS: usize = 50;
+
+// Modes in which characters run commands, rather than being typed as text.
+const COMMAND_MODES: [&str; 8] = [
+    "normal", "select", "select_line", "search", "search_select", "jump", "confirm", "diff"
+];
+
+static PANIC_HOOK: Once = Once::new();
+
+/// What the application was up to, kept up to date as it runs
+/// so that it's available to the panic hook if it crashes.
+#[derive(Default)]
+struct Context {
+    recent_keys: VecDeque<String>,
+    buffer_paths: Vec<String>,
+}
+
+lazy_static! {
+    static ref CONTEXT: Mutex<Context> = Mutex::new(Context::default());
+}
+
+/// Writes a crash report when the application panics, and prints its location.
+/// This runs after any previously installed hooks, so install it before the
+/// terminal's (which restores it before anything's printed to it).
+pub fn install() {
+    PANIC_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            previous_hook(info);
+
+            match write(&info.to_string()) {
+                Ok(path) => eprintln!(
+                    "A crash report has been written to {}; please include it when reporting this problem.",
+                    path.to_string_lossy()
+                ),
+                Err(e) => eprintln!("Couldn't write a crash report: {}", e),
+            }
+        }));
+    });
+}
+
+/// Adds a key to the list of those most recently pressed. Characters typed
+/// as text are recorded without their values, which could be sensitive.
+pub fn record_key(mode: &str, key: &Key) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.recent_keys.push_back(describe_key(mode, key));
+
+        if context.recent_keys.len() > MAX_RECENT_KEYS {
+            context.recent_keys.pop_front();
+        }
+    }
+}
+
+pub fn record_buffer_paths(paths: Vec<String>) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.buffer_paths = paths;
+    }
+}
+
+fn describe_key(mode: &str, key: &Key) -> String {
+    match *key {
+        Key::Char(_) if !COMMAND_MODES.contains(&mode) => format!("{}: Char(_)", mode),
+        _ => format!("{}: {:?}", mode, key),
+    }
+}
+
+fn write(panic: &str) -> Result<PathBuf> {
+    let directory = Preferences::state_path()?.join(DIRECTORY_NAME);
+    fs::create_dir_all(&directory).chain_err(|| "Couldn't create crash report directory")?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let path = directory.join(format!("crash-{}.txt", timestamp));
+
+    // The panic may have happened while the context was locked;
+    // waiting on it here would never finish, so do without.
+    let mut report = match CONTEXT.try_lock() {
+        Ok(context) => format_report(panic, &context),
+        Err(_) => format_report(panic, &Context::default()),
+    };
+    report.push_str(&format!("\nBacktrace:\n{:?}\n", Backtrace::new()));
+    fs::write(&path, report).chain_err(|| "Couldn't write crash report")?;
+
+    Ok(path)
+}
+
+fn format_report(panic: &str, context: &Context) -> String {
+    let mut report = format!(
+        "amp {} ({} {})\n\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        env::consts::OS,
+        env::consts::ARCH,
+        panic
+    );
+
+    report.push_str("\nOpen buffers:\n");
+    for path in &context.buffer_paths {
+        report.push_str(&format!("  {}\n", path));
+    }
+
+    report.push_str("\nRecent keys (oldest first):\n");
+    for key in &context.recent_keys {
+        report.push_str(&format!("  {}\n", key));
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::Key;
+    use super::Context;
+
+    #[test]
+    fn describe_key_omits_characters_typed_as_text() {
+        assert_eq!(super::describe_key("normal", &Key::Char('j')), "normal: Char('j')");
+        assert_eq!(super::describe_key("insert", &Key::Char('p')), "insert: Char(_)");
+        assert_eq!(super::describe_key("insert", &Key::Esc), "insert: Esc");
+    }
+
+    #[test]
+    fn format_report_includes_buffers_and_recent_keys() {
+        let mut context = Context::default();
+        context.buffer_paths.push(String::from("src/main.rs"));
+        context.recent_keys.push_back(String::from("normal: Char('j')"));
+        let report = super::format_report("panicked at 'oops'", &context);
+
+        assert!(report.starts_with(&format!("amp {}", env!("CARGO_PKG_VERSION"))));
+        assert!(report.contains("panicked at 'oops'\n"));
+        assert!(report.contains("Open buffers:\n  src/main.rs\n"));
+        assert!(report.contains("Recent keys (oldest first):\n  normal: Char('j')\n"));
+    }
+}
@@ -1,9 +1,28 @@
 use crate::input::Key;
+use crate::models::application::follow::FileChange;
+#[cfg(feature = "git")]
+use crate::models::application::modes::Revision;
 use crate::models::application::modes::open::Index;
+use crate::util::lint::Diagnostic;
+use crate::util::task::Task;
+use std::path::PathBuf;
 
 #[derive(Debug, PartialEq)]
 pub enum Event {
     Key(Key),
     Resize,
-    OpenModeIndexComplete(Index)
+    Suspend,
+    Terminate,
+    OpenModeIndexComplete(Index),
+    LintComplete(PathBuf, Result<Vec<Diagnostic>, String>),
+    TaskScanComplete(Vec<Task>),
+    TagsGenerated(Result<(), String>),
+    /// Themes have finished loading in the background.
+    ThemesLoaded,
+    /// The commits that changed the file, newest first.
+    #[cfg(feature = "git")]
+    FileHistoryLoaded(PathBuf, Result<Vec<Revision>, String>),
+    FileChanged(PathBuf, FileChange),
+    /// Paths sent by a client (using `amp --remote`), identified by the number.
+    OpenRequested(usize, Vec<PathBuf>),
 }
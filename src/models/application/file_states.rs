@@ -0,0 +1,231 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use crate::view::View;
+use crate::yaml::YamlEmitter;
+use crate::yaml::yaml::{Hash, Yaml, YamlLoader};
+use scribe::{Buffer, Workspace};
+use scribe::buffer::Position;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = "file_states.yml";
+const MAX_ENTRIES: usize = 500;
+
+/// Editor state for a single file, captured when it's closed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileState {
+    pub cursor: Position,
+    pub line_offset: usize,
+}
+
+/// Remembers where the cursor and viewport were for recently closed files,
+/// so that reopening one (in this session or a later one) picks up where
+/// it left off. Entries are ordered from least to most recently recorded.
+pub struct FileStates {
+    path: Option<PathBuf>,
+    entries: Vec<(PathBuf, FileState)>,
+}
+
+impl FileStates {
+    /// Builds an empty store. Without a path, the store is in-memory only.
+    pub fn new(path: Option<PathBuf>) -> FileStates {
+        FileStates {
+            path,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Loads previously persisted states from the application state
+    /// directory, falling back to an empty store if they can't be read.
+    pub fn load() -> FileStates {
+        let path = Preferences::state_path().ok().map(|dir| dir.join(FILE_NAME));
+        let mut states = FileStates::new(path);
+
+        if let Some(ref path) = states.path {
+            if let Ok(entries) = read_entries(path) {
+                states.entries = entries;
+            }
+        }
+
+        states
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&FileState> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|&&(ref entry_path, _)| entry_path == path)
+            .map(|&(_, ref state)| state)
+    }
+
+    pub fn insert(&mut self, path: PathBuf, state: FileState) {
+        self.entries.retain(|&(ref entry_path, _)| *entry_path != path);
+        self.entries.push((path, state));
+
+        // Discard the least recently recorded entries.
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(0..excess);
+        }
+    }
+
+    /// Captures the cursor and scroll position for the specified buffer.
+    /// Buffers without a path are ignored.
+    pub fn record(&mut self, buffer: &Buffer, view: &mut View) -> Result<()> {
+        if let Some(ref path) = buffer.path {
+            let state = FileState {
+                cursor: *buffer.cursor,
+                line_offset: view.line_offset(buffer)?,
+            };
+            self.insert(path.clone(), state);
+        }
+
+        Ok(())
+    }
+
+    /// Captures the state of every open buffer in the workspace,
+    /// leaving the originally selected buffer selected.
+    pub fn record_workspace(&mut self, workspace: &mut Workspace, view: &mut View) -> Result<()> {
+        let initial_id = match workspace.current_buffer() {
+            Some(buffer) => buffer.id,
+            None => return Ok(()),
+        };
+
+        loop {
+            if let Some(buffer) = workspace.current_buffer() {
+                self.record(buffer, view)?;
+            }
+
+            workspace.next_buffer();
+            if workspace.current_buffer().map(|b| b.id) == Some(initial_id) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves the buffer's cursor and viewport to their previously recorded
+    /// positions, provided they're still valid for the buffer's contents.
+    pub fn restore(&self, buffer: &mut Buffer, view: &mut View) -> Result<()> {
+        let state = match buffer.path.as_ref().and_then(|path| self.get(path)) {
+            Some(state) => state.clone(),
+            None => return Ok(()),
+        };
+
+        // The file may have changed since we last saw it;
+        // move_to will refuse out-of-bounds positions.
+        if buffer.cursor.move_to(state.cursor) {
+            view.set_line_offset(buffer, state.line_offset)?;
+            view.scroll_to_cursor(buffer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the store to disk, if it was built with a path.
+    pub fn save(&self) -> Result<()> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+
+        let entries = self.entries
+            .iter()
+            .map(|&(ref path, ref state)| {
+                let mut entry = Hash::new();
+                entry.insert(
+                    Yaml::String("path".to_string()),
+                    Yaml::String(path.to_string_lossy().into_owned())
+                );
+                entry.insert(
+                    Yaml::String("line".to_string()),
+                    Yaml::Integer(state.cursor.line as i64)
+                );
+                entry.insert(
+                    Yaml::String("offset".to_string()),
+                    Yaml::Integer(state.cursor.offset as i64)
+                );
+                entry.insert(
+                    Yaml::String("line_offset".to_string()),
+                    Yaml::Integer(state.line_offset as i64)
+                );
+
+                Yaml::Hash(entry)
+            })
+            .collect();
+
+        let mut data = String::new();
+        YamlEmitter::new(&mut data)
+            .dump(&Yaml::Array(entries))
+            .map_err(|_| Error::from("Couldn't serialize file states"))?;
+        fs::write(path, data).chain_err(|| "Couldn't write file states")
+    }
+}
+
+fn read_entries(path: &Path) -> Result<Vec<(PathBuf, FileState)>> {
+    let data = fs::read_to_string(path).chain_err(|| "Couldn't read file states")?;
+    let documents = YamlLoader::load_from_str(&data)
+        .chain_err(|| "Couldn't parse file states")?;
+    let mut entries = Vec::new();
+
+    if let Some(Yaml::Array(items)) = documents.into_iter().nth(0) {
+        for item in items {
+            if let (Some(path), Some(line), Some(offset), Some(line_offset)) = (
+                item["path"].as_str(),
+                item["line"].as_i64(),
+                item["offset"].as_i64(),
+                item["line_offset"].as_i64(),
+            ) {
+                entries.push((
+                    PathBuf::from(path),
+                    FileState {
+                        cursor: Position {
+                            line: line as usize,
+                            offset: offset as usize,
+                        },
+                        line_offset: line_offset as usize,
+                    },
+                ));
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileState, FileStates, MAX_ENTRIES};
+    use scribe::buffer::Position;
+    use std::path::{Path, PathBuf};
+
+    fn state(line: usize) -> FileState {
+        FileState {
+            cursor: Position { line, offset: 0 },
+            line_offset: 0,
+        }
+    }
+
+    #[test]
+    fn insert_replaces_existing_entries_for_the_same_path() {
+        let mut states = FileStates::new(None);
+        states.insert(PathBuf::from("/amp/file"), state(1));
+        states.insert(PathBuf::from("/amp/file"), state(2));
+
+        assert_eq!(states.entries.len(), 1);
+        assert_eq!(states.get(Path::new("/amp/file")), Some(&state(2)));
+    }
+
+    #[test]
+    fn insert_discards_least_recently_recorded_entries() {
+        let mut states = FileStates::new(None);
+        for index in 0..MAX_ENTRIES + 1 {
+            states.insert(PathBuf::from(format!("/amp/{}", index)), state(index));
+        }
+
+        assert_eq!(states.entries.len(), MAX_ENTRIES);
+        assert!(states.get(Path::new("/amp/0")).is_none());
+        assert!(states.get(Path::new("/amp/1")).is_some());
+    }
+}
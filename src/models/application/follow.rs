@@ -0,0 +1,158 @@
+use crate::errors::*;
+use crate::models::application::Event;
+use crate::view::View;
+use scribe::Buffer;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::str;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+/// How often (in milliseconds) followed files are checked for new content.
+const POLL_INTERVAL: u64 = 250;
+
+/// What's happened to a followed file since it was last checked.
+#[derive(Debug, PartialEq)]
+pub enum FileChange {
+    Appended(String),
+    /// The file's shrunk (e.g. it's been truncated or rotated),
+    /// so its buffer has to be reloaded from scratch.
+    Truncated,
+}
+
+/// Watches a file in the background, sending an event whenever content is
+/// appended to it, like `tail -f`. The watch ends once this is dropped.
+pub struct FileWatcher {
+    stopped: Arc<AtomicBool>,
+}
+
+impl FileWatcher {
+    /// Starts watching for content beyond the specified length (in bytes),
+    /// which should be that of the file's content when it was last read.
+    pub fn start(path: PathBuf, mut length: u64, events: Sender<Event>) -> Result<FileWatcher> {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let watcher = FileWatcher { stopped: stopped.clone() };
+
+        thread::spawn(move || {
+            loop {
+                thread::sleep(Duration::from_millis(POLL_INTERVAL));
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                // The file may briefly go missing while it's being rotated;
+                // it's picked up again (as truncated) once it's back.
+                if let Ok(Some(change)) = check(&path, &mut length) {
+                    if events.send(Event::FileChanged(path.clone(), change)).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(watcher)
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Brings a followed file's buffer up to date with the change. If the cursor
+/// was resting at the end of the buffer, it's moved (and the view scrolled)
+/// to the new content; otherwise, it's left where the user put it.
+pub fn apply(change: FileChange, buffer: &mut Buffer, view: &mut View) -> Result<()> {
+    let last_line_visible = view.line_offset(buffer)? + view.page_height() >= buffer.line_count();
+    let at_end = last_line_visible && buffer.cursor.line + 1 >= buffer.line_count();
+
+    match change {
+        FileChange::Appended(content) => {
+            // The content goes straight into the buffer's data, rather than
+            // through its history, so that it can't be undone, and doesn't
+            // change whether the buffer has modifications of its own.
+            let position = *buffer.cursor;
+            buffer.cursor.move_to_last_line();
+            buffer.cursor.move_to_end_of_line();
+            let end = *buffer.cursor;
+            buffer.cursor.data.borrow_mut().insert(&content, &end);
+            buffer.cursor.move_to(position);
+
+            // Let the view know, as it would for any other change.
+            if let Some(ref callback) = buffer.change_callback {
+                callback(end);
+            }
+        },
+        FileChange::Truncated => buffer.reload().chain_err(|| "Couldn't reload the followed file")?,
+    }
+
+    if at_end {
+        buffer.cursor.move_to_last_line();
+        view.scroll_to_cursor(buffer)?;
+    }
+
+    Ok(())
+}
+
+// Reads whatever's been added to the file since it was
+// last checked, when it was the specified number of bytes.
+fn check(path: &Path, length: &mut u64) -> io::Result<Option<FileChange>> {
+    let mut file = File::open(path)?;
+    let current_length = file.metadata()?.len();
+    if current_length == *length {
+        return Ok(None);
+    } else if current_length < *length {
+        *length = current_length;
+        return Ok(Some(FileChange::Truncated));
+    }
+
+    let mut content = Vec::new();
+    file.seek(SeekFrom::Start(*length))?;
+    file.read_to_end(&mut content)?;
+
+    // A character that's only been partly written is left for next time.
+    let complete_length = match str::from_utf8(&content) {
+        Err(ref error) if error.error_len().is_none() => error.valid_up_to(),
+        _ => content.len(),
+    };
+    if complete_length == 0 {
+        return Ok(None);
+    }
+    content.truncate(complete_length);
+    *length += complete_length as u64;
+
+    Ok(Some(FileChange::Appended(String::from_utf8_lossy(&content).into_owned())))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use super::{check, FileChange};
+
+    #[test]
+    fn check_reads_appended_content_and_detects_truncation() {
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/followed.log"));
+        fs::write(&path, "started\n").unwrap();
+        let mut length = 8;
+        assert_eq!(check(&path, &mut length).unwrap(), None);
+
+        // Partly written characters wait for the rest of their bytes.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"caf\xC3").unwrap();
+        assert_eq!(check(&path, &mut length).unwrap(), Some(FileChange::Appended(String::from("caf"))));
+        file.write_all(b"\xA9\n").unwrap();
+        assert_eq!(check(&path, &mut length).unwrap(), Some(FileChange::Appended(String::from("é\n"))));
+        assert_eq!(length, 14);
+
+        fs::write(&path, "rotated\n").unwrap();
+        assert_eq!(check(&path, &mut length).unwrap(), Some(FileChange::Truncated));
+        assert_eq!(length, 8);
+    }
+}
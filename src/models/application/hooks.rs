@@ -0,0 +1,92 @@
+use crate::commands;
+use crate::errors::*;
+use crate::models::application::Application;
+
+/// Points in the editor's lifecycle at which configured commands are run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hook {
+    /// A file has been opened in a new buffer.
+    BufferOpen,
+    /// The current buffer is about to be written to disk.
+    BeforeSave,
+    /// The current buffer has been written to disk.
+    AfterSave,
+    /// The application has switched to a different mode.
+    ModeChange,
+}
+
+impl Hook {
+    /// The name the hook is configured under (e.g. "after_save").
+    pub fn name(self) -> &'static str {
+        match self {
+            Hook::BufferOpen => "buffer_open",
+            Hook::BeforeSave => "before_save",
+            Hook::AfterSave => "after_save",
+            Hook::ModeChange => "mode_change",
+        }
+    }
+}
+
+/// Runs the commands configured for the hook, in order, stopping at the first
+/// one that fails. Hooks don't trigger other hooks (e.g. an after_save hook
+/// that saves the buffer), so they can't loop.
+pub fn run(app: &mut Application, hook: Hook) -> Result<()> {
+    if app.running_hook {
+        return Ok(());
+    }
+
+    let command_names = app.preferences.borrow().hook_commands(hook.name());
+    if command_names.is_empty() {
+        return Ok(());
+    }
+
+    let commands = commands::hash_map();
+    app.running_hook = true;
+    let result = command_names.iter().try_for_each(|name| {
+        let command = commands
+            .get(name.as_str())
+            .ok_or_else(|| format!("Hook command \"{}\" doesn't exist", name))?;
+
+        command(app)
+    });
+    app.running_hook = false;
+
+    result.chain_err(|| format!("Failed to run {} hook", hook.name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::{Application, Preferences};
+    use crate::yaml::yaml::YamlLoader;
+    use scribe::Buffer;
+    use super::Hook;
+
+    fn app_with_hooks(hooks: &str) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str(hooks).unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+
+        app
+    }
+
+    #[test]
+    fn run_runs_configured_commands_in_order() {
+        let mut app = app_with_hooks("hooks:\n  buffer_open:\n    - cursor::move_to_last_line\n    - cursor::move_to_end_of_line");
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        app.workspace.add_buffer(buffer);
+        super::run(&mut app, Hook::BufferOpen).unwrap();
+
+        let position = *app.workspace.current_buffer().unwrap().cursor;
+        assert_eq!((position.line, position.offset), (1, 6));
+    }
+
+    #[test]
+    fn run_reports_unknown_commands() {
+        let mut app = app_with_hooks("hooks:\n  after_save: buffer::nonexistent");
+        let error = super::run(&mut app, Hook::AfterSave).unwrap_err();
+
+        assert_eq!(error.to_string(), "Failed to run after_save hook");
+        assert!(!app.running_hook);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::errors::*;
+use crate::input::{self, Key};
+use crate::models::application::Event;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Writes keys to a file as they're pressed, so that a session can be
+/// replayed later. Each line holds the number of milliseconds since recording
+/// started and the key, using keymap notation (e.g. "1520 ctrl-r").
+pub struct InputRecorder {
+    output: BufWriter<File>,
+    started: Instant,
+}
+
+impl InputRecorder {
+    pub fn create(path: &Path) -> Result<InputRecorder> {
+        let file = File::create(path).chain_err(|| "Couldn't create the input recording")?;
+        let mut output = BufWriter::new(file);
+        writeln!(output, "# amp {} input recording", env!("CARGO_PKG_VERSION"))
+            .chain_err(|| "Couldn't write to the input recording")?;
+
+        Ok(InputRecorder { output, started: Instant::now() })
+    }
+
+    pub fn record(&mut self, key: &Key) -> Result<()> {
+        let elapsed = self.started.elapsed();
+        let milliseconds = elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_millis());
+
+        // Flushed right away, so that keys leading up to a crash are kept.
+        writeln!(self.output, "{} {}", milliseconds, key_name(key))
+            .and_then(|_| self.output.flush())
+            .chain_err(|| "Couldn't write to the input recording")
+    }
+}
+
+/// Parses a recording into keys, along with when they were pressed. Blank
+/// lines and comments (starting with "#") are skipped, so recordings can be
+/// annotated or written by hand.
+pub fn parse(recording: &str) -> Result<Vec<(Duration, Key)>> {
+    let mut keys = Vec::new();
+
+    for (index, line) in recording.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.splitn(2, ' ');
+        let time = fields.next().and_then(|time| time.parse().ok());
+        let key = fields.next().and_then(|name| parse_key_name(name));
+        match (time, key) {
+            (Some(time), Some(key)) => keys.push((Duration::from_millis(time), key)),
+            _ => bail!("Invalid input recording entry on line {}: {}", index + 1, line),
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Sends the keys to the application in the background,
+/// at the same pace at which they were recorded.
+pub fn replay(keys: Vec<(Duration, Key)>, events: Sender<Event>) {
+    thread::spawn(move || {
+        let started = Instant::now();
+
+        for (time, key) in keys {
+            let elapsed = started.elapsed();
+            if time > elapsed {
+                thread::sleep(time - elapsed);
+            }
+            if events.send(Event::Key(key)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+fn key_name(key: &Key) -> String {
+    match *key {
+        Key::Backspace => String::from("backspace"),
+        Key::Left => String::from("left"),
+        Key::Right => String::from("right"),
+        Key::Up => String::from("up"),
+        Key::Down => String::from("down"),
+        Key::Home => String::from("home"),
+        Key::End => String::from("end"),
+        Key::PageUp => String::from("page_up"),
+        Key::PageDown => String::from("page_down"),
+        Key::Delete => String::from("delete"),
+        Key::Insert => String::from("insert"),
+        Key::Esc => String::from("escape"),
+        Key::Tab => String::from("tab"),
+        Key::Enter => String::from("enter"),
+        Key::AnyChar => String::from("_"),
+        Key::Char(' ') => String::from("space"),
+        Key::Char(character) => character.to_string(),
+        Key::Ctrl(character) => format!("ctrl-{}", character),
+        Key::Alt(character) => format!("alt-{}", character),
+    }
+}
+
+// Single characters are always taken literally, since
+// keys like "_" and "-" have other meanings in keymaps.
+fn parse_key_name(name: &str) -> Option<Key> {
+    let mut characters = name.chars();
+    match (characters.next(), characters.next()) {
+        (Some(character), None) => Some(Key::Char(character)),
+        _ => input::parse_key(name).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::Key;
+    use crate::models::application::Event;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+    use super::{parse, replay, InputRecorder};
+
+    #[test]
+    fn recorded_keys_can_be_parsed() {
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/input_recording"));
+        let mut recorder = InputRecorder::create(&path).unwrap();
+        let keys = vec![Key::Char('_'), Key::Char('-'), Key::Char(' '), Key::Ctrl('r'), Key::Alt('j'), Key::PageDown];
+        for key in &keys {
+            recorder.record(key).unwrap();
+        }
+
+        let parsed: Vec<Key> = parse(&fs::read_to_string(&path).unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|(_, key)| key)
+            .collect();
+        assert_eq!(parsed, keys);
+        assert!(parse("10 ctrl-").is_err());
+        assert!(parse("later a").is_err());
+    }
+
+    #[test]
+    fn replay_sends_keys_at_the_recorded_pace() {
+        let keys = parse("# Type \"amp\"\n0 i\n5 a\n10 m\n\n40 p\n").unwrap();
+        let (events, received) = mpsc::channel();
+        let started = Instant::now();
+        replay(keys, events);
+
+        let received: Vec<Event> = received.iter().collect();
+        assert!(started.elapsed() >= Duration::from_millis(40));
+        assert_eq!(received, vec![
+            Event::Key(Key::Char('i')),
+            Event::Key(Key::Char('a')),
+            Event::Key(Key::Char('m')),
+            Event::Key(Key::Char('p')),
+        ]);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::errors::*;
+use fragment::matching::AsStr;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DIRECTORY_NAME: &str = "history";
+
+/// The number of saved versions kept for each file.
+const MAX_SNAPSHOTS: usize = 50;
+
+/// Saved versions older than this (in days) are pruned.
+const MAX_AGE: u64 = 30;
+
+/// A copy of a file as it was when it was saved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub saved_at: SystemTime,
+    label: String,
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl AsStr for Snapshot {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Returns the directory that copies of saved files are
+/// written to, creating it if necessary.
+pub fn directory() -> Result<PathBuf> {
+    let path = state_path()?.join(DIRECTORY_NAME);
+    fs::create_dir_all(&path).chain_err(|| "Couldn't create local history directory")?;
+
+    Ok(path)
+}
+
+/// Keeps a copy of the file's saved content in the directory, unless it's
+/// the same as the last one kept, pruning copies that are too old or too many.
+pub fn record(path: &Path, content: &str, directory: &Path) -> Result<()> {
+    let snapshot_directory = directory.join(file_name(path));
+    let snapshots = list(path, directory);
+    if let Some(latest) = snapshots.first() {
+        if fs::read_to_string(&latest.path).map(|data| data == content).unwrap_or(false) {
+            return Ok(());
+        }
+    }
+
+    fs::create_dir_all(&snapshot_directory).chain_err(|| "Couldn't create local history directory")?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let snapshot_path = snapshot_directory.join(format!("{}", timestamp.as_secs() * 1000 + u64::from(timestamp.subsec_millis())));
+    fs::write(&snapshot_path, content).chain_err(|| "Couldn't write to local history")?;
+
+    let max_age = Duration::from_secs(MAX_AGE * 24 * 60 * 60);
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        let expired = snapshot.saved_at.elapsed().map(|age| age > max_age).unwrap_or(false);
+
+        // The new copy takes up one of the available spots.
+        if expired || index + 1 >= MAX_SNAPSHOTS {
+            let _ = fs::remove_file(&snapshot.path);
+        }
+    }
+
+    Ok(())
+}
+
+/// The copies kept for the file, from newest to oldest.
+pub fn list(path: &Path, directory: &Path) -> Vec<Snapshot> {
+    let entries = match fs::read_dir(directory.join(file_name(path))) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let now = SystemTime::now();
+
+    let mut snapshots: Vec<Snapshot> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let millis: u64 = entry.file_name().to_str()?.parse().ok()?;
+            let saved_at = UNIX_EPOCH + Duration::from_millis(millis);
+            let age = now.duration_since(saved_at).unwrap_or_default();
+
+            Some(Snapshot {
+                path: entry.path(),
+                saved_at,
+                label: format!("saved {}", describe_age(age)),
+            })
+        }).collect();
+    snapshots.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+
+    snapshots
+}
+
+#[cfg(not(test))]
+fn state_path() -> Result<PathBuf> {
+    crate::models::application::Preferences::state_path()
+}
+
+// Keep test runs from writing to the user's state.
+#[cfg(test)]
+fn state_path() -> Result<PathBuf> {
+    Ok(PathBuf::from(env!("OUT_DIR")))
+}
+
+// Flattens the file's path into a directory name (e.g. "%home%amp%notes.md"),
+// so that copies of identically named files don't end up together.
+fn file_name(path: &Path) -> String {
+    path.to_string_lossy().replace(MAIN_SEPARATOR, "%")
+}
+
+fn describe_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    let (count, unit) = if seconds < 60 {
+        return String::from("just now");
+    } else if seconds < 60 * 60 {
+        (seconds / 60, "minute")
+    } else if seconds < 24 * 60 * 60 {
+        (seconds / (60 * 60), "hour")
+    } else {
+        (seconds / (24 * 60 * 60), "day")
+    };
+
+    format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::thread;
+    use std::time::Duration;
+    use super::{describe_age, list, record};
+
+    #[test]
+    fn record_skips_unchanged_content_and_lists_newest_first() {
+        let directory = PathBuf::from(concat!(env!("OUT_DIR"), "/local_history"));
+        let _ = fs::remove_dir_all(&directory);
+        let path = Path::new("/amp/notes.md");
+
+        record(path, "amp", &directory).unwrap();
+        record(path, "amp", &directory).unwrap();
+        thread::sleep(Duration::from_millis(5));
+        record(path, "editor", &directory).unwrap();
+
+        let snapshots = list(path, &directory);
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(fs::read_to_string(&snapshots[0].path).unwrap(), "editor");
+        assert_eq!(fs::read_to_string(&snapshots[1].path).unwrap(), "amp");
+        assert_eq!(snapshots[0].to_string(), "saved just now");
+        assert!(list(Path::new("/amp/other.md"), &directory).is_empty());
+    }
+
+    #[test]
+    fn describe_age_uses_the_largest_whole_unit() {
+        assert_eq!(describe_age(Duration::from_secs(59)), "just now");
+        assert_eq!(describe_age(Duration::from_secs(60)), "1 minute ago");
+        assert_eq!(describe_age(Duration::from_secs(7300)), "2 hours ago");
+        assert_eq!(describe_age(Duration::from_secs(86400 * 3)), "3 days ago");
+    }
+}
@@ -0,0 +1,80 @@
+use crate::errors::*;
+use std::fmt;
+
+const MAX_ENTRIES: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Info,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Info => write!(f, "info"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LoggedMessage {
+    pub severity: Severity,
+    pub content: String,
+}
+
+/// Keeps the messages and errors reported by commands, which are otherwise
+/// only shown in the status line until the next key is pressed.
+/// Entries are ordered from oldest to newest.
+pub struct MessageLog {
+    entries: Vec<LoggedMessage>,
+}
+
+impl MessageLog {
+    pub fn new() -> MessageLog {
+        MessageLog{ entries: Vec::new() }
+    }
+
+    pub fn record<T: Into<String>>(&mut self, severity: Severity, content: T) {
+        self.entries.push(LoggedMessage{ severity, content: content.into() });
+
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Records an error, along with the chain of errors that caused it.
+    pub fn record_error(&mut self, error: &Error) {
+        let causes: Vec<String> = error.iter().map(|cause| cause.to_string()).collect();
+
+        self.record(Severity::Error, causes.join(": "));
+    }
+
+    pub fn entries(&self) -> &[LoggedMessage] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::*;
+    use super::{MessageLog, Severity};
+
+    #[test]
+    fn record_error_includes_its_causes() {
+        let mut log = MessageLog::new();
+        let error: Result<()> = Err(Error::from("Permission denied"));
+        log.record(Severity::Info, "Created src");
+        log.record_error(&error.chain_err(|| "Unable to save buffer").unwrap_err());
+
+        let entries: Vec<(Severity, &str)> = log.entries()
+            .iter()
+            .map(|entry| (entry.severity, entry.content.as_str()))
+            .collect();
+        assert_eq!(entries, vec![
+            (Severity::Info, "Created src"),
+            (Severity::Error, "Unable to save buffer: Permission denied"),
+        ]);
+    }
+}
@@ -1,113 +1,373 @@
 mod clipboard;
+mod crash_report;
 mod event;
+mod file_states;
+mod follow;
+mod hooks;
+mod input_recording;
+pub mod local_history;
+mod message_log;
 pub mod modes;
 mod preferences;
+mod recent_files;
+mod recovery;
+mod search_history;
+mod server;
 
 // Published API
-pub use self::clipboard::ClipboardContent;
+pub use self::clipboard::{ClipboardContent, Paste};
+pub use self::crash_report::install as install_crash_reporter;
 pub use self::event::Event;
+pub use self::hooks::{run as run_hook, Hook};
+pub use self::message_log::{LoggedMessage, MessageLog, Severity};
 pub use self::preferences::Preferences;
+pub use self::server::send_to_server;
 
 use self::clipboard::Clipboard;
+use self::file_states::FileStates;
+use self::follow::{FileChange, FileWatcher};
+use self::input_recording::InputRecorder;
+use self::recent_files::RecentFiles;
+use self::search_history::SearchHistory;
+use self::server::Server;
 use self::modes::*;
 use crate::commands;
 use crate::errors::*;
+use crate::input::Key;
+#[cfg(feature = "git")]
 use git2::Repository;
+use crate::plugins::{self, PluginMode};
 use crate::presenters;
+use crate::util;
+use crate::util::abbreviation::Expansion;
+use crate::util::encryption::{self, Cipher};
+use crate::util::movement_lexer::LexCache;
+use crate::util::occurrences::{self, Occurrences};
+use crate::util::remote::RemoteFile;
+use crate::util::snapshot::Snapshots;
+use crate::util::timings::Timings;
 use scribe::{Buffer, Workspace};
+use scribe::buffer::Position;
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::path::Path;
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Instant;
 use crate::view::View;
 
 pub enum Mode {
+    CharacterInput(CharacterInputMode),
     Confirm(ConfirmMode),
     Command(CommandMode),
+    CommandLine(CommandLineMode),
+    Diff(DiffMode),
     Exit,
+    #[cfg(feature = "git")]
+    FileHistory(FileHistoryMode),
+    Hex(HexMode),
     Insert,
     Jump(JumpMode),
     LineJump(LineJumpMode),
+    LocalHistory(LocalHistoryMode),
     Path(PathMode),
     Normal,
+    MaskedInput(MaskedInputMode),
     Open(OpenMode),
+    Plugin(Box<PluginMode>),
+    Recent(RecentMode),
+    Replace(ReplaceMode),
     Select(SelectMode),
+    SelectBlock(SelectBlockMode),
     SelectLine(SelectLineMode),
     Search(SearchMode),
+    Spelling(SpellingMode),
     SymbolJump(SymbolJumpMode),
+    Tag(TagMode),
+    Task(TaskMode),
     Theme(ThemeMode),
+    Workspace(WorkspaceMode),
+}
+
+// A workspace that's open, but not the current one, along with
+// the state that's kept separately for each of them.
+struct BackgroundWorkspace {
+    workspace: Workspace,
+    open_mode_index: IndexCache,
+    snapshots: Snapshots,
+    read_only_buffers: HashSet<usize>,
 }
 
 pub struct Application {
     pub mode: Mode,
     pub workspace: Workspace,
     pub search_query: Option<String>,
+    pub search_history: SearchHistory,
+    pub search_options: SearchOptions,
     pub view: View,
     pub clipboard: Clipboard,
+    pub file_states: FileStates,
+    pub recent_files: RecentFiles,
+    pub open_mode_index: IndexCache,
+    #[cfg(feature = "git")]
     pub repository: Option<Repository>,
     pub error: Option<Error>,
+    pub message: Option<String>,
+    pub messages: MessageLog,
+    pub pending_count: Option<usize>,
+    pub sticky_line_end: Option<Position>,
+    /// The abbreviation expanded by the last character typed, if any.
+    pub last_expansion: Option<Expansion>,
+    /// Written to standard output once the editor has exited.
+    pub output: Option<String>,
+    pub occurrences: Occurrences,
+    /// Earlier versions of buffers' content, used to go back in time.
+    pub snapshots: Snapshots,
+    /// IDs of buffers that only allow navigation, refusing changes.
+    pub read_only_buffers: HashSet<usize>,
+    /// IDs of the public keys that GnuPG files were encrypted to, by path.
+    /// Files without an entry were encrypted using a passphrase.
+    pub encryption_recipients: HashMap<PathBuf, Vec<String>>,
+    pub lex_cache: Rc<RefCell<LexCache>>,
     pub preferences: Rc<RefCell<Preferences>>,
+    pub startup_timings: Timings,
     pub event_channel: Sender<Event>,
     events: Receiver<Event>,
+    // Keys pressed are written here, if input is being recorded.
+    input_recorder: Option<InputRecorder>,
+    // Files whose buffers are kept up to date as content is appended.
+    followed_files: HashMap<PathBuf, FileWatcher>,
+    // Other open workspaces, from least to most recently used.
+    background_workspaces: Vec<BackgroundWorkspace>,
+    // Accepts files to open from `amp --remote`, if started with `--server`.
+    server: Option<Server>,
+    // Clients waiting for the files they sent to be closed.
+    waiting_clients: Vec<(usize, Vec<PathBuf>)>,
+    running_hook: bool,
 }
 
 impl Application {
     pub fn new(args: &Vec<String>) -> Result<Application> {
+        Application::create(args, false)
+    }
+
+    /// Builds an application that leaves the terminal alone,
+    /// for running commands non-interactively (e.g. in batch mode).
+    pub fn new_headless(args: &Vec<String>) -> Result<Application> {
+        Application::create(args, true)
+    }
+
+    fn create(args: &Vec<String>, headless: bool) -> Result<Application> {
+        let mut startup_timings = Timings::new();
         let preferences = initialize_preferences();
+        startup_timings.record("preferences");
 
+        // The view starts loading themes in the background.
         let (event_channel, events) = mpsc::channel();
-        let mut view = View::new(preferences.clone(), event_channel.clone())?;
+        let mut view = if headless {
+            View::new_headless(preferences.clone(), event_channel.clone())?
+        } else {
+            View::new(preferences.clone(), event_channel.clone())?
+        };
+        startup_timings.record("view");
         let clipboard = Clipboard::new();
+        let file_states = initialize_file_states();
+        let mut recent_files = initialize_recent_files();
+        startup_timings.record("file history");
 
         // Set up a workspace in the current directory.
-        let workspace = create_workspace(&mut view, args)?;
+        let workspace = create_workspace(&mut view, &file_states, &mut recent_files, args)?;
+        startup_timings.record("workspace");
+        log_info!("Starting amp {} in {}", env!("CARGO_PKG_VERSION"), workspace.path.to_string_lossy());
 
-        Ok(Application {
+        // Layer project preferences over the user's, reporting
+        // any problems with them once the editor is up and running.
+        let error = preferences.borrow_mut().load_project(&workspace.path).err();
+        let mut messages = MessageLog::new();
+        if let Some(ref error) = error {
+            messages.record_error(error);
+        }
+        let search_history = initialize_search_history();
+        #[cfg(feature = "git")]
+        let repository = Repository::discover(&env::current_dir()?).ok();
+        startup_timings.record("project");
+
+        let mut application = Application {
             mode: Mode::Normal,
             workspace,
             search_query: None,
+            search_history,
+            search_options: SearchOptions::default(),
             view,
             clipboard,
-            repository: Repository::discover(&env::current_dir()?).ok(),
-            error: None,
+            file_states,
+            recent_files,
+            open_mode_index: IndexCache::new(),
+            #[cfg(feature = "git")]
+            repository,
+            error,
+            message: None,
+            messages,
+            pending_count: None,
+            sticky_line_end: None,
+            last_expansion: None,
+            output: None,
+            occurrences: Occurrences::new(),
+            snapshots: Snapshots::new(),
+            read_only_buffers: HashSet::new(),
+            encryption_recipients: HashMap::new(),
+            lex_cache: Rc::new(RefCell::new(LexCache::new())),
             preferences,
+            startup_timings,
             event_channel,
             events,
-        })
+            input_recorder: None,
+            followed_files: HashMap::new(),
+            background_workspaces: Vec::new(),
+            server: None,
+            waiting_clients: Vec::new(),
+            running_hook: false,
+        };
+
+        // Buffers opened from arguments are set up before project preferences
+        // are loaded, so their syntax preferences and hooks are applied last.
+        if let Err(error) = application.set_up_argument_buffers() {
+            application.messages.record_error(&error);
+            if application.error.is_none() {
+                application.error = Some(error);
+            }
+        }
+
+        // Remote files are fetched before the editor starts.
+        for arg in args.iter().skip(1) {
+            if let Err(error) = util::open_remote_file(Path::new(arg), &mut application) {
+                application.messages.record_error(&error);
+                application.error = Some(error);
+            }
+        }
+
+        // Only the first encrypted or binary file specified is opened. Files
+        // that weren't opened as buffers (and aren't encrypted) are binary.
+        for arg in args.iter().skip(1) {
+            let path = Path::new(arg);
+            match util::open_encrypted_file(path, &mut application) {
+                Ok(true) => break,
+                Ok(false) => if path.is_file() && !application.workspace.contains_buffer_with_path(path) {
+                    if let Err(error) = util::show_hex_view(path, &mut application) {
+                        application.messages.record_error(&error);
+                        application.error = Some(error);
+                    }
+                    break;
+                },
+                Err(error) => {
+                    application.messages.record_error(&error);
+                    application.error = Some(error);
+                    break;
+                },
+            }
+        }
+
+        Ok(application)
     }
 
     pub fn run(&mut self) -> Result<()> {
+        self.render();
+        self.startup_timings.record("first render");
+        log_debug!("Startup timings:\n{}", self.startup_timings);
+
+        // Syntaxes beyond the defaults are loaded once the first frame's
+        // been drawn, rather than holding it up, and it's redrawn with them.
+        self.load_syntaxes();
+        self.render();
+
         loop {
-            self.render();
             self.wait_for_event()?;
 
             if let Mode::Exit = self.mode {
                 break;
             }
+
+            self.render();
+            self.view.finish_frame();
         }
 
+        // Remember where we were in each open file. Failing to do
+        // so isn't worth interrupting an otherwise clean exit.
+        if self.file_states.record_workspace(&mut self.workspace, &mut self.view).is_ok() {
+            let _ = self.file_states.save();
+        }
+        let _ = self.recent_files.save();
+
         Ok(())
     }
 
     fn render(&mut self) {
+        if let Some(mode) = self.mode_str() {
+            self.view.set_cursor_shape(mode);
+        }
+        self.view.set_input_timeout();
+
         if let Err(error) = self.present() {
             presenters::error::display(&mut self.workspace, &mut self.view, &error);
         } else if let Some(ref error) = self.error {
             // Display an error from previous command invocation, if one exists.
             presenters::error::display(&mut self.workspace, &mut self.view, error);
+        } else if let Some(ref message) = self.message {
+            // Display a message from the previous command invocation.
+            if let Err(error) = presenters::message::display(&mut self.workspace, &mut self.view, message) {
+                presenters::error::display(&mut self.workspace, &mut self.view, &error);
+            }
         }
     }
 
+    // Adds user syntaxes to the workspace, reporting any problems with them,
+    // and drops highlighting done before buffers' syntaxes could change.
+    fn load_syntaxes(&mut self) {
+        if let Err(error) = util::load_syntaxes(&mut self.workspace) {
+            self.messages.record_error(&error);
+            self.error = Some(error);
+        }
+        self.view.clear_render_caches();
+    }
+
     fn present(&mut self) -> Result<()> {
+        // Plugins' status line segments are only shown in normal mode.
+        let mut status_segments = match self.mode {
+            Mode::Normal => plugins::status_segments(self),
+            _ => Vec::new(),
+        };
+
+        // Show how new lines in the current buffer will be indented.
+        if let Mode::Normal = self.mode {
+            if let Some(buffer) = self.workspace.current_buffer() {
+                let indentation = self.preferences.borrow().indentation(buffer.path.as_ref());
+                status_segments.insert(0, format!("[{}]", indentation));
+            }
+        }
+        let read_only = self.is_read_only();
+
         match self.mode {
-            Mode::Confirm(_) => {
-                presenters::modes::confirm::display(&mut self.workspace, &mut self.view)
+            Mode::Confirm(ref mode) => {
+                presenters::modes::confirm::display(&mut self.workspace, mode, &mut self.view)
             }
             Mode::Command(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
-            Mode::Insert => presenters::modes::insert::display(&mut self.workspace, &mut self.view),
+            Mode::CommandLine(ref mode) => {
+                presenters::modes::command_line::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Diff(ref mode) => presenters::modes::diff::display(mode, &mut self.view),
+            Mode::Hex(ref mode) => presenters::modes::hex::display(mode, &mut self.view),
+            Mode::MaskedInput(ref mode) => {
+                presenters::modes::masked_input::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Insert | Mode::CharacterInput(_) => {
+                presenters::modes::insert::display(&mut self.workspace, &mut self.view)
+            }
             Mode::Open(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
@@ -123,21 +383,52 @@ impl Application {
             Mode::Path(ref mode) => {
                 presenters::modes::path::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::Recent(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Replace(ref mut mode) => presenters::modes::replace::display(mode, &mut self.view),
+            Mode::Spelling(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, &mut **mode, &mut self.view)
+            }
             Mode::SymbolJump(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::Tag(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, &mut **mode, &mut self.view)
+            }
+            Mode::LocalHistory(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, &mut **mode, &mut self.view)
+            }
+            #[cfg(feature = "git")]
+            Mode::FileHistory(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, &mut **mode, &mut self.view)
+            }
+            Mode::Task(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, &mut **mode, &mut self.view)
+            }
             Mode::Select(ref mode) => {
                 presenters::modes::select::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::SelectBlock(ref mode) => {
+                presenters::modes::select_block::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::SelectLine(ref mode) => {
                 presenters::modes::select_line::display(&mut self.workspace, mode, &mut self.view)
             }
             Mode::Normal => presenters::modes::normal::display(
                 &mut self.workspace,
                 &mut self.view,
-                &self.repository,
+                &status_segments,
+                self.occurrences.highlighted_word(),
+                read_only,
             ),
+            Mode::Plugin(ref mode) => {
+                presenters::modes::plugin::display(&mut self.workspace, &**mode, &mut self.view)
+            }
             Mode::Theme(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, &mut **mode, &mut self.view)
+            }
+            Mode::Workspace(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
             Mode::Exit => Ok(()),
@@ -145,17 +436,61 @@ impl Application {
     }
 
     fn wait_for_event(&mut self) -> Result<()> {
-        let event = self
-            .events
-            .recv()
-            .chain_err(|| "Error receiving application event")?;
+        // Wake up to highlight occurrences of the word under
+        // the cursor, if it's been resting on one long enough.
+        let event = match self.occurrences.time_remaining() {
+            Some(timeout) => match self.events.recv_timeout(timeout) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => return Ok(()),
+                Err(RecvTimeoutError::Disconnected) => bail!("Error receiving application event"),
+            },
+            None => self
+                .events
+                .recv()
+                .chain_err(|| "Error receiving application event")?,
+        };
+        self.view.start_frame();
+
         match event {
             Event::Key(key) => {
+                match self.mode {
+                    // Masked input shouldn't end up in logs or crash reports.
+                    Mode::MaskedInput(_) => (),
+                    _ => {
+                        log_trace!("Key pressed: {:?}", key);
+                        crash_report::record_key(self.mode_str().unwrap_or(""), &key);
+                        self.record_input(&key);
+                    },
+                }
                 self.view.last_key = Some(key);
+                self.message = None;
+                let mode = self.mode_str();
                 self.error = commands::application::handle_input(self).err();
+                if self.error.is_none() && self.mode_str() != mode {
+                    self.error = hooks::run(self, Hook::ModeChange).err();
+                }
+                self.view.record_frame_phase("commands");
+                crash_report::record_buffer_paths(self.buffer_paths());
+                self.log_feedback();
+                self.release_waiting_clients();
+                self.update_occurrences();
+                self.record_snapshot();
             }
             Event::Resize => {}
+            Event::Suspend => self.view.suspend(),
+            Event::Terminate => {
+                // There may not be anyone around to save changes; hold onto
+                // them before exiting. Nothing's left to report failures to.
+                log_warn!("Terminated; writing recovery files for modified buffers");
+                if let Err(e) = self.write_recovery_files() {
+                    log_error!("Failed to write recovery files: {}", e);
+                }
+                self.mode = Mode::Exit;
+            }
             Event::OpenModeIndexComplete(index) => {
+                let index = Rc::new(index);
+                self.open_mode_index.set(index.clone());
+
                 if let Mode::Open(ref mut open_mode) = self.mode {
                     open_mode.set_index(index);
 
@@ -164,12 +499,596 @@ impl Application {
                     open_mode.search();
                 }
             }
+            Event::LintComplete(path, Ok(diagnostics)) => {
+                if diagnostics.is_empty() {
+                    self.view.diagnostics.remove(&path);
+                } else {
+                    self.view.diagnostics.insert(path, diagnostics);
+                }
+            }
+            Event::LintComplete(_, Err(error)) => {
+                self.error = Some(error.into());
+            }
+            Event::TaskScanComplete(tasks) => {
+                if tasks.is_empty() {
+                    if let Mode::Task(_) = self.mode {
+                        self.mode = Mode::Normal;
+                        self.message = Some(String::from("No TODO, FIXME, or HACK comments found"));
+                    }
+                } else if let Mode::Task(ref mut mode) = self.mode {
+                    mode.set_items(tasks);
+                }
+            }
+            #[cfg(feature = "git")]
+            Event::FileHistoryLoaded(path, result) => {
+                let loading = match self.mode {
+                    Mode::FileHistory(ref mode) => mode.path == path,
+                    _ => false,
+                };
+                if loading {
+                    match result {
+                        Ok(ref revisions) if revisions.is_empty() => {
+                            self.mode = Mode::Normal;
+                            self.message = Some(String::from("No commits found for this file"));
+                        },
+                        Ok(revisions) => if let Mode::FileHistory(ref mut mode) = self.mode {
+                            mode.set_items(revisions);
+                        },
+                        Err(error) => {
+                            self.mode = Mode::Normal;
+                            self.error = Some(error.into());
+                        },
+                    }
+                }
+            }
+            Event::ThemesLoaded => {
+                // Swap the loaded themes in, reporting any that couldn't be.
+                self.view.theme_set()?;
+                for theme_error in self.view.take_theme_errors() {
+                    self.messages.record_error(&theme_error);
+                    if self.error.is_none() {
+                        self.error = Some(theme_error);
+                    }
+                }
+            }
+            Event::TagsGenerated(Ok(())) => {
+                self.message = Some(String::from("Generated the tags file"));
+            }
+            Event::TagsGenerated(Err(error)) => {
+                self.error = Some(error.into());
+            }
+            Event::FileChanged(path, change) => {
+                self.error = self.apply_file_change(&path, change).err();
+            }
+            Event::OpenRequested(id, paths) => {
+                self.error = self.open_requested_files(id, &paths).err();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keeps the file's buffer up to date with content appended to it, like
+    /// `tail -f`, until it's unfollowed or the buffer is closed. Content beyond
+    /// the specified length (i.e. that of the buffer's content) is appended.
+    pub fn follow_file(&mut self, path: &Path, length: u64) -> Result<()> {
+        let watcher = FileWatcher::start(path.to_path_buf(), length, self.event_channel.clone())?;
+        self.followed_files.insert(path.to_path_buf(), watcher);
+
+        Ok(())
+    }
+
+    /// Moves the workspace to another directory (relative paths are based on
+    /// the current one), which the file finder, path prompts, workspace-wide
+    /// searches, and external commands (e.g. linters) then work from.
+    pub fn change_directory(&mut self, path: &Path) -> Result<()> {
+        let directory = self.workspace.path
+            .join(path)
+            .canonicalize()
+            .chain_err(|| format!("Couldn't find {}", path.to_string_lossy()))?;
+        if !directory.is_dir() {
+            bail!("{} isn't a directory", path.to_string_lossy());
+        }
+        env::set_current_dir(&directory).chain_err(|| "Couldn't change the working directory")?;
+
+        log_info!("Changing directory to {}", directory.to_string_lossy());
+        self.workspace.path = directory;
+        self.open_mode_index.invalidate();
+        #[cfg(feature = "git")]
+        self.repository = Repository::discover(&self.workspace.path).ok();
+
+        Ok(())
+    }
+
+    /// Opens another project directory alongside the current one (relative
+    /// paths are based on the current one), switching to it. Each workspace
+    /// has its own buffers, file finder index, and buffer history.
+    pub fn open_workspace(&mut self, path: &Path) -> Result<()> {
+        let directory = self.workspace.path
+            .join(path)
+            .canonicalize()
+            .chain_err(|| format!("Couldn't find {}", path.to_string_lossy()))?;
+        if !directory.is_dir() {
+            bail!("{} isn't a directory", path.to_string_lossy());
+        }
+        if directory == self.workspace.path {
+            return Ok(());
+        }
+        if self.background_workspaces.iter().any(|background| background.workspace.path == directory) {
+            return self.switch_workspace(&directory);
+        }
+
+        let workspace = Workspace::new(&directory).chain_err(|| "Couldn't open the workspace")?;
+        self.activate_workspace(BackgroundWorkspace {
+            workspace,
+            open_mode_index: IndexCache::new(),
+            snapshots: Snapshots::new(),
+            read_only_buffers: HashSet::new(),
+        })
+    }
+
+    /// Switches to the other open workspace with the specified directory.
+    pub fn switch_workspace(&mut self, path: &Path) -> Result<()> {
+        let index = self.background_workspaces
+            .iter()
+            .position(|background| background.workspace.path == path)
+            .ok_or_else(|| format!("No workspace is open for {}", path.to_string_lossy()))?;
+        let background = self.background_workspaces.remove(index);
+
+        self.activate_workspace(background)
+    }
+
+    /// The directories of the other open workspaces, most recently used first.
+    pub fn background_workspace_paths(&self) -> Vec<PathBuf> {
+        self.background_workspaces
+            .iter()
+            .rev()
+            .map(|background| background.workspace.path.clone())
+            .collect()
+    }
+
+    // Sets the current workspace aside, making the other one current.
+    fn activate_workspace(&mut self, mut background: BackgroundWorkspace) -> Result<()> {
+        // Buffer IDs are only unique within a workspace, so the view's state
+        // for the current buffers is recorded, to be restored on their return.
+        self.file_states.record_workspace(&mut self.workspace, &mut self.view)?;
+        env::set_current_dir(&background.workspace.path).chain_err(|| "Couldn't change the working directory")?;
+        log_info!("Switching to the workspace in {}", background.workspace.path.to_string_lossy());
+        let view = &mut self.view;
+        each_workspace_buffer(&mut self.workspace, |buffer| {
+            let _ = view.forget_buffer(buffer);
+        });
+
+        mem::swap(&mut self.workspace, &mut background.workspace);
+        mem::swap(&mut self.open_mode_index, &mut background.open_mode_index);
+        mem::swap(&mut self.snapshots, &mut background.snapshots);
+        mem::swap(&mut self.read_only_buffers, &mut background.read_only_buffers);
+        self.background_workspaces.push(background);
+
+        let file_states = &self.file_states;
+        let mut result = Ok(());
+        each_workspace_buffer(&mut self.workspace, |buffer| {
+            let restored = view.initialize_buffer(buffer).and_then(|_| file_states.restore(buffer, view));
+            if result.is_ok() {
+                result = restored;
+            }
+        });
+
+        self.preferences.borrow_mut().switch_project(&self.workspace.path)?;
+        #[cfg(feature = "git")]
+        self.repository = Repository::discover(&self.workspace.path).ok();
+
+        result
+    }
+
+    /// Starts accepting files to open from other invocations
+    /// of the editor, made with `--remote` (e.g. as `$EDITOR`).
+    pub fn start_server(&mut self) -> Result<()> {
+        self.server = Some(Server::start(server::socket_path()?, self.event_channel.clone())?);
+
+        Ok(())
+    }
+
+    /// Stops following the file, returning whether it was being followed.
+    pub fn unfollow_file(&mut self, path: &Path) -> bool {
+        self.followed_files.remove(path).is_some()
+    }
+
+    /// Starts writing keys to the specified file as they're pressed (e.g. when
+    /// started with `--record-input`). Keys typed as masked input aren't kept.
+    pub fn start_recording_input(&mut self, path: &Path) -> Result<()> {
+        self.input_recorder = Some(InputRecorder::create(path)?);
+
+        Ok(())
+    }
+
+    /// Replays keys from a recording made with `start_recording_input`, as
+    /// though they were being pressed again (e.g. when started with `--replay-input`).
+    pub fn replay_input(&mut self, path: &Path) -> Result<()> {
+        let recording = fs::read_to_string(path).chain_err(|| "Couldn't read the input recording")?;
+        input_recording::replay(input_recording::parse(&recording)?, self.event_channel.clone());
+
+        Ok(())
+    }
+
+    /// Runs the command line input (e.g. "s/foo/bar/" or "write") against each
+    /// open buffer in turn, returning the messages the commands reported.
+    /// Commands that would prompt for input (e.g. saving a buffer without
+    /// a path) are treated as failures, since there's no one to answer.
+    pub fn run_batch(&mut self, commands: &[String]) -> Result<Vec<String>> {
+        let mut buffers = Vec::new();
+        self.each_buffer(|buffer| {
+            let label = match buffer.path {
+                Some(ref path) => path.to_string_lossy().into_owned(),
+                None => String::from("untitled"),
+            };
+            buffers.extend(buffer.id.map(|id| (id, label)));
+        });
+        if buffers.is_empty() {
+            bail!("Please provide files to run the commands against");
+        }
+
+        let mut messages = Vec::new();
+        for (id, label) in buffers {
+            if !self.select_buffer(id) {
+                bail!("{} was closed before its commands could be run", label);
+            }
+            if let Some(buffer) = self.workspace.current_buffer() {
+                buffer.cursor.move_to(Position{ line: 0, offset: 0 });
+            }
+
+            for command in commands {
+                let mut mode = CommandLineMode::new();
+                mode.input = command.clone();
+                self.mode = Mode::CommandLine(mode);
+                commands::command_line::accept(self)
+                    .chain_err(|| format!("{}: \"{}\" failed", label, command))?;
+                match self.mode {
+                    Mode::Normal => (),
+                    _ => bail!("{}: \"{}\" needs input, which isn't available in batch mode", label, command),
+                }
+                if let Some(message) = self.message.take() {
+                    messages.push(format!("{}: {}", label, message));
+                }
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Opens content piped to the editor in a new buffer, highlighting
+    /// it based on its first line (e.g. a shebang or XML declaration).
+    pub fn open_stdin_buffer(&mut self, content: &str) -> Result<()> {
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        util::load_syntaxes(&mut self.workspace)?;
+        buffer.syntax_definition = content
+            .lines()
+            .nth(0)
+            .and_then(|line| self.workspace.syntax_set.find_syntax_by_first_line(line))
+            .cloned();
+
+        util::add_buffer(buffer, self)
+    }
+
+    /// Whether the current buffer has been opened read-only.
+    pub fn is_read_only(&mut self) -> bool {
+        self.workspace
+            .current_buffer()
+            .and_then(|buffer| buffer.id)
+            .map(|id| self.read_only_buffers.contains(&id))
+            .unwrap_or(false)
+    }
+
+    /// Refuses changes to the current buffer if it's been opened read-only.
+    /// Every command that changes a buffer's content checks this first.
+    pub fn ensure_writable(&mut self) -> Result<()> {
+        if self.is_read_only() {
+            bail!(BUFFER_READ_ONLY);
+        }
+
+        Ok(())
+    }
+
+    /// Opens all of the buffers read-only (e.g. when started with `-R`),
+    /// including those without a path (e.g. content piped to the editor).
+    pub fn make_buffers_read_only(&mut self) {
+        let mut ids = Vec::new();
+        self.each_buffer(|buffer| ids.extend(buffer.id));
+        self.read_only_buffers.extend(ids);
+    }
+
+    /// The content of open buffers with paths, which may not have been saved.
+    pub fn buffer_contents(&mut self) -> HashMap<PathBuf, String> {
+        let mut contents = HashMap::new();
+        self.each_buffer(|buffer| {
+            if let Some(path) = buffer.path.clone() {
+                contents.insert(path, buffer.data());
+            }
+        });
+
+        contents
+    }
+
+    /// Runs the function against the open buffer for the path, if there
+    /// is one, returning whether it was found.
+    pub fn with_buffer_at<F: FnMut(&mut Buffer)>(&mut self, path: &Path, mut f: F) -> bool {
+        let mut found = false;
+        self.each_buffer(|buffer| if buffer.path.as_ref().map(|buffer_path| buffer_path == path).unwrap_or(false) {
+            f(buffer);
+            found = true;
+        });
+
+        found
+    }
+
+    /// Paths of buffers with changes that haven't been saved.
+    pub fn modified_buffer_paths(&mut self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        self.each_modified_buffer(|buffer| paths.extend(buffer.path.clone()));
+
+        paths
+    }
+
+    /// Saves every buffer with unsaved changes, reporting
+    /// those that couldn't be saved after trying them all.
+    pub fn save_modified_buffers(&mut self) -> Result<()> {
+        let mut failures = Vec::new();
+        self.each_modified_buffer(|buffer| {
+            if let Some(path) = buffer.path.as_ref().map(|path| path.to_string_lossy().into_owned()) {
+                let remote_file = buffer.path.as_ref().and_then(|path| RemoteFile::parse(path));
+                if encryption::is_encrypted(buffer) {
+                    failures.push(format!("{} (encrypted files are saved individually)", path));
+                } else if let Some(remote_file) = remote_file {
+                    let saved = remote_file.write(&buffer.data()).and_then(|_| encryption::mark_saved(buffer));
+                    if let Err(error) = saved {
+                        failures.push(format!("{} ({})", path, error));
+                    }
+                } else if let Err(error) = buffer.save() {
+                    failures.push(format!("{} ({})", path, error));
+                }
+            }
+        });
+
+        if !failures.is_empty() {
+            bail!("Couldn't save {}", failures.join(", "));
+        }
+
+        Ok(())
+    }
+
+    /// Writes copies of buffers with unsaved changes (including those
+    /// without paths) to the recovery directory, leaving their files intact.
+    pub fn write_recovery_files(&mut self) -> Result<()> {
+        let directory = recovery::directory()?;
+        let mut failures = Vec::new();
+        self.each_modified_buffer(|buffer| {
+            // Decrypted content is never written anywhere but its own file.
+            if encryption::is_encrypted(buffer) {
+                return;
+            }
+
+            if let Err(error) = recovery::write(buffer, &directory) {
+                failures.push(error.to_string());
+            }
+        });
+
+        if !failures.is_empty() {
+            bail!("Couldn't write recovery files: {}", failures.join(", "));
+        }
+
+        Ok(())
+    }
+
+    // Includes buffers in other workspaces, which shouldn't be
+    // overlooked when exiting (or writing recovery files).
+    fn each_modified_buffer<F: FnMut(&mut Buffer)>(&mut self, mut f: F) {
+        self.each_buffer(|buffer| if buffer.modified() { f(buffer) });
+        for background in &mut self.background_workspaces {
+            each_workspace_buffer(&mut background.workspace, |buffer| if buffer.modified() { f(buffer) });
+        }
+    }
+
+    // Paths of all open buffers, with unsaved buffers listed as "untitled".
+    fn buffer_paths(&mut self) -> Vec<String> {
+        let mut paths = Vec::new();
+        self.each_buffer(|buffer| {
+            paths.push(match buffer.path {
+                Some(ref path) => path.to_string_lossy().into_owned(),
+                None => String::from("untitled"),
+            });
+        });
+
+        paths
+    }
+
+    // Applies the configured syntax definitions, detected indentation,
+    // and buffer_open hooks to all open buffers.
+    fn set_up_argument_buffers(&mut self) -> Result<()> {
+        let initial_id = match self.workspace.current_buffer() {
+            Some(buffer) => buffer.id,
+            None => return Ok(()),
+        };
+
+        loop {
+            util::apply_syntax_preference(&mut self.workspace, &self.preferences.borrow())?;
+            util::detect_indentation(&mut self.workspace, &mut self.preferences.borrow_mut());
+            hooks::run(self, Hook::BufferOpen)?;
+
+            self.workspace.next_buffer();
+            if self.workspace.current_buffer().map(|b| b.id) == Some(initial_id) {
+                return Ok(());
+            }
+        }
+    }
+
+    // Cycles through buffers until the one with the specified id is current,
+    // returning false (with the current buffer unchanged) if it isn't open.
+    fn select_buffer(&mut self, id: usize) -> bool {
+        let initial_id = match self.workspace.current_buffer() {
+            Some(buffer) => buffer.id,
+            None => return false,
+        };
+
+        loop {
+            if self.workspace.current_buffer().and_then(|b| b.id) == Some(id) {
+                return true;
+            }
+
+            self.workspace.next_buffer();
+            if self.workspace.current_buffer().map(|b| b.id) == Some(initial_id) {
+                return false;
+            }
         }
+    }
+
+    // Opens files sent by a client, which waits until they've all been closed.
+    fn open_requested_files(&mut self, id: usize, paths: &[PathBuf]) -> Result<()> {
+        let mut opened_paths = Vec::new();
+        for path in paths {
+            if let Err(error) = util::open_buffer(path, self) {
+                // Don't leave the client waiting on files that never opened.
+                self.waiting_clients.push((id, opened_paths));
+                self.release_waiting_clients();
+
+                return Err(error);
+            }
+            opened_paths.extend(self.workspace.current_buffer().and_then(|buffer| buffer.path.clone()));
+        }
+        self.message = Some(match opened_paths.len() {
+            1 => String::from("Opened 1 file sent using --remote; close it to finish"),
+            count => format!("Opened {} files sent using --remote; close them to finish", count),
+        });
+        self.waiting_clients.push((id, opened_paths));
+        self.release_waiting_clients();
 
         Ok(())
     }
 
-    pub fn mode_str(&self) -> Option<&'static str> {
+    // Lets clients know once the files they sent have all been closed.
+    fn release_waiting_clients(&mut self) {
+        if self.waiting_clients.is_empty() {
+            return;
+        }
+
+        let mut open_paths = HashSet::new();
+        self.each_buffer(|buffer| open_paths.extend(buffer.path.clone()));
+        for background in &mut self.background_workspaces {
+            each_workspace_buffer(&mut background.workspace, |buffer| open_paths.extend(buffer.path.clone()));
+        }
+
+        let server = &self.server;
+        self.waiting_clients.retain(|&(id, ref paths)| {
+            let waiting = paths.iter().any(|path| open_paths.contains(path));
+            if !waiting {
+                if let Some(ref server) = *server {
+                    server.release(id);
+                }
+            }
+
+            waiting
+        });
+    }
+
+    // Applies a change to a followed file to its buffer, if it's still open.
+    fn apply_file_change(&mut self, path: &Path, change: FileChange) -> Result<()> {
+        let initial_id = self.workspace.current_buffer().map(|buffer| buffer.id);
+        let mut change = Some(change);
+        let mut result = Ok(());
+
+        // Buffers are cycled through (leaving the current one selected
+        // once done), as the workspace can't look them up by path.
+        loop {
+            if let Some(buffer) = self.workspace.current_buffer() {
+                if buffer.path.as_ref().map(|buffer_path| buffer_path == path).unwrap_or(false) {
+                    if let Some(change) = change.take() {
+                        result = follow::apply(change, buffer, &mut self.view);
+                    }
+                }
+            }
+
+            self.workspace.next_buffer();
+            if self.workspace.current_buffer().map(|buffer| buffer.id) == initial_id {
+                break;
+            }
+        }
+
+        // There's no point watching files whose buffers have been closed.
+        if change.is_some() {
+            self.unfollow_file(path);
+        }
+
+        result
+    }
+
+    fn each_buffer<F: FnMut(&mut Buffer)>(&mut self, f: F) {
+        each_workspace_buffer(&mut self.workspace, f);
+    }
+
+    // Keeps a record of the latest command's feedback, which
+    // is otherwise cleared as soon as another key is pressed.
+    fn log_feedback(&mut self) {
+        if let Some(ref message) = self.message {
+            self.messages.record(Severity::Info, message.as_str());
+        }
+        if let Some(ref error) = self.error {
+            self.messages.record_error(error);
+
+            if let Some(entry) = self.messages.entries().last() {
+                log_error!("{}", entry.content);
+            }
+        }
+    }
+
+    // Tracks the word under the cursor, which is only highlighted in normal mode.
+    fn update_occurrences(&mut self) {
+        let word = match self.mode {
+            Mode::Normal => self.workspace.current_buffer().and_then(|buffer| {
+                let data = buffer.data();
+                let line = data.lines().nth(buffer.cursor.line)?;
+
+                occurrences::word_at(line, buffer.cursor.offset)
+            }),
+            _ => None,
+        };
+
+        self.occurrences.update(word);
+    }
+
+    // Writes the key to the input recording, if there is one. Recording
+    // stops if that fails, rather than reporting it on every key press.
+    fn record_input(&mut self, key: &Key) {
+        let failed = match self.input_recorder {
+            Some(ref mut recorder) => recorder.record(key).is_err(),
+            None => false,
+        };
+        if failed {
+            log_error!("Failed to write to the input recording; recording has stopped");
+            self.input_recorder = None;
+        }
+    }
+
+    // Notes the current buffer's content, so that it can later
+    // be restored to (or compared with) what it is now. Its content is
+    // only copied when it's changed, and large buffers aren't tracked.
+    fn record_snapshot(&mut self) {
+        if let Some(buffer) = self.workspace.current_buffer() {
+            if let Some(id) = buffer.id {
+                if !self.view.take_change(buffer) {
+                    return;
+                }
+
+                let data = buffer.data();
+                if self.view.is_large_buffer(buffer, &data) {
+                    self.snapshots.forget(id);
+                } else {
+                    self.snapshots.record(id, data, Instant::now());
+                }
+            }
+        }
+    }
+
+    pub fn mode_str(&mut self) -> Option<&'static str> {
         match self.mode {
             Mode::Command(ref mode) => if mode.insert_mode() {
                 Some("search_select_insert")
@@ -186,18 +1105,68 @@ impl Application {
             } else {
                 Some("search_select")
             },
+            Mode::Recent(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
             Mode::Theme(ref mode) => if mode.insert_mode() {
                 Some("search_select_insert")
             } else {
                 Some("search_select")
             },
-            Mode::Normal => Some("normal"),
+            Mode::Workspace(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Spelling(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Tag(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::LocalHistory(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            // Revisions can be viewed, as well as compared,
+            // so the mode has its own (non-insert) key bindings.
+            #[cfg(feature = "git")]
+            Mode::FileHistory(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("file_history")
+            },
+            Mode::Task(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Normal => if self.is_read_only() {
+                Some("read_only")
+            } else {
+                Some("normal")
+            },
+            Mode::CommandLine(_) => Some("command_line"),
             Mode::Path(_) => Some("path"),
+            Mode::Plugin(ref mode) => Some(mode.name()),
             Mode::Confirm(_) => Some("confirm"),
+            Mode::Diff(_) => Some("diff"),
+            Mode::Hex(_) => Some("hex"),
             Mode::Insert => Some("insert"),
+            Mode::CharacterInput(_) => Some("character_input"),
             Mode::Jump(_) => Some("jump"),
             Mode::LineJump(_) => Some("line_jump"),
+            Mode::MaskedInput(_) => Some("masked_input"),
+            Mode::Replace(_) => Some("replace"),
             Mode::Select(_) => Some("select"),
+            Mode::SelectBlock(_) => Some("select_block"),
             Mode::SelectLine(_) => Some("select_line"),
             Mode::Search(ref mode) => if mode.insert_mode() {
                 Some("search_insert")
@@ -215,7 +1184,58 @@ fn initialize_preferences() -> Rc<RefCell<Preferences>> {
     ))
 }
 
-fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
+#[cfg(not(test))]
+fn initialize_file_states() -> FileStates {
+    FileStates::load()
+}
+
+// Keep test runs from reading or clobbering the user's state.
+#[cfg(test)]
+fn initialize_file_states() -> FileStates {
+    FileStates::new(None)
+}
+
+#[cfg(not(test))]
+fn initialize_recent_files() -> RecentFiles {
+    RecentFiles::load()
+}
+
+#[cfg(test)]
+fn initialize_recent_files() -> RecentFiles {
+    RecentFiles::new(None)
+}
+
+#[cfg(not(test))]
+fn initialize_search_history() -> SearchHistory {
+    SearchHistory::load()
+}
+
+#[cfg(test)]
+fn initialize_search_history() -> SearchHistory {
+    SearchHistory::new(None)
+}
+
+// Runs the function against each of the workspace's buffers,
+// leaving the current buffer selected once it's done.
+fn each_workspace_buffer<F: FnMut(&mut Buffer)>(workspace: &mut Workspace, mut f: F) {
+    let initial_id = match workspace.current_buffer() {
+        Some(buffer) => buffer.id,
+        None => return,
+    };
+
+    loop {
+        if let Some(buffer) = workspace.current_buffer() {
+            f(buffer);
+        }
+
+        workspace.next_buffer();
+        if workspace.current_buffer().map(|b| b.id) == Some(initial_id) {
+            break;
+        }
+    }
+}
+
+fn create_workspace(view: &mut View, file_states: &FileStates, recent_files: &mut RecentFiles, args: &Vec<String>) -> Result<Workspace> {
     // Discard the executable portion of the argument list.
     let mut path_args = args.iter().skip(1).peekable();
 
@@ -230,18 +1250,9 @@ fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
     }
 
     let workspace_dir = env::current_dir()?;
+    // User syntaxes are loaded later, once the first frame's been drawn.
     let mut workspace = Workspace::new(&workspace_dir)?;
 
-    // Load user syntax definitions.
-    //
-    // It's important to do this before opening buffers, as that's when syntax
-    // definitions are associated; we want the complete set before that happens.
-    let syntax_path = Preferences::syntax_path()?;
-    if let Err(e) = workspace.syntax_set.load_syntaxes(syntax_path, true) {
-        bail!("Failed to load user syntaxes: {:?}", e);
-    }
-    workspace.syntax_set.link_syntaxes();
-
     // If the first argument was a directory, we've navigated into
     // it; skip it before evaluating file args, lest we interpret
     // it again as a non-existent file and create a buffer for it.
@@ -253,10 +1264,20 @@ fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
 
         if path.is_dir() { continue; }
 
+        // Binary, encrypted, and remote files can't be opened as
+        // buffers; they're handled separately once the editor is running.
+        if util::hex::is_binary_file(path) || Cipher::for_path(path).is_some() { continue; }
+        if RemoteFile::parse(path).is_some() { continue; }
+
         // Open the specified path if it exists, or
         // create a new buffer pointing to it if it doesn't.
         let argument_buffer = if path.exists() {
-            Buffer::from_file(path)?
+            match Buffer::from_file(path) {
+                Ok(buffer) => buffer,
+                // Binary content past the start of the file is only found now.
+                Err(ref error) if error.kind() == io::ErrorKind::InvalidData => continue,
+                Err(error) => return Err(error.into()),
+            }
         } else {
             let mut buffer = Buffer::new();
 
@@ -271,6 +1292,10 @@ fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
         };
         workspace.add_buffer(argument_buffer);
         view.initialize_buffer(workspace.current_buffer().unwrap())?;
+        file_states.restore(workspace.current_buffer().unwrap(), view)?;
+        if let Some(ref path) = workspace.current_buffer().unwrap().path {
+            recent_files.record(path.clone());
+        }
     }
 
     Ok(workspace)
@@ -279,9 +1304,11 @@ fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
 #[cfg(test)]
 mod tests {
     use super::Application;
+    use super::follow::FileChange;
     use scribe::Buffer;
     use std::env;
-    use std::path::Path;
+    use std::fs;
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn application_uses_file_arguments_to_load_contents_into_buffers_when_files_exist() {
@@ -299,6 +1326,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn open_stdin_buffer_adds_an_unsaved_buffer_with_the_content() {
+        let mut application = Application::new(&Vec::new()).unwrap();
+        application.open_stdin_buffer("#!/bin/sh\necho amp\n").unwrap();
+        let buffer = application.workspace.current_buffer().unwrap();
+
+        assert_eq!(buffer.data(), "#!/bin/sh\necho amp\n");
+        assert!(buffer.path.is_none());
+        assert!(buffer.syntax_definition.is_some());
+    }
+
+    #[test]
+    fn read_only_buffers_use_the_read_only_key_bindings() {
+        let mut application =
+            Application::new(&vec![String::new(), String::from("Cargo.lock")]).unwrap();
+        assert_eq!(application.mode_str(), Some("normal"));
+
+        application.make_buffers_read_only();
+        assert!(application.is_read_only());
+        assert_eq!(application.mode_str(), Some("read_only"));
+    }
+
+    #[test]
+    fn make_buffers_read_only_includes_buffers_without_a_path() {
+        let mut application = Application::new(&Vec::new()).unwrap();
+        application.open_stdin_buffer("amp\n").unwrap();
+
+        application.make_buffers_read_only();
+        assert!(application.is_read_only());
+        assert!(application.ensure_writable().is_err());
+    }
+
     #[test]
     fn application_uses_file_arguments_to_create_new_buffers_when_files_do_not_exist() {
         let mut application =
@@ -310,4 +1369,78 @@ mod tests {
         );
         assert_eq!(application.workspace.current_buffer().unwrap().data(), "");
     }
+
+    #[test]
+    fn save_modified_buffers_writes_buffers_with_unsaved_changes() {
+        let mut application = Application::new(&Vec::new()).unwrap();
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/modified_buffer"));
+        let mut buffer = Buffer::new();
+        buffer.path = Some(path.clone());
+        buffer.insert("amp");
+        application.workspace.add_buffer(buffer);
+        application.workspace.add_buffer(Buffer::new());
+
+        assert_eq!(application.modified_buffer_paths(), vec![path.clone()]);
+        application.save_modified_buffers().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "amp");
+        assert!(application.modified_buffer_paths().is_empty());
+    }
+
+    #[test]
+    fn run_batch_runs_commands_against_each_file() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/batch"));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.txt"), "foo\n").unwrap();
+        fs::write(root.join("b.txt"), "foo foo\n").unwrap();
+        let args = vec![
+            String::new(),
+            root.join("a.txt").to_string_lossy().into_owned(),
+            root.join("b.txt").to_string_lossy().into_owned(),
+        ];
+
+        let mut application = Application::new_headless(&args).unwrap();
+        let messages = application.run_batch(&[String::from("replace foo bar"), String::from("write")]).unwrap();
+
+        assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "bar\n");
+        assert_eq!(fs::read_to_string(root.join("b.txt")).unwrap(), "bar bar\n");
+        assert!(messages.iter().any(|message| message.ends_with("a.txt: Replaced 1 match")));
+        assert!(application.run_batch(&[String::from("unknown")]).is_err());
+    }
+
+    #[test]
+    fn change_directory_only_accepts_directories() {
+        let mut application = Application::new(&Vec::new()).unwrap();
+        let path = application.workspace.path.clone();
+
+        assert!(application.change_directory(Path::new("Cargo.toml")).is_err());
+        assert!(application.change_directory(Path::new("non_existent_directory")).is_err());
+        assert_eq!(application.workspace.path, path);
+    }
+
+    #[test]
+    fn apply_file_change_only_scrolls_to_new_content_from_the_end_of_the_buffer() {
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/follow.log"));
+        fs::write(&path, "one\ntwo\n").unwrap();
+        let mut application = Application::new(&vec![String::new(), path.to_string_lossy().into_owned()]).unwrap();
+        let path = application.workspace.current_buffer_path().unwrap().to_path_buf();
+
+        application.apply_file_change(&path, FileChange::Appended(String::from("three\n"))).unwrap();
+        {
+            let buffer = application.workspace.current_buffer().unwrap();
+            assert_eq!(buffer.data(), "one\ntwo\nthree\n");
+            assert_eq!(buffer.cursor.line, 0);
+            assert!(!buffer.modified());
+
+            // Appended content can't be undone.
+            buffer.undo();
+            assert_eq!(buffer.data(), "one\ntwo\nthree\n");
+            buffer.cursor.move_to_last_line();
+        }
+
+        application.apply_file_change(&path, FileChange::Appended(String::from("four\n"))).unwrap();
+        let buffer = application.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "one\ntwo\nthree\nfour\n");
+        assert_eq!(buffer.cursor.line, 4);
+    }
 }
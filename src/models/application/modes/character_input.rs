@@ -0,0 +1,124 @@
+use crate::errors::*;
+use std::char;
+use std::collections::HashMap;
+
+/// The ways in which characters that aren't on the keyboard can be entered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CharacterInputKind {
+    /// A "u" followed by four hex digits, or a "U" followed by up to eight
+    /// (e.g. "u00e9" for "é"). Any other character is inserted as-is.
+    Codepoint,
+    /// Two characters, looked up in the digraph table (e.g. "e'" for "é").
+    Digraph,
+}
+
+pub struct CharacterInputMode {
+    pub kind: CharacterInputKind,
+    pub input: String,
+}
+
+impl CharacterInputMode {
+    pub fn new(kind: CharacterInputKind) -> CharacterInputMode {
+        CharacterInputMode {
+            kind,
+            input: String::new(),
+        }
+    }
+
+    /// The character described by the input, once enough of it has been typed.
+    pub fn character(&self, digraphs: &HashMap<String, char>) -> Result<Option<char>> {
+        self.resolve(digraphs, false)
+    }
+
+    /// The character described by the input, which has been explicitly
+    /// accepted; codepoints with fewer digits than usual (e.g. "ue9") are allowed.
+    pub fn accept(&self, digraphs: &HashMap<String, char>) -> Result<char> {
+        self.resolve(digraphs, true)?
+            .ok_or_else(|| "Please enter a character to insert".into())
+    }
+
+    /// A description of the input so far, for the status line (e.g. "^V u00").
+    pub fn status(&self) -> String {
+        match self.kind {
+            CharacterInputKind::Codepoint => format!("^V {}", self.input),
+            CharacterInputKind::Digraph => format!("^K {}", self.input),
+        }
+    }
+
+    fn resolve(&self, digraphs: &HashMap<String, char>, accepted: bool) -> Result<Option<char>> {
+        match self.kind {
+            CharacterInputKind::Codepoint => {
+                let mut chars = self.input.chars();
+                let required_digits = match chars.next() {
+                    Some('u') => 4,
+                    Some('U') => 8,
+                    Some(character) => return Ok(Some(character)),
+                    None => return Ok(None),
+                };
+                let digits = chars.as_str();
+
+                if !digits.chars().all(|c| c.is_digit(16)) {
+                    bail!("Codepoints should only contain hex digits");
+                }
+                if digits.len() < required_digits && !(accepted && !digits.is_empty()) {
+                    return Ok(None);
+                }
+
+                let codepoint = u32::from_str_radix(digits, 16)
+                    .chain_err(|| "Couldn't parse the codepoint")?;
+                char::from_u32(codepoint)
+                    .map(Some)
+                    .ok_or_else(|| format!("U+{:X} isn't a valid character", codepoint).into())
+            },
+            CharacterInputKind::Digraph => {
+                if self.input.chars().count() < 2 {
+                    if accepted {
+                        bail!("Digraphs are two characters long");
+                    }
+
+                    return Ok(None);
+                }
+
+                digraphs
+                    .get(&self.input)
+                    .map(|&character| Some(character))
+                    .ok_or_else(|| format!("Unknown digraph \"{}\"", self.input).into())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CharacterInputKind, CharacterInputMode};
+    use std::collections::HashMap;
+
+    fn mode(kind: CharacterInputKind, input: &str) -> CharacterInputMode {
+        let mut mode = CharacterInputMode::new(kind);
+        mode.input.push_str(input);
+
+        mode
+    }
+
+    #[test]
+    fn codepoints_are_complete_once_all_of_their_digits_are_entered() {
+        let digraphs = HashMap::new();
+
+        assert_eq!(mode(CharacterInputKind::Codepoint, "u00e").character(&digraphs).unwrap(), None);
+        assert_eq!(mode(CharacterInputKind::Codepoint, "u00e9").character(&digraphs).unwrap(), Some('é'));
+        assert_eq!(mode(CharacterInputKind::Codepoint, "U1f600").accept(&digraphs).unwrap(), '😀');
+        assert_eq!(mode(CharacterInputKind::Codepoint, "\t").character(&digraphs).unwrap(), Some('\t'));
+        assert!(mode(CharacterInputKind::Codepoint, "u00g").character(&digraphs).is_err());
+        assert!(mode(CharacterInputKind::Codepoint, "Ud800").accept(&digraphs).is_err());
+    }
+
+    #[test]
+    fn digraphs_are_looked_up_once_both_characters_are_entered() {
+        let mut digraphs = HashMap::new();
+        digraphs.insert(String::from("e'"), 'é');
+
+        assert_eq!(mode(CharacterInputKind::Digraph, "e").character(&digraphs).unwrap(), None);
+        assert_eq!(mode(CharacterInputKind::Digraph, "e'").character(&digraphs).unwrap(), Some('é'));
+        assert!(mode(CharacterInputKind::Digraph, "e?").character(&digraphs).is_err());
+    }
+}
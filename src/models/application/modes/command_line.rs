@@ -0,0 +1,128 @@
+use crate::commands;
+use crate::models::application::Preferences;
+use crate::util::completion::{self, Completions};
+use scribe::buffer::Range;
+use std::fmt;
+
+/// Commands handled by the command line itself, rather than the command registry.
+pub const BUILT_IN_COMMANDS: [&str; 12] = ["cd", "e", "edit", "q", "quit", "replace", "set", "w", "workspace", "wq", "write", "x"];
+
+/// A vim-style prompt for typed commands, which can be given arguments.
+pub struct CommandLineMode {
+    pub input: String,
+    pub completions: Completions,
+    /// The selection active when the command line was opened, which
+    /// limits the scope of commands like substitutions.
+    pub selection: Option<Range>,
+    command_names: Vec<&'static str>,
+}
+
+impl CommandLineMode {
+    pub fn new() -> CommandLineMode {
+        let mut command_names: Vec<&'static str> = commands::hash_map().keys().cloned().collect();
+        command_names.extend_from_slice(&BUILT_IN_COMMANDS);
+
+        CommandLineMode {
+            input: String::new(),
+            completions: Completions::new(),
+            selection: None,
+            command_names,
+        }
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.input.push(c);
+        self.completions.clear();
+    }
+
+    pub fn pop_char(&mut self) {
+        self.input.pop();
+        self.completions.clear();
+    }
+
+    /// Completes the last word of the input, using command names for the first
+    /// word, and paths or preference names for arguments (depending on the
+    /// command).
+    pub fn complete(&mut self) {
+        let word_start = self.word_start();
+        let (command_input, word) = self.input.split_at(word_start);
+        let command = command_input.split_whitespace().next();
+
+        let candidates = match command {
+            None => completion::matching(word, &self.command_names),
+            Some("e") | Some("edit") => completion::paths(word, self.completions.show_hidden),
+            Some("set") if command_input.split_whitespace().count() == 1 => {
+                completion::matching(word, Preferences::settable_keys())
+            },
+            _ => Vec::new(),
+        };
+
+        if let Some(mut completed_word) = self.completions.complete(word, candidates) {
+            // Move on to the next argument after unique matches,
+            // leaving directories open for further completion.
+            if self.completions.candidates.is_empty() && !completed_word.ends_with('/') {
+                completed_word.push(' ');
+            }
+            self.input = format!("{}{}", command_input, completed_word);
+        }
+    }
+
+    /// The byte offset of the word being typed (and completed).
+    pub fn word_start(&self) -> usize {
+        self.input.rfind(' ').map(|index| index + 1).unwrap_or(0)
+    }
+}
+
+impl fmt::Display for CommandLineMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.selection.is_some() {
+            write!(f, "[selection]:")
+        } else {
+            write!(f, ":")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommandLineMode;
+
+    #[test]
+    fn complete_finishes_unique_command_names() {
+        let mut mode = CommandLineMode::new();
+        mode.input = String::from("buffer::display_st");
+        mode.complete();
+
+        assert_eq!(mode.input, "buffer::display_statistics ");
+        assert!(mode.completions.candidates.is_empty());
+    }
+
+    #[test]
+    fn complete_extends_ambiguous_input_and_lists_candidates() {
+        let mut mode = CommandLineMode::new();
+        mode.input = String::from("w");
+        mode.complete();
+
+        assert_eq!(mode.input, "w");
+        assert!(mode.completions.candidates.contains(&String::from("wq")));
+        assert!(mode.completions.candidates.contains(&String::from("workspace::new_buffer")));
+    }
+
+    #[test]
+    fn complete_completes_paths_for_edit_command() {
+        let mut mode = CommandLineMode::new();
+        mode.input = String::from("e src/comm");
+        mode.complete();
+
+        assert_eq!(mode.input, "e src/commands/");
+    }
+
+    #[test]
+    fn complete_completes_preference_names_for_set_command() {
+        let mut mode = CommandLineMode::new();
+        mode.input = String::from("set tab_");
+        mode.complete();
+
+        assert_eq!(mode.input, "set tab_width ");
+    }
+}
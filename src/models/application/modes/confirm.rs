@@ -2,10 +2,16 @@ use crate::commands::Command;
 
 pub struct ConfirmMode {
     pub command: Command,
+    pub prompt: String,
 }
 
 impl ConfirmMode {
     pub fn new(command: Command) -> ConfirmMode {
-        ConfirmMode { command }
+        ConfirmMode::with_prompt(command, "Are you sure?")
+    }
+
+    /// Asks a specific question, rather than the default one.
+    pub fn with_prompt(command: Command, prompt: &str) -> ConfirmMode {
+        ConfirmMode { command, prompt: prompt.to_string() }
     }
 }
@@ -0,0 +1,195 @@
+use crate::util::diff::{self, Hunk};
+use std::cmp;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Where the right-hand side of a diff was sourced from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffSource {
+    Disk,
+    NextBuffer,
+    /// The buffer's own content, as it was at the specified time.
+    Earlier(Instant),
+    /// A copy of the buffer's file, kept in its local history when it was saved.
+    Snapshot(PathBuf),
+    /// The buffer's file as it was committed in a revision (identified by
+    /// its commit ID), along with its content, which can't change.
+    #[cfg(feature = "git")]
+    Revision(String, String),
+}
+
+/// A single line of the side-by-side display, pairing up lines from each
+/// side. Either side can be absent when lines were added or removed.
+#[derive(Debug, PartialEq)]
+pub struct DiffRow {
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+    pub hunk: Option<usize>,
+}
+
+/// Side-by-side comparison of the current buffer (on the left) and either
+/// its on-disk contents or the next buffer in the workspace (on the right).
+pub struct DiffMode {
+    pub source: DiffSource,
+    pub left_title: String,
+    pub right_title: String,
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+    pub hunks: Vec<Hunk>,
+    pub rows: Vec<DiffRow>,
+    pub line_offset: usize,
+    pub selected_hunk: Option<usize>,
+}
+
+impl DiffMode {
+    pub fn new(source: DiffSource, left_title: String, left: &str, right_title: String, right: &str) -> DiffMode {
+        let mut mode = DiffMode {
+            source,
+            left_title,
+            right_title,
+            left: Vec::new(),
+            right: Vec::new(),
+            hunks: Vec::new(),
+            rows: Vec::new(),
+            line_offset: 0,
+            selected_hunk: None,
+        };
+        mode.update(left, right);
+        mode.next_change();
+
+        mode
+    }
+
+    /// Re-runs the comparison against new content, keeping the
+    /// scroll position and the selected change (where possible).
+    pub fn update(&mut self, left: &str, right: &str) {
+        self.left = left.lines().map(String::from).collect();
+        self.right = right.lines().map(String::from).collect();
+        self.hunks = diff::hunks(&self.left, &self.right);
+        self.rows = build_rows(self.left.len(), &self.hunks);
+        self.selected_hunk = match self.selected_hunk {
+            _ if self.hunks.is_empty() => None,
+            Some(index) => Some(cmp::min(index, self.hunks.len() - 1)),
+            None => None,
+        };
+        self.line_offset = cmp::min(self.line_offset, self.max_line_offset());
+    }
+
+    pub fn selected_hunk(&self) -> Option<&Hunk> {
+        self.selected_hunk.and_then(|index| self.hunks.get(index))
+    }
+
+    pub fn next_change(&mut self) -> bool {
+        let next = match self.selected_hunk {
+            Some(index) if index + 1 < self.hunks.len() => index + 1,
+            None if !self.hunks.is_empty() => 0,
+            _ => return false,
+        };
+        self.select_hunk(next);
+
+        true
+    }
+
+    pub fn previous_change(&mut self) -> bool {
+        match self.selected_hunk {
+            Some(index) if index > 0 => {
+                self.select_hunk(index - 1);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    pub fn scroll_up(&mut self, amount: usize) {
+        self.line_offset = self.line_offset.checked_sub(amount).unwrap_or(0);
+    }
+
+    pub fn scroll_down(&mut self, amount: usize) {
+        self.line_offset = cmp::min(self.line_offset + amount, self.max_line_offset());
+    }
+
+    fn select_hunk(&mut self, index: usize) {
+        self.selected_hunk = Some(index);
+
+        // Bring the change into view, with a little leading context.
+        if let Some(row) = self.rows.iter().position(|row| row.hunk == Some(index)) {
+            self.line_offset = row.checked_sub(3).unwrap_or(0);
+        }
+    }
+
+    fn max_line_offset(&self) -> usize {
+        self.rows.len().checked_sub(1).unwrap_or(0)
+    }
+}
+
+/// Aligns both sides of the diff, pairing unchanged lines and padding
+/// the shorter side of each hunk so that subsequent lines line up.
+fn build_rows(left_len: usize, hunks: &[Hunk]) -> Vec<DiffRow> {
+    let mut rows = Vec::new();
+    let (mut left, mut right) = (0, 0);
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        while left < hunk.left.start {
+            rows.push(DiffRow{ left: Some(left), right: Some(right), hunk: None });
+            left += 1;
+            right += 1;
+        }
+
+        let height = cmp::max(hunk.left.len(), hunk.right.len());
+        for row in 0..height {
+            rows.push(DiffRow{
+                left: if row < hunk.left.len() { Some(hunk.left.start + row) } else { None },
+                right: if row < hunk.right.len() { Some(hunk.right.start + row) } else { None },
+                hunk: Some(index),
+            });
+        }
+        left = hunk.left.end;
+        right = hunk.right.end;
+    }
+
+    while left < left_len {
+        rows.push(DiffRow{ left: Some(left), right: Some(right), hunk: None });
+        left += 1;
+        right += 1;
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiffMode, DiffRow, DiffSource};
+
+    #[test]
+    fn new_aligns_rows_and_selects_the_first_change() {
+        let mode = DiffMode::new(
+            DiffSource::Disk,
+            String::from("left"), "amp\neditor\nrust\n",
+            String::from("right"), "amp\nrust\nlang\n"
+        );
+
+        assert_eq!(mode.rows, vec![
+            DiffRow{ left: Some(0), right: Some(0), hunk: None },
+            DiffRow{ left: Some(1), right: None, hunk: Some(0) },
+            DiffRow{ left: Some(2), right: Some(1), hunk: None },
+            DiffRow{ left: None, right: Some(2), hunk: Some(1) },
+        ]);
+        assert_eq!(mode.selected_hunk, Some(0));
+    }
+
+    #[test]
+    fn next_and_previous_change_stop_at_the_ends() {
+        let mut mode = DiffMode::new(
+            DiffSource::Disk,
+            String::from("left"), "a\nb\nc\n",
+            String::from("right"), "x\nb\ny\n"
+        );
+
+        assert!(!mode.previous_change());
+        assert!(mode.next_change());
+        assert_eq!(mode.selected_hunk, Some(1));
+        assert!(!mode.next_change());
+        assert!(mode.previous_change());
+        assert_eq!(mode.selected_hunk, Some(0));
+    }
+}
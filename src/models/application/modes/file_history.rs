@@ -0,0 +1,72 @@
+use crate::commands;
+use crate::models::application::Application;
+use crate::models::application::modes::{PickerMode, SearchSelectConfig};
+use fragment::matching::AsStr;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
+/// Run against the selected revision when the selection is accepted.
+pub type RevisionAction = fn(&mut Application, &Path, &Revision) -> commands::Result;
+
+/// A commit that changed the file whose history is being listed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Revision {
+    /// The commit's full object ID.
+    pub id: String,
+    /// The commit's abbreviated ID, date, author, and subject
+    /// (e.g. "1a2b3c4 2019-03-01 Jordan MacDonald: Fix the thing").
+    pub label: String,
+}
+
+impl fmt::Display for Revision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl AsStr for Revision {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Lists the commits that changed a file, from newest to oldest. The history
+/// is read in the background; the list is empty until it's been loaded.
+pub struct FileHistoryMode {
+    pub path: PathBuf,
+    pub action: RevisionAction,
+    picker: PickerMode<Revision>,
+}
+
+impl FileHistoryMode {
+    pub fn new(path: PathBuf, action: RevisionAction, config: SearchSelectConfig) -> FileHistoryMode {
+        FileHistoryMode {
+            path,
+            action,
+            picker: PickerMode::new("FILE HISTORY", Vec::new(), config)
+                .list_without_query()
+                .with_empty_message("Reading the file's history..."),
+        }
+    }
+}
+
+impl fmt::Display for FileHistoryMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.picker)
+    }
+}
+
+impl Deref for FileHistoryMode {
+    type Target = PickerMode<Revision>;
+
+    fn deref(&self) -> &PickerMode<Revision> {
+        &self.picker
+    }
+}
+
+impl DerefMut for FileHistoryMode {
+    fn deref_mut(&mut self) -> &mut PickerMode<Revision> {
+        &mut self.picker
+    }
+}
@@ -0,0 +1,64 @@
+use crate::util::hex;
+use std::cmp;
+use std::path::PathBuf;
+
+/// A read-only hex dump of a binary file, which can't be opened as a buffer.
+pub struct HexMode {
+    pub path: PathBuf,
+    pub data: Vec<u8>,
+    pub line_offset: usize,
+}
+
+impl HexMode {
+    pub fn new(path: PathBuf, data: Vec<u8>) -> HexMode {
+        HexMode {
+            path,
+            data,
+            line_offset: 0,
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        hex::line_count(&self.data)
+    }
+
+    pub fn scroll_up(&mut self, amount: usize) {
+        self.line_offset = self.line_offset.checked_sub(amount).unwrap_or(0);
+    }
+
+    pub fn scroll_down(&mut self, amount: usize) {
+        self.line_offset = cmp::min(self.line_offset + amount, self.max_line_offset());
+    }
+
+    pub fn scroll_to_start(&mut self) {
+        self.line_offset = 0;
+    }
+
+    pub fn scroll_to_end(&mut self) {
+        self.line_offset = self.max_line_offset();
+    }
+
+    fn max_line_offset(&self) -> usize {
+        self.line_count().checked_sub(1).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HexMode;
+    use std::path::PathBuf;
+
+    #[test]
+    fn scrolling_stays_within_the_dump() {
+        let mut mode = HexMode::new(PathBuf::from("amp.bin"), vec![0; 40]);
+
+        mode.scroll_up(1);
+        assert_eq!(mode.line_offset, 0);
+        mode.scroll_down(5);
+        assert_eq!(mode.line_offset, 2);
+        mode.scroll_to_start();
+        assert_eq!(mode.line_offset, 0);
+        mode.scroll_to_end();
+        assert_eq!(mode.line_offset, 2);
+    }
+}
@@ -2,8 +2,10 @@ mod tag_generator;
 mod single_character_tag_generator;
 
 use luthor::token::Category;
-use crate::util::movement_lexer;
+use crate::util::movement_lexer::LexCache;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 use scribe::buffer::{Distance, Position};
 use crate::models::application::modes::select::SelectMode;
 use crate::models::application::modes::select_line::SelectLineMode;
@@ -34,10 +36,17 @@ pub struct JumpMode {
     single_characters: SingleCharacterTagGenerator,
     current_position: Position,
     mapped_lexeme_values: Vec<MappedLexemeValue>,
+    lex_cache: Rc<RefCell<LexCache>>,
 }
 
 impl JumpMode {
     pub fn new(cursor_line: usize) -> JumpMode {
+        JumpMode::with_lex_cache(cursor_line, Rc::new(RefCell::new(LexCache::new())))
+    }
+
+    /// Builds the mode using a lexer cache that outlives it, so that
+    /// lines that haven't changed since the last activation aren't re-lexed.
+    pub fn with_lex_cache(cursor_line: usize, lex_cache: Rc<RefCell<LexCache>>) -> JumpMode {
         JumpMode {
             input: String::new(),
             first_phase: true,
@@ -48,6 +57,7 @@ impl JumpMode {
             single_characters: SingleCharacterTagGenerator::new(),
             current_position: Position{ line: 0, offset: 0 },
             mapped_lexeme_values: Vec::new(),
+            lex_cache,
         }
     }
 
@@ -72,17 +82,19 @@ impl LexemeMapper for JumpMode {
     // We also track jump tag locations so that tags can be
     // resolved to positions for performing the actual jump later on.
     fn map<'a, 'b>(&'a mut self, lexeme: &'b str, position: Position) -> Vec<MappedLexeme<'a>> {
-        self.mapped_lexeme_values = Vec::new();
+        // Reuse the previous lexeme's allocation.
+        self.mapped_lexeme_values.clear();
         self.current_position = position;
 
-        for subtoken in movement_lexer::lex(lexeme) {
+        let subtokens = self.lex_cache.borrow_mut().lex(lexeme);
+        for subtoken in subtokens.iter() {
             if subtoken.category == Category::Whitespace {
                 let distance = Distance::of_str(&subtoken.lexeme);
 
                 // We don't do anything to whitespace tokens.
                 self.mapped_lexeme_values.push(
                     MappedLexemeValue::Text((
-                        subtoken.lexeme,
+                        subtoken.lexeme.clone(),
                         self.current_position
                     ))
                 );
@@ -124,12 +136,13 @@ impl LexemeMapper for JumpMode {
                             offset: tag_len
                         };
 
-                        let suffix: String =
-                            subtoken
+                        let tagged_len = subtoken
                             .lexeme
-                            .chars()
-                            .skip(tag_len)
-                            .collect();
+                            .char_indices()
+                            .nth(tag_len)
+                            .map(|(index, _)| index)
+                            .unwrap_or_else(|| subtoken.lexeme.len());
+                        let suffix = subtoken.lexeme[tagged_len..].to_string();
                         let suffix_len = suffix.len();
 
                         if suffix_len > 0 {
@@ -154,7 +167,7 @@ impl LexemeMapper for JumpMode {
                         // We couldn't tag this subtoken; move along.
                         self.mapped_lexeme_values.push(
                             MappedLexemeValue::Text((
-                                subtoken.lexeme,
+                                subtoken.lexeme.clone(),
                                 self.current_position
                             ))
                         );
@@ -0,0 +1,38 @@
+use crate::models::application::local_history::Snapshot;
+use crate::models::application::modes::{PickerMode, SearchSelectConfig};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Lists the copies kept of the current buffer's file each time it was
+/// saved, from newest to oldest, any of which can be compared against it.
+pub struct LocalHistoryMode {
+    picker: PickerMode<Snapshot>,
+}
+
+impl LocalHistoryMode {
+    pub fn new(snapshots: Vec<Snapshot>, config: SearchSelectConfig) -> LocalHistoryMode {
+        LocalHistoryMode {
+            picker: PickerMode::new("HISTORY", snapshots, config).list_without_query(),
+        }
+    }
+}
+
+impl fmt::Display for LocalHistoryMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.picker)
+    }
+}
+
+impl Deref for LocalHistoryMode {
+    type Target = PickerMode<Snapshot>;
+
+    fn deref(&self) -> &PickerMode<Snapshot> {
+        &self.picker
+    }
+}
+
+impl DerefMut for LocalHistoryMode {
+    fn deref_mut(&mut self) -> &mut PickerMode<Snapshot> {
+        &mut self.picker
+    }
+}
@@ -0,0 +1,26 @@
+use crate::errors::*;
+use crate::models::application::Application;
+
+/// Run with the input once it's been entered.
+pub type MaskedInputCallback = Box<Fn(&mut Application, &str) -> Result<()>>;
+
+/// Prompts for sensitive input (e.g. passphrases or access tokens), showing
+/// only its length. The input is handed to the callback once it's accepted,
+/// and is never logged, copied to the clipboard, or kept in any history.
+pub struct MaskedInputMode {
+    pub prompt: String,
+    pub input: String,
+    pub callback: MaskedInputCallback,
+}
+
+impl MaskedInputMode {
+    pub fn new<F>(prompt: String, callback: F) -> MaskedInputMode
+        where F: Fn(&mut Application, &str) -> Result<()> + 'static
+    {
+        MaskedInputMode {
+            prompt,
+            input: String::new(),
+            callback: Box::new(callback),
+        }
+    }
+}
@@ -1,25 +1,57 @@
+mod character_input;
 mod confirm;
 mod command;
+mod command_line;
+mod diff;
+#[cfg(feature = "git")]
+mod file_history;
+mod hex;
 pub mod jump;
 mod line_jump;
+mod local_history;
+mod masked_input;
 pub mod open;
 mod path;
+mod picker;
+pub mod recent;
+mod replace;
 mod search;
 mod search_select;
 mod select;
+mod select_block;
 mod select_line;
+mod spelling;
 mod symbol_jump;
+mod tag;
+mod task;
 mod theme;
+pub mod workspace;
 
+pub use self::character_input::{CharacterInputKind, CharacterInputMode};
 pub use self::confirm::ConfirmMode;
 pub use self::command::CommandMode;
+pub use self::command_line::CommandLineMode;
+pub use self::diff::{DiffMode, DiffRow, DiffSource};
+#[cfg(feature = "git")]
+pub use self::file_history::{FileHistoryMode, Revision, RevisionAction};
+pub use self::hex::HexMode;
 pub use self::jump::JumpMode;
 pub use self::line_jump::LineJumpMode;
-pub use self::path::PathMode;
-pub use self::open::OpenMode;
-pub use self::search::SearchMode;
-pub use self::search_select::{SearchSelectMode, SearchSelectConfig};
+pub use self::local_history::LocalHistoryMode;
+pub use self::masked_input::{MaskedInputCallback, MaskedInputMode};
+pub use self::path::{PathAction, PathMode};
+pub use self::picker::PickerMode;
+pub use self::recent::RecentMode;
+pub use self::replace::{ReplaceMode, ReplaceRow};
+pub use self::open::{IndexCache, OpenMode};
+pub use self::search::{SearchMode, SearchOptions};
+pub use self::search_select::{Preview, PreviewCache, RenderedPreview, SearchSelectMode, SearchSelectConfig};
 pub use self::select::SelectMode;
+pub use self::select_block::SelectBlockMode;
 pub use self::select_line::SelectLineMode;
+pub use self::spelling::SpellingMode;
 pub use self::symbol_jump::SymbolJumpMode;
+pub use self::tag::TagMode;
+pub use self::task::TaskMode;
 pub use self::theme::ThemeMode;
+pub use self::workspace::WorkspaceMode;
@@ -1,6 +1,10 @@
 use crate::errors::*;
 use bloodhound::ExclusionPattern;
 use crate::yaml::Yaml;
+use std::fs;
+use std::path::Path;
+
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
 
 pub fn parse(exclusion_data: &[Yaml]) -> Result<Vec<ExclusionPattern>> {
     let mut mapped_exclusions = Vec::new();
@@ -20,9 +24,54 @@ pub fn parse(exclusion_data: &[Yaml]) -> Result<Vec<ExclusionPattern>> {
     Ok(mapped_exclusions)
 }
 
+/// Translates the ignore files at the root of the specified directory into
+/// exclusion patterns. Only the subset of the gitignore syntax that maps onto
+/// glob patterns is supported: negated patterns are skipped, and ignore files
+/// in subdirectories aren't consulted.
+pub fn ignore_file_patterns(root: &Path) -> Vec<ExclusionPattern> {
+    IGNORE_FILES
+        .iter()
+        .filter_map(|file_name| fs::read_to_string(root.join(file_name)).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .filter_map(|line| ignore_pattern(root, line))
+                .collect::<Vec<ExclusionPattern>>()
+        })
+        .collect()
+}
+
+// Converts a single ignore file line into an absolute glob pattern, since
+// that's what the index matches exclusions against.
+fn ignore_pattern(root: &Path, line: &str) -> Option<ExclusionPattern> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+        return None;
+    }
+
+    // Trailing slashes limit patterns to directories; we can't
+    // make that distinction, so they're treated as any other path.
+    let pattern = line.trim_end_matches('/');
+    if pattern.is_empty() {
+        return None;
+    }
+
+    // Patterns with a leading or inner slash are relative to the ignore
+    // file's directory; anything else can match at any depth.
+    let root = ExclusionPattern::escape(root.to_string_lossy().trim_end_matches('/'));
+    let absolute_pattern = if pattern.contains('/') {
+        format!("{}/{}", root, pattern.trim_start_matches('/'))
+    } else {
+        format!("{}/**/{}", root, pattern)
+    };
+
+    ExclusionPattern::new(&absolute_pattern).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
 
     #[test]
     fn parse_converts_yaml_strings_into_glob_patterns() {
@@ -46,4 +95,25 @@ mod tests {
 
         assert!(parse(&exclusion_data).is_err());
     }
+
+    #[test]
+    fn ignore_file_patterns_excludes_matching_paths_at_any_depth() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/ignore_files"));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join(".gitignore"), "# comment\ntarget/\n!keep\n*.log\n").unwrap();
+        fs::write(root.join(".ignore"), "/node_modules\n").unwrap();
+        let patterns = ignore_file_patterns(&root);
+        let excluded = |path: &str| {
+            let path = root.join(path);
+            patterns.iter().any(|pattern| pattern.matches(&path.to_string_lossy()))
+        };
+
+        assert_eq!(patterns.len(), 3);
+        assert!(excluded("target"));
+        assert!(excluded("nested/target"));
+        assert!(excluded("nested/debug.log"));
+        assert!(excluded("node_modules"));
+        assert!(!excluded("nested/node_modules"));
+        assert!(!excluded("src/main.rs"));
+    }
 }
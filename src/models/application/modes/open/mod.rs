@@ -3,28 +3,66 @@ pub mod exclusions;
 
 use std::fmt;
 use std::path::PathBuf;
+use std::rc::Rc;
 use std::slice::Iter;
 use bloodhound::ExclusionPattern;
 use crate::util::SelectableVec;
-use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::modes::{Preview, PreviewCache, SearchSelectMode, SearchSelectConfig};
 use crate::models::application::Event;
 use std::sync::mpsc::Sender;
 use std::thread;
+use std::time::{Duration, Instant};
 pub use bloodhound::Index;
 pub use self::displayable_path::DisplayablePath;
 
+// How long a cached index is trusted to reflect the file system. Changes made
+// from within the editor invalidate the cache immediately; this catches the rest.
+const INDEX_CACHE_LIFETIME: Duration = Duration::from_secs(60);
+
 #[derive(PartialEq)]
 pub enum OpenModeIndex {
-    Complete(Index),
+    Complete(Rc<Index>),
     Indexing(PathBuf)
 }
 
+/// Holds on to the most recently built index, so that open mode
+/// can be re-entered without waiting on the workspace to be indexed.
+#[derive(Default)]
+pub struct IndexCache {
+    entry: Option<(Rc<Index>, Instant)>,
+}
+
+impl IndexCache {
+    pub fn new() -> IndexCache {
+        IndexCache::default()
+    }
+
+    /// Returns the cached index, provided it isn't stale.
+    pub fn get(&self) -> Option<Rc<Index>> {
+        self.entry
+            .as_ref()
+            .filter(|&&(_, built_at)| built_at.elapsed() < INDEX_CACHE_LIFETIME)
+            .map(|&(ref index, _)| index.clone())
+    }
+
+    pub fn set(&mut self, index: Rc<Index>) {
+        self.entry = Some((index, Instant::now()));
+    }
+
+    /// Discards the cached index; used when the file system is changed.
+    pub fn invalidate(&mut self) {
+        self.entry = None;
+    }
+}
+
 pub struct OpenMode {
     pub insert: bool,
     pub input: String,
     index: OpenModeIndex,
     pub results: SelectableVec<DisplayablePath>,
+    recent_paths: Vec<PathBuf>,
     config: SearchSelectConfig,
+    preview_cache: PreviewCache,
 }
 
 impl OpenMode {
@@ -44,11 +82,32 @@ impl OpenMode {
             input: String::new(),
             index: OpenModeIndex::Indexing(path),
             results: SelectableVec::new(Vec::new()),
+            recent_paths: Vec::new(),
             config,
+            preview_cache: PreviewCache::default(),
         }
     }
 
-    pub fn set_index(&mut self, index: Index) {
+    /// Builds the mode using a previously populated index.
+    pub fn with_index(index: Rc<Index>, config: SearchSelectConfig) -> OpenMode {
+        OpenMode {
+            insert: true,
+            input: String::new(),
+            index: OpenModeIndex::Complete(index),
+            results: SelectableVec::new(Vec::new()),
+            recent_paths: Vec::new(),
+            config,
+            preview_cache: PreviewCache::default(),
+        }
+    }
+
+    /// Recently opened paths, relative to the indexed directory,
+    /// which are moved ahead of other matching results.
+    pub fn set_recent_paths(&mut self, paths: Vec<PathBuf>) {
+        self.recent_paths = paths;
+    }
+
+    pub fn set_index(&mut self, index: Rc<Index>) {
         self.index = OpenModeIndex::Complete(index)
     }
 }
@@ -61,7 +120,7 @@ impl fmt::Display for OpenMode {
 
 impl SearchSelectMode<DisplayablePath> for OpenMode {
     fn search(&mut self) {
-        let results =
+        let mut results: Vec<DisplayablePath> =
             if let OpenModeIndex::Complete(ref index) = self.index {
                 index.find(
                     &self.input.to_lowercase(),
@@ -73,6 +132,10 @@ impl SearchSelectMode<DisplayablePath> for OpenMode {
                 vec![]
             };
 
+        // The sort is stable, so matches keep their relative order otherwise.
+        let recent_paths = &self.recent_paths;
+        results.sort_by_key(|&DisplayablePath(ref path)| !recent_paths.contains(path));
+
         self.results = SelectableVec::new(results);
     }
 
@@ -112,6 +175,14 @@ impl SearchSelectMode<DisplayablePath> for OpenMode {
         &self.config
     }
 
+    fn preview(&self) -> Option<Preview> {
+        self.selection().and_then(|&DisplayablePath(ref path)| Preview::file(path))
+    }
+
+    fn preview_cache(&mut self) -> Option<&mut PreviewCache> {
+        Some(&mut self.preview_cache)
+    }
+
     fn message(&mut self) -> Option<String> {
         if let OpenModeIndex::Indexing(ref path) = self.index {
             Some(format!("Indexing {}", path.to_string_lossy()))
@@ -124,3 +195,22 @@ impl SearchSelectMode<DisplayablePath> for OpenMode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Index, IndexCache};
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    #[test]
+    fn index_cache_returns_index_until_invalidated() {
+        let mut cache = IndexCache::new();
+        assert!(cache.get().is_none());
+
+        cache.set(Rc::new(Index::new(PathBuf::from("."))));
+        assert!(cache.get().is_some());
+
+        cache.invalidate();
+        assert!(cache.get().is_none());
+    }
+}
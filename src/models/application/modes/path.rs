@@ -1,27 +1,71 @@
+use crate::util::completion::{self, Completions};
 use std::fmt;
 
+/// What's done with the current buffer's file when a path is accepted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathAction {
+    /// Points the buffer at the path, leaving the file system alone.
+    SetPath,
+    /// Moves the buffer's file to the path.
+    Rename,
+    /// Creates an empty file at the path, and opens it.
+    CreateFile,
+    /// Creates a directory at the path.
+    CreateDirectory,
+}
+
 pub struct PathMode {
     pub input: String,
+    pub action: PathAction,
     pub save_on_accept: bool,
+    pub completions: Completions,
 }
 
 impl PathMode {
     pub fn new(initial_path: String) -> PathMode {
         PathMode {
             input: initial_path,
-            save_on_accept: false
+            action: PathAction::SetPath,
+            save_on_accept: false,
+            completions: Completions::new(),
         }
     }
     pub fn push_char(&mut self, c: char) {
         self.input.push(c);
+        self.completions.clear();
     }
     pub fn pop_char(&mut self) {
         self.input.pop();
+        self.completions.clear();
+    }
+    pub fn complete(&mut self) {
+        let candidates = completion::paths(&self.input, self.completions.show_hidden);
+        if let Some(completed_path) = self.completions.complete(&self.input, candidates) {
+            self.input = completed_path;
+        }
     }
 }
 
 impl fmt::Display for PathMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "PATH")
+        match self.action {
+            PathAction::SetPath => write!(f, "PATH"),
+            PathAction::Rename => write!(f, "RENAME"),
+            PathAction::CreateFile => write!(f, "NEW FILE"),
+            PathAction::CreateDirectory => write!(f, "NEW DIRECTORY"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathMode;
+
+    #[test]
+    fn complete_completes_the_input_path() {
+        let mut mode = PathMode::new(String::from("src/models/application/modes/pa"));
+        mode.complete();
+
+        assert_eq!(mode.input, "src/models/application/modes/path.rs");
     }
 }
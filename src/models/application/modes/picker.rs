@@ -0,0 +1,219 @@
+use fragment;
+use fragment::matching::AsStr;
+use crate::util::SelectableVec;
+use std::fmt::{self, Display};
+use std::slice::Iter;
+use crate::models::application::modes::{Preview, PreviewCache, SearchSelectMode, SearchSelectConfig};
+
+/// A search/select mode that narrows a set of items down using a fuzzy query.
+/// Pickers only need to provide the items; how they're ranked, what's listed
+/// before anything's been typed, and how the selection is previewed can all
+/// be customized, when the defaults don't suit.
+pub struct PickerMode<T> {
+    name: &'static str,
+    insert: bool,
+    input: String,
+    items: Vec<T>,
+    results: SelectableVec<T>,
+    config: SearchSelectConfig,
+    ranker: Box<Fn(&str, &[T], usize) -> Vec<T>>,
+    previewer: Option<Box<Fn(&T) -> Option<Preview>>>,
+    preview_cache: PreviewCache,
+    list_without_query: bool,
+    empty_message: Option<String>,
+}
+
+impl<T: AsStr + Clone + Display + 'static> PickerMode<T> {
+    /// Builds a picker with the specified name (shown in the status line),
+    /// which ranks items using the same fuzzy matching as other modes.
+    pub fn new(name: &'static str, items: Vec<T>, config: SearchSelectConfig) -> PickerMode<T> {
+        PickerMode {
+            name,
+            insert: true,
+            input: String::new(),
+            items,
+            results: SelectableVec::new(Vec::new()),
+            config,
+            ranker: Box::new(|query: &str, items: &[T], max_results: usize| {
+                fragment::matching::find(query, items, max_results)
+                    .into_iter()
+                    .map(|result| (*result).clone())
+                    .collect()
+            }),
+            previewer: None,
+            preview_cache: PreviewCache::default(),
+            list_without_query: false,
+            empty_message: None,
+        }
+    }
+
+    /// Replaces the default fuzzy ranking, which is given the query, the
+    /// items, and the maximum number of results, and returns the results.
+    pub fn with_ranking<F>(mut self, ranker: F) -> PickerMode<T>
+        where F: Fn(&str, &[T], usize) -> Vec<T> + 'static
+    {
+        self.ranker = Box::new(ranker);
+        self
+    }
+
+    /// Describes the selected item, for display alongside the results.
+    pub fn with_preview<F>(mut self, previewer: F) -> PickerMode<T>
+        where F: Fn(&T) -> Option<Preview> + 'static
+    {
+        self.previewer = Some(Box::new(previewer));
+        self
+    }
+
+    /// Lists items in their original order until a query is entered,
+    /// rather than prompting for one.
+    pub fn list_without_query(mut self) -> PickerMode<T> {
+        self.list_without_query = true;
+        self
+    }
+
+    /// The message shown when there are no items at all.
+    pub fn with_empty_message(mut self, message: &str) -> PickerMode<T> {
+        self.empty_message = Some(message.to_string());
+        self
+    }
+
+    /// Replaces the items being picked from (e.g. once they've been
+    /// reloaded from their source), and refreshes the results.
+    pub fn set_items(&mut self, items: Vec<T>) {
+        self.items = items;
+        self.search();
+    }
+}
+
+impl<T> fmt::Display for PickerMode<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl<T: AsStr + Clone + Display> SearchSelectMode<T> for PickerMode<T> {
+    fn search(&mut self) {
+        let results = if self.input.is_empty() && self.list_without_query {
+            self.items
+                .iter()
+                .take(self.config.max_results)
+                .cloned()
+                .collect()
+        } else {
+            (self.ranker)(&self.input, &self.items, self.config.max_results)
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut String {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<T> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&T> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn preview(&self) -> Option<Preview> {
+        let previewer = self.previewer.as_ref()?;
+
+        self.selection().and_then(|item| previewer(item))
+    }
+
+    fn preview_cache(&mut self) -> Option<&mut PreviewCache> {
+        Some(&mut self.preview_cache)
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.items.is_empty() && self.empty_message.is_some() {
+            self.empty_message.clone()
+        } else if self.input.is_empty() && !self.list_without_query {
+            Some(String::from("Enter a search query to start."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PickerMode;
+    use crate::models::application::modes::{Preview, SearchSelectMode, SearchSelectConfig};
+
+    fn items() -> Vec<String> {
+        vec![String::from("amp"), String::from("editor"), String::from("map")]
+    }
+
+    #[test]
+    fn search_uses_the_configured_ranking() {
+        let mut mode = PickerMode::new("TEST", items(), SearchSelectConfig::default())
+            .with_ranking(|query, items, _| {
+                items.iter().filter(|item| item.ends_with(query)).cloned().collect()
+            });
+        mode.query().push_str("p");
+        mode.search();
+
+        assert_eq!(mode.results().cloned().collect::<Vec<String>>(), vec!["amp", "map"]);
+    }
+
+    #[test]
+    fn search_lists_items_in_order_without_a_query_when_configured() {
+        let mut mode = PickerMode::new("TEST", items(), SearchSelectConfig::default());
+        mode.search();
+        assert!(mode.message().is_some());
+
+        let mut mode = mode.list_without_query();
+        mode.search();
+        assert_eq!(mode.results().cloned().collect::<Vec<String>>(), items());
+        assert!(mode.message().is_none());
+    }
+
+    #[test]
+    fn preview_describes_the_selection() {
+        let mut mode = PickerMode::new("TEST", items(), SearchSelectConfig::default())
+            .list_without_query()
+            .with_preview(|item| Some(Preview{ content: item.to_uppercase(), path: None }));
+        mode.search();
+        mode.select_next();
+
+        assert_eq!(mode.preview().map(|preview| preview.content), Some(String::from("EDITOR")));
+    }
+
+    #[test]
+    fn message_uses_the_empty_message_when_there_are_no_items() {
+        let mut mode = PickerMode::new("TEST", Vec::<String>::new(), SearchSelectConfig::default())
+            .with_empty_message("Nothing to pick.");
+
+        assert_eq!(mode.message(), Some(String::from("Nothing to pick.")));
+    }
+}
@@ -0,0 +1,27 @@
+use crate::models::application::modes::{PickerMode, Preview, SearchSelectConfig};
+use std::path::Path;
+
+pub type RecentMode = PickerMode<String>;
+
+/// Lists paths ordered from most to least recently opened, until a query is
+/// entered, at which point they're narrowed down using fuzzy matching.
+pub fn new(paths: Vec<String>, config: SearchSelectConfig) -> RecentMode {
+    PickerMode::new("RECENT", paths, config)
+        .list_without_query()
+        .with_empty_message("No files have been opened yet.")
+        .with_preview(|path| Preview::file(Path::new(path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+    #[test]
+    fn search_lists_paths_in_recency_order_without_a_query() {
+        let paths = vec![String::from("src/main.rs"), String::from("Cargo.toml")];
+        let mut mode = super::new(paths.clone(), SearchSelectConfig::default());
+        mode.search();
+
+        assert_eq!(mode.results().cloned().collect::<Vec<String>>(), paths);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::util::workspace_replace::FileOccurrences;
+
+/// A line of the review listing: either a file heading,
+/// or one of the occurrences found in that file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplaceRow {
+    File(usize),
+    Occurrence(usize, usize),
+}
+
+/// Review of the occurrences found for a workspace-wide replacement (or rename),
+/// grouped by file, each of which can be toggled before they're replaced.
+pub struct ReplaceMode {
+    pub query: String,
+    pub replacement: String,
+    pub files: Vec<FileOccurrences>,
+    pub rows: Vec<ReplaceRow>,
+    pub selected_row: usize,
+    pub line_offset: usize,
+    /// Whether the occurrences are of an identifier being renamed, rather than matches.
+    pub rename: bool,
+    /// Whether occurrences' lines are shown as they'll read once replaced.
+    pub preview: bool,
+}
+
+impl ReplaceMode {
+    pub fn new(query: String, replacement: String, files: Vec<FileOccurrences>) -> ReplaceMode {
+        let mut rows = Vec::new();
+        for (file_index, file) in files.iter().enumerate() {
+            rows.push(ReplaceRow::File(file_index));
+            rows.extend((0..file.occurrences.len()).map(|index| ReplaceRow::Occurrence(file_index, index)));
+        }
+
+        // The first row is always a file heading; start on its first occurrence.
+        let selected_row = if rows.len() > 1 { 1 } else { 0 };
+
+        ReplaceMode {
+            query,
+            replacement,
+            files,
+            rows,
+            selected_row,
+            line_offset: 0,
+            rename: false,
+            preview: false,
+        }
+    }
+
+    /// Review of the occurrences of an identifier being renamed throughout the workspace.
+    pub fn rename(name: String, replacement: String, files: Vec<FileOccurrences>) -> ReplaceMode {
+        ReplaceMode {
+            rename: true,
+            ..ReplaceMode::new(name, replacement, files)
+        }
+    }
+
+    pub fn toggle_preview(&mut self) {
+        self.preview = !self.preview;
+    }
+
+    /// Moves to the next occurrence, skipping over file headings.
+    pub fn select_next(&mut self) {
+        if let Some(index) = self.rows.iter().skip(self.selected_row + 1).position(is_occurrence) {
+            self.selected_row += index + 1;
+        }
+    }
+
+    /// Moves to the previous occurrence, skipping over file headings.
+    pub fn select_previous(&mut self) {
+        if let Some(index) = self.rows[..self.selected_row].iter().rposition(is_occurrence) {
+            self.selected_row = index;
+        }
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some(&ReplaceRow::Occurrence(file, index)) = self.rows.get(self.selected_row) {
+            let occurrence = &mut self.files[file].occurrences[index];
+            occurrence.selected = !occurrence.selected;
+        }
+    }
+
+    /// Selects all of the current file's occurrences, or
+    /// deselects them if they're all already selected.
+    pub fn toggle_file(&mut self) {
+        if let Some(&ReplaceRow::Occurrence(file, _)) = self.rows.get(self.selected_row) {
+            let file = &mut self.files[file];
+            let selected = file.selected_count() < file.occurrences.len();
+            for occurrence in file.occurrences.iter_mut() {
+                occurrence.selected = selected;
+            }
+        }
+    }
+
+    pub fn selected_count(&self) -> usize {
+        self.files.iter().map(|file| file.selected_count()).sum()
+    }
+
+    pub fn occurrence_count(&self) -> usize {
+        self.files.iter().map(|file| file.occurrences.len()).sum()
+    }
+
+    /// Scrolls the listing just enough to keep the selected row visible,
+    /// along with its file's heading, where it fits.
+    pub fn scroll_to_selection(&mut self, height: usize) {
+        let heading = self.rows[..self.selected_row]
+            .iter()
+            .rposition(|row| !is_occurrence(row))
+            .unwrap_or(0);
+
+        if self.selected_row < self.line_offset {
+            self.line_offset = if self.selected_row - heading < height { heading } else { self.selected_row };
+        } else if height > 0 && self.selected_row >= self.line_offset + height {
+            self.line_offset = self.selected_row + 1 - height;
+        }
+    }
+}
+
+fn is_occurrence(row: &ReplaceRow) -> bool {
+    match *row {
+        ReplaceRow::Occurrence(_, _) => true,
+        ReplaceRow::File(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::{Position, Range};
+    use crate::util::workspace_replace::{FileOccurrences, Occurrence};
+    use std::path::PathBuf;
+    use super::{ReplaceMode, ReplaceRow};
+
+    fn file(label: &str, count: usize) -> FileOccurrences {
+        FileOccurrences {
+            path: PathBuf::from(label),
+            label: String::from(label),
+            content: String::from("amp"),
+            occurrences: (0..count).map(|line| Occurrence {
+                range: Range::new(Position{ line, offset: 0 }, Position{ line, offset: 3 }),
+                line_content: String::from("amp"),
+                replacement: String::from("editor"),
+                selected: true,
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn selection_skips_file_headings() {
+        let mut mode = ReplaceMode::new(String::from("amp"), String::from("editor"), vec![file("a", 1), file("b", 2)]);
+        assert_eq!(mode.rows[mode.selected_row], ReplaceRow::Occurrence(0, 0));
+
+        mode.select_next();
+        assert_eq!(mode.rows[mode.selected_row], ReplaceRow::Occurrence(1, 0));
+        mode.select_next();
+        mode.select_next();
+        assert_eq!(mode.rows[mode.selected_row], ReplaceRow::Occurrence(1, 1));
+
+        mode.select_previous();
+        mode.select_previous();
+        mode.select_previous();
+        assert_eq!(mode.rows[mode.selected_row], ReplaceRow::Occurrence(0, 0));
+    }
+
+    #[test]
+    fn toggles_apply_to_occurrences_and_files() {
+        let mut mode = ReplaceMode::new(String::from("amp"), String::from("editor"), vec![file("a", 2)]);
+        mode.toggle_selected();
+        assert_eq!(mode.selected_count(), 1);
+
+        mode.toggle_file();
+        assert_eq!(mode.selected_count(), 2);
+        mode.toggle_file();
+        assert_eq!(mode.selected_count(), 0);
+    }
+}
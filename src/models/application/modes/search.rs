@@ -1,49 +1,197 @@
 use crate::errors::*;
 use crate::util::SelectableVec;
+use crate::util::line_index::LineIndex;
+use regex::{self, Regex};
 use std::fmt;
-use scribe::buffer::{Buffer, Distance, Range};
+use scribe::buffer::{Buffer, Range};
+
+/// Options controlling how search queries are matched.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SearchOptions {
+    /// Ignore case, unless the query contains uppercase characters.
+    pub smart_case: bool,
+    /// Treat the query as a regular expression, rather than literal text.
+    pub regex: bool,
+    /// Only match the query when it isn't part of a larger word.
+    pub whole_word: bool,
+}
+
+impl SearchOptions {
+    /// A short summary of the enabled options, if there are any.
+    pub fn description(&self) -> Option<String> {
+        let mut enabled = Vec::new();
+        if self.smart_case { enabled.push("smart case"); }
+        if self.regex { enabled.push("regex"); }
+        if self.whole_word { enabled.push("whole word"); }
+
+        if enabled.is_empty() {
+            None
+        } else {
+            Some(enabled.join(", "))
+        }
+    }
+
+    /// Builds a pattern for the query that reflects the options.
+    pub fn pattern(&self, query: &str) -> Result<Regex> {
+        let mut pattern = if self.regex {
+            query.to_string()
+        } else {
+            regex::quote(query)
+        };
+        if self.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+        if self.smart_case && !query.chars().any(char::is_uppercase) {
+            pattern = format!("(?i){}", pattern);
+        }
+
+        // Anchors match at line boundaries, rather than those of the buffer.
+        pattern = format!("(?m){}", pattern);
+
+        Regex::new(&pattern).chain_err(|| format!("Invalid search pattern: {}", query))
+    }
+}
 
 pub struct SearchMode {
     pub insert: bool,
     pub input: Option<String>,
     pub results: Option<SelectableVec<Range>>,
+    pub options: SearchOptions,
+    history: Vec<String>,
+    history_index: Option<usize>,
+    history_filter: String,
 }
 
 impl SearchMode {
     pub fn new(query: Option<String>) -> SearchMode {
+        SearchMode::with_history(query, Vec::new())
+    }
+
+    /// Builds the mode with previous queries, ordered from most to
+    /// least recent, which can be recalled into the search prompt.
+    pub fn with_history(query: Option<String>, history: Vec<String>) -> SearchMode {
         SearchMode {
             insert: true,
             input: query,
             results: None,
+            options: SearchOptions::default(),
+            history,
+            history_index: None,
+            history_filter: String::new(),
+        }
+    }
+
+    /// Replaces the query with the next-oldest history entry containing the
+    /// query that was typed before navigating the history, if there is one.
+    pub fn recall_previous_query(&mut self) {
+        if self.history_index.is_none() {
+            self.history_filter = self.input.clone().unwrap_or_default();
+        }
+
+        let start = self.history_index.map(|index| index + 1).unwrap_or(0);
+        let filter = &self.history_filter;
+        let match_index = self.history
+            .iter()
+            .enumerate()
+            .skip(start)
+            .find(|(_, entry)| entry.contains(filter.as_str()))
+            .map(|(index, _)| index);
+
+        if let Some(index) = match_index {
+            self.history_index = Some(index);
+            self.input = Some(self.history[index].clone());
         }
     }
 
+    /// Replaces the query with the next-newest matching history entry,
+    /// restoring the originally typed query once there are none left.
+    pub fn recall_next_query(&mut self) {
+        let end = match self.history_index {
+            Some(index) => index,
+            None => return,
+        };
+        let filter = &self.history_filter;
+        let match_index = self.history[..end]
+            .iter()
+            .rposition(|entry| entry.contains(filter.as_str()));
+
+        self.history_index = match_index;
+        self.input = match match_index {
+            Some(index) => Some(self.history[index].clone()),
+            None if self.history_filter.is_empty() => None,
+            None => Some(self.history_filter.clone()),
+        };
+    }
+
+    /// Stops navigating the history; used when the query is edited,
+    /// so that subsequent navigation is filtered using the new query.
+    pub fn reset_history_navigation(&mut self) {
+        self.history_index = None;
+    }
+
     pub fn insert_mode(&self) -> bool {
         self.insert
     }
 
     // Searches the specified buffer for the input string
     // and stores the result as a collection of ranges.
-    pub fn search(&mut self, buffer: &Buffer) -> Result<()> {
+    pub fn search(&mut self, buffer: &Buffer, line_index: &mut LineIndex) -> Result<()> {
         let query = self.input.as_ref().ok_or(SEARCH_QUERY_MISSING)?;
-        let distance = Distance::of_str(&query);
-
-        // Buffer search returns match starting positions, but we'd like ranges.
-        // This maps the positions to ranges using the search query distance
-        // before storing them.
-        self.results = Some(
-            SelectableVec::new(
-                buffer.search(&query)
-                    .into_iter()
-                    .map(|start| Range::new(start, start + distance))
-                    .collect()
-            )
-        );
+        let pattern = self.options.pattern(query)?;
+        let data = buffer.data();
+        let mut results = Vec::new();
+
+        // Matches are found using byte offsets, but buffer positions
+        // use lines and grapheme offsets; the index maps between them.
+        for (start, end) in line_matches(&pattern, &data) {
+            let range = (line_index.position(&data, start), line_index.position(&data, end));
+            if let (Some(start), Some(end)) = range {
+                results.push(Range::new(start, end));
+            }
+        }
+        self.results = Some(SelectableVec::new(results));
 
         Ok(())
     }
 }
 
+// Finds the pattern's non-empty matches within the data's lines, just as
+// searching each line on its own would, but in a single pass over the data.
+// Matches that would span lines (e.g. using "\s") are tried again, limited
+// to the line they start on.
+fn line_matches(pattern: &Regex, data: &str) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+    let mut position = 0;
+
+    while let Some((start, end)) = pattern.find_at(data, position) {
+        let line_end = data[start..].find('\n').map(|offset| start + offset).unwrap_or(data.len());
+        let (start, end) = if end > line_end {
+            match pattern.find_at(&data[..line_end], start) {
+                Some(line_match) => line_match,
+                None => {
+                    position = line_end + 1;
+                    continue;
+                }
+            }
+        } else {
+            (start, end)
+        };
+
+        if start == end {
+            // Step past empty matches, to the next character.
+            position = end + data[end..].chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+        } else {
+            matches.push((start, end));
+            position = end;
+        }
+        if position > data.len() {
+            break;
+        }
+    }
+
+    matches
+}
+
 impl fmt::Display for SearchMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "SEARCH")
@@ -52,8 +200,114 @@ impl fmt::Display for SearchMode {
 
 #[cfg(test)]
 mod tests {
+    use crate::util::line_index::LineIndex;
     use scribe::buffer::{Buffer, Position, Range};
-    use super::SearchMode;
+    use super::{SearchMode, SearchOptions};
+
+    #[test]
+    fn recall_previous_query_filters_history_using_the_typed_query() {
+        let history = vec![
+            String::from("amp editor"),
+            String::from("vim"),
+            String::from("amp"),
+        ];
+        let mut mode = SearchMode::with_history(Some(String::from("amp")), history);
+
+        mode.recall_previous_query();
+        assert_eq!(mode.input, Some(String::from("amp editor")));
+        mode.recall_previous_query();
+        assert_eq!(mode.input, Some(String::from("amp")));
+        mode.recall_previous_query();
+        assert_eq!(mode.input, Some(String::from("amp")));
+    }
+
+    #[test]
+    fn recall_next_query_restores_the_typed_query() {
+        let history = vec![String::from("amp editor"), String::from("amp")];
+        let mut mode = SearchMode::with_history(Some(String::from("am")), history);
+
+        mode.recall_previous_query();
+        mode.recall_previous_query();
+        mode.recall_next_query();
+        assert_eq!(mode.input, Some(String::from("amp editor")));
+        mode.recall_next_query();
+        assert_eq!(mode.input, Some(String::from("am")));
+    }
+
+    #[test]
+    fn search_ignores_case_for_lowercase_queries_when_using_smart_case() {
+        let mut buffer = Buffer::new();
+        buffer.insert("Amp amp");
+
+        let mut mode = SearchMode::new(Some(String::from("amp")));
+        mode.options.smart_case = true;
+        mode.search(&buffer, &mut LineIndex::new()).unwrap();
+        assert_eq!(mode.results.as_ref().unwrap().len(), 2);
+
+        mode.input = Some(String::from("Amp"));
+        mode.search(&buffer, &mut LineIndex::new()).unwrap();
+        assert_eq!(mode.results.as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn search_matches_regular_expressions_and_whole_words() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp ample a.p");
+
+        let mut mode = SearchMode::new(Some(String::from("a.p")));
+        mode.search(&buffer, &mut LineIndex::new()).unwrap();
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![Range::new(Position{ line: 0, offset: 10 }, Position{ line: 0, offset: 13 })]
+        );
+
+        let mut mode = SearchMode::new(Some(String::from("am\\w+")));
+        mode.options = SearchOptions{ regex: true, whole_word: true, ..SearchOptions::default() };
+        mode.search(&buffer, &mut LineIndex::new()).unwrap();
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![
+                Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 3 }),
+                Range::new(Position{ line: 0, offset: 4 }, Position{ line: 0, offset: 9 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_matches_within_lines() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp \neditor\namp");
+
+        let mut mode = SearchMode::new(Some(String::from("amp\\s+editor")));
+        mode.options.regex = true;
+        mode.search(&buffer, &mut LineIndex::new()).unwrap();
+        assert!(mode.results.unwrap().is_empty());
+
+        // Matches that would otherwise span lines end with the line.
+        let mut mode = SearchMode::new(Some(String::from("amp[^x]*")));
+        mode.options.regex = true;
+        mode.search(&buffer, &mut LineIndex::new()).unwrap();
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![
+                Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 4 }),
+                Range::new(Position{ line: 2, offset: 0 }, Position{ line: 2, offset: 3 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_uses_grapheme_offsets() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ñamp");
+
+        let mut mode = SearchMode::new(Some(String::from("amp")));
+        mode.search(&buffer, &mut LineIndex::new()).unwrap();
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![Range::new(Position{ line: 0, offset: 1 }, Position{ line: 0, offset: 4 })]
+        );
+    }
 
     #[test]
     fn search_populates_results_with_correct_ranges() {
@@ -61,7 +315,7 @@ mod tests {
         buffer.insert("test\ntest");
 
         let mut mode = SearchMode::new(Some(String::from("test")));
-        mode.search(&buffer).unwrap();
+        mode.search(&buffer, &mut LineIndex::new()).unwrap();
 
         assert_eq!(
             *mode.results.unwrap(),
@@ -1,19 +1,92 @@
+use crate::view::Colors;
 use std::fmt::Display;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::slice::Iter;
 
+// Previews only need as much of a file as fits on the screen; reading
+// any more would hold up moving through results for large files.
+const PREVIEW_BYTE_LIMIT: u64 = 64 * 1024;
+const PREVIEW_LINE_LIMIT: usize = 200;
+
 #[derive(Clone)]
 pub struct SearchSelectConfig {
     pub max_results: usize,
+    /// Whether the selected result is previewed alongside the results.
+    pub preview: bool,
 }
 
 impl Default for SearchSelectConfig {
     fn default() -> SearchSelectConfig {
         SearchSelectConfig {
             max_results: 5,
+            preview: true,
         }
     }
 }
 
+/// Read-only content describing the selected result.
+#[derive(Debug, PartialEq)]
+pub struct Preview {
+    pub content: String,
+    /// The file the content was read from, used to highlight it.
+    pub path: Option<PathBuf>,
+}
+
+impl Preview {
+    /// Previews the beginning of a file, or nothing, if it can't be read.
+    pub fn file(path: &Path) -> Option<Preview> {
+        let mut data = Vec::new();
+        File::open(path)
+            .and_then(|file| file.take(PREVIEW_BYTE_LIMIT).read_to_end(&mut data))
+            .ok()?;
+
+        let content = if data.contains(&0) {
+            String::from("(binary file)")
+        } else {
+            String::from_utf8_lossy(&data)
+                .lines()
+                .take(PREVIEW_LINE_LIMIT)
+                .collect::<Vec<&str>>()
+                .join("\n")
+        };
+
+        Some(Preview{ content, path: Some(path.to_path_buf()) })
+    }
+}
+
+/// A preview, highlighted for display.
+pub struct RenderedPreview {
+    pub title: String,
+    pub lines: Vec<Vec<(Colors, String)>>,
+}
+
+/// Holds the selected result's rendered preview, so that it's only rebuilt
+/// (re-reading and re-highlighting its content) when the selection changes,
+/// rather than every time the mode is drawn.
+#[derive(Default)]
+pub struct PreviewCache {
+    key: Option<String>,
+    preview: Option<RenderedPreview>,
+}
+
+impl PreviewCache {
+    /// Whether the cached preview was rendered for the key.
+    pub fn is_for(&self, key: &str) -> bool {
+        self.key.as_ref().map(|cached_key| cached_key == key).unwrap_or(false)
+    }
+
+    pub fn store(&mut self, key: String, preview: Option<RenderedPreview>) {
+        self.key = Some(key);
+        self.preview = preview;
+    }
+
+    pub fn preview(&self) -> Option<&RenderedPreview> {
+        self.preview.as_ref()
+    }
+}
+
 /// This trait will become vastly simpler if/when fields are added to traits.
 /// See: https://github.com/rust-lang/rfcs/pull/1546
 pub trait SearchSelectMode<T: Display>: Display {
@@ -27,6 +100,20 @@ pub trait SearchSelectMode<T: Display>: Display {
     fn select_previous(&mut self);
     fn select_next(&mut self);
     fn config(&self) -> &SearchSelectConfig;
+
+    /// A description of the selected result, shown alongside the results.
+    /// This is only called when it's about to be shown, so it's fine to load
+    /// (a limited amount of) content here, rather than when searching.
+    fn preview(&self) -> Option<Preview> {
+        None
+    }
+
+    /// Where the rendered preview is kept; previews are only shown for modes
+    /// that provide one, since rebuilding them on every frame is slow.
+    fn preview_cache(&mut self) -> Option<&mut PreviewCache> {
+        None
+    }
+
     fn message(&mut self) -> Option<String> {
         if self.query().is_empty() {
             Some(String::from("Enter a search query to start."))
@@ -62,8 +149,9 @@ pub trait SearchSelectMode<T: Display>: Display {
 #[cfg(test)]
 mod tests {
     use std::fmt;
+    use std::path::Path;
     use std::slice::Iter;
-    use super::{SearchSelectMode, SearchSelectConfig};
+    use super::{Preview, PreviewCache, RenderedPreview, SearchSelectMode, SearchSelectConfig};
 
     #[derive(Default)]
     struct TestMode {
@@ -95,6 +183,26 @@ mod tests {
         fn config(&self) -> &SearchSelectConfig { &self.config }
     }
 
+    #[test]
+    fn preview_file_reads_the_beginning_of_readable_files() {
+        let preview = Preview::file(Path::new("Cargo.toml")).unwrap();
+
+        assert!(preview.content.starts_with("[package]"));
+        assert_eq!(preview.path, Some(Path::new("Cargo.toml").to_path_buf()));
+        assert!(Preview::file(Path::new("missing.rs")).is_none());
+    }
+
+    #[test]
+    fn preview_cache_holds_the_preview_rendered_for_a_key() {
+        let mut cache = PreviewCache::default();
+        assert!(!cache.is_for("amp.rs"));
+
+        cache.store(String::from("amp.rs"), Some(RenderedPreview{ title: String::from(" amp.rs"), lines: Vec::new() }));
+        assert!(cache.is_for("amp.rs"));
+        assert!(!cache.is_for("editor.rs"));
+        assert_eq!(cache.preview().map(|preview| preview.title.as_str()), Some(" amp.rs"));
+    }
+
     #[test]
     fn push_search_char_updates_query() {
         let mut mode = TestMode{ .. Default::default() };
@@ -0,0 +1,53 @@
+use scribe::buffer::{Position, Range};
+use std::cmp;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Selects a rectangle spanning the lines and columns between the anchor
+/// and the cursor (inclusive). Columns are grapheme offsets, so tabs count
+/// as a single column.
+pub struct SelectBlockMode {
+    pub anchor: Position,
+}
+
+impl SelectBlockMode {
+    pub fn new(anchor: Position) -> SelectBlockMode {
+        SelectBlockMode { anchor }
+    }
+
+    /// The selected part of each line, limited to the content it has;
+    /// lines that end before the selected columns have an empty range.
+    pub fn to_ranges(&self, cursor: &Position, data: &str) -> Vec<Range> {
+        let first_line = cmp::min(self.anchor.line, cursor.line);
+        let last_line = cmp::max(self.anchor.line, cursor.line);
+        let start_offset = cmp::min(self.anchor.offset, cursor.offset);
+        let end_offset = cmp::max(self.anchor.offset, cursor.offset) + 1;
+        let lines: Vec<&str> = data.lines().collect();
+
+        (first_line..last_line + 1).map(|line| {
+            let length = lines.get(line).map(|content| content.graphemes(true).count()).unwrap_or(0);
+
+            Range::new(
+                Position{ line, offset: cmp::min(start_offset, length) },
+                Position{ line, offset: cmp::min(end_offset, length) }
+            )
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::{Position, Range};
+    use super::SelectBlockMode;
+
+    #[test]
+    fn to_ranges_spans_the_columns_on_each_line_limited_to_their_content() {
+        let mode = SelectBlockMode::new(Position{ line: 2, offset: 3 });
+        let ranges = mode.to_ranges(&Position{ line: 0, offset: 1 }, "amp editor\nab\nbuffer");
+
+        assert_eq!(ranges, vec![
+            Range::new(Position{ line: 0, offset: 1 }, Position{ line: 0, offset: 4 }),
+            Range::new(Position{ line: 1, offset: 1 }, Position{ line: 1, offset: 2 }),
+            Range::new(Position{ line: 2, offset: 1 }, Position{ line: 2, offset: 4 }),
+        ]);
+    }
+}
@@ -0,0 +1,40 @@
+use crate::models::application::modes::{PickerMode, SearchSelectConfig};
+use scribe::buffer::Range;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Lists corrections for a misspelled word, closest first, narrowed down by
+/// a fuzzy query. The selected correction replaces the word in the buffer.
+pub struct SpellingMode {
+    picker: PickerMode<String>,
+    pub word: Range,
+}
+
+impl SpellingMode {
+    pub fn new(word: Range, suggestions: Vec<String>, config: SearchSelectConfig) -> SpellingMode {
+        SpellingMode {
+            picker: PickerMode::new("SPELLING", suggestions, config).list_without_query(),
+            word,
+        }
+    }
+}
+
+impl fmt::Display for SpellingMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.picker)
+    }
+}
+
+impl Deref for SpellingMode {
+    type Target = PickerMode<String>;
+
+    fn deref(&self) -> &PickerMode<String> {
+        &self.picker
+    }
+}
+
+impl DerefMut for SpellingMode {
+    fn deref_mut(&mut self) -> &mut PickerMode<String> {
+        &mut self.picker
+    }
+}
@@ -0,0 +1,38 @@
+use crate::models::application::modes::{PickerMode, SearchSelectConfig};
+use crate::util::tags::Tag;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Lists definitions from the workspace's tags file (either all of them, or
+/// those sharing a name), narrowed down by a fuzzy query.
+pub struct TagMode {
+    picker: PickerMode<Tag>,
+}
+
+impl TagMode {
+    pub fn new(tags: Vec<Tag>, config: SearchSelectConfig) -> TagMode {
+        TagMode {
+            picker: PickerMode::new("TAGS", tags, config).list_without_query(),
+        }
+    }
+}
+
+impl fmt::Display for TagMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.picker)
+    }
+}
+
+impl Deref for TagMode {
+    type Target = PickerMode<Tag>;
+
+    fn deref(&self) -> &PickerMode<Tag> {
+        &self.picker
+    }
+}
+
+impl DerefMut for TagMode {
+    fn deref_mut(&mut self) -> &mut PickerMode<Tag> {
+        &mut self.picker
+    }
+}
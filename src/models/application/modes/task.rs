@@ -0,0 +1,41 @@
+use crate::models::application::modes::{PickerMode, SearchSelectConfig};
+use crate::util::task::Task;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Lists the TODO, FIXME, and HACK comments in the workspace, grouped by
+/// file, narrowed down by a fuzzy query. Tasks are found in the background;
+/// the list is empty until the scan is complete.
+pub struct TaskMode {
+    picker: PickerMode<Task>,
+}
+
+impl TaskMode {
+    pub fn new(config: SearchSelectConfig) -> TaskMode {
+        TaskMode {
+            picker: PickerMode::new("TASKS", Vec::new(), config)
+                .list_without_query()
+                .with_empty_message("Scanning the workspace for tasks..."),
+        }
+    }
+}
+
+impl fmt::Display for TaskMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.picker)
+    }
+}
+
+impl Deref for TaskMode {
+    type Target = PickerMode<Task>;
+
+    fn deref(&self) -> &PickerMode<Task> {
+        &self.picker
+    }
+}
+
+impl DerefMut for TaskMode {
+    fn deref_mut(&mut self) -> &mut PickerMode<Task> {
+        &mut self.picker
+    }
+}
@@ -1,83 +1,59 @@
-use fragment;
-use crate::util::SelectableVec;
+use crate::models::application::modes::{PickerMode, SearchSelectConfig};
 use std::fmt;
-use std::slice::Iter;
-use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use std::ops::{Deref, DerefMut};
 
+/// Lists the available themes, narrowed down by a fuzzy query. The selected
+/// theme is applied as the selection changes, so that it can be previewed;
+/// the theme that was in use beforehand is kept around to restore on cancel.
 pub struct ThemeMode {
-    insert: bool,
-    input: String,
-    themes: Vec<String>,
-    results: SelectableVec<String>,
-    config: SearchSelectConfig,
+    picker: PickerMode<String>,
+    pub original_theme: String,
 }
 
 impl ThemeMode {
-    pub fn new(themes: Vec<String>, config: SearchSelectConfig) -> ThemeMode {
+    pub fn new(mut themes: Vec<String>, original_theme: String, config: SearchSelectConfig) -> ThemeMode {
+        // List the current theme first, so that nothing changes until the selection does.
+        themes.sort_by_key(|theme| *theme != original_theme);
+
         ThemeMode {
-            insert: true,
-            input: String::new(),
-            themes,
-            results: SelectableVec::new(Vec::new()),
-            config,
+            picker: PickerMode::new("THEME", themes, config).list_without_query(),
+            original_theme,
         }
     }
 }
 
 impl fmt::Display for ThemeMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "THEME")
+        write!(f, "{}", self.picker)
     }
 }
 
-impl SearchSelectMode<String> for ThemeMode {
-    fn search(&mut self) {
-        // Find the themes we're looking for using the query.
-        let results = fragment::matching::find(&self.input, &self.themes, self.config.max_results);
-
-        // We don't care about the result objects; we just want
-        // the underlying symbols. Map the collection to get these.
-        self.results = SelectableVec::new(
-            results
-            .into_iter()
-            .map(|r| r.clone())
-            .collect()
-        );
-    }
-
-    fn query(&mut self) -> &mut String {
-        &mut self.input
-    }
-
-    fn insert_mode(&self) -> bool {
-        self.insert
-    }
+impl Deref for ThemeMode {
+    type Target = PickerMode<String>;
 
-    fn set_insert_mode(&mut self, insert_mode: bool) {
-        self.insert = insert_mode;
-    }
-
-    fn results(&self) -> Iter<String> {
-        self.results.iter()
-    }
-
-    fn selection(&self) -> Option<&String> {
-        self.results.selection()
+    fn deref(&self) -> &PickerMode<String> {
+        &self.picker
     }
+}
 
-    fn selected_index(&self) -> usize {
-        self.results.selected_index()
+impl DerefMut for ThemeMode {
+    fn deref_mut(&mut self) -> &mut PickerMode<String> {
+        &mut self.picker
     }
+}
 
-    fn select_previous(&mut self) {
-        self.results.select_previous();
-    }
+#[cfg(test)]
+mod tests {
+    use super::ThemeMode;
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
 
-    fn select_next(&mut self) {
-        self.results.select_next();
-    }
+    #[test]
+    fn search_lists_the_original_theme_first_without_a_query() {
+        let themes = vec![String::from("dark"), String::from("light")];
+        let mut mode = ThemeMode::new(themes, String::from("light"), SearchSelectConfig::default());
+        mode.search();
 
-    fn config(&self) -> &SearchSelectConfig {
-        &self.config
+        assert_eq!(mode.selection(), Some(&String::from("light")));
+        assert_eq!(mode.results().count(), 2);
     }
 }
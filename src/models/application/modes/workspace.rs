@@ -0,0 +1,27 @@
+use crate::models::application::modes::{PickerMode, SearchSelectConfig};
+
+pub type WorkspaceMode = PickerMode<String>;
+
+/// Lists the directories of the other open workspaces, most recently used
+/// first, until a query is entered, narrowing them down using fuzzy matching.
+pub fn new(paths: Vec<String>, config: SearchSelectConfig) -> WorkspaceMode {
+    PickerMode::new("WORKSPACE", paths, config)
+        .list_without_query()
+        .with_empty_message("No other workspaces are open.")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+    #[test]
+    fn search_narrows_down_workspaces_by_path() {
+        let paths = vec![String::from("~/code/amp"), String::from("~/code/scribe")];
+        let mut mode = super::new(paths, SearchSelectConfig::default());
+        mode.push_search_char('s');
+        mode.push_search_char('c');
+        mode.search();
+
+        assert_eq!(mode.selection(), Some(&String::from("~/code/scribe")));
+    }
+}
@@ -2,41 +2,192 @@ use app_dirs::{app_dir, app_root, get_app_root, AppDataType, AppInfo};
 use bloodhound::ExclusionPattern;
 use crate::errors::*;
 use crate::input::KeyMap;
+use crate::view::CursorShape;
 use crate::models::application::modes::open;
 use scribe::Buffer;
-use std::fs::OpenOptions;
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, OpenOptions};
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use crate::yaml::yaml::{Hash, Yaml, YamlLoader};
 use crate::models::application::modes::SearchSelectConfig;
+use crate::util::{commit_message, completion};
+use crate::util::indentation::Indentation;
+use crate::util::lint::{self, Linter};
+use regex::Regex;
 
 const APP_INFO: AppInfo = AppInfo {
     name: "amp",
     author: "Jordan MacDonald",
 };
 const FILE_NAME: &str = "config.yml";
+const ABBREVIATIONS_KEY: &str = "abbreviations";
+const AGE_IDENTITY_KEY: &str = "age_identity";
+const BIDI_MARKERS_DEFAULT: bool = true;
+const BIDI_MARKERS_KEY: &str = "bidi_markers";
+const COLOR_SWATCHES_KEY: &str = "color_swatches";
+const CURSOR_SHAPES_KEY: &str = "cursor_shapes";
+const DEFINITIONS_DEFAULT: &str = "entity.name.function, markup.heading";
+const DEFINITIONS_KEY: &str = "definitions";
+const DICTIONARIES_DEFAULT: [&str; 1] = ["/usr/share/dict/words"];
+const DICTIONARIES_KEY: &str = "dictionaries";
+const DICTIONARY_FILE_NAME: &str = "dictionary.txt";
+const DETECT_INDENTATION_DEFAULT: bool = true;
+const DETECT_INDENTATION_KEY: &str = "detect_indentation";
+const DIGRAPHS_KEY: &str = "digraphs";
+const GIT_HOSTS_KEY: &str = "git_hosts";
+const HOOKS_KEY: &str = "hooks";
+const INDENT_GUIDES_DEFAULT: bool = false;
+const INDENT_GUIDES_KEY: &str = "indent_guides";
+const INLINE_DIAGNOSTICS_DEFAULT: bool = true;
+const INLINE_DIAGNOSTICS_KEY: &str = "inline_diagnostics";
+const INPUT_TIMEOUT_DEFAULT: u64 = 50;
+const INPUT_TIMEOUT_KEY: &str = "input_timeout";
+const KEYMAP_KEY: &str = "keymap";
+const KEYMAP_PRESET_DEFAULT: &str = "amp";
+const KEYMAP_PRESET_KEY: &str = "keymap_preset";
+const LARGE_FILE_THRESHOLD_DEFAULT: usize = 5_000_000;
+const LARGE_FILE_THRESHOLD_KEY: &str = "large_file_threshold";
 const LINE_LENGTH_GUIDE_DEFAULT: usize = 80;
 const LINE_LENGTH_GUIDE_KEY: &str = "line_length_guide";
 const LINE_WRAPPING_DEFAULT: bool = true;
 const LINE_WRAPPING_KEY: &str = "line_wrapping";
+const LINT_COMMAND_KEY: &str = "command";
+const LINT_FORMAT_KEY: &str = "format";
+const LINT_KEY: &str = "lint";
+const LOCAL_HISTORY_DEFAULT: bool = true;
+const LOCAL_HISTORY_KEY: &str = "local_history";
+const OPEN_MODE_IGNORE_FILES_DEFAULT: bool = true;
+const OPEN_MODE_KEY: &str = "open_mode";
+const PROJECT_FILE_NAME: &str = ".amp.yml";
+const SCROLL_AMOUNT_DEFAULT: usize = 10;
+const SCROLL_AMOUNT_KEY: &str = "scroll_amount";
 const SEARCH_SELECT_KEY: &str = "search_select";
 const SOFT_TABS_DEFAULT: bool = true;
 const SOFT_TABS_KEY: &str = "soft_tabs";
+const SPELL_CHECK_DEFAULT: bool = false;
+const SPELL_CHECK_KEY: &str = "spell_check";
+const SYNTAX_KEY: &str = "syntax";
 const SYNTAX_PATH: &str = "syntaxes";
+const TAGS_COMMAND_DEFAULT: &str = "ctags -R";
+const TAGS_COMMAND_KEY: &str = "tags_command";
 const TAB_WIDTH_DEFAULT: usize = 2;
 const TAB_WIDTH_KEY: &str = "tab_width";
 const THEME_DEFAULT: &str = "solarized_dark";
 const THEME_KEY: &str = "theme";
 const THEME_PATH: &str = "themes";
 const TYPES_KEY: &str = "types";
+const WRAP_ALIAS: &str = "wrap";
+const WRAP_AT_WORDS_DEFAULT: bool = false;
+const WRAP_AT_WORDS_KEY: &str = "wrap_at_words";
+const WRAP_HANGING_INDENT_DEFAULT: bool = false;
+const WRAP_HANGING_INDENT_KEY: &str = "wrap_hanging_indent";
+const WRAP_INDICATOR_KEY: &str = "wrap_indicator";
+
+/// Preferences recognized in config files, at the top level.
+const KNOWN_KEYS: [&str; 32] = [
+    ABBREVIATIONS_KEY,
+    AGE_IDENTITY_KEY,
+    BIDI_MARKERS_KEY,
+    COLOR_SWATCHES_KEY,
+    CURSOR_SHAPES_KEY,
+    DEFINITIONS_KEY,
+    DETECT_INDENTATION_KEY,
+    DICTIONARIES_KEY,
+    DIGRAPHS_KEY,
+    GIT_HOSTS_KEY,
+    HOOKS_KEY,
+    INDENT_GUIDES_KEY,
+    INLINE_DIAGNOSTICS_KEY,
+    INPUT_TIMEOUT_KEY,
+    KEYMAP_KEY,
+    KEYMAP_PRESET_KEY,
+    LARGE_FILE_THRESHOLD_KEY,
+    LINE_LENGTH_GUIDE_KEY,
+    LINE_WRAPPING_KEY,
+    LOCAL_HISTORY_KEY,
+    OPEN_MODE_KEY,
+    SCROLL_AMOUNT_KEY,
+    SEARCH_SELECT_KEY,
+    SOFT_TABS_KEY,
+    SPELL_CHECK_KEY,
+    TAB_WIDTH_KEY,
+    TAGS_COMMAND_KEY,
+    THEME_KEY,
+    TYPES_KEY,
+    WRAP_AT_WORDS_KEY,
+    WRAP_HANGING_INDENT_KEY,
+    WRAP_INDICATOR_KEY,
+];
+
+/// Modes in which text is typed, which use a bar cursor by default.
+const BAR_CURSOR_MODES: [&str; 7] = [
+    "character_input", "command_line", "insert", "masked_input", "path", "search_insert",
+    "search_select_insert"
+];
+
+/// Preferences recognized within type-specific sections.
+const KNOWN_TYPE_KEYS: [&str; 9] = [
+    ABBREVIATIONS_KEY, DEFINITIONS_KEY, INDENT_GUIDES_KEY, LINE_LENGTH_GUIDE_KEY, LINT_KEY,
+    SOFT_TABS_KEY, SPELL_CHECK_KEY, SYNTAX_KEY, TAB_WIDTH_KEY
+];
+
+/// Digraphs available without any configuration, mostly following RFC 1345.
+const DEFAULT_DIGRAPHS: [(&str, char); 68] = [
+    ("a!", 'à'), ("a'", 'á'), ("a>", 'â'), ("a?", 'ã'), ("a:", 'ä'), ("aa", 'å'),
+    ("A!", 'À'), ("A'", 'Á'), ("A>", 'Â'), ("A?", 'Ã'), ("A:", 'Ä'), ("AA", 'Å'),
+    ("e!", 'è'), ("e'", 'é'), ("e>", 'ê'), ("e:", 'ë'),
+    ("E!", 'È'), ("E'", 'É'), ("E>", 'Ê'), ("E:", 'Ë'),
+    ("i!", 'ì'), ("i'", 'í'), ("i>", 'î'), ("i:", 'ï'),
+    ("I!", 'Ì'), ("I'", 'Í'), ("I>", 'Î'), ("I:", 'Ï'),
+    ("o!", 'ò'), ("o'", 'ó'), ("o>", 'ô'), ("o?", 'õ'), ("o:", 'ö'), ("o/", 'ø'),
+    ("O!", 'Ò'), ("O'", 'Ó'), ("O>", 'Ô'), ("O?", 'Õ'), ("O:", 'Ö'), ("O/", 'Ø'),
+    ("u!", 'ù'), ("u'", 'ú'), ("u>", 'û'), ("u:", 'ü'),
+    ("U!", 'Ù'), ("U'", 'Ú'), ("U>", 'Û'), ("U:", 'Ü'),
+    ("c,", 'ç'), ("C,", 'Ç'), ("n?", 'ñ'), ("N?", 'Ñ'),
+    ("ae", 'æ'), ("AE", 'Æ'), ("ss", 'ß'),
+    ("Eu", '€'), ("Pd", '£'), ("Ye", '¥'), ("Co", '©'), ("Rg", '®'),
+    ("DG", '°'), ("+-", '±'), ("*X", '×'), ("-:", '÷'),
+    ("->", '→'), ("<-", '←'), ("!=", '≠'), ("=<", '≤'),
+];
+
+/// Preferences that can be overridden for the current session.
+const SETTABLE_KEYS: [&str; 16] = [
+    BIDI_MARKERS_KEY,
+    COLOR_SWATCHES_KEY,
+    INDENT_GUIDES_KEY,
+    INLINE_DIAGNOSTICS_KEY,
+    INPUT_TIMEOUT_KEY,
+    LARGE_FILE_THRESHOLD_KEY,
+    LINE_LENGTH_GUIDE_KEY,
+    LINE_WRAPPING_KEY,
+    SCROLL_AMOUNT_KEY,
+    SOFT_TABS_KEY,
+    SPELL_CHECK_KEY,
+    TAB_WIDTH_KEY,
+    THEME_KEY,
+    WRAP_ALIAS,
+    WRAP_AT_WORDS_KEY,
+    WRAP_HANGING_INDENT_KEY,
+];
 
 /// Loads, creates, and provides default values for application preferences.
 /// Values are immutable once loaded, with the exception of those that provide
 /// expicit setter methods (e.g. `theme`).
+///
+/// Preferences are resolved in the following order, with earlier sources
+/// taking precedence: values set for the current session, the project's
+/// `.amp.yml` file, the user's config file, and finally built-in defaults.
 pub struct Preferences {
     data: Option<Yaml>,
     keymap: KeyMap,
     theme: Option<String>,
+    project_path: Option<PathBuf>,
+    /// Indentation detected in (or converted to for) open files,
+    /// which takes precedence over the configured soft_tabs and tab_width.
+    indentation: HashMap<PathBuf, Indentation>,
 }
 
 impl Preferences {
@@ -45,31 +196,71 @@ impl Preferences {
         Preferences {
             data,
             keymap: KeyMap::default().expect("Failed to load default keymap!"),
-            theme: None
+            theme: None,
+            project_path: None,
+            indentation: HashMap::new(),
         }
     }
 
     /// Loads preferences from disk, returning any filesystem or parse errors.
     pub fn load() -> Result<Preferences> {
         let data = load_document()?;
-        let keymap = load_keymap(
-            data.as_ref().and_then(|data| data["keymap"].as_hash())
-        )?;
+        let keymap = load_keymap(data.as_ref())?;
 
-        Ok(Preferences { data, keymap, theme: None })
+        Ok(Preferences { data, keymap, theme: None, project_path: None, indentation: HashMap::new() })
+    }
+
+    /// Layers the specified workspace's project preferences (if it has any)
+    /// over the current set. The project file is re-read on reload.
+    pub fn load_project(&mut self, workspace_path: &Path) -> Result<()> {
+        self.project_path = Some(workspace_path.join(PROJECT_FILE_NAME));
+        self.merge_project_document()
+    }
+
+    /// Replaces the current project's preferences with those of
+    /// another workspace (e.g. when switching between them).
+    pub fn switch_project(&mut self, workspace_path: &Path) -> Result<()> {
+        self.project_path = Some(workspace_path.join(PROJECT_FILE_NAME));
+        self.reload()
     }
 
     /// Reloads all user preferences from disk and merges them with defaults.
     pub fn reload(&mut self) -> Result<()> {
         let data = load_document()?;
-        let keymap = load_keymap(
-            data.as_ref().and_then(|data| data["keymap"].as_hash())
-        )?;
+        let keymap = load_keymap(data.as_ref())?;
 
         self.data = data;
         self.keymap = keymap;
         self.theme = None;
 
+        self.merge_project_document()
+    }
+
+    /// Whether the path refers to the user's config file or the project's,
+    /// changes to which should be picked up by reloading preferences.
+    pub fn is_preferences_file(&self, path: &Path) -> bool {
+        let user_path = get_app_root(AppDataType::UserConfig, &APP_INFO)
+            .ok()
+            .map(|directory| directory.join(FILE_NAME));
+
+        [user_path.as_ref(), self.project_path.as_ref()]
+            .iter()
+            .filter_map(|candidate| *candidate)
+            .any(|candidate| same_file(candidate, path))
+    }
+
+    fn merge_project_document(&mut self) -> Result<()> {
+        let project_data = match self.project_path {
+            Some(ref path) => load_project_document(path)?,
+            None => return Ok(()),
+        };
+
+        if let Some(project_data) = project_data {
+            let data = self.data.take().unwrap_or_else(|| Yaml::Hash(Hash::new()));
+            self.data = Some(merge(data, project_data));
+            self.keymap = load_keymap(self.data.as_ref())?;
+        }
+
         Ok(())
     }
 
@@ -90,6 +281,19 @@ impl Preferences {
             .chain_err(|| "Couldn't create syntax directory or build a path to it.")
     }
 
+    /// A path pointing to the application state directory, used to persist
+    /// editor state (e.g. cursor positions) between sessions.
+    pub fn state_path() -> Result<PathBuf> {
+        app_root(AppDataType::UserData, &APP_INFO)
+            .chain_err(|| "Couldn't create state directory or build a path to it.")
+    }
+
+    /// A path pointing to the personal spelling dictionary,
+    /// to which words are added as they're accepted.
+    pub fn dictionary_path() -> Result<PathBuf> {
+        Ok(Preferences::directory()?.join(DICTIONARY_FILE_NAME))
+    }
+
     /// Returns the preference file loaded into a buffer for editing.
     /// If the file doesn't already exist, it will return a new in-memory buffer
     /// with a pre-populated path, creating the parent config directories
@@ -137,6 +341,157 @@ impl Preferences {
         self.theme = Some(theme.into());
     }
 
+    /// Preferences in the loaded config files that aren't recognized (most
+    /// likely typos), which would otherwise be ignored without any indication.
+    /// Type-specific preferences are qualified with their type (e.g. "types.rs.tab_widht").
+    pub fn unknown_keys(&self) -> Vec<String> {
+        let data = match self.data {
+            Some(Yaml::Hash(ref data)) => data,
+            _ => return Vec::new(),
+        };
+        let mut unknown_keys = Vec::new();
+
+        for (key, value) in data {
+            match key.as_str() {
+                Some(TYPES_KEY) => {
+                    for (file_type, type_data) in value.as_hash().into_iter().flat_map(|types| types.iter()) {
+                        for type_key in type_data.as_hash().into_iter().flat_map(|keys| keys.keys()) {
+                            match type_key.as_str() {
+                                Some(type_key) if KNOWN_TYPE_KEYS.contains(&type_key) => (),
+                                _ => unknown_keys.push(format!(
+                                    "{}.{}.{}",
+                                    TYPES_KEY,
+                                    yaml_key_name(file_type),
+                                    yaml_key_name(type_key)
+                                )),
+                            }
+                        }
+                    }
+                },
+                Some(key) if KNOWN_KEYS.contains(&key) => (),
+                _ => unknown_keys.push(yaml_key_name(key)),
+            }
+        }
+
+        unknown_keys
+    }
+
+    /// The names of the commands to run when the named hook is triggered,
+    /// configured as either a single command or a list of them.
+    pub fn hook_commands(&self, hook: &str) -> Vec<String> {
+        self.data
+            .as_ref()
+            .map(|data| match data[HOOKS_KEY][hook] {
+                Yaml::String(ref command) => vec![command.clone()],
+                Yaml::Array(ref commands) => commands
+                    .iter()
+                    .filter_map(|command| command.as_str())
+                    .map(|command| command.to_string())
+                    .collect(),
+                _ => Vec::new(),
+            })
+            .unwrap_or_default()
+    }
+
+    /// Two-character sequences and the characters they stand for, used to
+    /// insert characters that aren't on the keyboard. The built-in digraphs
+    /// can be overridden (or added to) using the `digraphs` preference.
+    pub fn digraphs(&self) -> HashMap<String, char> {
+        let mut digraphs: HashMap<String, char> = DEFAULT_DIGRAPHS
+            .iter()
+            .map(|&(digraph, character)| (digraph.to_string(), character))
+            .collect();
+
+        let user_digraphs = self.data
+            .as_ref()
+            .and_then(|data| data[DIGRAPHS_KEY].as_hash());
+        for (digraph, character) in user_digraphs.into_iter().flat_map(|digraphs| digraphs.iter()) {
+            let character = character.as_str().and_then(|character| character.chars().next());
+
+            if let (Some(digraph), Some(character)) = (digraph.as_str(), character) {
+                digraphs.insert(digraph.to_string(), character);
+            }
+        }
+
+        digraphs
+    }
+
+    /// Git hosts (e.g. "git.example.com") and the kind of service they run
+    /// ("github" or "gitlab"), for remotes whose kind isn't apparent from the host.
+    pub fn git_hosts(&self) -> HashMap<String, String> {
+        let hosts = self.data
+            .as_ref()
+            .and_then(|data| data[GIT_HOSTS_KEY].as_hash());
+
+        hosts
+            .into_iter()
+            .flat_map(|hosts| hosts.iter())
+            .filter_map(|(host, kind)| Some((host.as_str()?.to_string(), kind.as_str()?.to_string())))
+            .collect()
+    }
+
+    /// Abbreviations expanded as they're typed, and their expansions. Those
+    /// configured for the path's file type take precedence over the rest.
+    pub fn abbreviations(&self, path: Option<&PathBuf>) -> HashMap<String, String> {
+        let mut abbreviations = HashMap::new();
+        let data = match self.data {
+            Some(ref data) => data,
+            None => return abbreviations,
+        };
+        let type_abbreviations = path_extension(path)
+            .and_then(|extension| data[TYPES_KEY][extension][ABBREVIATIONS_KEY].as_hash());
+        let sources = data[ABBREVIATIONS_KEY].as_hash().into_iter().chain(type_abbreviations);
+
+        for (abbreviation, expansion) in sources.flat_map(|source| source.iter()) {
+            if let (Some(abbreviation), Some(expansion)) = (abbreviation.as_str(), expansion.as_str()) {
+                abbreviations.insert(abbreviation.to_string(), expansion.to_string());
+            }
+        }
+
+        abbreviations
+    }
+
+    /// The names of preferences that can be overridden using `set`.
+    pub fn settable_keys() -> &'static [&'static str] {
+        &SETTABLE_KEYS
+    }
+
+    /// Overrides a top-level preference in memory, until preferences are
+    /// reloaded. Values are interpreted as they would be in the config file,
+    /// with "on" and "off" also accepted for boolean preferences.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let key = match key {
+            WRAP_ALIAS => LINE_WRAPPING_KEY,
+            THEME_KEY => {
+                self.set_theme(value);
+                return Ok(());
+            },
+            _ if SETTABLE_KEYS.contains(&key) => key,
+            _ => bail!("Unknown preference: {}", key),
+        };
+
+        let value = match value {
+            "on" => Yaml::Boolean(true),
+            "off" => Yaml::Boolean(false),
+            _ => YamlLoader::load_from_str(value)
+                .chain_err(|| "Couldn't parse the preference value")?
+                .into_iter()
+                .nth(0)
+                .ok_or("Please provide a preference value")?,
+        };
+
+        // Start from scratch if there's no (usable) config file data.
+        match self.data {
+            Some(Yaml::Hash(_)) => (),
+            _ => self.data = Some(Yaml::Hash(Hash::new())),
+        }
+        if let Some(Yaml::Hash(ref mut data)) = self.data {
+            data.insert(Yaml::String(key.to_string()), value);
+        }
+
+        Ok(())
+    }
+
     pub fn tab_width(&self, path: Option<&PathBuf>) -> usize {
         self.data
             .as_ref()
@@ -162,11 +517,18 @@ impl Preferences {
             if let Yaml::Integer(max_results) = data[SEARCH_SELECT_KEY]["max_results"] {
                 result.max_results = max_results as usize;
             }
+            if let Yaml::Boolean(preview) = data[SEARCH_SELECT_KEY]["preview"] {
+                result.preview = preview;
+            }
         }
         result
     }
 
     pub fn soft_tabs(&self, path: Option<&PathBuf>) -> bool {
+        if let Some(&indentation) = path.and_then(|path| self.indentation.get(path)) {
+            return indentation != Indentation::Tabs;
+        }
+
         self.data
             .as_ref()
             .and_then(|data| {
@@ -185,20 +547,133 @@ impl Preferences {
             .unwrap_or(SOFT_TABS_DEFAULT)
     }
 
-    pub fn line_length_guide(&self) -> Option<usize> {
+    /// Whether misspelled words are underlined in the path's file type.
+    pub fn spell_check(&self, path: Option<&PathBuf>) -> bool {
         self.data
             .as_ref()
-            .and_then(|data| match data[LINE_LENGTH_GUIDE_KEY] {
-                          Yaml::Integer(line_length) => Some(line_length as usize),
-                          Yaml::Boolean(line_length_guide) => {
-                              if line_length_guide {
-                                  Some(LINE_LENGTH_GUIDE_DEFAULT)
-                              } else {
-                                  None
-                              }
-                          }
-                          _ => None,
-                      })
+            .and_then(|data| {
+                path_extension(path)
+                    .and_then(|extension| data[TYPES_KEY][extension][SPELL_CHECK_KEY].as_bool())
+                    .or_else(|| data[SPELL_CHECK_KEY].as_bool())
+            })
+            .unwrap_or(SPELL_CHECK_DEFAULT)
+    }
+
+    /// Whether guides are drawn in the leading whitespace
+    /// of lines, at each indentation level, in the path's file type.
+    pub fn indent_guides(&self, path: Option<&PathBuf>) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| {
+                path_extension(path)
+                    .and_then(|extension| data[TYPES_KEY][extension][INDENT_GUIDES_KEY].as_bool())
+                    .or_else(|| data[INDENT_GUIDES_KEY].as_bool())
+            })
+            .unwrap_or(INDENT_GUIDES_DEFAULT)
+    }
+
+    /// The word lists that spelling is checked against.
+    pub fn dictionaries(&self) -> Vec<PathBuf> {
+        self.data
+            .as_ref()
+            .and_then(|data| data[DICTIONARIES_KEY].as_vec())
+            .map(|paths| {
+                paths.iter().filter_map(|path| path.as_str()).map(PathBuf::from).collect()
+            })
+            .unwrap_or_else(|| DICTIONARIES_DEFAULT.iter().map(|path| PathBuf::from(*path)).collect())
+    }
+
+    /// The identity file used to decrypt and re-encrypt age files, if any.
+    pub fn age_identity(&self) -> Option<PathBuf> {
+        self.data
+            .as_ref()
+            .and_then(|data| data[AGE_IDENTITY_KEY].as_str())
+            .map(completion::expand_home)
+    }
+
+    /// The scope selector matching definitions (e.g. functions or headings)
+    /// in the path's file type, which structural navigation moves between.
+    pub fn definitions(&self, path: Option<&PathBuf>) -> String {
+        self.data
+            .as_ref()
+            .and_then(|data| {
+                path_extension(path)
+                    .and_then(|extension| data[TYPES_KEY][extension][DEFINITIONS_KEY].as_str())
+                    .or_else(|| data[DEFINITIONS_KEY].as_str())
+            })
+            .unwrap_or(DEFINITIONS_DEFAULT)
+            .to_string()
+    }
+
+    /// The name of the syntax definition configured for the path's file
+    /// type, used in place of the one detected from its extension.
+    pub fn syntax(&self, path: Option<&PathBuf>) -> Option<String> {
+        let extension = path_extension(path)?;
+
+        self.data
+            .as_ref()
+            .and_then(|data| data[TYPES_KEY][extension][SYNTAX_KEY].as_str())
+            .map(|name| name.to_string())
+    }
+
+    /// The linter configured for the path's file type, if any.
+    pub fn linter(&self, path: Option<&PathBuf>) -> Result<Option<Linter>> {
+        let data = match (self.data.as_ref(), path_extension(path)) {
+            (Some(data), Some(extension)) => &data[TYPES_KEY][extension][LINT_KEY],
+            _ => return Ok(None),
+        };
+        let command = match data[LINT_COMMAND_KEY].as_str() {
+            Some(command) => command.split_whitespace().map(|part| part.to_string()).collect(),
+            None => return Ok(None),
+        };
+        let format = Regex::new(data[LINT_FORMAT_KEY].as_str().unwrap_or(lint::DEFAULT_FORMAT))
+            .chain_err(|| "Couldn't parse the lint output format")?;
+
+        Ok(Some(Linter{ command, format }))
+    }
+
+    /// The command that generates the workspace's tags file, split into
+    /// its program and arguments. It's run from the workspace directory.
+    pub fn tags_command(&self) -> Vec<String> {
+        self.data
+            .as_ref()
+            .and_then(|data| data[TAGS_COMMAND_KEY].as_str())
+            .unwrap_or(TAGS_COMMAND_DEFAULT)
+            .split_whitespace()
+            .map(|part| part.to_string())
+            .collect()
+    }
+
+    /// Whether a copy of each file is kept in the state directory
+    /// every time it's saved, to be compared with or restored later.
+    pub fn local_history(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[LOCAL_HISTORY_KEY].as_bool())
+            .unwrap_or(LOCAL_HISTORY_DEFAULT)
+    }
+
+    pub fn line_length_guide(&self) -> Option<usize> {
+        self.line_length_guides(None).into_iter().nth(0)
+    }
+
+    /// The columns at which line length guides are drawn, in ascending order.
+    /// Guides configured for the path's file type take precedence, and git
+    /// commit messages fall back to the conventional 50/72 column guides.
+    pub fn line_length_guides(&self, path: Option<&PathBuf>) -> Vec<usize> {
+        let type_guides = self.data.as_ref().and_then(|data| {
+            path_extension(path).and_then(|extension| {
+                parse_line_length_guides(&data[TYPES_KEY][extension][LINE_LENGTH_GUIDE_KEY])
+            })
+        });
+        let commit_message_guides = path
+            .filter(|path| commit_message::is_commit_message(path))
+            .map(|_| commit_message::LINE_LENGTH_GUIDES.to_vec());
+
+        type_guides
+            .or(commit_message_guides)
+            .or_else(|| self.data.as_ref().and_then(|data| parse_line_length_guides(&data[LINE_LENGTH_GUIDE_KEY])))
+            .unwrap_or_default()
     }
 
     pub fn line_wrapping(&self) -> bool {
@@ -212,16 +687,185 @@ impl Preferences {
             .unwrap_or(LINE_WRAPPING_DEFAULT)
     }
 
+    /// The shape of the cursor in the specified mode. Setting `cursor_shapes`
+    /// to false leaves the terminal's own cursor shape alone in every mode.
+    pub fn cursor_shape(&self, mode: &str) -> CursorShape {
+        let configured_shape = self.data.as_ref().and_then(|data| match data[CURSOR_SHAPES_KEY] {
+            Yaml::Boolean(false) => Some(CursorShape::Default),
+            ref shapes => shapes[mode].as_str().and_then(|shape| shape.parse().ok()),
+        });
+
+        configured_shape.unwrap_or_else(|| {
+            if BAR_CURSOR_MODES.contains(&mode) {
+                CursorShape::Bar
+            } else {
+                CursorShape::Block
+            }
+        })
+    }
+
+    /// Whether bidirectional formatting characters are drawn as visible
+    /// markers, and right-to-left text is distinguished from its surroundings.
+    pub fn bidi_markers(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[BIDI_MARKERS_KEY].as_bool())
+            .unwrap_or(BIDI_MARKERS_DEFAULT)
+    }
+
+    /// Whether linter messages are shown after the lines they refer to,
+    /// rather than only being flagged by highlighting their line numbers.
+    pub fn inline_diagnostics(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[INLINE_DIAGNOSTICS_KEY].as_bool())
+            .unwrap_or(INLINE_DIAGNOSTICS_DEFAULT)
+    }
+
+    /// Whether colors written in buffers (e.g. "#ff8800") are previewed
+    /// alongside them. By default, they are in terminals that advertise
+    /// truecolor support, where the previews match the written values.
+    pub fn color_swatches(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[COLOR_SWATCHES_KEY].as_bool())
+            .unwrap_or_else(|| {
+                env::var("COLORTERM")
+                    .map(|value| value == "truecolor" || value == "24bit")
+                    .unwrap_or(false)
+            })
+    }
+
+    /// Whether wrapped lines are broken between words,
+    /// rather than at the last column of the screen.
+    pub fn wrap_at_words(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(wrap_at_words) = data[WRAP_AT_WORDS_KEY] {
+                          Some(wrap_at_words)
+                      } else {
+                          None
+                      })
+            .unwrap_or(WRAP_AT_WORDS_DEFAULT)
+    }
+
+    /// Whether the continuation rows of wrapped lines
+    /// are indented to match the line's indentation.
+    pub fn wrap_hanging_indent(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(hanging_indent) = data[WRAP_HANGING_INDENT_KEY] {
+                          Some(hanging_indent)
+                      } else {
+                          None
+                      })
+            .unwrap_or(WRAP_HANGING_INDENT_DEFAULT)
+    }
+
+    /// Content shown in the gutter alongside the continuation rows of wrapped lines.
+    pub fn wrap_indicator(&self) -> Option<String> {
+        self.data
+            .as_ref()
+            .and_then(|data| data[WRAP_INDICATOR_KEY].as_str())
+            .map(String::from)
+    }
+
+    /// The size (in bytes) beyond which buffers are treated as large files,
+    /// skipping features that would otherwise make editing them sluggish.
+    /// How long to wait for the rest of an escape sequence (e.g. an arrow key
+    /// or alt-chord) after receiving an escape, before treating it as the
+    /// escape key. Slow connections may need longer than the default.
+    pub fn input_timeout(&self) -> Duration {
+        let milliseconds = self.data
+            .as_ref()
+            .and_then(|data| data[INPUT_TIMEOUT_KEY].as_i64())
+            .filter(|&timeout| timeout >= 0)
+            .map(|timeout| timeout as u64)
+            .unwrap_or(INPUT_TIMEOUT_DEFAULT);
+
+        Duration::from_millis(milliseconds)
+    }
+
+    pub fn large_file_threshold(&self) -> usize {
+        self.data
+            .as_ref()
+            .and_then(|data| data[LARGE_FILE_THRESHOLD_KEY].as_i64())
+            .map(|threshold| threshold as usize)
+            .unwrap_or(LARGE_FILE_THRESHOLD_DEFAULT)
+    }
+
+    pub fn scroll_amount(&self) -> usize {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Integer(amount) = data[SCROLL_AMOUNT_KEY] {
+                          Some(amount as usize)
+                      } else {
+                          None
+                      })
+            .unwrap_or(SCROLL_AMOUNT_DEFAULT)
+    }
+
+    /// How new indentation is added to the path's file.
+    pub fn indentation(&self, path: Option<&PathBuf>) -> Indentation {
+        if self.soft_tabs(path) {
+            Indentation::Spaces(self.indent_width(path))
+        } else {
+            Indentation::Tabs
+        }
+    }
+
+    /// The number of spaces in a soft tab. This is the configured tab_width,
+    /// unless the file's been found to use a different number; tabs are
+    /// still displayed using the configured width, regardless.
+    pub fn indent_width(&self, path: Option<&PathBuf>) -> usize {
+        match path.and_then(|path| self.indentation.get(path)) {
+            Some(&Indentation::Spaces(width)) => width,
+            _ => self.tab_width(path),
+        }
+    }
+
+    /// Uses the indentation (e.g. detected from the file's content)
+    /// for the file, in place of the configured soft_tabs and tab_width.
+    pub fn set_indentation(&mut self, path: &Path, indentation: Indentation) {
+        self.indentation.insert(path.to_path_buf(), indentation);
+    }
+
+    /// Goes back to using the configured soft_tabs and tab_width for the file.
+    pub fn clear_indentation(&mut self, path: &Path) {
+        self.indentation.remove(path);
+    }
+
+    /// Whether files' indentation is detected from their content when they're opened.
+    pub fn detect_indentation(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[DETECT_INDENTATION_KEY].as_bool())
+            .unwrap_or(DETECT_INDENTATION_DEFAULT)
+    }
+
     pub fn tab_content(&self, path: Option<&PathBuf>) -> String {
         if self.soft_tabs(path) {
-            format!("{:1$}", "", self.tab_width(path))
+            format!("{:1$}", "", self.indent_width(path))
         } else {
             String::from("\t")
         }
     }
 
+    /// Whether open mode should skip paths listed in the
+    /// workspace's .gitignore and .ignore files.
+    pub fn open_mode_ignore_files(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| if let Yaml::Boolean(ignore_files) = data[OPEN_MODE_KEY]["ignore_files"] {
+                          Some(ignore_files)
+                      } else {
+                          None
+                      })
+            .unwrap_or(OPEN_MODE_IGNORE_FILES_DEFAULT)
+    }
+
     pub fn open_mode_exclusions(&self) -> Result<Option<Vec<ExclusionPattern>>> {
-        if let Some(exclusion_data) = self.data.as_ref().map(|data| &data["open_mode"]["exclusions"]) {
+        if let Some(exclusion_data) = self.data.as_ref().map(|data| &data[OPEN_MODE_KEY]["exclusions"]) {
             match *exclusion_data {
                 Yaml::Array(ref exclusions) => {
                     open::exclusions::parse(exclusions)
@@ -246,6 +890,11 @@ fn load_document() -> Result<Option<Yaml>> {
             .chain_err(|| "Couldn't open application config directory")?;
     config_path.push(FILE_NAME);
 
+    // Users aren't required to have a config file.
+    if !config_path.exists() {
+        return Ok(None);
+    }
+
     // Open (or create) the config file.
     let mut config_file = OpenOptions::new()
         .read(true)
@@ -264,19 +913,105 @@ fn load_document() -> Result<Option<Yaml>> {
     Ok(parsed_data.into_iter().nth(0))
 }
 
-/// Loads default keymaps, merging in the provided overrides.
-fn load_keymap(keymap_overrides: Option<&Hash>) -> Result<KeyMap> {
-    let mut keymap = KeyMap::default()?;
+/// Loads the first YAML document in a project's config file, if it has one.
+fn load_project_document(path: &Path) -> Result<Option<Yaml>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let data = fs::read_to_string(path)
+        .chain_err(|| "Couldn't read project config file")?;
+    let document = YamlLoader::load_from_str(&data)
+        .chain_err(|| "Couldn't parse project config file")?
+        .into_iter()
+        .nth(0);
+
+    match document {
+        Some(Yaml::Hash(_)) | None => Ok(document),
+        _ => bail!("Project config file should contain a set of preferences"),
+    }
+}
 
-    // Merge user-defined keymaps into defaults.
+/// Recursively merges the override document into the base document. Nested
+/// hashes are combined; any other value replaces its counterpart outright.
+fn merge(base: Yaml, overrides: Yaml) -> Yaml {
+    match (base, overrides) {
+        (Yaml::Hash(mut base), Yaml::Hash(overrides)) => {
+            for (key, value) in overrides {
+                let merged_value = match base.remove(&key) {
+                    Some(base_value) => merge(base_value, value),
+                    None => value,
+                };
+                base.insert(key, merged_value);
+            }
+
+            Yaml::Hash(base)
+        },
+        (_, overrides) => overrides,
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Loads the configured keymap preset, merging in the user's own bindings.
+fn load_keymap(data: Option<&Yaml>) -> Result<KeyMap> {
+    let preset = data
+        .and_then(|data| data[KEYMAP_PRESET_KEY].as_str())
+        .unwrap_or(KEYMAP_PRESET_DEFAULT);
+    let keymap_overrides = data.and_then(|data| data[KEYMAP_KEY].as_hash());
+    let mut keymap = KeyMap::preset(preset)?;
+
+    // Merge user-defined keymaps into defaults. Bindings for modes that
+    // don't exist would otherwise be discarded without any indication why.
     if let Some(keymap_data) = keymap_overrides {
-        KeyMap::from(keymap_data).map(|data| keymap.merge(data))?;
+        let overrides = KeyMap::from(keymap_data)?;
+        if let Some(mode) = overrides.keys().find(|mode| !keymap.contains_key(*mode)) {
+            bail!("Keymap mode \"{}\" doesn't exist", mode);
+        }
+        keymap.merge(overrides);
     }
 
     Ok(keymap)
 }
 
+// Keys are usually strings, but YAML allows others (e.g. numbers).
+fn yaml_key_name(key: &Yaml) -> String {
+    match *key {
+        Yaml::String(ref key) => key.clone(),
+        Yaml::Integer(key) => key.to_string(),
+        Yaml::Real(ref key) => key.clone(),
+        Yaml::Boolean(key) => key.to_string(),
+        _ => format!("{:?}", key),
+    }
+}
+
 /// Maps a path to its file extension.
+// Guides can be configured as a single column, a list of
+// columns, or a boolean to toggle the default column.
+fn parse_line_length_guides(value: &Yaml) -> Option<Vec<usize>> {
+    let mut guides = match *value {
+        Yaml::Integer(column) => vec![column as usize],
+        Yaml::Boolean(true) => vec![LINE_LENGTH_GUIDE_DEFAULT],
+        Yaml::Boolean(false) => Vec::new(),
+        Yaml::Array(ref columns) => columns
+            .iter()
+            .filter_map(|column| column.as_i64())
+            .filter(|column| *column >= 0)
+            .map(|column| column as usize)
+            .collect(),
+        _ => return None,
+    };
+    guides.sort();
+    guides.dedup();
+
+    Some(guides)
+}
+
 fn path_extension(path: Option<&PathBuf>) -> Option<&str> {
     path
         .and_then(|p| p.extension().or_else(|| p.as_path().file_name()))
@@ -292,8 +1027,13 @@ fn default_open_mode_exclusions() -> Result<Option<Vec<ExclusionPattern>>> {
 #[cfg(test)]
 mod tests {
     use super::{ExclusionPattern, Preferences, YamlLoader};
+    use std::env;
+    use std::fs;
     use std::path::PathBuf;
+    use std::time::Duration;
     use crate::input::KeyMap;
+    use crate::util::indentation::Indentation;
+    use crate::view::CursorShape;
     use crate::yaml::yaml::Hash;
 
     #[test]
@@ -312,6 +1052,27 @@ mod tests {
         assert_eq!(preferences.theme(), "new_in_memory_theme");
     }
 
+    #[test]
+    fn file_indentation_takes_precedence_over_configured_indentation() {
+        let data = YamlLoader::load_from_str("tab_width: 4\nsoft_tabs: true").unwrap();
+        let mut preferences = Preferences::new(data.into_iter().nth(0));
+        let tabs_path = PathBuf::from("Makefile");
+        let spaces_path = PathBuf::from("main.rs");
+        preferences.set_indentation(&tabs_path, Indentation::Tabs);
+        preferences.set_indentation(&spaces_path, Indentation::Spaces(2));
+
+        assert_eq!(preferences.indentation(Some(&tabs_path)), Indentation::Tabs);
+        assert_eq!(preferences.indentation(Some(&spaces_path)), Indentation::Spaces(2));
+        assert_eq!(preferences.indentation(Some(&PathBuf::from("main.py"))), Indentation::Spaces(4));
+
+        // Tabs are displayed using the configured width, regardless.
+        assert_eq!(preferences.tab_width(Some(&spaces_path)), 4);
+
+        preferences.clear_indentation(&spaces_path);
+        assert_eq!(preferences.indentation(Some(&spaces_path)), Indentation::Spaces(4));
+        assert_eq!(preferences.indentation(Some(&tabs_path)), Indentation::Tabs);
+    }
+
     #[test]
     fn tab_width_returns_user_defined_data() {
         let data = YamlLoader::load_from_str("tab_width: 12").unwrap();
@@ -355,6 +1116,128 @@ mod tests {
         assert_eq!(preferences.soft_tabs(Some(PathBuf::from("preferences.rs")).as_ref()), false);
     }
 
+    #[test]
+    fn syntax_returns_user_defined_type_specific_data() {
+        let data = YamlLoader::load_from_str("types:\n  tpl:\n    syntax: HTML").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.syntax(Some(PathBuf::from("index.tpl")).as_ref()), Some(String::from("HTML")));
+        assert_eq!(preferences.syntax(Some(PathBuf::from("index.html")).as_ref()), None);
+        assert_eq!(preferences.syntax(None), None);
+    }
+
+    #[test]
+    fn abbreviations_prefers_type_specific_expansions() {
+        let data = YamlLoader::load_from_str(
+            "abbreviations:\n  teh: the\n  fn: function\ntypes:\n  rs:\n    abbreviations:\n      fn: \"fn () {}\""
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+        let abbreviations = preferences.abbreviations(Some(PathBuf::from("main.rs")).as_ref());
+
+        assert_eq!(abbreviations.get("teh").map(String::as_str), Some("the"));
+        assert_eq!(abbreviations.get("fn").map(String::as_str), Some("fn () {}"));
+        assert_eq!(
+            preferences.abbreviations(None).get("fn").map(String::as_str),
+            Some("function")
+        );
+    }
+
+    #[test]
+    fn spell_check_prefers_type_specific_values() {
+        let data = YamlLoader::load_from_str("spell_check: true\ntypes:\n  rs:\n    spell_check: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.spell_check(Some(PathBuf::from("README.md")).as_ref()));
+        assert!(!preferences.spell_check(Some(PathBuf::from("main.rs")).as_ref()));
+        assert!(!Preferences::new(None).spell_check(None));
+    }
+
+    #[test]
+    fn indent_guides_prefers_type_specific_values() {
+        let data = YamlLoader::load_from_str("indent_guides: true\ntypes:\n  md:\n    indent_guides: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.indent_guides(Some(PathBuf::from("main.rs")).as_ref()));
+        assert!(!preferences.indent_guides(Some(PathBuf::from("README.md")).as_ref()));
+        assert!(!Preferences::new(None).indent_guides(None));
+    }
+
+    #[test]
+    fn definitions_prefers_type_specific_values() {
+        let data = YamlLoader::load_from_str(
+            "definitions: entity.name.function\ntypes:\n  rs:\n    definitions: entity.name.impl"
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.definitions(Some(PathBuf::from("main.rs")).as_ref()), "entity.name.impl");
+        assert_eq!(preferences.definitions(Some(PathBuf::from("main.py")).as_ref()), "entity.name.function");
+        assert_eq!(Preferences::new(None).definitions(None), "entity.name.function, markup.heading");
+    }
+
+    #[test]
+    fn age_identity_expands_the_home_directory() {
+        let data = YamlLoader::load_from_str("age_identity: ~/.age/key.txt").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.age_identity(), Some(PathBuf::from(env::var_os("HOME").unwrap()).join(".age/key.txt")));
+        assert_eq!(Preferences::new(None).age_identity(), None);
+    }
+
+    #[test]
+    fn input_timeout_can_be_set_for_the_session() {
+        let mut preferences = Preferences::new(None);
+        assert_eq!(preferences.input_timeout(), Duration::from_millis(50));
+
+        preferences.set("input_timeout", "200").unwrap();
+        assert_eq!(preferences.input_timeout(), Duration::from_millis(200));
+
+        preferences.set("input_timeout", "-1").unwrap();
+        assert_eq!(preferences.input_timeout(), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn large_file_threshold_can_be_set_for_the_session() {
+        let mut preferences = Preferences::new(None);
+        assert_eq!(preferences.large_file_threshold(), 5_000_000);
+
+        preferences.set("large_file_threshold", "1000").unwrap();
+        assert_eq!(preferences.large_file_threshold(), 1000);
+    }
+
+    #[test]
+    fn linter_uses_the_configured_command_and_format() {
+        let data = YamlLoader::load_from_str(
+            "types:\n  sh:\n    lint:\n      command: shellcheck -f gcc\n  py:\n    lint:\n      command: ruff\n      format: \"(\""
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+        let linter = preferences.linter(Some(PathBuf::from("build.sh")).as_ref()).unwrap().unwrap();
+
+        assert_eq!(linter.command, vec!["shellcheck", "-f", "gcc"]);
+        assert_eq!(linter.parse("build.sh:2:1: error: Oops").len(), 1);
+        assert!(preferences.linter(Some(PathBuf::from("main.rs")).as_ref()).unwrap().is_none());
+        assert!(preferences.linter(Some(PathBuf::from("main.py")).as_ref()).is_err());
+    }
+
+    #[test]
+    fn tags_command_uses_the_configured_command() {
+        assert_eq!(Preferences::new(None).tags_command(), vec!["ctags", "-R"]);
+
+        let data = YamlLoader::load_from_str("tags_command: ctags -R --exclude=target").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.tags_command(), vec!["ctags", "-R", "--exclude=target"]);
+    }
+
+    #[test]
+    fn local_history_can_be_disabled() {
+        assert!(Preferences::new(None).local_history());
+
+        let data = YamlLoader::load_from_str("local_history: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(!preferences.local_history());
+    }
+
     #[test]
     fn soft_tabs_returns_default_when_user_defined_type_specific_data_not_found() {
         let data = YamlLoader::load_from_str("soft_tabs: false").unwrap();
@@ -387,6 +1270,38 @@ mod tests {
         assert_eq!(preferences.line_length_guide(), None);
     }
 
+    #[test]
+    fn preferences_returns_user_defined_line_length_guides() {
+        let data = YamlLoader::load_from_str("line_length_guide: [100, 80]").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.line_length_guides(None), vec![80, 100]);
+        assert_eq!(preferences.line_length_guide(), Some(80));
+    }
+
+    #[test]
+    fn preferences_returns_type_specific_line_length_guides() {
+        let data = YamlLoader::load_from_str("line_length_guide: 80\ntypes:\n  rs:\n    line_length_guide: [100]\n  md:\n    line_length_guide: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.line_length_guides(Some(&PathBuf::from("main.rs"))), vec![100]);
+        assert_eq!(preferences.line_length_guides(Some(&PathBuf::from("README.md"))), Vec::<usize>::new());
+        assert_eq!(preferences.line_length_guides(Some(&PathBuf::from("main.py"))), vec![80]);
+    }
+
+    #[test]
+    fn preferences_returns_commit_message_line_length_guides() {
+        let data = YamlLoader::load_from_str("line_length_guide: 80
+types:
+  MERGE_MSG:
+    line_length_guide: [72]").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.line_length_guides(Some(&PathBuf::from(".git/COMMIT_EDITMSG"))), vec![50, 72]);
+        assert_eq!(preferences.line_length_guides(Some(&PathBuf::from(".git/MERGE_MSG"))), vec![72]);
+        assert_eq!(Preferences::new(None).line_length_guides(Some(&PathBuf::from(".git/COMMIT_EDITMSG"))), vec![50, 72]);
+    }
+
     #[test]
     fn preferences_returns_user_default_line_length_guide() {
         let data = YamlLoader::load_from_str("line_length_guide: true").unwrap();
@@ -403,6 +1318,124 @@ mod tests {
         assert_eq!(preferences.line_wrapping(), false);
     }
 
+    #[test]
+    fn cursor_shape_uses_configured_shapes_for_each_mode() {
+        let preferences = Preferences::new(None);
+        assert_eq!(preferences.cursor_shape("insert"), CursorShape::Bar);
+        assert_eq!(preferences.cursor_shape("normal"), CursorShape::Block);
+
+        let data = YamlLoader::load_from_str("cursor_shapes:\n  normal: underline\n  insert: unknown").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+        assert_eq!(preferences.cursor_shape("normal"), CursorShape::Underline);
+        assert_eq!(preferences.cursor_shape("insert"), CursorShape::Bar);
+
+        let data = YamlLoader::load_from_str("cursor_shapes: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+        assert_eq!(preferences.cursor_shape("insert"), CursorShape::Default);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_bidi_markers() {
+        assert_eq!(Preferences::new(None).bidi_markers(), true);
+
+        let data = YamlLoader::load_from_str("bidi_markers: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.bidi_markers(), false);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_color_swatches() {
+        let data = YamlLoader::load_from_str("color_swatches: true").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.color_swatches(), true);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_inline_diagnostics() {
+        assert_eq!(Preferences::new(None).inline_diagnostics(), true);
+
+        let data = YamlLoader::load_from_str("inline_diagnostics: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.inline_diagnostics(), false);
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_scroll_amount() {
+        let data = YamlLoader::load_from_str("scroll_amount: 5").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.scroll_amount(), 5);
+    }
+
+    #[test]
+    fn set_overrides_user_defined_data() {
+        let data = YamlLoader::load_from_str("tab_width: 12").unwrap();
+        let mut preferences = Preferences::new(data.into_iter().nth(0));
+        preferences.set("tab_width", "4").unwrap();
+        preferences.set("wrap", "off").unwrap();
+
+        assert_eq!(preferences.tab_width(None), 4);
+        assert_eq!(preferences.line_wrapping(), false);
+    }
+
+    #[test]
+    fn unknown_keys_lists_unrecognized_top_level_and_type_specific_preferences() {
+        let data = YamlLoader::load_from_str(
+            "tab_widht: 4\nsoft_tabs: true\ntypes:\n  rs:\n    tab_width: 4\n    soft_tab: false"
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(
+            preferences.unknown_keys(),
+            vec![String::from("tab_widht"), String::from("types.rs.soft_tab")]
+        );
+    }
+
+    #[test]
+    fn hook_commands_accepts_single_commands_and_lists() {
+        let data = YamlLoader::load_from_str(
+            "hooks:\n  after_save: buffer::display_statistics\n  buffer_open:\n    - cursor::move_to_last_line\n    - view::scroll_to_cursor"
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.hook_commands("after_save"), vec!["buffer::display_statistics"]);
+        assert_eq!(
+            preferences.hook_commands("buffer_open"),
+            vec!["cursor::move_to_last_line", "view::scroll_to_cursor"]
+        );
+        assert!(preferences.hook_commands("before_save").is_empty());
+    }
+
+    #[test]
+    fn digraphs_include_user_defined_entries() {
+        let data = YamlLoader::load_from_str("digraphs:\n  e': ě\n  oo: ∞").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+        let digraphs = preferences.digraphs();
+
+        assert_eq!(digraphs.get("e'"), Some(&'ě'));
+        assert_eq!(digraphs.get("oo"), Some(&'∞'));
+        assert_eq!(digraphs.get("a:"), Some(&'ä'));
+    }
+
+    #[test]
+    fn git_hosts_maps_hosts_to_their_kind() {
+        let data = YamlLoader::load_from_str("git_hosts:\n  git.example.com: gitlab").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.git_hosts().get("git.example.com").map(String::as_str), Some("gitlab"));
+        assert!(Preferences::new(None).git_hosts().is_empty());
+    }
+
+    #[test]
+    fn set_rejects_unknown_preferences() {
+        let mut preferences = Preferences::new(None);
+
+        assert!(preferences.set("keymap", "none").is_err());
+    }
+
     #[test]
     fn tab_content_uses_tab_width_spaces_when_soft_tabs_are_enabled() {
         let data = YamlLoader::load_from_str("soft_tabs: true\ntab_width: 5").unwrap();
@@ -470,6 +1503,78 @@ mod tests {
         assert!(preferences.open_mode_exclusions().unwrap().is_none());
     }
 
+    #[test]
+    fn wrap_preferences_default_to_wrapping_at_the_last_column() {
+        let preferences = Preferences::new(None);
+
+        assert!(!preferences.wrap_at_words());
+        assert!(!preferences.wrap_hanging_indent());
+        assert_eq!(preferences.wrap_indicator(), None);
+    }
+
+    #[test]
+    fn wrap_preferences_return_user_defined_values() {
+        let data = YamlLoader::load_from_str("wrap_at_words: true\nwrap_hanging_indent: true\nwrap_indicator: \"~\"").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.wrap_at_words());
+        assert!(preferences.wrap_hanging_indent());
+        assert_eq!(preferences.wrap_indicator(), Some(String::from("~")));
+    }
+
+    #[test]
+    fn open_mode_ignore_files_defaults_to_true() {
+        let preferences = Preferences::new(None);
+
+        assert!(preferences.open_mode_ignore_files());
+    }
+
+    #[test]
+    fn open_mode_ignore_files_returns_user_defined_value() {
+        let data = YamlLoader::load_from_str("open_mode:\n  ignore_files: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(!preferences.open_mode_ignore_files());
+    }
+
+    #[test]
+    fn load_project_merges_project_preferences_over_existing_ones() {
+        let directory = PathBuf::from(concat!(env!("OUT_DIR"), "/project_preferences"));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join(".amp.yml"), "tab_width: 4\ntypes:\n  rs:\n    soft_tabs: false\n").unwrap();
+        let data = YamlLoader::load_from_str("tab_width: 8\nline_length_guide: 100\ntypes:\n  rs:\n    tab_width: 6").unwrap();
+        let mut preferences = Preferences::new(data.into_iter().nth(0));
+        let rust_path = PathBuf::from("main.rs");
+
+        preferences.load_project(&directory).unwrap();
+
+        assert_eq!(preferences.tab_width(None), 4);
+        assert_eq!(preferences.line_length_guide(), Some(100));
+        assert_eq!(preferences.tab_width(Some(&rust_path)), 6);
+        assert!(!preferences.soft_tabs(Some(&rust_path)));
+    }
+
+    #[test]
+    fn load_project_rejects_non_hash_documents() {
+        let directory = PathBuf::from(concat!(env!("OUT_DIR"), "/invalid_project_preferences"));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join(".amp.yml"), "- tab_width").unwrap();
+        let mut preferences = Preferences::new(None);
+
+        assert!(preferences.load_project(&directory).is_err());
+    }
+
+    #[test]
+    fn is_preferences_file_recognizes_project_file() {
+        let directory = PathBuf::from(concat!(env!("OUT_DIR"), "/tracked_project_preferences"));
+        fs::create_dir_all(&directory).unwrap();
+        let mut preferences = Preferences::new(None);
+        preferences.load_project(&directory).unwrap();
+
+        assert!(preferences.is_preferences_file(&directory.join(".amp.yml")));
+        assert!(!preferences.is_preferences_file(&directory.join("amp.yml")));
+    }
+
     #[test]
     fn reload_clears_in_memory_theme() {
         // Create an on-disk preferences file first, if one doesn't already exist.
@@ -497,7 +1602,9 @@ mod tests {
         let mut preferences = Preferences{
             data: None,
             keymap: KeyMap::from(&Hash::new()).unwrap(),
-            theme: None
+            theme: None,
+            project_path: None,
+            indentation: HashMap::new(),
         };
 
         // Reload the preferences, ensuring that it refreshes the keymap.
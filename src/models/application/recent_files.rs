@@ -0,0 +1,125 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use crate::yaml::YamlEmitter;
+use crate::yaml::yaml::{Yaml, YamlLoader};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = "recent_files.yml";
+const MAX_ENTRIES: usize = 100;
+
+/// Tracks the files most recently opened in the editor, across sessions.
+/// Entries are ordered from most to least recently opened.
+pub struct RecentFiles {
+    path: Option<PathBuf>,
+    entries: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    /// Builds an empty list. Without a path, the list is in-memory only.
+    pub fn new(path: Option<PathBuf>) -> RecentFiles {
+        RecentFiles {
+            path,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Loads the previously persisted list from the application state
+    /// directory, falling back to an empty list if it can't be read.
+    pub fn load() -> RecentFiles {
+        let path = Preferences::state_path().ok().map(|dir| dir.join(FILE_NAME));
+        let mut recent_files = RecentFiles::new(path);
+
+        if let Some(ref path) = recent_files.path {
+            if let Ok(entries) = read_entries(path) {
+                recent_files.entries = entries;
+            }
+        }
+
+        recent_files
+    }
+
+    /// Moves the path to the front of the list, adding it if necessary.
+    pub fn record(&mut self, path: PathBuf) {
+        self.entries.retain(|entry| *entry != path);
+        self.entries.insert(0, path);
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.entries
+    }
+
+    /// Writes the list to disk, if it was built with a path.
+    pub fn save(&self) -> Result<()> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+
+        let entries = self.entries
+            .iter()
+            .map(|entry| Yaml::String(entry.to_string_lossy().into_owned()))
+            .collect();
+
+        let mut data = String::new();
+        YamlEmitter::new(&mut data)
+            .dump(&Yaml::Array(entries))
+            .map_err(|_| Error::from("Couldn't serialize recent files"))?;
+        fs::write(path, data).chain_err(|| "Couldn't write recent files")
+    }
+}
+
+fn read_entries(path: &Path) -> Result<Vec<PathBuf>> {
+    let data = fs::read_to_string(path).chain_err(|| "Couldn't read recent files")?;
+    let documents = YamlLoader::load_from_str(&data)
+        .chain_err(|| "Couldn't parse recent files")?;
+    let mut entries = Vec::new();
+
+    if let Some(Yaml::Array(items)) = documents.into_iter().nth(0) {
+        for item in items {
+            if let Some(path) = item.as_str() {
+                entries.push(PathBuf::from(path));
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecentFiles, MAX_ENTRIES};
+    use std::path::PathBuf;
+
+    #[test]
+    fn record_moves_paths_to_the_front_without_duplicating_them() {
+        let mut recent_files = RecentFiles::new(None);
+        recent_files.record(PathBuf::from("amp"));
+        recent_files.record(PathBuf::from("editor"));
+        recent_files.record(PathBuf::from("amp"));
+
+        assert_eq!(recent_files.paths(), &[PathBuf::from("amp"), PathBuf::from("editor")]);
+    }
+
+    #[test]
+    fn record_discards_the_least_recent_entries() {
+        let mut recent_files = RecentFiles::new(None);
+        for index in 0..MAX_ENTRIES + 1 {
+            recent_files.record(PathBuf::from(index.to_string()));
+        }
+
+        assert_eq!(recent_files.paths().len(), MAX_ENTRIES);
+        assert_eq!(recent_files.paths()[0], PathBuf::from(MAX_ENTRIES.to_string()));
+    }
+
+    #[test]
+    fn save_and_load_round_trip_entries() {
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/recent_files.yml"));
+        let mut recent_files = RecentFiles::new(Some(path.clone()));
+        recent_files.record(PathBuf::from("/amp/editor.rs"));
+        recent_files.save().unwrap();
+
+        assert_eq!(super::read_entries(&path).unwrap(), vec![PathBuf::from("/amp/editor.rs")]);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use scribe::Buffer;
+use std::fs;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+
+const DIRECTORY_NAME: &str = "recovery";
+
+/// Returns the directory that unsaved changes are written to
+/// when the editor is terminated, creating it if necessary.
+pub fn directory() -> Result<PathBuf> {
+    let path = Preferences::state_path()?.join(DIRECTORY_NAME);
+    fs::create_dir_all(&path).chain_err(|| "Couldn't create recovery directory")?;
+
+    Ok(path)
+}
+
+/// Writes a copy of the buffer's contents to the specified directory,
+/// leaving its file untouched, and returns the path of the copy.
+pub fn write(buffer: &Buffer, directory: &Path) -> Result<PathBuf> {
+    let path = directory.join(file_name(buffer));
+    fs::write(&path, buffer.data()).chain_err(|| "Couldn't write recovery file")?;
+
+    Ok(path)
+}
+
+// Flattens the buffer's path into a file name (e.g. "%home%amp%notes.md"),
+// so that copies of identically named files don't overwrite one another.
+fn file_name(buffer: &Buffer) -> String {
+    match buffer.path {
+        Some(ref path) => path.to_string_lossy().replace(MAIN_SEPARATOR, "%"),
+        None => format!("untitled-{}", buffer.id.unwrap_or(0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::Buffer;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn write_copies_buffer_contents_using_a_flattened_path() {
+        let directory = Path::new(env!("OUT_DIR"));
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("/amp/notes.md"));
+        buffer.insert("amp");
+
+        let path = super::write(&buffer, directory).unwrap();
+
+        assert_eq!(path, directory.join("%amp%notes.md"));
+        assert_eq!(fs::read_to_string(path).unwrap(), "amp");
+    }
+}
@@ -0,0 +1,119 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use crate::yaml::YamlEmitter;
+use crate::yaml::yaml::{Yaml, YamlLoader};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = "search_history.yml";
+const MAX_ENTRIES: usize = 100;
+
+/// Tracks previously run search queries, across sessions.
+/// Entries are ordered from most to least recently used.
+pub struct SearchHistory {
+    path: Option<PathBuf>,
+    entries: Vec<String>,
+}
+
+impl SearchHistory {
+    /// Builds an empty history. Without a path, the history is in-memory only.
+    pub fn new(path: Option<PathBuf>) -> SearchHistory {
+        SearchHistory {
+            path,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Loads the previously persisted history from the application
+    /// state directory, falling back to an empty one if it can't be read.
+    pub fn load() -> SearchHistory {
+        let path = Preferences::state_path().ok().map(|dir| dir.join(FILE_NAME));
+        let mut history = SearchHistory::new(path);
+
+        if let Some(ref path) = history.path {
+            if let Ok(entries) = read_entries(path) {
+                history.entries = entries;
+            }
+        }
+
+        history
+    }
+
+    /// Moves the query to the front of the history, adding it if necessary.
+    pub fn record(&mut self, query: &str) {
+        if query.is_empty() {
+            return;
+        }
+
+        self.entries.retain(|entry| entry != query);
+        self.entries.insert(0, query.to_string());
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Writes the history to disk, if it was built with a path.
+    pub fn save(&self) -> Result<()> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+
+        let entries = self.entries
+            .iter()
+            .map(|entry| Yaml::String(entry.clone()))
+            .collect();
+
+        let mut data = String::new();
+        YamlEmitter::new(&mut data)
+            .dump(&Yaml::Array(entries))
+            .map_err(|_| Error::from("Couldn't serialize search history"))?;
+        fs::write(path, data).chain_err(|| "Couldn't write search history")
+    }
+}
+
+fn read_entries(path: &Path) -> Result<Vec<String>> {
+    let data = fs::read_to_string(path).chain_err(|| "Couldn't read search history")?;
+    let documents = YamlLoader::load_from_str(&data)
+        .chain_err(|| "Couldn't parse search history")?;
+    let mut entries = Vec::new();
+
+    if let Some(Yaml::Array(items)) = documents.into_iter().nth(0) {
+        for item in items {
+            if let Some(entry) = item.as_str() {
+                entries.push(entry.to_string());
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SearchHistory;
+    use std::path::PathBuf;
+
+    #[test]
+    fn record_moves_queries_to_the_front_without_duplicating_them() {
+        let mut history = SearchHistory::new(None);
+        history.record("amp");
+        history.record("editor");
+        history.record("amp");
+        history.record("");
+
+        assert_eq!(history.entries(), &[String::from("amp"), String::from("editor")]);
+    }
+
+    #[test]
+    fn save_and_load_round_trip_entries() {
+        let path = PathBuf::from(concat!(env!("OUT_DIR"), "/search_history.yml"));
+        let mut history = SearchHistory::new(Some(path.clone()));
+        history.record("amp: editor");
+        history.save().unwrap();
+
+        assert_eq!(super::read_entries(&path).unwrap(), vec![String::from("amp: editor")]);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::errors::*;
+use crate::models::application::{Event, Preferences};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::Sender;
+use std::thread;
+
+const SOCKET_FILE_NAME: &str = "server.sock";
+
+/// Listens for paths sent using `amp --remote`, to be opened in this instance.
+/// Clients' connections are held open until the files they sent are closed,
+/// so that they can wait for them, as `$EDITOR` (e.g. for git commits) should.
+pub struct Server {
+    path: PathBuf,
+    clients: Arc<Mutex<HashMap<usize, UnixStream>>>,
+}
+
+impl Server {
+    pub fn start(path: PathBuf, events: Sender<Event>) -> Result<Server> {
+        // Only one instance can act as the server, but a
+        // socket left behind by one that's crashed is replaced.
+        if UnixStream::connect(&path).is_ok() {
+            bail!("Another instance is already running as a server");
+        }
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).chain_err(|| "Couldn't create the server's socket")?;
+
+        let clients = Arc::new(Mutex::new(HashMap::new()));
+        let server = Server { path, clients: clients.clone() };
+        thread::spawn(move || {
+            for (id, stream) in listener.incoming().enumerate() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+
+                // Clients are handled separately, so that
+                // one that's slow to write doesn't hold up others.
+                let clients = clients.clone();
+                let events = events.clone();
+                thread::spawn(move || {
+                    // Connections without paths (e.g. checking
+                    // whether a server's running) are ignored.
+                    match read_paths(&stream) {
+                        Ok(ref paths) if paths.is_empty() => (),
+                        Ok(paths) => {
+                            if let Ok(mut clients) = clients.lock() {
+                                clients.insert(id, stream);
+                            }
+                            let _ = events.send(Event::OpenRequested(id, paths));
+                        },
+                        Err(_) => (),
+                    }
+                });
+            }
+        });
+
+        Ok(server)
+    }
+
+    /// Ends the client's connection, letting it know its files have been closed.
+    pub fn release(&self, id: usize) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.remove(&id);
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.clear();
+        }
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+pub fn socket_path() -> Result<PathBuf> {
+    Ok(Preferences::state_path()?.join(SOCKET_FILE_NAME))
+}
+
+/// Sends the paths to the instance running as a server, waiting until
+/// they've been closed. Returns false if there's no server to send them to.
+pub fn send_to_server(paths: &[PathBuf]) -> Result<bool> {
+    send(&socket_path()?, paths)
+}
+
+fn send(socket_path: &Path, paths: &[PathBuf]) -> Result<bool> {
+    let mut stream = match UnixStream::connect(socket_path) {
+        Ok(stream) => stream,
+        Err(_) => return Ok(false),
+    };
+    for path in paths {
+        writeln!(stream, "{}", path.to_string_lossy()).chain_err(|| "Couldn't send paths to the server")?;
+    }
+    stream.shutdown(Shutdown::Write).chain_err(|| "Couldn't send paths to the server")?;
+
+    // Nothing's sent back; the connection's closed once the files are.
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).chain_err(|| "Lost the connection to the server")?;
+
+    Ok(true)
+}
+
+// Reads the paths sent by a client, one per line, until it stops writing.
+fn read_paths(stream: &UnixStream) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if !line.is_empty() {
+            paths.push(PathBuf::from(line));
+        }
+    }
+
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::Event;
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+    use std::thread;
+    use super::{send, Server};
+
+    #[test]
+    fn clients_wait_until_they_are_released() {
+        let socket_path = PathBuf::from(concat!(env!("OUT_DIR"), "/server.sock"));
+        let (events, received) = mpsc::channel();
+        let server = Server::start(socket_path.clone(), events).unwrap();
+        assert!(Server::start(socket_path.clone(), mpsc::channel().0).is_err());
+
+        let paths = vec![PathBuf::from("/amp/src/main.rs"), PathBuf::from("/amp/Cargo.toml")];
+        let client_paths = paths.clone();
+        let client = thread::spawn(move || send(&socket_path, &client_paths).unwrap());
+
+        let id = match received.recv().unwrap() {
+            Event::OpenRequested(id, received_paths) => {
+                assert_eq!(received_paths, paths);
+                id
+            },
+            event => panic!("Unexpected event: {:?}", event),
+        };
+        server.release(id);
+        assert!(client.join().unwrap());
+
+        drop(server);
+        assert!(!send(&PathBuf::from(concat!(env!("OUT_DIR"), "/server.sock")), &[]).unwrap());
+    }
+}
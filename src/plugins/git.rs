@@ -0,0 +1,509 @@
+use crate::errors::*;
+use crate::errors;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, ClipboardContent, Event, Mode};
+use crate::models::application::modes::{DiffMode, DiffSource, FileHistoryMode, Revision, SearchSelectMode};
+use crate::plugins::{Plugin, Registry};
+use crate::util::{encryption, reference};
+use git2::{self, Commit, Oid, Repository, Sort, Status};
+use regex::Regex;
+use scribe::Buffer;
+use std::cmp;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// The number of characters that commit IDs are abbreviated to.
+const SHORT_ID_LENGTH: usize = 7;
+
+/// Git integration: staging, linking to, and browsing the history of the
+/// current file, and its status in the normal mode status line.
+pub struct GitPlugin;
+
+impl Plugin for GitPlugin {
+    fn register(&self, registry: &mut Registry) {
+        registry.add_command("git::add", add);
+        registry.add_command("git::copy_remote_url", copy_remote_url);
+        registry.add_command("git::open_remote_url", open_remote_url);
+        registry.add_command("git::file_history", file_history);
+        registry.add_command("git::view_revision", view_revision);
+        registry.add_key_bindings(include_str!("git.yml"));
+        registry.add_status_segment(status);
+    }
+}
+
+fn add(app: &mut Application) -> Result {
+    let repo = app.repository.as_ref().ok_or("No repository available")?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let mut index = repo.index().chain_err(|| "Couldn't get the repository index")?;
+    let buffer_path = buffer.path.as_ref().ok_or(BUFFER_PATH_MISSING)?;
+    let repo_path = repo.workdir().ok_or("No path found for the repository")?;
+    let relative_path = buffer_path.strip_prefix(repo_path).chain_err(|| {
+        "Failed to build a relative buffer path"
+    })?;
+
+    index.add_path(relative_path).chain_err(|| "Failed to add path to index.")?;
+    index.write().chain_err(|| "Failed to write index.")
+}
+
+/// Copies the web URL for the current line (or selected lines)
+/// of the current file, on its repository's GitHub or GitLab page.
+fn copy_remote_url(app: &mut Application) -> Result {
+    let url = remote_url(app)?;
+    app.clipboard.set_content(ClipboardContent::Inline(url))?;
+
+    commands::application::switch_to_normal_mode(app)
+}
+
+/// Opens the web URL for the current line (or selected lines) of the
+/// current file, on its repository's GitHub or GitLab page, in the browser.
+fn open_remote_url(app: &mut Application) -> Result {
+    let url = remote_url(app)?;
+    reference::open_url(&url)?;
+
+    commands::application::switch_to_normal_mode(app)
+}
+
+fn remote_url(app: &mut Application) -> errors::Result<String> {
+    let repo = app.repository.as_ref().ok_or("No repository available")?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let buffer_path = buffer.path.as_ref().ok_or(BUFFER_PATH_MISSING)?;
+
+    // Links point to the remote the current branch tracks, if it tracks one.
+    let remote_name = upstream_remote(repo).unwrap_or_else(|| String::from("origin"));
+    let remote = repo.find_remote(&remote_name).chain_err(|| {
+        format!("Couldn't find a remote \"{}\"", remote_name)
+    })?;
+    let url = remote.url().ok_or_else(|| format!("No URL for remote/{}", remote_name))?;
+
+    let git_hosts = app.preferences.borrow().git_hosts();
+    let (kind, host, repo_path) = get_remote_repository(url, &git_hosts)?;
+    let relative_path = relative_path(repo, buffer_path)?;
+
+    let status = repo.status_file(&relative_path).chain_err(|| {
+        "Couldn't get status info for the specified path"
+    })?;
+    if status.contains(git2::Status::WT_NEW) || status.contains(git2::Status::INDEX_NEW) {
+        bail!("The provided path doesn't exist in the repository");
+    }
+
+    // We want to build URLs that point to an object ID, so that they'll
+    // refer to a snapshot of the file as it looks at this very moment.
+    let mut revisions = repo.revwalk().chain_err(|| {
+        "Couldn't build a list of revisions for the repository"
+    })?;
+
+    // We need to set a starting point for the commit graph we'll
+    // traverse. We want the most recent commit, so start at HEAD.
+    revisions.push_head().chain_err(|| "Failed to push HEAD to commit graph.")?;
+
+    // Pull the first revision (HEAD).
+    let last_oid = revisions.next().and_then(|revision| revision.ok()).ok_or(
+        "Couldn't find a git object ID for this file"
+    )?;
+
+    // Avoid zero-based line numbers.
+    let (first_line, last_line) = match app.mode {
+        Mode::SelectLine(ref s) => (
+            cmp::min(buffer.cursor.line, s.anchor) + 1,
+            cmp::max(buffer.cursor.line, s.anchor) + 1,
+        ),
+        _ => (buffer.cursor.line + 1, buffer.cursor.line + 1),
+    };
+
+    Ok(kind.file_url(
+        host,
+        repo_path,
+        &format!("{:?}", last_oid),
+        &relative_path.to_string_lossy(),
+        first_line,
+        last_line
+    ))
+}
+
+// The name of the remote that the current branch tracks, if it tracks one.
+fn upstream_remote(repo: &Repository) -> Option<String> {
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    let branch = head.shorthand()?;
+
+    repo.config().ok()?.get_string(&format!("branch.{}.remote", branch)).ok()
+}
+
+/// Lists the commits that changed the current buffer's file, which can be
+/// compared against the buffer or viewed. The history is read in the
+/// background, as walking it can take a while in large repositories.
+fn file_history(app: &mut Application) -> Result {
+    let path = app.workspace
+        .current_buffer()
+        .ok_or(BUFFER_MISSING)?
+        .path
+        .clone()
+        .ok_or(BUFFER_PATH_MISSING)?;
+    let repo = app.repository.as_ref().ok_or("No repository available")?;
+    let repo_path = repo.workdir().ok_or("No path found for the repository")?.to_path_buf();
+    let relative_path = relative_path(repo, &path)?;
+
+    // Repositories can't be shared across threads; the history is read using its own.
+    let events = app.event_channel.clone();
+    let history_path = path.clone();
+    thread::spawn(move || {
+        let revisions = history(&repo_path, &relative_path).map_err(|error| error.to_string());
+        let _ = events.send(Event::FileHistoryLoaded(history_path, revisions));
+    });
+
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::FileHistory(FileHistoryMode::new(path, compare_revision, config));
+    commands::search_select::search(app)
+}
+
+/// Opens the file as it was in the selected revision, in a read-only buffer.
+fn view_revision(app: &mut Application) -> Result {
+    let (path, revision) = match app.mode {
+        Mode::FileHistory(ref mode) => (mode.path.clone(), mode.selection().cloned().ok_or("No revision selected")?),
+        _ => bail!("Can't view revisions outside of file history mode"),
+    };
+    let content = {
+        let repo = app.repository.as_ref().ok_or("No repository available")?;
+        revision_content(repo, &relative_path(repo, &path)?, &revision)?
+    };
+
+    // The buffer's named after the revision (e.g. "main.rs@1a2b3c4"), which
+    // doesn't have an extension to detect, so it's highlighted like the file.
+    let mut buffer = Buffer::new();
+    buffer.insert(content);
+    buffer.path = Some(revision_path(&path, &revision.id));
+    buffer.syntax_definition = app.workspace
+        .current_buffer()
+        .and_then(|buffer| buffer.syntax_definition.clone());
+    encryption::mark_saved(&mut buffer)?;
+
+    app.workspace.add_buffer(buffer);
+    if let Some(buffer) = app.workspace.current_buffer() {
+        app.view.initialize_buffer(buffer)?;
+        app.read_only_buffers.extend(buffer.id);
+    }
+
+    commands::application::switch_to_normal_mode(app)
+}
+
+// Compares the buffer against its file, as it was in the revision.
+fn compare_revision(app: &mut Application, path: &Path, revision: &Revision) -> Result {
+    let content = {
+        let repo = app.repository.as_ref().ok_or("No repository available")?;
+        revision_content(repo, &relative_path(repo, path)?, revision)?
+    };
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let title = path.to_string_lossy().into_owned();
+
+    app.mode = Mode::Diff(DiffMode::new(
+        DiffSource::Revision(revision.id.clone(), content.clone()),
+        title.clone(),
+        &buffer.data(),
+        format!("{} ({})", title, &revision.id[..SHORT_ID_LENGTH]),
+        &content
+    ));
+
+    Ok(())
+}
+
+// The commits that changed the file (relative to the
+// repository), newest first, starting from HEAD.
+fn history(repo_path: &Path, path: &Path) -> errors::Result<Vec<Revision>> {
+    let repo = Repository::open(repo_path).chain_err(|| "Couldn't open the repository")?;
+    let mut revisions = repo.revwalk().chain_err(|| {
+        "Couldn't build a list of revisions for the repository"
+    })?;
+    revisions.set_sorting(Sort::TIME);
+    revisions.push_head().chain_err(|| "Failed to push HEAD to commit graph.")?;
+
+    let mut history = Vec::new();
+    for oid in revisions {
+        let commit = oid.and_then(|oid| repo.find_commit(oid)).chain_err(|| "Couldn't read a commit")?;
+        let blob_id = match file_id(&commit, path) {
+            Some(id) => id,
+            None => continue,
+        };
+
+        // Commits that leave the file as one of their parents had
+        // it (e.g. merges of unrelated changes) aren't listed.
+        if commit.parents().all(|parent| file_id(&parent, path) != Some(blob_id)) {
+            history.push(Revision {
+                id: commit.id().to_string(),
+                label: revision_label(&commit),
+            });
+        }
+    }
+
+    Ok(history)
+}
+
+// The ID of the file's blob in the commit, if it exists there.
+fn file_id(commit: &Commit, path: &Path) -> Option<Oid> {
+    commit.tree().ok()?.get_path(path).ok().map(|entry| entry.id())
+}
+
+fn revision_content(repo: &Repository, path: &Path, revision: &Revision) -> errors::Result<String> {
+    let oid = Oid::from_str(&revision.id).chain_err(|| "Couldn't parse the revision's commit ID")?;
+    let commit = repo.find_commit(oid).chain_err(|| "Couldn't find the revision's commit")?;
+    let blob_id = file_id(&commit, path).ok_or("The file doesn't exist in the selected revision")?;
+    let blob = repo.find_blob(blob_id).chain_err(|| "Couldn't read the file's revision")?;
+
+    String::from_utf8(blob.content().to_vec()).chain_err(|| "The file's revision isn't valid UTF-8")
+}
+
+fn revision_label(commit: &Commit) -> String {
+    let id = commit.id().to_string();
+    let time = commit.time();
+    let author = commit.author();
+
+    format!(
+        "{} {} {}: {}",
+        &id[..SHORT_ID_LENGTH],
+        format_date(time.seconds() + i64::from(time.offset_minutes()) * 60),
+        author.name().unwrap_or("unknown"),
+        commit.summary().unwrap_or("")
+    )
+}
+
+// Names a revision of the file after its commit (e.g. "src/main.rs@1a2b3c4").
+fn revision_path(path: &Path, id: &str) -> PathBuf {
+    let mut revision_path = path.as_os_str().to_os_string();
+    revision_path.push(format!("@{}", &id[..SHORT_ID_LENGTH]));
+
+    PathBuf::from(revision_path)
+}
+
+fn relative_path(repo: &Repository, path: &Path) -> errors::Result<PathBuf> {
+    let repo_path = repo.workdir().ok_or("No path found for the repository")?;
+    let relative_path = path.strip_prefix(repo_path).chain_err(|| {
+        "Failed to build a relative buffer path"
+    })?;
+
+    Ok(relative_path.to_path_buf())
+}
+
+// Formats a time (in seconds since the Unix epoch) as a calendar
+// date (e.g. "2019-03-01"), using Howard Hinnant's civil_from_days.
+fn format_date(seconds: i64) -> String {
+    let days = if seconds < 0 { (seconds - 86_399) / 86_400 } else { seconds / 86_400 };
+    let days = days + 719_468;
+    let era = (if days >= 0 { days } else { days - 146_096 }) / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// The current buffer's git status, if it's in the repository.
+fn status(app: &mut Application) -> Option<String> {
+    let repo = app.repository.as_ref()?;
+    let path = app.workspace.current_buffer()?.path.as_ref()?;
+    let repo_path = repo.workdir()?;
+    let relative_path = path.strip_prefix(repo_path).ok()?;
+    let status = repo.status_file(relative_path).ok()?;
+
+    Some(presentable_status(&status).to_string())
+}
+
+fn presentable_status(status: &Status) -> &str {
+    if status.contains(git2::Status::WT_NEW) {
+        if status.contains(git2::Status::INDEX_NEW) {
+            // Parts of the file are staged as new in the index.
+            "[partially staged]"
+        } else {
+            // The file has never been added to the repository.
+            "[untracked]"
+        }
+    } else if status.contains(git2::Status::INDEX_NEW) {
+        // The complete file is staged as new in the index.
+        "[staged]"
+    } else if status.contains(git2::Status::WT_MODIFIED) {
+        if status.contains(git2::Status::INDEX_MODIFIED) {
+            // The file has both staged and unstaged modifications.
+            "[partially staged]"
+        } else {
+            // The file has unstaged modifications.
+            "[modified]"
+        }
+    } else if status.contains(git2::Status::INDEX_MODIFIED) {
+        // The file has staged modifications.
+        "[staged]"
+    } else {
+        // The file is tracked, but has no modifications.
+        "[ok]"
+    }
+}
+
+/// The kind of service hosting the repository, which has a web page for each of its files.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RemoteHost {
+    GitHub,
+    GitLab,
+}
+
+impl RemoteHost {
+    // The web URL for the lines of the file (which are one-based and inclusive)
+    // as of the revision, in the repository (e.g. "jmacdonald/amp") on the host.
+    fn file_url(self, host: &str, repo_path: &str, revision: &str, path: &str, first_line: usize, last_line: usize) -> String {
+        match self {
+            RemoteHost::GitHub => {
+                let lines = if first_line == last_line {
+                    format!("#L{}", first_line)
+                } else {
+                    format!("#L{}-L{}", first_line, last_line)
+                };
+
+                format!("https://{}/{}/blob/{}/{}{}", host, repo_path, revision, path, lines)
+            },
+            RemoteHost::GitLab => {
+                let lines = if first_line == last_line {
+                    format!("#L{}", first_line)
+                } else {
+                    format!("#L{}-{}", first_line, last_line)
+                };
+
+                format!("https://{}/{}/-/blob/{}/{}{}", host, repo_path, revision, path, lines)
+            },
+        }
+    }
+}
+
+// The kind of host, the host, and the path of the repository that the remote
+// URL refers to. Hosts that don't name their kind (e.g. "git.example.com")
+// are looked up in the configured git hosts.
+fn get_remote_repository<'a>(url: &'a str, git_hosts: &HashMap<String, String>) -> errors::Result<(RemoteHost, &'a str, &'a str)> {
+    lazy_static! {
+        // Either a URL (e.g. "ssh://git@host:22/path"), or scp-like syntax (e.g. "git@host:path").
+        static ref REGEX: Regex = Regex::new(
+            r"^(?:(?:https?|ssh|git)://(?:[^@/]+@)?([^:/]+)(?::\d+)?/|(?:[^@/]+@)?([^:/]+):)([^/].*?)(?:\.git)?/?$"
+        ).unwrap();
+    }
+    let captures = REGEX.captures(url).chain_err(|| "Failed to capture remote repo path")?;
+    let host = captures.at(1).or_else(|| captures.at(2)).chain_err(|| "Failed to capture remote host")?;
+    let path = captures.at(3).chain_err(|| "Failed to capture remote repo path")?;
+
+    let kind = match git_hosts.get(host).map(String::as_str) {
+        Some("github") => RemoteHost::GitHub,
+        Some("gitlab") => RemoteHost::GitLab,
+        Some(kind) => bail!("Unknown kind of git host \"{}\"; expected github or gitlab", kind),
+        None if host.contains("gitlab") => RemoteHost::GitLab,
+        None if host.contains("github") => RemoteHost::GitHub,
+        None => bail!("Couldn't tell what kind of host \"{}\" is; add it to the git_hosts preference", host),
+    };
+
+    Ok((kind, host, path))
+}
+
+#[test]
+fn test_get_remote_repository() {
+    let mut git_hosts = HashMap::new();
+    git_hosts.insert(String::from("git.example.com"), String::from("gitlab"));
+    let cases = [
+        ("git@github.com:jmacdonald/amp.git", RemoteHost::GitHub, "github.com", "jmacdonald/amp"),
+        ("https://github.com/jmacdonald/amp.git", RemoteHost::GitHub, "github.com", "jmacdonald/amp"),
+        ("https://github.com/jmacdonald/amp", RemoteHost::GitHub, "github.com", "jmacdonald/amp"),
+        ("git@gitlab.com:group/subgroup/amp.git", RemoteHost::GitLab, "gitlab.com", "group/subgroup/amp"),
+        ("ssh://git@gitlab.com/group/amp.git", RemoteHost::GitLab, "gitlab.com", "group/amp"),
+        ("https://user@github.example.com/org/amp.git", RemoteHost::GitHub, "github.example.com", "org/amp"),
+        ("ssh://git@gitlab.example.com:2222/group/amp.git", RemoteHost::GitLab, "gitlab.example.com", "group/amp"),
+        ("git@git.example.com:group/amp.git", RemoteHost::GitLab, "git.example.com", "group/amp"),
+    ];
+
+    cases.iter().for_each(|(url, expected_kind, expected_host, expected_path)| {
+        assert_eq!(
+            get_remote_repository(url, &git_hosts).unwrap(),
+            (*expected_kind, *expected_host, *expected_path)
+        )
+    });
+    assert!(get_remote_repository("https://example.com/jmacdonald/amp.git", &git_hosts).is_err());
+    assert!(get_remote_repository("/srv/git/amp.git", &git_hosts).is_err());
+}
+
+#[cfg(test)]
+mod tests {
+    use git2;
+    use std::path::{Path, PathBuf};
+    use super::{format_date, presentable_status, revision_path, RemoteHost};
+
+    #[test]
+    fn format_date_converts_times_to_calendar_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(-86_400), "1969-12-31");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_551_398_400 + 86_399), "2019-03-01");
+    }
+
+    #[test]
+    fn file_url_links_to_the_lines_on_the_host() {
+        assert_eq!(
+            RemoteHost::GitHub.file_url("github.com", "jmacdonald/amp", "1a2b3c4", "src/main.rs", 3, 3),
+            "https://github.com/jmacdonald/amp/blob/1a2b3c4/src/main.rs#L3"
+        );
+        assert_eq!(
+            RemoteHost::GitHub.file_url("github.com", "jmacdonald/amp", "1a2b3c4", "src/main.rs", 3, 5),
+            "https://github.com/jmacdonald/amp/blob/1a2b3c4/src/main.rs#L3-L5"
+        );
+        assert_eq!(
+            RemoteHost::GitLab.file_url("gitlab.com", "group/amp", "1a2b3c4", "src/main.rs", 3, 5),
+            "https://gitlab.com/group/amp/-/blob/1a2b3c4/src/main.rs#L3-5"
+        );
+        assert_eq!(
+            RemoteHost::GitLab.file_url("git.example.com", "group/amp", "1a2b3c4", "src/main.rs", 3, 3),
+            "https://git.example.com/group/amp/-/blob/1a2b3c4/src/main.rs#L3"
+        );
+    }
+
+    #[test]
+    fn revision_path_appends_the_abbreviated_commit_id() {
+        assert_eq!(
+            revision_path(Path::new("/amp/src/main.rs"), "1a2b3c4d5e6f"),
+            PathBuf::from("/amp/src/main.rs@1a2b3c4")
+        );
+    }
+
+    #[test]
+    pub fn presentable_status_returns_untracked_when_status_is_locally_new() {
+        let status = git2::Status::WT_NEW;
+        assert_eq!(presentable_status(&status), "[untracked]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_status_returns_ok_when_status_unmodified() {
+        let status = git2::Status::CURRENT;
+        assert_eq!(presentable_status(&status), "[ok]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_status_returns_staged_when_only_modified_in_index() {
+        let status = git2::Status::INDEX_MODIFIED;
+        assert_eq!(presentable_status(&status), "[staged]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_status_returns_staged_when_new_in_index() {
+        let status = git2::Status::INDEX_NEW;
+        assert_eq!(presentable_status(&status), "[staged]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_status_returns_partially_staged_when_modified_locally_and_in_index() {
+        let status = git2::Status::WT_MODIFIED | git2::Status::INDEX_MODIFIED;
+        assert_eq!(presentable_status(&status),
+                   "[partially staged]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_status_returns_partially_staged_when_new_locally_and_in_index() {
+        let status = git2::Status::WT_NEW | git2::Status::INDEX_NEW;
+        assert_eq!(presentable_status(&status),
+                   "[partially staged]".to_string());
+    }
+}
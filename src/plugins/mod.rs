@@ -0,0 +1,181 @@
+//! Features that live outside of the core editor register their commands,
+//! key bindings and status line segments here, rather than being wired in
+//! directly. Built-in integrations use the same API, behind feature flags.
+
+#[cfg(feature = "git")]
+mod git;
+
+use crate::commands::Command;
+use crate::models::application::Application;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A status line segment, shown in normal mode whenever it has content.
+pub type StatusSegment = fn(&mut Application) -> Option<String>;
+
+/// An extension to the editor. Plugins are registered before the
+/// application is started, so that their commands can be bound to keys.
+pub trait Plugin {
+    fn register(&self, registry: &mut Registry);
+}
+
+/// A mode defined outside of the core editor, entered by a plugin's commands
+/// (e.g. `app.mode = Mode::Plugin(Box::new(mode))`). The current buffer is
+/// shown underneath the mode's popup, if it has one.
+pub trait PluginMode {
+    /// The name that the mode's key bindings are listed under.
+    fn name(&self) -> &'static str;
+
+    /// Shown in the status line, in place of the mode's name.
+    fn status(&self) -> String {
+        format!(" {} ", self.name().to_uppercase())
+    }
+
+    /// Lines shown in a popup at the bottom of the screen.
+    fn popup(&self) -> Option<Vec<String>> {
+        None
+    }
+}
+
+/// What plugins have contributed to the editor.
+pub struct Registry {
+    commands: HashMap<&'static str, Command>,
+    key_bindings: Vec<&'static str>,
+    status_segments: Vec<StatusSegment>,
+}
+
+impl Registry {
+    fn new() -> Registry {
+        Registry {
+            commands: HashMap::new(),
+            key_bindings: Vec::new(),
+            status_segments: Vec::new(),
+        }
+    }
+
+    /// Makes a command available to key bindings and command mode,
+    /// using the same naming scheme as built-in ones (e.g. "git::add").
+    pub fn add_command(&mut self, name: &'static str, command: Command) {
+        self.commands.insert(name, command);
+    }
+
+    /// Adds key bindings, written in the same YAML format as the default key
+    /// map. They're applied over the defaults (but under user preferences),
+    /// and can define bindings for plugin modes.
+    pub fn add_key_bindings(&mut self, key_bindings: &'static str) {
+        self.key_bindings.push(key_bindings);
+    }
+
+    pub fn add_status_segment(&mut self, segment: StatusSegment) {
+        self.status_segments.push(segment);
+    }
+
+    pub fn commands(&self) -> HashMap<&'static str, Command> {
+        self.commands.clone()
+    }
+
+    pub fn key_bindings(&self) -> Vec<&'static str> {
+        self.key_bindings.clone()
+    }
+
+    /// The registered status line segments, copied out so that they can run
+    /// without the registry being borrowed (in case they register anything).
+    pub fn status_segments(&self) -> Vec<StatusSegment> {
+        self.status_segments.clone()
+    }
+}
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = {
+        let mut registry = Registry::new();
+        for plugin in built_in_plugins() {
+            plugin.register(&mut registry);
+        }
+
+        Mutex::new(registry)
+    };
+}
+
+#[allow(unused_mut)]
+fn built_in_plugins() -> Vec<Box<Plugin>> {
+    let mut plugins: Vec<Box<Plugin>> = Vec::new();
+
+    #[cfg(feature = "git")]
+    plugins.push(Box::new(git::GitPlugin));
+
+    plugins
+}
+
+/// Adds a plugin's contributions to the editor. This needs to
+/// happen before the application is created to take effect.
+pub fn register(plugin: &Plugin) {
+    if let Ok(mut registry) = REGISTRY.lock() {
+        plugin.register(&mut registry);
+    }
+}
+
+pub fn commands() -> HashMap<&'static str, Command> {
+    REGISTRY
+        .lock()
+        .map(|registry| registry.commands())
+        .unwrap_or_default()
+}
+
+pub fn key_bindings() -> Vec<&'static str> {
+    REGISTRY
+        .lock()
+        .map(|registry| registry.key_bindings())
+        .unwrap_or_default()
+}
+
+/// The content of each status line segment that has something to show.
+pub fn status_segments(app: &mut Application) -> Vec<String> {
+    // The registry isn't locked while the segments run.
+    let segments = REGISTRY
+        .lock()
+        .map(|registry| registry.status_segments())
+        .unwrap_or_default();
+
+    segments.iter().filter_map(|segment| segment(app)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::Result;
+    use crate::models::application::Application;
+    use super::{Plugin, Registry};
+
+    struct TestPlugin;
+
+    impl Plugin for TestPlugin {
+        fn register(&self, registry: &mut Registry) {
+            registry.add_command("test_plugin::greet", greet);
+            registry.add_key_bindings("test_plugin:\n  g: test_plugin::greet");
+            registry.add_status_segment(|_| Some(String::from("[greeter]")));
+        }
+    }
+
+    fn greet(app: &mut Application) -> Result {
+        app.message = Some(String::from("Hello!"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn registered_plugins_contribute_commands_bindings_and_status_segments() {
+        let mut registry = Registry::new();
+        TestPlugin.register(&mut registry);
+        let mut app = Application::new(&Vec::new()).unwrap();
+
+        let command = registry.commands()["test_plugin::greet"];
+        command(&mut app).unwrap();
+        assert_eq!(app.message, Some(String::from("Hello!")));
+        assert_eq!(registry.key_bindings(), vec!["test_plugin:\n  g: test_plugin::greet"]);
+        let segments: Vec<String> = registry
+            .status_segments()
+            .iter()
+            .filter_map(|segment| segment(&mut app))
+            .collect();
+        assert_eq!(segments, vec![String::from("[greeter]")]);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::util::completion::Completions;
+use crate::view::{Popup, PopupAnchor, Presenter};
+use scribe::buffer::Position;
+
+// Keeps long candidate lists from covering the entire buffer.
+const MAX_HEIGHT: usize = 10;
+
+/// Adds a popup listing completion candidates just above the status line,
+/// aligned with the specified column, highlighting the selected candidate.
+pub fn display(presenter: &mut Presenter, completions: &Completions, offset: usize) {
+    let status_line = presenter.height().checked_sub(1).unwrap_or(0);
+
+    // Line candidates up with the word being completed, inside the border.
+    let mut popup = Popup::new(
+        PopupAnchor::Above(Position{ line: status_line, offset: offset.saturating_sub(1) }),
+        completions.candidates.clone()
+    );
+    popup.selected = completions.selected;
+    popup.max_height = MAX_HEIGHT;
+
+    presenter.add_popup(popup);
+}
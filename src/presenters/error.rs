@@ -3,7 +3,11 @@ use scribe::Workspace;
 use crate::view::{Colors, StatusLineData, Style, View};
 
 pub fn display(workspace: &mut Workspace, view: &mut View, error: &Error) {
-    let mut presenter = view.build_presenter().unwrap();
+    // There's nowhere left to report the error if we can't draw.
+    let mut presenter = match view.build_presenter() {
+        Ok(presenter) => presenter,
+        Err(_) => return,
+    };
 
     let mut data = String::new();
     if let Some(buffer) = workspace.current_buffer() {
@@ -0,0 +1,23 @@
+use crate::errors::*;
+use scribe::Workspace;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, view: &mut View, message: &str) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    let data;
+    if let Some(buffer) = workspace.current_buffer() {
+        data = buffer.data();
+        presenter.print_buffer(buffer, &data, None, None)?;
+    }
+
+    presenter.print_status_line(&[StatusLineData {
+        content: message.to_string(),
+        style: Style::Default,
+        colors: Colors::Inverted,
+    }]);
+
+    presenter.present();
+
+    Ok(())
+}
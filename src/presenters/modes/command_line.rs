@@ -0,0 +1,47 @@
+use crate::errors::*;
+use crate::presenters;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::models::application::modes::CommandLineMode;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &CommandLineMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    // Draw the visible set of tokens to the terminal.
+    let data;
+    if let Some(buffer) = workspace.current_buffer() {
+        data = buffer.data();
+        presenter.print_buffer(buffer, &data, None, None)?;
+    }
+
+    let input = format!("{}{}", mode, mode.input);
+    let cursor_offset = input.graphemes(true).count();
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: input,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    ]);
+
+    // List completion candidates above the word being completed.
+    let word_offset = format!("{}", mode).graphemes(true).count() +
+        mode.input[..mode.word_start()].graphemes(true).count();
+    presenters::completions::display(&mut presenter, &mode.completions, word_offset);
+
+    // Move the cursor to the end of the input.
+    {
+        let cursor_line = presenter.height() - 1;
+        presenter.set_cursor(Some(Position {
+            line: cursor_line,
+            offset: cursor_offset
+        }));
+    }
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
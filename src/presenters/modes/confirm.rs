@@ -1,8 +1,9 @@
 use crate::errors::*;
+use crate::models::application::modes::ConfirmMode;
 use scribe::Workspace;
 use crate::view::{Colors, StatusLineData, Style, View};
 
-pub fn display(workspace: &mut Workspace, view: &mut View) -> Result<()> {
+pub fn display(workspace: &mut Workspace, mode: &ConfirmMode, view: &mut View) -> Result<()> {
     let mut presenter = view.build_presenter()?;
     let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let data = buf.data();
@@ -11,7 +12,7 @@ pub fn display(workspace: &mut Workspace, view: &mut View) -> Result<()> {
     presenter.print_buffer(buf, &data, None, None)?;
 
     // Draw the status line as a search prompt.
-    let confirmation = "Are you sure? (y/n)".to_string();
+    let confirmation = format!("{} (y/n)", mode.prompt);
     presenter.print_status_line(&[
         StatusLineData {
             content: confirmation,
@@ -0,0 +1,122 @@
+use crate::errors::*;
+use crate::models::application::modes::DiffMode;
+use crate::util::diff;
+use crate::view::{Colors, Presenter, StatusLineData, Style, View};
+use scribe::buffer::Position;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub fn display(mode: &DiffMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let content_height = presenter.height() - 1;
+    let pane_width = presenter.width().checked_sub(1).unwrap_or(0) / 2;
+
+    let visible_rows = mode.rows.iter().skip(mode.line_offset).take(content_height);
+    for (line, row) in visible_rows.enumerate() {
+        // Changed lines are shown with a subtle background, with the portion
+        // that actually differs (or the entire line, for added/removed lines)
+        // highlighted more strongly.
+        let (left_span, right_span) = match (row.hunk, row.left, row.right) {
+            (Some(_), Some(left), Some(right)) => {
+                let (left_span, right_span) = diff::changed_span(&mode.left[left], &mode.right[right]);
+                (Some(left_span), Some(right_span))
+            },
+            (Some(_), _, _) => (Some(0..usize::max_value()), Some(0..usize::max_value())),
+            _ => (None, None),
+        };
+        let colors = if row.hunk.is_some() {
+            Colors::Focused
+        } else {
+            Colors::Default
+        };
+
+        print_side(
+            &mut presenter,
+            Position{ line, offset: 0 },
+            pane_width,
+            row.left.map(|index| mode.left[index].as_str()),
+            colors,
+            left_span.map(|span| (span, Colors::Warning))
+        );
+        presenter.print(
+            &Position{ line, offset: pane_width },
+            Style::Default,
+            Colors::Focused,
+            if row.hunk.is_some() && row.hunk == mode.selected_hunk { ">" } else { " " }
+        );
+        print_side(
+            &mut presenter,
+            Position{ line, offset: pane_width + 1 },
+            pane_width,
+            row.right.map(|index| mode.right[index].as_str()),
+            colors,
+            right_span.map(|span| (span, Colors::Insert))
+        );
+    }
+
+    let change_status = match mode.selected_hunk {
+        Some(index) => format!(" change {} of {} ", index + 1, mode.hunks.len()),
+        None => String::from(" no changes "),
+    };
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: String::from(" DIFF "),
+            style: Style::Default,
+            colors: Colors::Inverted,
+        },
+        StatusLineData {
+            content: format!(" {} vs {}", mode.left_title, mode.right_title),
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+        StatusLineData {
+            content: change_status,
+            style: Style::Default,
+            colors: Colors::Focused,
+        }
+    ]);
+
+    presenter.set_cursor(None);
+    presenter.present();
+
+    Ok(())
+}
+
+// Prints one side of a diff row, truncated and padded to fill its pane,
+// highlighting the specified grapheme range, if any. Absent lines (the
+// other side of an addition or removal) are left blank.
+fn print_side(presenter: &mut Presenter, position: Position, width: usize, content: Option<&str>, colors: Colors, highlight: Option<(Range<usize>, Colors)>) {
+    let mut runs: Vec<(usize, String, Colors)> = Vec::new();
+
+    for (index, grapheme) in content.unwrap_or("").graphemes(true).take(width).enumerate() {
+        // Tabs would throw off alignment between the panes.
+        let grapheme = if grapheme == "\t" { " " } else { grapheme };
+        let grapheme_colors = match highlight {
+            Some((ref range, highlight_colors)) if index >= range.start && index < range.end => highlight_colors,
+            _ => colors,
+        };
+
+        // Group adjacent graphemes with the same colors into a single run.
+        if runs.last().map(|run| run.2 == grapheme_colors).unwrap_or(false) {
+            if let Some(run) = runs.last_mut() {
+                run.1.push_str(grapheme);
+            }
+        } else {
+            runs.push((index, grapheme.to_string(), grapheme_colors));
+        }
+    }
+
+    let used_width: usize = runs.iter().map(|run| run.1.graphemes(true).count()).sum();
+    if used_width < width {
+        runs.push((used_width, " ".repeat(width - used_width), colors));
+    }
+
+    for (offset, content, run_colors) in runs {
+        presenter.print(
+            &Position{ line: position.line, offset: position.offset + offset },
+            Style::Default,
+            run_colors,
+            content
+        );
+    }
+}
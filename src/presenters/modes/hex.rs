@@ -0,0 +1,41 @@
+use crate::errors::*;
+use crate::models::application::modes::HexMode;
+use crate::util::hex;
+use crate::view::{Colors, StatusLineData, Style, View};
+use scribe::buffer::Position;
+
+pub fn display(mode: &HexMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let content_height = presenter.height() - 1;
+
+    for line in 0..content_height {
+        let content = match hex::dump_line(&mode.data, mode.line_offset + line) {
+            Some(content) => content,
+            None => break,
+        };
+        presenter.print(&Position{ line, offset: 0 }, Style::Default, Colors::Default, content);
+    }
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: String::from(" HEX "),
+            style: Style::Default,
+            colors: Colors::Inverted,
+        },
+        StatusLineData {
+            content: format!(" {}", mode.path.to_string_lossy()),
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+        StatusLineData {
+            content: format!(" {} bytes ", mode.data.len()),
+            style: Style::Default,
+            colors: Colors::Focused,
+        }
+    ]);
+
+    presenter.set_cursor(None);
+    presenter.present();
+
+    Ok(())
+}
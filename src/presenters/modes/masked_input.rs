@@ -0,0 +1,38 @@
+use crate::errors::*;
+use crate::models::application::modes::MaskedInputMode;
+use crate::view::{Colors, StatusLineData, Style, View};
+use scribe::Workspace;
+use scribe::buffer::Position;
+
+pub fn display(workspace: &mut Workspace, mode: &MaskedInputMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    // Input can be requested before any buffers are open (e.g. to decrypt one).
+    if let Some(buf) = workspace.current_buffer() {
+        let data = buf.data();
+        presenter.print_buffer(buf, &data, None, None)?;
+    }
+
+    // Draw the status line as an input prompt, masking the input.
+    let input_prompt = format!("{}: {}", mode.prompt, "*".repeat(mode.input.chars().count()));
+    let input_prompt_len = input_prompt.chars().count();
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: input_prompt,
+            style: Style::Default,
+            colors: Colors::Default,
+        }
+    ]);
+
+    // Move the cursor to the end of the input.
+    let cursor_line = presenter.height() - 1;
+    presenter.set_cursor(Some(Position {
+        line: cursor_line,
+        offset: input_prompt_len,
+    }));
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -1,10 +1,17 @@
+pub mod command_line;
 pub mod confirm;
+pub mod diff;
+pub mod hex;
 pub mod insert;
 pub mod jump;
 pub mod line_jump;
 pub mod path;
+pub mod plugin;
+pub mod masked_input;
 pub mod normal;
+pub mod replace;
 pub mod search;
 pub mod search_select;
 pub mod select;
+pub mod select_block;
 pub mod select_line;
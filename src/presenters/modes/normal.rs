@@ -1,17 +1,23 @@
 use crate::errors::*;
 use scribe::Workspace;
 use scribe::buffer::Position;
-use crate::presenters::{current_buffer_status_line_data, git_status_line_data};
-use git2::Repository;
+use crate::presenters::current_buffer_status_line_data;
+use crate::util::{commit_message, completion};
 use crate::view::{Colors, StatusLineData, Style, View};
 
-pub fn display(workspace: &mut Workspace, view: &mut View, repo: &Option<Repository>) -> Result<()> {
+pub fn display(workspace: &mut Workspace, view: &mut View, status_segments: &[String], occurrence: Option<&str>, read_only: bool) -> Result<()> {
     let mut presenter = view.build_presenter()?;
     let buffer_status = current_buffer_status_line_data(workspace);
+    let directory = completion::collapse_home(&workspace.path);
 
     if let Some(buf) = workspace.current_buffer() {
+        if let Some(word) = occurrence {
+            presenter.highlight_occurrences(word);
+        }
+
         // Draw the visible set of tokens to the terminal.
         let data = buf.data();
+        let large_file = presenter.view.is_large_buffer(buf, &data);
         presenter.print_buffer(buf, &data, None, None)?;
 
         // Determine mode display color based on buffer modification status.
@@ -21,15 +27,33 @@ pub fn display(workspace: &mut Workspace, view: &mut View, repo: &Option<Reposit
             Colors::Inverted
         };
 
+        // Flag large files, which have some features turned off.
+        let mut segments = status_segments.to_vec();
+        if large_file {
+            segments.insert(0, String::from("[large file]"));
+        }
+
+        // Summarize what's being committed, from git's comments.
+        if buf.path.as_ref().map(|path| commit_message::is_commit_message(path)).unwrap_or(false) {
+            if let Some(summary) = commit_message::summary(&data) {
+                segments.insert(0, summary);
+            }
+        }
+        segments.push(directory);
+
         // Build the status line mode and buffer title display.
         presenter.print_status_line(&[
             StatusLineData {
-                content: " NORMAL ".to_string(),
+                content: if read_only { " READ ONLY " } else { " NORMAL " }.to_string(),
                 style: Style::Default,
                 colors,
             },
             buffer_status,
-            git_status_line_data(&repo, &buf.path)
+            StatusLineData {
+                content: segments.join(" "),
+                style: Style::Default,
+                colors: Colors::Focused,
+            }
         ]);
 
         presenter.present();
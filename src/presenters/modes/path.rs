@@ -1,4 +1,5 @@
 use crate::errors::*;
+use crate::presenters;
 use scribe::Workspace;
 use scribe::buffer::Position;
 use crate::models::application::modes::PathMode;
@@ -19,6 +20,7 @@ pub fn display(workspace: &mut Workspace, mode: &PathMode, view: &mut View) -> R
         mode.input
     );
 
+    let input_offset = mode_display.graphemes(true).count() + 1;
     let cursor_offset =
         mode_display.graphemes(true).count() +
         search_input.graphemes(true).count();
@@ -36,6 +38,9 @@ pub fn display(workspace: &mut Workspace, mode: &PathMode, view: &mut View) -> R
         },
     ]);
 
+    // List completion candidates above the input.
+    presenters::completions::display(&mut presenter, &mode.completions, input_offset);
+
     // Move the cursor to the end of the search query input.
     {
         let cursor_line = presenter.height() - 1;
@@ -0,0 +1,34 @@
+use crate::errors::*;
+use crate::plugins::PluginMode;
+use scribe::Workspace;
+use crate::presenters::current_buffer_status_line_data;
+use crate::view::{Colors, Popup, PopupAnchor, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &PluginMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let buffer_status = current_buffer_status_line_data(workspace);
+
+    if let Some(buf) = workspace.current_buffer() {
+        let data = buf.data();
+        presenter.print_buffer(buf, &data, None, None)?;
+    }
+
+    if let Some(lines) = mode.popup() {
+        let mut popup = Popup::new(PopupAnchor::Bottom, lines);
+        popup.title = Some(mode.name().to_string());
+        presenter.add_popup(popup);
+    }
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: mode.status(),
+            style: Style::Default,
+            colors: Colors::Inverted,
+        },
+        buffer_status
+    ]);
+
+    presenter.present();
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use crate::errors::*;
+use crate::models::application::modes::{ReplaceMode, ReplaceRow};
+use crate::view::{Colors, Presenter, StatusLineData, Style, View};
+use scribe::buffer::Position;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub fn display(mode: &mut ReplaceMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let content_height = presenter.height() - 1;
+    let width = presenter.width();
+    mode.scroll_to_selection(content_height);
+
+    let visible_rows = mode.rows.iter().enumerate().skip(mode.line_offset).take(content_height);
+    for (line, (row_index, row)) in visible_rows.enumerate() {
+        match *row {
+            ReplaceRow::File(index) => {
+                let file = &mode.files[index];
+                presenter.print(
+                    &Position{ line, offset: 0 },
+                    Style::Bold,
+                    Colors::Default,
+                    format!("{} ({} of {})", file.label, file.selected_count(), file.occurrences.len())
+                );
+            },
+            ReplaceRow::Occurrence(file, index) => {
+                let occurrence = &mode.files[file].occurrences[index];
+                let colors = if row_index == mode.selected_row { Colors::Focused } else { Colors::Default };
+                let prefix = format!(
+                    "{} [{}] {}: ",
+                    if row_index == mode.selected_row { ">" } else { " " },
+                    if occurrence.selected { "x" } else { " " },
+                    occurrence.range.start().line + 1
+                );
+                let prefix_width = prefix.graphemes(true).count();
+                let start = occurrence.range.start().offset;
+
+                // Previews show the line as it'll read once replaced, with the replacement highlighted.
+                let (content, end, highlight) = if mode.preview {
+                    (occurrence.replaced_line(), start + occurrence.replacement.graphemes(true).count(), Colors::Insert)
+                } else {
+                    (occurrence.line_content.clone(), occurrence.range.end().offset, Colors::Warning)
+                };
+
+                presenter.print(&Position{ line, offset: 0 }, Style::Default, colors, prefix);
+
+                // Tabs would throw off the highlighted occurrence's alignment.
+                let graphemes = content.graphemes(true).take(width.saturating_sub(prefix_width));
+                for (offset, grapheme) in graphemes.enumerate() {
+                    let grapheme_colors = if offset >= start && offset < end { highlight } else { colors };
+                    presenter.print(
+                        &Position{ line, offset: prefix_width + offset },
+                        Style::Default,
+                        grapheme_colors,
+                        if grapheme == "\t" { String::from(" ") } else { grapheme.to_string() }
+                    );
+                }
+            },
+        }
+    }
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: String::from(if mode.rename { " RENAME " } else { " REPLACE " }),
+            style: Style::Default,
+            colors: Colors::Inverted,
+        },
+        StatusLineData {
+            content: format!(
+                " {} {} {}{}",
+                mode.query,
+                if mode.rename { "to" } else { "with" },
+                mode.replacement,
+                if mode.preview { " (preview)" } else { "" }
+            ),
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+        StatusLineData {
+            content: format!(" {} of {} selected ", mode.selected_count(), mode.occurrence_count()),
+            style: Style::Default,
+            colors: Colors::Focused,
+        }
+    ]);
+
+    presenter.set_cursor(None);
+    presenter.present();
+
+    Ok(())
+}
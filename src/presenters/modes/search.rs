@@ -13,7 +13,10 @@ pub fn display(workspace: &mut Workspace, mode: &SearchMode, view: &mut View) ->
     let data = buffer.data();
     presenter.print_buffer(buffer, &data, mode.results.as_ref().map(|r| r.as_slice()), None)?;
 
-    let mode_display = format!(" {} ", mode);
+    let mode_display = match mode.options.description() {
+        Some(options) => format!(" {} ({}) ", mode, options),
+        None => format!(" {} ", mode),
+    };
     let search_input = format!(
         " {}",
         mode.input.as_ref().unwrap_or(&String::new())
@@ -1,7 +1,7 @@
 use crate::errors::*;
 use std::cmp;
 use std::fmt::Display;
-use crate::models::application::modes::{SearchSelectMode};
+use crate::models::application::modes::{RenderedPreview, SearchSelectMode};
 use pad::PadStr;
 use crate::presenters::current_buffer_status_line_data;
 use scribe::Workspace;
@@ -9,6 +9,9 @@ use scribe::buffer::Position;
 use crate::view::{Colors, StatusLineData, Style, View};
 use unicode_segmentation::UnicodeSegmentation;
 
+// Narrower screens don't have room for a preview alongside the results.
+const MIN_PREVIEW_WIDTH: usize = 60;
+
 pub fn display<T: Display>(workspace: &mut Workspace, mode: &mut SearchSelectMode<T>, view: &mut View) -> Result<()> {
     let mut presenter = view.build_presenter()?;
     let mode_config = mode.config().clone();
@@ -86,6 +89,45 @@ pub fn display<T: Display>(workspace: &mut Workspace, mode: &mut SearchSelectMod
                colors,
                &padded_content);
 
+    // Preview the selected result in a pane covering the right half of the screen.
+    if mode_config.preview && presenter.width() >= MIN_PREVIEW_WIDTH {
+        if let Some(selection) = mode.selection().map(|result| result.to_string()) {
+            // Previews are only rebuilt when the selection (or the theme
+            // they're highlighted with, once loaded) changes.
+            let key = format!("{}\n{}", presenter.theme_name().unwrap_or_default(), selection);
+            let current = mode.preview_cache().map(|cache| cache.is_for(&key)).unwrap_or(false);
+            if !current {
+                let preview = mode.preview().map(|preview| {
+                    let syntax_set = &workspace.syntax_set;
+                    let syntax = preview.path
+                        .as_ref()
+                        .and_then(|path| path.extension())
+                        .and_then(|extension| syntax_set.find_syntax_by_extension(&extension.to_string_lossy()))
+                        .or_else(|| {
+                            preview.content.lines().nth(0).and_then(|line| syntax_set.find_syntax_by_first_line(line))
+                        })
+                        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+                    RenderedPreview {
+                        title: preview.path
+                            .as_ref()
+                            .map(|path| format!(" {}", path.to_string_lossy()))
+                            .unwrap_or_default(),
+                        lines: presenter.highlight(&preview.content, syntax),
+                    }
+                });
+                if let Some(cache) = mode.preview_cache() {
+                    cache.store(key, preview);
+                }
+            }
+
+            let offset = presenter.width() / 2;
+            if let Some(preview) = mode.preview_cache().and_then(|cache| cache.preview()) {
+                presenter.print_pane(offset, &preview.title, &preview.lines);
+            }
+        }
+    }
+
     // Place the cursor on the search input line, right after its contents.
     presenter.set_cursor(Some(Position {
         line: mode_config.max_results,
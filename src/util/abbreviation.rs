@@ -0,0 +1,66 @@
+use scribe::buffer::Range;
+use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// An abbreviation that's been expanded, which can be reverted
+/// to what was typed, if it wasn't meant to be expanded.
+pub struct Expansion {
+    pub range: Range,
+    pub expanded: String,
+    pub typed: String,
+}
+
+/// Whether typing the character after an abbreviation expands it.
+/// Word characters continue the word, rather than ending it.
+pub fn is_trigger(character: char) -> bool {
+    !(character.is_alphanumeric() || character == '_')
+}
+
+/// The abbreviation at the end of the text, as its length (in graphemes)
+/// and expansion. Abbreviations can be whitespace-separated tokens (e.g.
+/// ";so"), or words at the end of them (e.g. "teh" in "(teh").
+pub fn find<'a>(text: &str, abbreviations: &'a HashMap<String, String>) -> Option<(usize, &'a str)> {
+    let token = text.rsplit(|character: char| character.is_whitespace()).next().unwrap_or("");
+    let word_start = token
+        .char_indices()
+        .rev()
+        .take_while(|&(_, character)| !is_trigger(character))
+        .last()
+        .map(|(index, _)| index)
+        .unwrap_or_else(|| token.len());
+    let word = &token[word_start..];
+
+    [token, word]
+        .iter()
+        .filter(|candidate| !candidate.is_empty())
+        .filter_map(|candidate| {
+            abbreviations
+                .get(*candidate)
+                .map(|expansion| (candidate.graphemes(true).count(), expansion.as_str()))
+        })
+        .next()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::find;
+
+    fn abbreviations() -> HashMap<String, String> {
+        let mut abbreviations = HashMap::new();
+        abbreviations.insert(String::from("teh"), String::from("the"));
+        abbreviations.insert(String::from(";so"), String::from("Stack Overflow"));
+
+        abbreviations
+    }
+
+    #[test]
+    fn find_matches_whole_tokens_and_words_at_their_end() {
+        let abbreviations = abbreviations();
+
+        assert_eq!(find("see ;so", &abbreviations), Some((3, "Stack Overflow")));
+        assert_eq!(find("(teh", &abbreviations), Some((3, "the")));
+        assert_eq!(find("tteh", &abbreviations), None);
+        assert_eq!(find("teh ", &abbreviations), None);
+    }
+}
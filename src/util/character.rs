@@ -0,0 +1,155 @@
+/// Names of the characters most likely to need inspecting: those that are
+/// invisible, easily mistaken for others, or otherwise ambiguous. Names of
+/// ASCII letters, digits, and control characters are derived, instead.
+const NAMES: [(char, &str); 72] = [
+    (' ', "SPACE"),
+    ('!', "EXCLAMATION MARK"),
+    ('"', "QUOTATION MARK"),
+    ('#', "NUMBER SIGN"),
+    ('$', "DOLLAR SIGN"),
+    ('%', "PERCENT SIGN"),
+    ('&', "AMPERSAND"),
+    ('\'', "APOSTROPHE"),
+    ('(', "LEFT PARENTHESIS"),
+    (')', "RIGHT PARENTHESIS"),
+    ('*', "ASTERISK"),
+    ('+', "PLUS SIGN"),
+    (',', "COMMA"),
+    ('-', "HYPHEN-MINUS"),
+    ('.', "FULL STOP"),
+    ('/', "SOLIDUS"),
+    (':', "COLON"),
+    (';', "SEMICOLON"),
+    ('<', "LESS-THAN SIGN"),
+    ('=', "EQUALS SIGN"),
+    ('>', "GREATER-THAN SIGN"),
+    ('?', "QUESTION MARK"),
+    ('@', "COMMERCIAL AT"),
+    ('[', "LEFT SQUARE BRACKET"),
+    ('\\', "REVERSE SOLIDUS"),
+    (']', "RIGHT SQUARE BRACKET"),
+    ('^', "CIRCUMFLEX ACCENT"),
+    ('_', "LOW LINE"),
+    ('`', "GRAVE ACCENT"),
+    ('{', "LEFT CURLY BRACKET"),
+    ('|', "VERTICAL LINE"),
+    ('}', "RIGHT CURLY BRACKET"),
+    ('~', "TILDE"),
+    ('\u{00A0}', "NO-BREAK SPACE"),
+    ('\u{00AD}', "SOFT HYPHEN"),
+    ('\u{0300}', "COMBINING GRAVE ACCENT"),
+    ('\u{0301}', "COMBINING ACUTE ACCENT"),
+    ('\u{0308}', "COMBINING DIAERESIS"),
+    ('\u{034F}', "COMBINING GRAPHEME JOINER"),
+    ('\u{0391}', "GREEK CAPITAL LETTER ALPHA"),
+    ('\u{039F}', "GREEK CAPITAL LETTER OMICRON"),
+    ('\u{03BF}', "GREEK SMALL LETTER OMICRON"),
+    ('\u{0410}', "CYRILLIC CAPITAL LETTER A"),
+    ('\u{0430}', "CYRILLIC SMALL LETTER A"),
+    ('\u{0435}', "CYRILLIC SMALL LETTER IE"),
+    ('\u{043E}', "CYRILLIC SMALL LETTER O"),
+    ('\u{0440}', "CYRILLIC SMALL LETTER ER"),
+    ('\u{0441}', "CYRILLIC SMALL LETTER ES"),
+    ('\u{0445}', "CYRILLIC SMALL LETTER HA"),
+    ('\u{0456}', "CYRILLIC SMALL LETTER BYELORUSSIAN-UKRAINIAN I"),
+    ('\u{2002}', "EN SPACE"),
+    ('\u{2003}', "EM SPACE"),
+    ('\u{2009}', "THIN SPACE"),
+    ('\u{200B}', "ZERO WIDTH SPACE"),
+    ('\u{200C}', "ZERO WIDTH NON-JOINER"),
+    ('\u{200D}', "ZERO WIDTH JOINER"),
+    ('\u{200E}', "LEFT-TO-RIGHT MARK"),
+    ('\u{200F}', "RIGHT-TO-LEFT MARK"),
+    ('\u{2010}', "HYPHEN"),
+    ('\u{2013}', "EN DASH"),
+    ('\u{2014}', "EM DASH"),
+    ('\u{2018}', "LEFT SINGLE QUOTATION MARK"),
+    ('\u{2019}', "RIGHT SINGLE QUOTATION MARK"),
+    ('\u{201C}', "LEFT DOUBLE QUOTATION MARK"),
+    ('\u{201D}', "RIGHT DOUBLE QUOTATION MARK"),
+    ('\u{2026}', "HORIZONTAL ELLIPSIS"),
+    ('\u{202E}', "RIGHT-TO-LEFT OVERRIDE"),
+    ('\u{2060}', "WORD JOINER"),
+    ('\u{2212}', "MINUS SIGN"),
+    ('\u{3000}', "IDEOGRAPHIC SPACE"),
+    ('\u{FE0F}', "VARIATION SELECTOR-16"),
+    ('\u{FEFF}', "ZERO WIDTH NO-BREAK SPACE"),
+];
+
+/// Names of the ASCII control characters, indexed by codepoint.
+const CONTROL_NAMES: [&str; 32] = [
+    "NULL", "START OF HEADING", "START OF TEXT", "END OF TEXT", "END OF TRANSMISSION",
+    "ENQUIRY", "ACKNOWLEDGE", "BELL", "BACKSPACE", "CHARACTER TABULATION", "LINE FEED",
+    "LINE TABULATION", "FORM FEED", "CARRIAGE RETURN", "SHIFT OUT", "SHIFT IN",
+    "DATA LINK ESCAPE", "DEVICE CONTROL ONE", "DEVICE CONTROL TWO", "DEVICE CONTROL THREE",
+    "DEVICE CONTROL FOUR", "NEGATIVE ACKNOWLEDGE", "SYNCHRONOUS IDLE",
+    "END OF TRANSMISSION BLOCK", "CANCEL", "END OF MEDIUM", "SUBSTITUTE", "ESCAPE",
+    "INFORMATION SEPARATOR FOUR", "INFORMATION SEPARATOR THREE",
+    "INFORMATION SEPARATOR TWO", "INFORMATION SEPARATOR ONE",
+];
+
+const DIGIT_NAMES: [&str; 10] = [
+    "ZERO", "ONE", "TWO", "THREE", "FOUR", "FIVE", "SIX", "SEVEN", "EIGHT", "NINE",
+];
+
+/// The Unicode name of the character, if it's one that's known.
+pub fn name(character: char) -> Option<String> {
+    let codepoint = character as u32;
+
+    if codepoint < 0x20 {
+        Some(CONTROL_NAMES[codepoint as usize].to_string())
+    } else if codepoint == 0x7F {
+        Some(String::from("DELETE"))
+    } else if character.is_ascii_uppercase() {
+        Some(format!("LATIN CAPITAL LETTER {}", character))
+    } else if character.is_ascii_lowercase() {
+        Some(format!("LATIN SMALL LETTER {}", character.to_ascii_uppercase()))
+    } else if character.is_ascii_digit() {
+        Some(format!("DIGIT {}", DIGIT_NAMES[(codepoint - 0x30) as usize]))
+    } else if (0x4E00 <= codepoint && codepoint <= 0x9FFF) || (0x3400 <= codepoint && codepoint <= 0x4DBF) {
+        Some(format!("CJK UNIFIED IDEOGRAPH-{:04X}", codepoint))
+    } else {
+        NAMES
+            .iter()
+            .find(|&&(named, _)| named == character)
+            .map(|&(_, name)| name.to_string())
+    }
+}
+
+/// Describes the grapheme's composition: each of its codepoints (along
+/// with their names, where known), followed by its UTF-8 encoding.
+pub fn describe(grapheme: &str) -> String {
+    let codepoints: Vec<String> = grapheme.chars().map(|character| {
+        match name(character) {
+            Some(name) => format!("U+{:04X} {}", character as u32, name),
+            None => format!("U+{:04X}", character as u32),
+        }
+    }).collect();
+    let bytes: Vec<String> = grapheme.bytes().map(|byte| format!("{:02X}", byte)).collect();
+
+    format!("{} | UTF-8: {}", codepoints.join(" + "), bytes.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe, name};
+
+    #[test]
+    fn name_covers_ascii_and_look_alike_characters() {
+        assert_eq!(name('a'), Some(String::from("LATIN SMALL LETTER A")));
+        assert_eq!(name('7'), Some(String::from("DIGIT SEVEN")));
+        assert_eq!(name('\t'), Some(String::from("CHARACTER TABULATION")));
+        assert_eq!(name('\u{0430}'), Some(String::from("CYRILLIC SMALL LETTER A")));
+        assert_eq!(name('\u{4E2D}'), Some(String::from("CJK UNIFIED IDEOGRAPH-4E2D")));
+        assert_eq!(name('\u{1F600}'), None);
+    }
+
+    #[test]
+    fn describe_lists_each_codepoint_and_the_utf8_bytes() {
+        assert_eq!(
+            describe("e\u{0301}"),
+            "U+0065 LATIN SMALL LETTER E + U+0301 COMBINING ACUTE ACCENT | UTF-8: 65 CC 81"
+        );
+        assert_eq!(describe("\u{200B}"), "U+200B ZERO WIDTH SPACE | UTF-8: E2 80 8B");
+    }
+}
@@ -0,0 +1,68 @@
+use regex::Regex;
+
+lazy_static! {
+    // Hex colors can't follow a word character or an ampersand, to rule out
+    // things like anchors in URLs and HTML character references ("&#123;").
+    static ref HEX_COLOR: Regex =
+        Regex::new(r"(?:^|[^\w&])#([0-9a-fA-F]{8}|[0-9a-fA-F]{6}|[0-9a-fA-F]{3,4})\b").unwrap();
+    static ref RGB_COLOR: Regex =
+        Regex::new(r"\brgba?\(\s*(\d{1,3})\s*,\s*(\d{1,3})\s*,\s*(\d{1,3})\s*(?:,\s*[\d.]+%?\s*)?\)").unwrap();
+}
+
+/// The colors written in the line as hex values (e.g. "#ff8800" or "#f80")
+/// or CSS rgb() and rgba() values, in the order they appear in it. Alpha
+/// components are ignored, as are rgb() values with components over 255.
+pub fn color_values(line: &str) -> Vec<(u8, u8, u8)> {
+    let mut colors = Vec::new();
+
+    for captures in HEX_COLOR.captures_iter(line) {
+        if let (Some((start, _)), Some(digits)) = (captures.pos(1), captures.at(1)) {
+            if let Some(color) = parse_hex(digits) {
+                colors.push((start, color));
+            }
+        }
+    }
+
+    for captures in RGB_COLOR.captures_iter(line) {
+        let components: Vec<u8> = (1..4)
+            .filter_map(|index| captures.at(index))
+            .filter_map(|component| component.parse().ok())
+            .collect();
+        if let (Some((start, _)), 3) = (captures.pos(0), components.len()) {
+            colors.push((start, (components[0], components[1], components[2])));
+        }
+    }
+
+    colors.sort_by_key(|&(start, _)| start);
+    colors.into_iter().map(|(_, color)| color).collect()
+}
+
+// Parses three, four, six, or eight hex digits, with the shorter forms
+// repeating each digit (i.e. "f80" is the same as "ff8800").
+fn parse_hex(digits: &str) -> Option<(u8, u8, u8)> {
+    let digits: String = if digits.len() <= 4 {
+        digits.chars().flat_map(|digit| vec![digit, digit]).collect()
+    } else {
+        digits.to_string()
+    };
+    let component = |index: usize| u8::from_str_radix(&digits[index * 2..index * 2 + 2], 16).ok();
+
+    Some((component(0)?, component(1)?, component(2)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::color_values;
+
+    #[test]
+    fn color_values_finds_hex_and_rgb_colors_in_order() {
+        let line = "color: rgb(255, 0, 10); background: #ff8800 url(page#top) #F80a;";
+
+        assert_eq!(color_values(line), vec![(255, 0, 10), (255, 136, 0), (255, 136, 0)]);
+    }
+
+    #[test]
+    fn color_values_skips_invalid_values() {
+        assert!(color_values("rgb(256, 0, 0) #ff88x &#123; #12345 issue#fff").is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::Path;
+
+/// The syntax definition used for commit messages, bundled with the editor.
+pub const SYNTAX: &str = include_str!("../syntaxes/git_commit_message.sublime-syntax");
+pub const SYNTAX_NAME: &str = "Git Commit Message";
+
+/// Where the conventional summary and body line lengths end.
+pub const LINE_LENGTH_GUIDES: [usize; 2] = [50, 72];
+
+const FILE_NAMES: [&str; 2] = ["COMMIT_EDITMSG", "MERGE_MSG"];
+const CHANGES_HEADING: &str = "# Changes to be committed:";
+
+/// Whether the file is one git asks for a commit message
+/// with (e.g. when the editor is run as `$EDITOR`).
+pub fn is_commit_message(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| FILE_NAMES.contains(&name))
+        .unwrap_or(false)
+}
+
+/// The message git will use, without its comments (and the diff
+/// below the scissors line added by `git commit -v`).
+pub fn message(data: &str) -> String {
+    let lines: Vec<&str> = data
+        .lines()
+        .take_while(|line| !is_scissors_line(line))
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+
+    lines.join("\n").trim().to_string()
+}
+
+/// Summarizes the changes being committed, based on the comments git adds to
+/// the message: the diff included by `git commit -v`, if there is one (e.g.
+/// "2 files changed, 10 insertions(+), 1 deletion(-)"), or otherwise the
+/// list of changes to be committed (e.g. "2 files to be committed").
+pub fn summary(data: &str) -> Option<String> {
+    let mut lines = data.lines().skip_while(|line| !is_scissors_line(line)).peekable();
+    if lines.peek().is_some() {
+        let (mut files, mut insertions, mut deletions) = (0, 0, 0);
+        for line in lines {
+            if line.starts_with("diff --git ") {
+                files += 1;
+            } else if line.starts_with('+') && !line.starts_with("+++") {
+                insertions += 1;
+            } else if line.starts_with('-') && !line.starts_with("---") {
+                deletions += 1;
+            }
+        }
+
+        let mut summary = vec![count(files, "file changed", "files changed")];
+        if insertions > 0 {
+            summary.push(count(insertions, "insertion(+)", "insertions(+)"));
+        }
+        if deletions > 0 {
+            summary.push(count(deletions, "deletion(-)", "deletions(-)"));
+        }
+
+        return Some(summary.join(", "));
+    }
+
+    // Changes are listed one per line (e.g. "#\tmodified:   src/main.rs"),
+    // until a line with nothing but the comment character.
+    let files = data
+        .lines()
+        .skip_while(|line| !line.starts_with(CHANGES_HEADING))
+        .skip(1)
+        .take_while(|line| line.trim() != "#")
+        .count();
+    if files == 0 {
+        return None;
+    }
+
+    Some(count(files, "file to be committed", "files to be committed"))
+}
+
+fn is_scissors_line(line: &str) -> bool {
+    line.starts_with("# -") && line.contains(" >8 ")
+}
+
+fn count(count: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", count, if count == 1 { singular } else { plural })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::{is_commit_message, message, summary};
+
+    const MESSAGE: &str = "Fix the thing\n\n\
+        # Please enter the commit message for your changes.\n\
+        #\n\
+        # Changes to be committed:\n\
+        #\tmodified:   src/main.rs\n\
+        #\tnew file:   src/util/thing.rs\n\
+        #\n";
+
+    #[test]
+    fn message_and_summary_ignore_the_comment_block() {
+        assert!(is_commit_message(Path::new("/amp/.git/COMMIT_EDITMSG")));
+        assert!(!is_commit_message(Path::new("/amp/src/main.rs")));
+
+        assert_eq!(message(MESSAGE), "Fix the thing");
+        assert_eq!(message("\n# Please enter the commit message\n"), "");
+        assert_eq!(summary(MESSAGE), Some(String::from("2 files to be committed")));
+        assert_eq!(summary("Fix the thing\n"), None);
+    }
+
+    #[test]
+    fn summary_counts_changes_in_verbose_diffs() {
+        let data = format!(
+            "{}# ------------------------ >8 ------------------------\n\
+            # Do not modify or remove the line above.\n\
+            diff --git a/src/main.rs b/src/main.rs\n\
+            --- a/src/main.rs\n\
+            +++ b/src/main.rs\n\
+            @@ -1,2 +1,2 @@\n\
+            -fn main() {{}}\n\
+            +fn main() {{ run(); }}\n\
+            +\n",
+            MESSAGE
+        );
+
+        assert_eq!(message(&data), "Fix the thing");
+        assert_eq!(summary(&data), Some(String::from("1 file changed, 2 insertions(+), 1 deletion(-)")));
+    }
+}
@@ -0,0 +1,233 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Tab completion state for prompts, reusable across modes. The first request
+/// extends the input as far as the candidates agree; subsequent requests
+/// cycle through the candidates, which are kept around for display.
+#[derive(Default)]
+pub struct Completions {
+    pub candidates: Vec<String>,
+    pub selected: Option<usize>,
+    pub show_hidden: bool,
+}
+
+impl Completions {
+    pub fn new() -> Completions {
+        Completions::default()
+    }
+
+    /// Returns a replacement for the word being completed, if there is one.
+    /// Unique matches are returned as-is, and discard the candidate list.
+    pub fn complete(&mut self, word: &str, candidates: Vec<String>) -> Option<String> {
+        // Keep cycling if the word hasn't changed since the last completion.
+        if self.cycling(word) {
+            let selected = self.selected.map(|index| (index + 1) % self.candidates.len()).unwrap_or(0);
+            self.selected = Some(selected);
+
+            return Some(self.candidates[selected].clone());
+        }
+
+        self.clear();
+        match candidates.len() {
+            0 => None,
+            1 => candidates.into_iter().nth(0),
+            _ => {
+                let prefix = common_prefix(&candidates);
+                self.candidates = candidates;
+
+                Some(prefix)
+            }
+        }
+    }
+
+    /// Discards the candidate list; used when the input changes.
+    pub fn clear(&mut self) {
+        self.candidates.clear();
+        self.selected = None;
+    }
+
+    pub fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.clear();
+    }
+
+    fn cycling(&self, word: &str) -> bool {
+        if self.candidates.is_empty() {
+            return false;
+        }
+
+        match self.selected {
+            Some(index) => self.candidates[index] == word,
+            None => common_prefix(&self.candidates) == word,
+        }
+    }
+}
+
+/// Returns the candidates beginning with the specified prefix, sorted.
+pub fn matching<T: AsRef<str>>(prefix: &str, candidates: &[T]) -> Vec<String> {
+    let mut matches: Vec<String> = candidates
+        .iter()
+        .map(|candidate| candidate.as_ref())
+        .filter(|candidate| candidate.starts_with(prefix))
+        .map(String::from)
+        .collect();
+    matches.sort();
+    matches.dedup();
+
+    matches
+}
+
+/// Finds the paths that could complete the partially-typed path, relative to
+/// the current directory. Directories are suffixed with a slash, so that they
+/// can be completed further. Hidden files are only included if requested, or
+/// if the partial file name starts with a dot. A leading tilde refers to the
+/// home directory, and is preserved in the results.
+pub fn paths(partial: &str, show_hidden: bool) -> Vec<String> {
+    let (directory, file_prefix) = match partial.rfind('/') {
+        Some(index) => (&partial[..index + 1], &partial[index + 1..]),
+        None => ("", partial),
+    };
+    let directory_path = if directory.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand_home(directory)
+    };
+    let show_hidden = show_hidden || file_prefix.starts_with('.');
+
+    let entries = match fs::read_dir(directory_path) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut paths: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with(file_prefix) || (name.starts_with('.') && !show_hidden) {
+                return None;
+            }
+            let suffix = if entry.path().is_dir() { "/" } else { "" };
+
+            Some(format!("{}{}{}", directory, name, suffix))
+        })
+        .collect();
+    paths.sort();
+
+    paths
+}
+
+/// Replaces a leading tilde with the user's home directory, if it's known.
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = env::var_os("HOME");
+
+    match home {
+        Some(ref home) if path == "~" => PathBuf::from(home),
+        Some(ref home) if path.starts_with("~/") => Path::new(home).join(&path[2..]),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Abbreviates the user's home directory to a tilde, undoing `expand_home`.
+pub fn collapse_home(path: &Path) -> String {
+    let relative_path = env::var_os("HOME").and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf));
+
+    match relative_path {
+        Some(ref relative_path) if relative_path.as_os_str().is_empty() => String::from("~"),
+        Some(relative_path) => format!("~/{}", relative_path.to_string_lossy()),
+        None => path.to_string_lossy().into_owned(),
+    }
+}
+
+/// The longest prefix shared by all of the candidates.
+pub fn common_prefix(candidates: &[String]) -> String {
+    let mut candidates = candidates.iter();
+    let mut prefix = match candidates.next() {
+        Some(candidate) => candidate.clone(),
+        None => return String::new(),
+    };
+
+    for candidate in candidates {
+        let length = prefix
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|&((_, a), b)| a != b)
+            .map(|((index, _), _)| index)
+            .unwrap_or_else(|| prefix.len().min(candidate.len()));
+        prefix.truncate(length);
+    }
+
+    prefix
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::PathBuf;
+    use super::{collapse_home, common_prefix, Completions, expand_home, matching, paths};
+
+    #[test]
+    fn complete_extends_to_common_prefix_and_then_cycles_through_candidates() {
+        let mut completions = Completions::new();
+        let candidates = vec![String::from("amp"), String::from("ample")];
+
+        assert_eq!(completions.complete("a", candidates.clone()), Some(String::from("amp")));
+        assert_eq!(completions.complete("amp", candidates.clone()), Some(String::from("amp")));
+        assert_eq!(completions.complete("amp", candidates.clone()), Some(String::from("ample")));
+        assert_eq!(completions.complete("ample", candidates.clone()), Some(String::from("amp")));
+        assert_eq!(completions.selected, Some(0));
+    }
+
+    #[test]
+    fn complete_discards_candidates_for_unique_matches() {
+        let mut completions = Completions::new();
+
+        assert_eq!(completions.complete("a", vec![String::from("amp")]), Some(String::from("amp")));
+        assert!(completions.candidates.is_empty());
+        assert_eq!(completions.complete("x", Vec::new()), None);
+    }
+
+    #[test]
+    fn matching_returns_sorted_candidates_with_prefix() {
+        let candidates = ["write", "buffer::save", "wq", "edit"];
+
+        assert_eq!(matching("w", &candidates), vec!["wq", "write"]);
+    }
+
+    #[test]
+    fn paths_completes_files_and_directories() {
+        assert_eq!(paths("src/commands/cur", false), vec!["src/commands/cursor.rs"]);
+        assert_eq!(paths("src/comm", false), vec!["src/commands/"]);
+    }
+
+    #[test]
+    fn paths_only_includes_hidden_files_when_requested() {
+        assert!(!paths("", false).contains(&String::from(".gitignore")));
+        assert!(paths("", true).contains(&String::from(".gitignore")));
+        assert!(paths(".git", false).contains(&String::from(".gitignore")));
+    }
+
+    #[test]
+    fn expand_home_replaces_leading_tilde() {
+        let home = PathBuf::from(env::var_os("HOME").unwrap());
+
+        assert_eq!(expand_home("~/amp"), home.join("amp"));
+        assert_eq!(expand_home("amp/~"), PathBuf::from("amp/~"));
+    }
+
+    #[test]
+    fn collapse_home_abbreviates_the_home_directory() {
+        let home = PathBuf::from(env::var_os("HOME").unwrap());
+
+        assert_eq!(collapse_home(&home), "~");
+        assert_eq!(collapse_home(&home.join("amp")), "~/amp");
+        assert_eq!(collapse_home(&PathBuf::from("/amp")), "/amp");
+    }
+
+    #[test]
+    fn common_prefix_finds_shared_prefix() {
+        let candidates = vec![String::from("buffer::save"), String::from("buffer::select")];
+
+        assert_eq!(common_prefix(&candidates), "buffer::s");
+        assert_eq!(common_prefix(&[]), "");
+    }
+}
@@ -0,0 +1,118 @@
+use std::ops::Range;
+
+const START_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR: &str = "=======";
+const END_MARKER: &str = ">>>>>>>";
+
+/// Portions of a merge conflict, as they relate to a single line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConflictSection {
+    Marker,
+    Ours,
+    Base,
+    Theirs,
+}
+
+/// Line numbers for the markers of a merge conflict. The base marker is
+/// only present for conflicts written using git's "diff3" conflict style.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub start: usize,
+    pub base: Option<usize>,
+    pub separator: usize,
+    pub end: usize,
+}
+
+impl Conflict {
+    pub fn section(&self, line: usize) -> Option<ConflictSection> {
+        if line < self.start || line > self.end {
+            None
+        } else if line == self.start || line == self.separator || line == self.end || Some(line) == self.base {
+            Some(ConflictSection::Marker)
+        } else if line > self.separator {
+            Some(ConflictSection::Theirs)
+        } else if self.base.map(|base| line > base).unwrap_or(false) {
+            Some(ConflictSection::Base)
+        } else {
+            Some(ConflictSection::Ours)
+        }
+    }
+
+    /// Line numbers for our side of the conflict, excluding markers.
+    pub fn ours(&self) -> Range<usize> {
+        self.start + 1..self.base.unwrap_or(self.separator)
+    }
+
+    /// Line numbers for their side of the conflict, excluding markers.
+    pub fn theirs(&self) -> Range<usize> {
+        self.separator + 1..self.end
+    }
+}
+
+/// Finds all complete merge conflicts in the provided content.
+/// Markers without a matching separator and end marker are ignored.
+pub fn conflicts(data: &str) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+
+    // Bail out early for the common case.
+    if !data.contains(START_MARKER) {
+        return conflicts;
+    }
+
+    let mut start = None;
+    let mut base = None;
+    let mut separator = None;
+    for (line_number, line) in data.lines().enumerate() {
+        if line.starts_with(START_MARKER) {
+            start = Some(line_number);
+            base = None;
+            separator = None;
+        } else if start.is_none() {
+            continue;
+        } else if line.starts_with(BASE_MARKER) && separator.is_none() {
+            base = Some(line_number);
+        } else if line.trim_end() == SEPARATOR && separator.is_none() {
+            separator = Some(line_number);
+        } else if line.starts_with(END_MARKER) {
+            if let (Some(start), Some(separator)) = (start, separator) {
+                conflicts.push(Conflict{ start, base, separator, end: line_number });
+            }
+            start = None;
+        }
+    }
+
+    conflicts
+}
+
+pub fn conflict_at(conflicts: &[Conflict], line: usize) -> Option<&Conflict> {
+    conflicts.iter().find(|conflict| conflict.section(line).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Conflict, ConflictSection, conflicts};
+
+    #[test]
+    fn conflicts_finds_complete_conflicts() {
+        let data = "amp\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n<<<<<<< HEAD\nincomplete\n";
+
+        assert_eq!(conflicts(data), vec![
+            Conflict{ start: 1, base: None, separator: 3, end: 5 }
+        ]);
+    }
+
+    #[test]
+    fn conflicts_supports_diff3_style_base_sections() {
+        let data = "<<<<<<< HEAD\nours\n||||||| base\nbase\n=======\ntheirs\n>>>>>>> branch\n";
+        let conflict = conflicts(data).remove(0);
+
+        assert_eq!(conflict.base, Some(2));
+        assert_eq!(conflict.ours(), 1..2);
+        assert_eq!(conflict.theirs(), 5..6);
+        assert_eq!(conflict.section(3), Some(ConflictSection::Base));
+        assert_eq!(conflict.section(4), Some(ConflictSection::Marker));
+        assert_eq!(conflict.section(5), Some(ConflictSection::Theirs));
+        assert_eq!(conflict.section(7), None);
+    }
+}
@@ -0,0 +1,89 @@
+use scribe::buffer::Position;
+use unicode_segmentation::UnicodeSegmentation;
+
+const PAIRS: [(&str, &str); 3] = [("(", ")"), ("[", "]"), ("{", "}")];
+
+/// The position of the delimiter paired with the one at the specified
+/// position. Like Vim's `%`, if there isn't a delimiter there, the next one
+/// on the same line is used instead. Nested pairs of the same kind are skipped.
+pub fn matching_delimiter(data: &str, position: &Position) -> Option<Position> {
+    let delimiters: Vec<(Position, &str)> = data
+        .lines()
+        .enumerate()
+        .flat_map(|(line, content)| {
+            content
+                .graphemes(true)
+                .enumerate()
+                .filter(|&(_, grapheme)| is_delimiter(grapheme))
+                .map(move |(offset, grapheme)| (Position{ line, offset }, grapheme))
+        })
+        .collect();
+
+    let index = delimiters
+        .iter()
+        .position(|&(delimiter_position, _)| delimiter_position >= *position)
+        .filter(|&index| delimiters[index].0.line == position.line)?;
+    let delimiter = delimiters[index].1;
+    let &(open, close) = PAIRS
+        .iter()
+        .find(|&&(open, close)| delimiter == open || delimiter == close)?;
+
+    let mut depth = 0;
+    let candidates: Box<Iterator<Item=&(Position, &str)>> = if delimiter == open {
+        Box::new(delimiters[index..].iter())
+    } else {
+        Box::new(delimiters[..index + 1].iter().rev())
+    };
+    for &(candidate_position, candidate) in candidates {
+        if candidate == delimiter {
+            depth += 1;
+        } else if candidate == open || candidate == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(candidate_position);
+            }
+        }
+    }
+
+    None
+}
+
+fn is_delimiter(grapheme: &str) -> bool {
+    PAIRS.iter().any(|&(open, close)| grapheme == open || grapheme == close)
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::Position;
+    use super::matching_delimiter;
+
+    #[test]
+    fn matching_delimiter_skips_nested_pairs_in_both_directions() {
+        let data = "fn amp(a: [u8]) {\n    (a)\n}";
+
+        assert_eq!(
+            matching_delimiter(data, &Position{ line: 0, offset: 16 }),
+            Some(Position{ line: 2, offset: 0 })
+        );
+        assert_eq!(
+            matching_delimiter(data, &Position{ line: 2, offset: 0 }),
+            Some(Position{ line: 0, offset: 16 })
+        );
+        assert_eq!(
+            matching_delimiter(data, &Position{ line: 0, offset: 14 }),
+            Some(Position{ line: 0, offset: 6 })
+        );
+    }
+
+    #[test]
+    fn matching_delimiter_uses_the_next_delimiter_on_the_line() {
+        let data = "amp(editor)\n()";
+
+        assert_eq!(
+            matching_delimiter(data, &Position{ line: 0, offset: 0 }),
+            Some(Position{ line: 0, offset: 10 })
+        );
+        assert_eq!(matching_delimiter(data, &Position{ line: 0, offset: 11 }), None);
+        assert_eq!(matching_delimiter("amp(", &Position{ line: 0, offset: 0 }), None);
+    }
+}
@@ -0,0 +1,130 @@
+use std::cmp;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Beyond this many line comparisons, a changed region is reported as a
+// single hunk rather than paying for the quadratic LCS table.
+const MAX_COMPARISONS: usize = 1_000_000;
+
+/// A contiguous set of differing lines, expressed as
+/// half-open line index ranges into each side of the diff.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hunk {
+    pub left: Range<usize>,
+    pub right: Range<usize>,
+}
+
+/// Compares two sequences of lines, returning the hunks that
+/// would need to change to turn the left side into the right.
+pub fn hunks<T: PartialEq>(left: &[T], right: &[T]) -> Vec<Hunk> {
+    // Common leading and trailing lines are unchanged; skip them.
+    let prefix = left.iter()
+        .zip(right.iter())
+        .take_while(|&(a, b)| a == b)
+        .count();
+    let suffix = left[prefix..].iter().rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|&(a, b)| a == b)
+        .count();
+    let left_end = left.len() - suffix;
+    let right_end = right.len() - suffix;
+    let a = &left[prefix..left_end];
+    let b = &right[prefix..right_end];
+
+    if a.is_empty() && b.is_empty() {
+        return Vec::new();
+    } else if a.len() * b.len() > MAX_COMPARISONS {
+        return vec![Hunk{ left: prefix..left_end, right: prefix..right_end }];
+    }
+
+    // Build an LCS table, where lengths[i * width + j]
+    // is the longest common subsequence of a[i..] and b[j..].
+    let width = b.len() + 1;
+    let mut lengths = vec![0; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i * width + j] = if a[i] == b[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                cmp::max(lengths[(i + 1) * width + j], lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    // Walk the table, grouping runs of unmatched lines into hunks.
+    let mut hunks = Vec::new();
+    let mut current: Option<Hunk> = None;
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            if let Some(hunk) = current.take() {
+                hunks.push(hunk);
+            }
+            i += 1;
+            j += 1;
+        } else {
+            let hunk = current.get_or_insert(Hunk{
+                left: prefix + i..prefix + i,
+                right: prefix + j..prefix + j,
+            });
+
+            if j >= b.len() || (i < a.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1]) {
+                i += 1;
+                hunk.left.end = prefix + i;
+            } else {
+                j += 1;
+                hunk.right.end = prefix + j;
+            }
+        }
+    }
+    if let Some(hunk) = current {
+        hunks.push(hunk);
+    }
+
+    hunks
+}
+
+/// Narrows a pair of changed lines down to the portions that differ,
+/// returning grapheme ranges that exclude their common prefix and suffix.
+pub fn changed_span(left: &str, right: &str) -> (Range<usize>, Range<usize>) {
+    let a: Vec<&str> = left.graphemes(true).collect();
+    let b: Vec<&str> = right.graphemes(true).collect();
+
+    let prefix = a.iter()
+        .zip(b.iter())
+        .take_while(|&(x, y)| x == y)
+        .count();
+    let suffix = a[prefix..].iter().rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|&(x, y)| x == y)
+        .count();
+
+    (prefix..a.len() - suffix, prefix..b.len() - suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{changed_span, hunks, Hunk};
+
+    #[test]
+    fn hunks_returns_nothing_for_identical_content() {
+        assert!(hunks(&["amp", "editor"], &["amp", "editor"]).is_empty());
+    }
+
+    #[test]
+    fn hunks_groups_changed_added_and_removed_lines() {
+        let left = ["a", "b", "c", "d", "e"];
+        let right = ["a", "x", "c", "e", "f"];
+
+        assert_eq!(hunks(&left, &right), vec![
+            Hunk{ left: 1..2, right: 1..2 },
+            Hunk{ left: 3..4, right: 3..3 },
+            Hunk{ left: 5..5, right: 4..5 },
+        ]);
+    }
+
+    #[test]
+    fn changed_span_excludes_common_prefix_and_suffix() {
+        assert_eq!(changed_span("let amp = 1;", "let editor = 1;"), (4..7, 4..10));
+    }
+}
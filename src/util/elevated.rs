@@ -0,0 +1,141 @@
+use crate::errors::*;
+use std::env;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The tool used to write files the user doesn't have permission to write.
+/// sudo is preferred; pkexec (polkit) is used on systems without it,
+/// relying on the desktop's authentication agent to prompt for a password.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Elevation {
+    Sudo,
+    Pkexec,
+}
+
+impl Elevation {
+    pub fn detect() -> Option<Elevation> {
+        if is_installed("sudo") {
+            Some(Elevation::Sudo)
+        } else if is_installed("pkexec") {
+            Some(Elevation::Pkexec)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a password has to be entered in the editor before writing.
+    /// sudo doesn't need one if it's been used recently (or is configured
+    /// not to ask); pkexec's authentication agent prompts on its own.
+    pub fn needs_password(self) -> bool {
+        match self {
+            Elevation::Sudo => !succeeds(Command::new("sudo").args(&["-n", "true"])),
+            Elevation::Pkexec => false,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Elevation::Sudo => "sudo",
+            Elevation::Pkexec => "pkexec",
+        }
+    }
+
+    /// Writes the content to the file with elevated privileges, leaving
+    /// an existing file's ownership and permissions as they were.
+    pub fn write(self, path: &Path, content: &str) -> Result<()> {
+        let (program, arguments) = self.write_command(path);
+        let mut command = Command::new(program);
+        command.args(&arguments);
+
+        run(command, content.as_bytes())
+            .chain_err(|| format!("Couldn't write {} using {}", path.to_string_lossy(), self.name()))
+    }
+
+    // The program and arguments used to write the file. tee truncates
+    // and writes to the file in place, rather than replacing it.
+    fn write_command(self, path: &Path) -> (&'static str, Vec<&OsStr>) {
+        let mut arguments = match self {
+            Elevation::Sudo => vec![OsStr::new("-n")],
+            Elevation::Pkexec => Vec::new(),
+        };
+        arguments.extend(&[OsStr::new("tee"), OsStr::new("--"), path.as_os_str()]);
+
+        (self.name(), arguments)
+    }
+}
+
+/// Caches sudo credentials using the password, so that the write that follows
+/// doesn't need it. Doing this separately keeps sudo from reading the file's
+/// content as further password attempts, should the password be wrong.
+pub fn authenticate(password: &str) -> Result<()> {
+    let mut command = Command::new("sudo");
+    command.args(&["-S", "-p", "", "-v"]);
+
+    run(command, format!("{}\n", password).as_bytes()).chain_err(|| "Couldn't authenticate using sudo")
+}
+
+// Runs the command with the input piped to it, discarding its output.
+// Passwords are only ever passed through pipes, never as arguments.
+fn run(mut command: Command, input: &[u8]) -> Result<()> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .chain_err(|| "Couldn't run the command")?;
+
+    // Errors writing the input (e.g. when the command exits early)
+    // are reported through its exit status, below.
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(input);
+    }
+
+    let output = child.wait_with_output().chain_err(|| "Couldn't run the command")?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(())
+}
+
+fn succeeds(command: &mut Command) -> bool {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn is_installed(program: &str) -> bool {
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|path| path.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+    use std::path::Path;
+    use super::Elevation;
+
+    #[test]
+    fn write_command_never_prompts_from_sudo() {
+        let path = Path::new("/etc/hosts");
+        let arguments = |arguments: &[&'static str]| -> Vec<&'static OsStr> {
+            arguments.iter().map(|&argument| OsStr::new(argument)).collect()
+        };
+
+        assert_eq!(
+            Elevation::Sudo.write_command(path),
+            ("sudo", arguments(&["-n", "tee", "--", "/etc/hosts"]))
+        );
+        assert_eq!(
+            Elevation::Pkexec.write_command(path),
+            ("pkexec", arguments(&["tee", "--", "/etc/hosts"]))
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use crate::errors::*;
+use scribe::Buffer;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+// Has GnuPG read the passphrase from the first line of its input.
+const PASSPHRASE_ARGUMENTS: [&str; 4] = ["--pinentry-mode", "loopback", "--passphrase-fd", "0"];
+
+// Key IDs that GnuPG reports for recipients it can't name.
+const HIDDEN_RECIPIENT: &str = "0000000000000000";
+
+/// The tools used to decrypt and re-encrypt files, based on their extension.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cipher {
+    Age,
+    Gpg,
+}
+
+impl Cipher {
+    pub fn for_path(path: &Path) -> Option<Cipher> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("age") => Some(Cipher::Age),
+            Some("gpg") => Some(Cipher::Gpg),
+            _ => None,
+        }
+    }
+
+    /// GnuPG files are decrypted using a passphrase (the file's own, or that of
+    /// the private key it was encrypted to), entered in the editor; age files
+    /// use the identity file configured in the preferences.
+    pub fn needs_passphrase(self) -> bool {
+        self == Cipher::Gpg
+    }
+}
+
+/// What decrypting or encrypting a file requires: either a
+/// passphrase (for GnuPG) or an identity file (for age). GnuPG
+/// files encrypted to public keys are re-encrypted to the same
+/// keys (identified by their IDs), which doesn't need a passphrase.
+pub enum Secret<'a> {
+    Identity(&'a Path),
+    Passphrase(&'a str),
+    Recipients(&'a [String]),
+}
+
+/// A file's decrypted content, along with the IDs of the public keys
+/// it was encrypted to, if it's a GnuPG file that wasn't encrypted
+/// using a passphrase (those are re-encrypted the same way on save).
+pub struct Decrypted {
+    pub content: String,
+    pub recipients: Vec<String>,
+}
+
+/// Whether the buffer holds decrypted content, which
+/// should never be written anywhere but its own file.
+pub fn is_encrypted(buffer: &Buffer) -> bool {
+    buffer.path.as_ref().and_then(|path| Cipher::for_path(path)).is_some()
+}
+
+/// Decrypts the file at the specified path, without writing its plaintext anywhere.
+pub fn decrypt(path: &Path, secret: &Secret) -> Result<Decrypted> {
+    let cipher = Cipher::for_path(path).ok_or("The file isn't encrypted")?;
+    let (command, input) = match (cipher, secret) {
+        (Cipher::Gpg, &Secret::Passphrase(passphrase)) => {
+            // Status lines (written to stderr) list the keys the file was
+            // encrypted to; the passphrase unlocks the private key, if so.
+            let mut command = gpg_command();
+            command.args(&PASSPHRASE_ARGUMENTS);
+            command.arg("--status-fd").arg("2").arg("--decrypt").arg(path);
+            (command, format!("{}\n", passphrase).into_bytes())
+        },
+        (Cipher::Age, &Secret::Identity(identity)) => {
+            let mut command = Command::new("age");
+            command.arg("--decrypt").arg("--identity").arg(identity).arg(path);
+            (command, Vec::new())
+        },
+        _ => bail!("The wrong kind of secret was provided to decrypt the file"),
+    };
+
+    let output = run(command, &input)?;
+    let content = String::from_utf8(output.stdout).chain_err(|| "The decrypted content isn't valid UTF-8")?;
+    let recipients = match cipher {
+        Cipher::Gpg => recipients(&String::from_utf8_lossy(&output.stderr)),
+        Cipher::Age => Vec::new(),
+    };
+
+    Ok(Decrypted{ content, recipients })
+}
+
+/// Encrypts the content and writes it to the specified path, which is
+/// only replaced once the encryption tool has finished successfully.
+pub fn encrypt(path: &Path, secret: &Secret, content: &str) -> Result<()> {
+    let cipher = Cipher::for_path(path).ok_or("The file isn't encrypted")?;
+    let (command, input) = match (cipher, secret) {
+        (Cipher::Gpg, &Secret::Passphrase(passphrase)) => {
+            // GnuPG reads the passphrase from the first line of its input,
+            // treating the rest of it as the content to be encrypted.
+            let mut command = gpg_command();
+            command.args(&gpg_encryption_arguments(&[])?);
+            (command, format!("{}\n{}", passphrase, content).into_bytes())
+        },
+        (Cipher::Gpg, &Secret::Recipients(recipients)) => {
+            let mut command = gpg_command();
+            command.args(&gpg_encryption_arguments(recipients)?);
+            (command, content.as_bytes().to_vec())
+        },
+        (Cipher::Age, &Secret::Identity(identity)) => {
+            let mut command = Command::new("age");
+            command.arg("--encrypt").arg("--identity").arg(identity);
+            (command, content.as_bytes().to_vec())
+        },
+        _ => bail!("The wrong kind of secret was provided to encrypt the file"),
+    };
+
+    let output = run(command, &input)?;
+    fs::write(path, output.stdout).chain_err(|| "Couldn't write the encrypted file")
+}
+
+/// Flags the buffer as matching its file. Saving it to the null device
+/// does that without leaving a plaintext copy of its content on disk.
+pub fn mark_saved(buffer: &mut Buffer) -> Result<()> {
+    let path = buffer.path.replace(PathBuf::from("/dev/null"));
+    let result = buffer.save();
+    buffer.path = path;
+
+    result.chain_err(|| "Couldn't mark the buffer as saved")
+}
+
+fn gpg_command() -> Command {
+    let mut command = Command::new("gpg");
+    command.args(&["--batch", "--quiet", "--yes"]);
+
+    command
+}
+
+// The arguments used to encrypt content read from the input: to the public
+// keys a file was encrypted to, if there are any, or using a passphrase.
+// Key IDs are marked with "!" so that the same (sub)keys are used again.
+fn gpg_encryption_arguments(recipients: &[String]) -> Result<Vec<String>> {
+    if recipients.is_empty() {
+        return Ok(PASSPHRASE_ARGUMENTS
+            .iter()
+            .chain(&["--symmetric", "--output", "-"])
+            .map(|argument| argument.to_string())
+            .collect());
+    }
+    if recipients.iter().any(|recipient| recipient == HIDDEN_RECIPIENT) {
+        bail!("The file was encrypted to a hidden recipient, so it can't be re-encrypted the same way");
+    }
+
+    // The keys were already trusted with this content; don't ask again.
+    let mut arguments: Vec<String> = ["--trust-model", "always", "--encrypt", "--output", "-"]
+        .iter()
+        .map(|argument| argument.to_string())
+        .collect();
+    for recipient in recipients {
+        arguments.push(String::from("--recipient"));
+        arguments.push(format!("{}!", recipient));
+    }
+
+    Ok(arguments)
+}
+
+// The IDs of the keys that GnuPG's status output says the file was encrypted
+// to. Files encrypted using a passphrase alone don't report any.
+fn recipients(status: &str) -> Vec<String> {
+    let mut recipients = Vec::new();
+    for line in status.lines() {
+        let mut fields = line.split_whitespace();
+        if let (Some("[GNUPG:]"), Some("ENC_TO"), Some(key_id)) = (fields.next(), fields.next(), fields.next()) {
+            if !recipients.iter().any(|recipient| recipient == key_id) {
+                recipients.push(key_id.to_string());
+            }
+        }
+    }
+
+    recipients
+}
+
+// Runs the command with the input piped to it, returning its output.
+// Secrets are only ever passed through pipes, never as arguments.
+fn run(mut command: Command, input: &[u8]) -> Result<Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .chain_err(|| "Couldn't run the encryption tool; is it installed?")?;
+
+    // Errors writing the input (e.g. when the tool exits early)
+    // are reported through its exit status, below.
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(input);
+    }
+
+    let output = child.wait_with_output().chain_err(|| "Couldn't run the encryption tool")?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        bail!("The encryption tool failed: {}", error.trim());
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::Buffer;
+    use std::path::{Path, PathBuf};
+    use super::{gpg_encryption_arguments, recipients, Cipher, is_encrypted, mark_saved};
+
+    #[test]
+    fn for_path_uses_the_extension() {
+        assert_eq!(Cipher::for_path(Path::new("notes.md.gpg")), Some(Cipher::Gpg));
+        assert_eq!(Cipher::for_path(Path::new("keys.age")), Some(Cipher::Age));
+        assert_eq!(Cipher::for_path(Path::new("notes.md")), None);
+    }
+
+    #[test]
+    fn recipients_are_read_from_gpg_status_output() {
+        let status = "[GNUPG:] ENC_TO 1A2B3C4D5E6F7A8B 1 0\n\
+                      [GNUPG:] KEY_CONSIDERED 0123 0\n\
+                      [GNUPG:] ENC_TO 8B7A6F5E4D3C2B1A 18 0\n\
+                      [GNUPG:] ENC_TO 1A2B3C4D5E6F7A8B 1 0\n";
+
+        assert_eq!(recipients(status), vec!["1A2B3C4D5E6F7A8B", "8B7A6F5E4D3C2B1A"]);
+        assert!(recipients("[GNUPG:] NEED_PASSPHRASE_SYM 9 3 2\n").is_empty());
+    }
+
+    #[test]
+    fn gpg_files_are_re_encrypted_the_way_they_were_encrypted() {
+        let symmetric = gpg_encryption_arguments(&[]).unwrap();
+        assert!(symmetric.contains(&String::from("--symmetric")));
+        assert!(!symmetric.contains(&String::from("--encrypt")));
+
+        let public_key = gpg_encryption_arguments(&[String::from("1A2B3C4D5E6F7A8B")]).unwrap();
+        assert!(public_key.contains(&String::from("--encrypt")));
+        assert!(!public_key.contains(&String::from("--symmetric")));
+        assert!(!public_key.contains(&String::from("--passphrase-fd")));
+        assert!(public_key.ends_with(&[String::from("--recipient"), String::from("1A2B3C4D5E6F7A8B!")]));
+
+        assert!(gpg_encryption_arguments(&[String::from("0000000000000000")]).is_err());
+    }
+
+    #[test]
+    fn mark_saved_keeps_the_buffer_path() {
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("/amp/secrets.txt.gpg"));
+        buffer.insert("amp");
+        assert!(buffer.modified());
+
+        mark_saved(&mut buffer).unwrap();
+        assert!(!buffer.modified());
+        assert!(is_encrypted(&buffer));
+        assert_eq!(buffer.path, Some(PathBuf::from("/amp/secrets.txt.gpg")));
+    }
+}
@@ -0,0 +1,52 @@
+use bloodhound::ExclusionPattern;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Files this large are more likely to be generated than written by hand.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// The files beneath the directory, skipping excluded paths, in sorted order.
+pub fn workspace_files(directory: &Path, exclusions: Option<&[ExclusionPattern]>) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect_files(directory, exclusions.unwrap_or(&[]), &mut files);
+    files.sort();
+
+    files
+}
+
+/// The file's content, unless it's large or binary.
+pub fn read_text(path: &Path) -> Option<String> {
+    if fs::metadata(path).ok()?.len() > MAX_FILE_SIZE {
+        return None;
+    }
+
+    // Null bytes give binary files away, even when they're valid UTF-8.
+    String::from_utf8(fs::read(path).ok()?)
+        .ok()
+        .filter(|content| !content.contains('\0'))
+}
+
+// Adds the files beneath the directory to the list, skipping excluded paths.
+fn collect_files(directory: &Path, exclusions: &[ExclusionPattern], files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let excluded = exclusions.iter().any(|exclusion| exclusion.matches(path.to_string_lossy().as_ref()));
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+
+        if excluded {
+            continue;
+        } else if file_type.is_dir() {
+            collect_files(&path, exclusions, files);
+        } else if file_type.is_file() {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str;
+
+pub const BYTES_PER_LINE: usize = 16;
+
+// Like Git, a null byte near the start of a file marks it as binary.
+const NULL_BYTE_SEARCH_LENGTH: u64 = 8000;
+
+/// Whether the data can't be shown as text, either because
+/// it contains null bytes or because it isn't valid UTF-8.
+pub fn is_binary(data: &[u8]) -> bool {
+    data.iter().take(NULL_BYTE_SEARCH_LENGTH as usize).any(|&byte| byte == 0) ||
+        str::from_utf8(data).is_err()
+}
+
+/// Whether the file at the specified path is binary, judging by its start
+/// alone, so that large files aren't read in full. Files that are only
+/// invalid UTF-8 further in fail to open as buffers instead, and files
+/// that can't be read are left for buffers to report on.
+pub fn is_binary_file(path: &Path) -> bool {
+    let mut data = Vec::new();
+    if !path.is_file() {
+        return false;
+    }
+    if File::open(path).and_then(|file| file.take(NULL_BYTE_SEARCH_LENGTH).read_to_end(&mut data)).is_err() {
+        return false;
+    }
+
+    // A multi-byte character cut off at the end of the data isn't invalid.
+    data.contains(&0) || str::from_utf8(&data).err().map(|error| error.error_len().is_some()).unwrap_or(false)
+}
+
+/// The number of lines needed to show the data, in `dump_line` format.
+pub fn line_count(data: &[u8]) -> usize {
+    (data.len() + BYTES_PER_LINE - 1) / BYTES_PER_LINE
+}
+
+/// A line of the classic hex dump format: the offset of its first byte,
+/// the bytes in hex (split into two groups of eight), and their printable
+/// ASCII equivalents, with everything else shown as a period.
+pub fn dump_line(data: &[u8], line: usize) -> Option<String> {
+    let start = line * BYTES_PER_LINE;
+    if start >= data.len() {
+        return None;
+    }
+    let bytes = &data[start..data.len().min(start + BYTES_PER_LINE)];
+
+    let mut hex = String::new();
+    for index in 0..BYTES_PER_LINE {
+        if index == BYTES_PER_LINE / 2 {
+            hex.push(' ');
+        }
+        match bytes.get(index) {
+            Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+            None => hex.push_str("   "),
+        }
+    }
+    let ascii: String = bytes
+        .iter()
+        .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+        .collect();
+
+    Some(format!("{:08x}  {} |{}|", start, hex, ascii))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use super::{dump_line, is_binary, is_binary_file, line_count, NULL_BYTE_SEARCH_LENGTH};
+
+    #[test]
+    fn is_binary_detects_null_bytes_and_invalid_utf8() {
+        assert!(!is_binary("amp ✓\n".as_bytes()));
+        assert!(is_binary(b"amp\0editor"));
+        assert!(is_binary(&[0x61, 0xff, 0x62]));
+    }
+
+    #[test]
+    fn is_binary_file_only_checks_the_start_of_files() {
+        let path = env::temp_dir().join("amp_is_binary_file_test.bin");
+        let mut data = vec![b'a'; NULL_BYTE_SEARCH_LENGTH as usize - 1];
+        data.extend_from_slice("✓\0".as_bytes());
+        fs::write(&path, &data).unwrap();
+        assert!(!is_binary_file(&path));
+
+        fs::write(&path, b"amp\0editor").unwrap();
+        assert!(is_binary_file(&path));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn dump_line_shows_offsets_hex_and_ascii_columns() {
+        let data = b"amp editor\0\x01 in Rust, with hex";
+
+        assert_eq!(line_count(data), 2);
+        assert_eq!(
+            dump_line(data, 0),
+            Some(String::from(
+                "00000000  61 6d 70 20 65 64 69 74  6f 72 00 01 20 69 6e 20  |amp editor.. in |"
+            ))
+        );
+        assert_eq!(
+            dump_line(data, 1),
+            Some(String::from(
+                "00000010  52 75 73 74 2c 20 77 69  74 68 20 68 65 78        |Rust, with hex|"
+            ))
+        );
+        assert_eq!(dump_line(data, 2), None);
+    }
+}
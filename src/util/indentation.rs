@@ -0,0 +1,127 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The number of lines looked at when detecting indentation,
+/// which is plenty to go on, without scanning large files in full.
+const DETECTION_LINE_LIMIT: usize = 1000;
+
+/// How a file's lines are indented.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Indentation {
+    Tabs,
+    /// Spaces, in steps of the specified width.
+    Spaces(usize),
+}
+
+impl fmt::Display for Indentation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Indentation::Tabs => write!(f, "tabs"),
+            Indentation::Spaces(width) => write!(f, "{} spaces", width),
+        }
+    }
+}
+
+/// Works out how the content is indented: using tabs, if most indented lines
+/// start with one, or otherwise using spaces, in steps of the most common
+/// increase in indentation from one line to the next. Returns None if
+/// there's nothing indented to go on.
+pub fn detect(content: &str) -> Option<Indentation> {
+    let (mut tab_lines, mut space_lines) = (0, 0);
+    let mut steps: HashMap<usize, usize> = HashMap::new();
+    let mut previous_width = Some(0);
+
+    for line in content.lines().take(DETECTION_LINE_LIMIT) {
+        let text = line.trim_start_matches(' ');
+        let width = line.len() - text.len();
+
+        // Blank lines don't change the indentation level.
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        if text.starts_with('\t') {
+            if width == 0 {
+                tab_lines += 1;
+            }
+            previous_width = None;
+            continue;
+        }
+
+        // Block comment continuations (e.g. " * ...")
+        // are aligned with their opening line, not indented.
+        if text.starts_with('*') {
+            continue;
+        }
+
+        if width > 0 {
+            space_lines += 1;
+        }
+        if let Some(previous_width) = previous_width {
+            if width > previous_width {
+                *steps.entry(width - previous_width).or_insert(0) += 1;
+            }
+        }
+        previous_width = Some(width);
+    }
+
+    if tab_lines == 0 && space_lines == 0 {
+        None
+    } else if tab_lines > space_lines {
+        Some(Indentation::Tabs)
+    } else {
+        // Ties go to the narrower step.
+        steps
+            .into_iter()
+            .max_by_key(|&(step, count)| (count, Reverse(step)))
+            .map(|(step, _)| Indentation::Spaces(step))
+    }
+}
+
+/// Re-indents each of the content's lines using the specified indentation,
+/// treating tabs in existing indentation as the specified width. Leftover
+/// columns that don't make up a full level (e.g. those aligning a wrapped
+/// argument list) are kept as spaces when converting to tabs.
+pub fn convert(content: &str, indentation: Indentation, width: usize) -> String {
+    let width = width.max(1);
+    let lines: Vec<String> = content.split('\n').map(|line| {
+        let text = line.trim_start_matches(|c: char| c == ' ' || c == '\t');
+        let columns = line[..line.len() - text.len()].chars().fold(0, |columns, c| {
+            if c == '\t' {
+                (columns / width + 1) * width
+            } else {
+                columns + 1
+            }
+        });
+
+        let leading_whitespace = match indentation {
+            Indentation::Tabs => format!("{}{:2$}", "\t".repeat(columns / width), "", columns % width),
+            Indentation::Spaces(_) => format!("{:1$}", "", columns),
+        };
+
+        format!("{}{}", leading_whitespace, text)
+    }).collect();
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert, detect, Indentation};
+
+    #[test]
+    fn detect_finds_the_dominant_indentation() {
+        assert_eq!(detect("fn amp() {\n    if x {\n        y();\n    }\n}\n"), Some(Indentation::Spaces(4)));
+        assert_eq!(detect("a:\n  b:\n    c: 1\n  d: 2\n"), Some(Indentation::Spaces(2)));
+        assert_eq!(detect("fn amp() {\n\tif x {\n\t\ty();\n\t}\n}\n"), Some(Indentation::Tabs));
+        assert_eq!(detect("/**\n * Docs\n */\nfn amp() {\n  x();\n}\n"), Some(Indentation::Spaces(2)));
+        assert_eq!(detect("amp\n\neditor\n"), None);
+    }
+
+    #[test]
+    fn convert_switches_between_tabs_and_spaces() {
+        assert_eq!(convert("a\n    b\n        c\n      d", Indentation::Tabs, 4), "a\n\tb\n\t\tc\n\t  d");
+        assert_eq!(convert("a\n\tb\n\t\tc\n\t  d", Indentation::Spaces(4), 4), "a\n    b\n        c\n      d");
+    }
+}
@@ -0,0 +1,104 @@
+use scribe::buffer::Position;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Converts byte offsets into a buffer's data to (line, grapheme offset)
+/// positions, without scanning the data from the start for every conversion.
+/// Line starts are found lazily, only as far into the data as conversions
+/// require, and edits only discard those following the edited line.
+pub struct LineIndex {
+    // Byte offsets at which each line starts; never empty.
+    line_starts: Vec<usize>,
+    // Whether every line in the data has been found.
+    complete: bool,
+}
+
+impl LineIndex {
+    pub fn new() -> LineIndex {
+        LineIndex {
+            line_starts: vec![0],
+            complete: false,
+        }
+    }
+
+    /// Discards the starts of lines following the specified line. The line's own
+    /// start isn't affected by changes to its content (or anything after it).
+    pub fn invalidate_from(&mut self, line: usize) {
+        self.line_starts.truncate(line + 1);
+        self.complete = false;
+    }
+
+    /// Converts a byte offset into the data to a position, provided
+    /// it's within the data and falls on a character boundary.
+    pub fn position(&mut self, data: &str, offset: usize) -> Option<Position> {
+        if !data.is_char_boundary(offset) {
+            return None;
+        }
+
+        self.index_until(data, |line_starts| line_starts.last().map(|&start| start > offset).unwrap_or(false));
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let line_start = self.line_starts[line];
+
+        Some(Position{ line, offset: data.get(line_start..offset)?.graphemes(true).count() })
+    }
+
+    /// The byte offset at which the line starts,
+    /// provided the data has that many lines.
+    pub fn line_start(&mut self, data: &str, line: usize) -> Option<usize> {
+        self.index_until(data, |line_starts| line_starts.len() > line);
+
+        self.line_starts.get(line).cloned()
+    }
+
+    // Finds line starts until the condition is met or the data's been fully indexed.
+    fn index_until<F>(&mut self, data: &str, done: F) where F: Fn(&[usize]) -> bool {
+        while !self.complete && !done(&self.line_starts) {
+            let last_start = self.line_starts[self.line_starts.len() - 1];
+
+            match data.get(last_start..).and_then(|remaining| remaining.find('\n')) {
+                Some(index) => self.line_starts.push(last_start + index + 1),
+                None => self.complete = true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::Position;
+    use super::LineIndex;
+
+    #[test]
+    fn position_converts_byte_offsets_to_grapheme_positions() {
+        let data = "amp\nñamp\n";
+        let mut index = LineIndex::new();
+
+        assert_eq!(index.position(data, 2), Some(Position{ line: 0, offset: 2 }));
+        assert_eq!(index.position(data, 6), Some(Position{ line: 1, offset: 1 }));
+        assert_eq!(index.position(data, 4), Some(Position{ line: 1, offset: 0 }));
+        assert_eq!(index.position(data, 11), Some(Position{ line: 2, offset: 0 }));
+        assert_eq!(index.position(data, 5), None);
+        assert_eq!(index.position(data, 12), None);
+    }
+
+    #[test]
+    fn line_start_finds_the_byte_offset_of_the_line() {
+        let mut index = LineIndex::new();
+
+        assert_eq!(index.line_start("amp\nñamp\n", 0), Some(0));
+        assert_eq!(index.line_start("amp\nñamp\n", 2), Some(10));
+        assert_eq!(index.line_start("amp\nñamp\n", 1), Some(4));
+        assert_eq!(index.line_start("amp\nñamp\n", 3), None);
+    }
+
+    #[test]
+    fn invalidate_from_discards_line_starts_following_the_edit() {
+        let mut index = LineIndex::new();
+        assert_eq!(index.position("amp\neditor\ntext", 11), Some(Position{ line: 2, offset: 0 }));
+
+        index.invalidate_from(1);
+        assert_eq!(index.position("amp\ned\nitor\ntext", 12), Some(Position{ line: 3, offset: 0 }));
+    }
+}
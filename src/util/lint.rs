@@ -0,0 +1,110 @@
+use crate::models::application::Event;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// Matches the `file:line:column: severity: message` format most linters
+/// can produce (e.g. `shellcheck -f gcc`), with the column and severity optional.
+pub const DEFAULT_FORMAT: &str =
+    r"^[^:]*:(?P<line>\d+):(?:(?P<column>\d+):)?\s*(?:(?P<severity>error|warning|note|info):)?\s*(?P<message>.+)$";
+
+/// A problem reported by a linter, with zero-based positions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub offset: usize,
+    pub message: String,
+}
+
+/// An external command that checks files of a particular type, run with the
+/// file's path as its last argument, along with the format of its output.
+/// The format is matched against each line of output; the `line` and
+/// `message` groups are required, and `column` and `severity` are optional.
+pub struct Linter {
+    pub command: Vec<String>,
+    pub format: Regex,
+}
+
+impl Linter {
+    /// Runs the linter against the path on a background thread,
+    /// sending its findings back to the application once it's finished.
+    pub fn spawn(self, path: PathBuf, events: Sender<Event>) {
+        thread::spawn(move || {
+            let result = self.run(&path);
+            let _ = events.send(Event::LintComplete(path, result));
+        });
+    }
+
+    fn run(&self, path: &Path) -> Result<Vec<Diagnostic>, String> {
+        let (program, arguments) = self.command
+            .split_first()
+            .ok_or_else(|| String::from("The lint command is empty"))?;
+        let output = Command::new(program)
+            .args(arguments)
+            .arg(path)
+            .output()
+            .map_err(|error| format!("Couldn't run {}: {}", program, error))?;
+
+        // Linters usually exit unsuccessfully when they find problems, so the
+        // status isn't an error; findings may be written to either stream.
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        Ok(self.parse(&format!("{}\n{}", stdout, stderr)))
+    }
+
+    /// Diagnostics for the lines of output matching the linter's format.
+    pub fn parse(&self, output: &str) -> Vec<Diagnostic> {
+        output.lines().filter_map(|line| {
+            let captures = self.format.captures(line)?;
+            let line_number: usize = captures.name("line")?.parse().ok()?;
+            let column: usize = captures.name("column")
+                .and_then(|column| column.parse().ok())
+                .unwrap_or(1);
+            let message = captures.name("message")?.trim();
+            let message = match captures.name("severity") {
+                Some(severity) => format!("{}: {}", severity, message),
+                None => message.to_string(),
+            };
+
+            Some(Diagnostic {
+                line: line_number.saturating_sub(1),
+                offset: column.saturating_sub(1),
+                message,
+            })
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use super::{Diagnostic, Linter, DEFAULT_FORMAT};
+
+    #[test]
+    fn parse_reads_diagnostics_in_the_default_format() {
+        let linter = Linter{ command: Vec::new(), format: Regex::new(DEFAULT_FORMAT).unwrap() };
+        let output = "script.sh:3:6: warning: Quote this to prevent word splitting.\n\
+                      In script.sh line 3:\n\
+                      script.sh:10: unexpected end of file";
+
+        assert_eq!(linter.parse(output), vec![
+            Diagnostic{ line: 2, offset: 5, message: String::from("warning: Quote this to prevent word splitting.") },
+            Diagnostic{ line: 9, offset: 0, message: String::from("unexpected end of file") },
+        ]);
+    }
+
+    #[test]
+    fn parse_uses_custom_formats() {
+        let linter = Linter{
+            command: Vec::new(),
+            format: Regex::new(r"^line (?P<line>\d+) \[(?P<severity>\w+)\] (?P<message>.*)$").unwrap()
+        };
+
+        assert_eq!(linter.parse("line 4 [E501] line too long"), vec![
+            Diagnostic{ line: 3, offset: 0, message: String::from("E501: line too long") },
+        ]);
+    }
+}
@@ -0,0 +1,195 @@
+use regex::Regex;
+use std::cmp;
+use unicode_segmentation::UnicodeSegmentation;
+
+lazy_static! {
+    // Indent, bullet (or number and delimiter), spacing, and optional checkbox.
+    static ref LIST_ITEM: Regex =
+        Regex::new(r"^(\s*)(?:([-*+])|(\d+)([.)]))(\s+)(\[[ xX]\]\s+)?").unwrap();
+    static ref SEPARATOR_CELL: Regex = Regex::new(r"^:?-+:?$").unwrap();
+}
+
+/// The leading portion of a Markdown list item (e.g. "  - [ ] ").
+#[derive(Debug, PartialEq)]
+pub struct ListItem {
+    /// The length of the item's prefix, in bytes.
+    pub prefix_length: usize,
+    /// The prefix for the item that follows this one, with its number
+    /// incremented, and its checkbox (if it has one) unchecked.
+    pub next_prefix: String,
+    /// Whether the item has no content beyond its prefix.
+    pub empty: bool,
+}
+
+pub fn list_item(line: &str) -> Option<ListItem> {
+    let captures = LIST_ITEM.captures(line)?;
+    let (_, prefix_length) = captures.pos(0)?;
+    let marker = match (captures.at(2), captures.at(3), captures.at(4)) {
+        (Some(bullet), _, _) => bullet.to_string(),
+        (None, Some(number), Some(delimiter)) => {
+            let number: usize = number.parse().ok()?;
+            format!("{}{}", number + 1, delimiter)
+        },
+        _ => return None,
+    };
+    let checkbox = if captures.at(6).is_some() { "[ ] " } else { "" };
+
+    Some(ListItem {
+        prefix_length,
+        next_prefix: format!("{}{}{}{}", captures.at(1).unwrap_or(""), marker, captures.at(5).unwrap_or(" "), checkbox),
+        empty: line[prefix_length..].trim().is_empty(),
+    })
+}
+
+/// Checks or unchecks the list item's checkbox, adding an unchecked one
+/// to items that don't have one. Lines that aren't list items are skipped.
+pub fn toggle_checkbox(line: &str) -> Option<String> {
+    let captures = LIST_ITEM.captures(line)?;
+    let (_, prefix_length) = captures.pos(0)?;
+
+    let toggled = match captures.pos(6) {
+        Some((start, _)) => {
+            let checked = &line[start + 1..start + 2] != " ";
+            format!("{}[{}]{}", &line[..start], if checked { " " } else { "x" }, &line[start + 3..])
+        },
+        None => format!("{}[ ] {}", &line[..prefix_length], &line[prefix_length..]),
+    };
+
+    Some(toggled)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// Whether the line looks like a row of a Markdown table.
+pub fn is_table_row(line: &str) -> bool {
+    line.trim_start().starts_with('|')
+}
+
+/// Reformats the rows of a Markdown table, padding cells so that their pipes
+/// line up, and rewriting the header separator row to match the column widths
+/// (keeping its alignment markers). Rows with missing cells are filled out.
+pub fn format_table(rows: &[&str]) -> Vec<String> {
+    let rows: Vec<Vec<String>> = rows.iter().map(|row| cells(row)).collect();
+    let column_count = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let separator = rows.get(1).filter(|row| row.iter().all(|cell| SEPARATOR_CELL.is_match(cell)));
+    let alignments: Vec<Alignment> = (0..column_count)
+        .map(|column| {
+            let cell = separator.and_then(|row| row.get(column)).map(|cell| cell.as_str()).unwrap_or("");
+            match (cell.starts_with(':'), cell.len() > 1 && cell.ends_with(':')) {
+                (true, true) => Alignment::Center,
+                (false, true) => Alignment::Right,
+                _ => Alignment::Left,
+            }
+        })
+        .collect();
+
+    // Separator rows need at least three dashes per column.
+    let widths: Vec<usize> = (0..column_count)
+        .map(|column| {
+            rows.iter()
+                .enumerate()
+                .filter(|&(index, _)| index != 1 || separator.is_none())
+                .filter_map(|(_, row)| row.get(column))
+                .map(|cell| cell.graphemes(true).count())
+                .fold(3, cmp::max)
+        })
+        .collect();
+
+    rows.iter().enumerate().map(|(index, row)| {
+        let formatted: Vec<String> = (0..column_count).map(|column| {
+            let width = widths[column];
+            if index == 1 && separator.is_some() {
+                return match alignments[column] {
+                    Alignment::Left if row.get(column).map(|cell| cell.starts_with(':')).unwrap_or(false) => {
+                        format!(":{}", "-".repeat(width - 1))
+                    },
+                    Alignment::Left => "-".repeat(width),
+                    Alignment::Center => format!(":{}:", "-".repeat(width - 2)),
+                    Alignment::Right => format!("{}:", "-".repeat(width - 1)),
+                };
+            }
+
+            let cell = row.get(column).map(|cell| cell.as_str()).unwrap_or("");
+            let padding = width - cell.graphemes(true).count();
+            match alignments[column] {
+                Alignment::Left => format!("{}{}", cell, " ".repeat(padding)),
+                Alignment::Center => format!("{}{}{}", " ".repeat(padding / 2), cell, " ".repeat(padding - padding / 2)),
+                Alignment::Right => format!("{}{}", " ".repeat(padding), cell),
+            }
+        }).collect();
+
+        format!("| {} |", formatted.join(" | "))
+    }).collect()
+}
+
+// The trimmed contents of the row's cells, ignoring its outer pipes.
+fn cells(row: &str) -> Vec<String> {
+    let row = row.trim();
+    let row = row.trim_start_matches('|');
+    let row = if row.ends_with('|') && !row.ends_with("\\|") { &row[..row.len() - 1] } else { row };
+
+    // Escaped pipes are part of a cell's content.
+    let mut cells = vec![String::new()];
+    let mut characters = row.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' if characters.peek() == Some(&'|') => {
+                cells.last_mut().unwrap().push_str("\\|");
+                characters.next();
+            },
+            '|' => cells.push(String::new()),
+            _ => cells.last_mut().unwrap().push(character),
+        }
+    }
+
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_table, list_item, toggle_checkbox, ListItem};
+
+    #[test]
+    fn list_item_continues_bullets_numbers_and_checkboxes() {
+        assert_eq!(list_item("  - amp"), Some(ListItem {
+            prefix_length: 4,
+            next_prefix: String::from("  - "),
+            empty: false,
+        }));
+        assert_eq!(list_item("9) amp").unwrap().next_prefix, "10) ");
+        assert_eq!(list_item("* [x] amp").unwrap().next_prefix, "* [ ] ");
+        assert!(list_item("1.  ").unwrap().empty);
+        assert_eq!(list_item("-amp"), None);
+        assert_eq!(list_item("amp"), None);
+    }
+
+    #[test]
+    fn toggle_checkbox_checks_unchecks_and_adds_checkboxes() {
+        assert_eq!(toggle_checkbox("- [ ] amp"), Some(String::from("- [x] amp")));
+        assert_eq!(toggle_checkbox("  1. [X] amp"), Some(String::from("  1. [ ] amp")));
+        assert_eq!(toggle_checkbox("+ amp"), Some(String::from("+ [ ] amp")));
+        assert_eq!(toggle_checkbox("amp"), None);
+    }
+
+    #[test]
+    fn format_table_aligns_pipes_and_normalizes_the_separator() {
+        let table = [
+            "|Name|Count|Notes|",
+            "|:-|-:|:---:|",
+            "| amp | 12 |a \\| b|",
+            "|editor|3",
+        ];
+
+        assert_eq!(format_table(&table), vec![
+            "| Name   | Count | Notes  |",
+            "| :----- | ----: | :----: |",
+            "| amp    |    12 | a \\| b |",
+            "| editor |     3 |        |",
+        ]);
+    }
+}
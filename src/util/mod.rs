@@ -1,12 +1,52 @@
 pub use self::selectable_vec::SelectableVec;
 
+pub mod abbreviation;
+pub mod character;
+pub mod color_value;
+pub mod commit_message;
+pub mod completion;
+pub mod conflict;
+pub mod delimiter;
+pub mod diff;
+pub mod elevated;
+pub mod encryption;
+pub mod files;
+pub mod hex;
+pub mod indentation;
+pub mod line_index;
+pub mod lint;
+pub mod markdown;
 pub mod movement_lexer;
+pub mod occurrences;
+pub mod reference;
+pub mod remote;
+pub mod rename;
+pub mod replace;
 mod selectable_vec;
+pub mod snapshot;
+pub mod spelling;
+pub mod tags;
+pub mod task;
+pub mod timings;
 pub mod token;
+pub mod workspace_replace;
 
+use bloodhound::ExclusionPattern;
 use crate::errors::*;
 use crate::models::Application;
+use crate::models::application::{run_hook, Hook, Mode, Preferences};
+use crate::models::application::modes::{open, HexMode, MaskedInputMode};
+use scribe::Workspace;
+use self::elevated::Elevation;
+use self::encryption::{Cipher, Decrypted, Secret};
+use self::remote::RemoteFile;
+use self::tags::Tag;
 use scribe::buffer::{Buffer, LineRange, Position, Range};
+use std::fs;
+use std::io;
+use std::path::Path;
+use syntect::parsing::SyntaxDefinition;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Translates a line range to a regular range, including its last line.
 /// Handles ranges including and end line without trailing newline character.
@@ -29,7 +69,7 @@ pub fn inclusive_range(line_range: &LineRange, buffer: &mut Buffer) -> Range {
                 // Found the last line's content; use it.
                 Position {
                     line: line_range.end(),
-                    offset: line_content.len(),
+                    offset: line_content.graphemes(true).count(),
                 }
             }
             // Couldn't find any content for the last line; use a zero offset.
@@ -49,18 +89,406 @@ pub fn inclusive_range(line_range: &LineRange, buffer: &mut Buffer) -> Range {
                end_position)
 }
 
+/// Inserts single-line content at the cursor, moving the cursor past it.
+/// Characters that combine with the grapheme before them (e.g. accents)
+/// become part of it, rather than advancing the cursor on their own.
+pub fn insert_at_cursor(buffer: &mut Buffer, content: &str) {
+    let offset = {
+        let mut prefix = content_before_cursor(buffer);
+        prefix.push_str(content);
+
+        prefix.graphemes(true).count()
+    };
+    let line = buffer.cursor.line;
+
+    buffer.insert(content);
+    buffer.cursor.move_to(Position{ line, offset });
+}
+
+/// The cursor line's content up to the cursor, read
+/// without copying the rest of the buffer's content.
+pub fn content_before_cursor(buffer: &Buffer) -> String {
+    let start = Position{ line: buffer.cursor.line, offset: 0 };
+
+    buffer.read(&Range::new(start, *buffer.cursor)).unwrap_or_default()
+}
+
 /// Convenience method to initialize and add a buffer to the workspace.
 pub fn add_buffer(buffer: Buffer, app: &mut Application) -> Result<()> {
     app.workspace.add_buffer(buffer);
+    apply_syntax_preference(&mut app.workspace, &app.preferences.borrow())?;
+    detect_indentation(&mut app.workspace, &mut app.preferences.borrow_mut());
     app.view.initialize_buffer(app.workspace.current_buffer().unwrap())?;
 
     Ok(())
 }
 
+/// Switches to the buffer for the path, opening it if it isn't already open.
+/// Paths that don't exist yet are opened as new buffers, which will create
+/// the file when they're saved.
+pub fn open_buffer(path: &Path, app: &mut Application) -> Result<()> {
+    if app.workspace.contains_buffer_with_path(path) {
+        app.workspace
+            .open_buffer(path)
+            .chain_err(|| "Couldn't open a buffer for the specified path.")?;
+        record_recent_file(app)?;
+    } else if open_remote_file(path, app)? || open_encrypted_file(path, app)? || open_hex_view(path, app) {
+        // Remote files are fetched, encrypted files are decrypted into
+        // memory, and binary files are shown in a hex view, rather than
+        // being opened from disk.
+    } else if path.exists() {
+        if let Err(error) = app.workspace.open_buffer(path) {
+            // Files that only turn out not to be valid UTF-8
+            // past the start checked above are shown in hex, too.
+            if error.kind() == io::ErrorKind::InvalidData {
+                return show_hex_view(path, app);
+            }
+
+            return Err(error).chain_err(|| "Couldn't open a buffer for the specified path.");
+        }
+        apply_syntax_preference(&mut app.workspace, &app.preferences.borrow())?;
+        detect_indentation(&mut app.workspace, &mut app.preferences.borrow_mut());
+        record_recent_file(app)?;
+
+        // Newly opened buffers pick up where we last left them.
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        app.view.initialize_buffer(buffer)?;
+        app.file_states.restore(buffer, &mut app.view)?;
+        run_hook(app, Hook::BufferOpen)?;
+    } else {
+        let mut buffer = Buffer::new();
+        buffer.path = Some(app.workspace.path.join(path));
+        add_buffer(buffer, app)?;
+    }
+
+    Ok(())
+}
+
+/// Opens the file containing the tag's definition, moving the cursor to it.
+pub fn jump_to_tag(tag: &Tag, app: &mut Application) -> Result<()> {
+    open_buffer(&tag.path, app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let line = tag.line(&buffer.data())
+        .ok_or_else(|| format!("Couldn't find the definition of {} in its file", tag.name))?;
+    buffer.cursor.move_to(Position{ line, offset: 0 });
+
+    Ok(())
+}
+
+/// Binary files can't be opened as buffers; if the path points to one, it's
+/// shown in a read-only hex view instead. Returns whether that happened.
+pub fn open_hex_view(path: &Path, app: &mut Application) -> bool {
+    hex::is_binary_file(path) && show_hex_view(path, app).is_ok()
+}
+
+/// Shows the file in a read-only hex view, which is the only time it's read in full.
+pub fn show_hex_view(path: &Path, app: &mut Application) -> Result<()> {
+    let data = fs::read(path).chain_err(|| format!("Couldn't read {}", path.to_string_lossy()))?;
+    app.mode = Mode::Hex(HexMode::new(path.to_path_buf(), data));
+
+    Ok(())
+}
+
+/// Encrypted files are decrypted into memory rather than being opened directly,
+/// prompting for a passphrase first, if one is needed. Returns whether the path
+/// was one that's not already open (those are switched to as usual).
+pub fn open_encrypted_file(path: &Path, app: &mut Application) -> Result<bool> {
+    let cipher = match Cipher::for_path(path) {
+        Some(cipher) if path.is_file() && !app.workspace.contains_buffer_with_path(path) => cipher,
+        _ => return Ok(false),
+    };
+
+    if cipher.needs_passphrase() {
+        let path = path.to_path_buf();
+        app.mode = Mode::MaskedInput(MaskedInputMode::new(passphrase_prompt(&path), move |app, passphrase| {
+            let decrypted = encryption::decrypt(&path, &Secret::Passphrase(passphrase))?;
+            add_decrypted_buffer(&path, decrypted, app)
+        }));
+    } else {
+        let identity = app.preferences.borrow().age_identity().ok_or(AGE_IDENTITY_MISSING)?;
+        let decrypted = encryption::decrypt(path, &Secret::Identity(&identity))?;
+        add_decrypted_buffer(path, decrypted, app)?;
+    }
+
+    Ok(true)
+}
+
+/// Adds a buffer for decrypted content, pointing to its encrypted file. The
+/// buffer is only ever written back to that file, re-encrypting it on save
+/// (to the same public keys, if that's how it was encrypted).
+pub fn add_decrypted_buffer(path: &Path, decrypted: Decrypted, app: &mut Application) -> Result<()> {
+    let path = path.canonicalize().chain_err(|| "Couldn't find the encrypted file")?;
+    let mut buffer = Buffer::new();
+    buffer.insert(decrypted.content);
+    buffer.path = Some(path.clone());
+    encryption::mark_saved(&mut buffer)?;
+    if decrypted.recipients.is_empty() {
+        app.encryption_recipients.remove(&path);
+    } else {
+        app.encryption_recipients.insert(path.clone(), decrypted.recipients);
+    }
+
+    // Use the syntax for the extension that precedes the
+    // encrypted one, if any (e.g. Markdown for "notes.md.gpg").
+    load_syntaxes(&mut app.workspace)?;
+    buffer.syntax_definition = path
+        .file_stem()
+        .and_then(|stem| Path::new(stem).extension())
+        .and_then(|extension| extension.to_str())
+        .and_then(|extension| app.workspace.syntax_set.find_syntax_by_extension(extension))
+        .cloned();
+
+    add_buffer(buffer, app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.file_states.restore(buffer, &mut app.view)?;
+    record_recent_file(app)?;
+    run_hook(app, Hook::BufferOpen)
+}
+
+/// Opens a buffer for a remote file (e.g. "ssh://host/notes.md"), fetching
+/// its content over SSH, and returning false for paths that aren't remote.
+/// Remote files that are already open are switched to, rather than re-fetched.
+pub fn open_remote_file(path: &Path, app: &mut Application) -> Result<bool> {
+    let remote_file = match RemoteFile::parse(path) {
+        Some(remote_file) => remote_file,
+        None => return Ok(false),
+    };
+
+    // The workspace can't find these itself, since they don't exist locally.
+    if let Some(initial_id) = app.workspace.current_buffer().map(|buffer| buffer.id) {
+        loop {
+            if app.workspace.current_buffer().and_then(|buffer| buffer.path.as_ref()).map(|p| p == path) == Some(true) {
+                record_recent_file(app)?;
+                return Ok(true);
+            }
+
+            app.workspace.next_buffer();
+            if app.workspace.current_buffer().map(|buffer| buffer.id) == Some(initial_id) {
+                break;
+            }
+        }
+    }
+
+    let content = remote_file.read()?;
+    let mut buffer = Buffer::new();
+    buffer.insert(content);
+    buffer.path = Some(path.to_path_buf());
+    encryption::mark_saved(&mut buffer)?;
+    load_syntaxes(&mut app.workspace)?;
+    buffer.syntax_definition = Path::new(&remote_file.path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(|extension| app.workspace.syntax_set.find_syntax_by_extension(extension))
+        .cloned();
+
+    add_buffer(buffer, app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.file_states.restore(buffer, &mut app.view)?;
+    record_recent_file(app)?;
+    run_hook(app, Hook::BufferOpen)?;
+
+    Ok(true)
+}
+
+/// Writes the current (remote) buffer back to its file over SSH.
+pub fn save_remote_buffer(app: &mut Application) -> Result<()> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    let remote_file = RemoteFile::parse(&path).ok_or("The buffer isn't remote")?;
+    remote_file.write(&buffer.data())?;
+    encryption::mark_saved(buffer)?;
+    app.message = Some(format!("Wrote {} lines to {}", buffer.data().lines().count(), path.to_string_lossy()));
+
+    Ok(())
+}
+
+/// Writes the current buffer to a file the user doesn't have permission
+/// to write, using sudo (prompting for a password, if needed) or pkexec.
+pub fn save_elevated_buffer(app: &mut Application) -> Result<()> {
+    let elevation = Elevation::detect().ok_or("Neither sudo nor pkexec is installed")?;
+
+    if elevation.needs_password() {
+        app.mode = Mode::MaskedInput(MaskedInputMode::new(String::from("Password for sudo"), move |app, password| {
+            elevated::authenticate(password)?;
+            write_elevated_buffer(elevation, app)
+        }));
+
+        Ok(())
+    } else {
+        write_elevated_buffer(elevation, app)
+    }
+}
+
+fn write_elevated_buffer(elevation: Elevation, app: &mut Application) -> Result<()> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    elevation.write(&path, &buffer.data())?;
+    encryption::mark_saved(buffer)?;
+    app.message = Some(format!(
+        "Wrote {} lines to {} using {}",
+        buffer.data().lines().count(),
+        path.to_string_lossy(),
+        elevation.name()
+    ));
+
+    Ok(())
+}
+
+/// Re-encrypts the current (decrypted) buffer to its file,
+/// prompting for a passphrase first, if one is needed.
+pub fn save_encrypted_buffer(app: &mut Application) -> Result<()> {
+    let path = app.workspace.current_buffer_path().ok_or(BUFFER_PATH_MISSING)?.to_path_buf();
+    let cipher = Cipher::for_path(&path).ok_or("The buffer isn't encrypted")?;
+
+    if let Some(recipients) = app.encryption_recipients.get(&path).cloned() {
+        write_encrypted_buffer(&Secret::Recipients(&recipients), app)
+    } else if cipher.needs_passphrase() {
+        app.mode = Mode::MaskedInput(MaskedInputMode::new(passphrase_prompt(&path), |app, passphrase| {
+            write_encrypted_buffer(&Secret::Passphrase(passphrase), app)
+        }));
+
+        Ok(())
+    } else {
+        let identity = app.preferences.borrow().age_identity().ok_or(AGE_IDENTITY_MISSING)?;
+        write_encrypted_buffer(&Secret::Identity(&identity), app)
+    }
+}
+
+/// Encrypts the current buffer's content using the secret, writing it to its file.
+pub fn write_encrypted_buffer(secret: &Secret, app: &mut Application) -> Result<()> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    encryption::encrypt(&path, secret, &buffer.data())?;
+    encryption::mark_saved(buffer)?;
+    app.message = Some(format!("Encrypted {} lines to {}", buffer.data().lines().count(), path.to_string_lossy()));
+
+    Ok(())
+}
+
+fn passphrase_prompt(path: &Path) -> String {
+    let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+
+    format!("Passphrase for {}", file_name)
+}
+
+/// Uses the syntax definition configured for the current buffer's file type
+/// (e.g. `types.tpl.syntax: HTML`), if there is one, in place of the one
+/// detected from its extension. Git commit messages, which can't be detected
+/// by extension, otherwise use the bundled commit message syntax.
+pub fn apply_syntax_preference(workspace: &mut Workspace, preferences: &Preferences) -> Result<()> {
+    let syntax_name = match workspace.current_buffer().and_then(|buffer| {
+        let path = buffer.path.as_ref();
+
+        preferences.syntax(path).or_else(|| {
+            path.filter(|path| commit_message::is_commit_message(path))
+                .map(|_| String::from(commit_message::SYNTAX_NAME))
+        })
+    }) {
+        Some(syntax_name) => syntax_name,
+        None => return Ok(()),
+    };
+    load_syntaxes(workspace)?;
+    let syntax_definition = workspace.syntax_set
+        .find_syntax_by_name(&syntax_name)
+        .cloned()
+        .ok_or_else(|| format!("Couldn't find the \"{}\" syntax", syntax_name))?;
+
+    if let Some(buffer) = workspace.current_buffer() {
+        buffer.syntax_definition = Some(syntax_definition);
+    }
+
+    Ok(())
+}
+
+/// Adds user syntaxes, along with the bundled commit message syntax, to the
+/// workspace's default set. This is deferred until the first frame's been
+/// drawn (unless they're needed sooner), and only happens once.
+pub fn load_syntaxes(workspace: &mut Workspace) -> Result<()> {
+    if workspace.syntax_set.find_syntax_by_name(commit_message::SYNTAX_NAME).is_some() {
+        return Ok(());
+    }
+
+    // User syntaxes are loaded first, so that they take precedence. Problems
+    // with them are reported once the rest are ready, so that this isn't retried.
+    let user_syntaxes = Preferences::syntax_path().and_then(|syntax_path| {
+        workspace.syntax_set
+            .load_syntaxes(syntax_path, true)
+            .map_err(|e| Error::from(format!("Failed to load user syntaxes: {:?}", e)))
+    });
+    match SyntaxDefinition::load_from_str(commit_message::SYNTAX, true, None) {
+        Ok(definition) => workspace.syntax_set.add_syntax(definition),
+        Err(e) => bail!("Failed to load the commit message syntax: {:?}", e),
+    }
+    workspace.syntax_set.link_syntaxes();
+
+    // Buffers opened before now were only matched against the default
+    // syntaxes; give those that weren't recognized another chance.
+    if let Some(initial_id) = workspace.current_buffer().map(|buffer| buffer.id) {
+        loop {
+            let plain_text = workspace
+                .current_buffer()
+                .and_then(|buffer| buffer.syntax_definition.as_ref().map(|syntax| syntax.name == "Plain Text"))
+                .unwrap_or(true);
+            if plain_text {
+                workspace.update_current_syntax().chain_err(|| "Couldn't update the buffer's syntax")?;
+            }
+
+            workspace.next_buffer();
+            if workspace.current_buffer().map(|buffer| buffer.id) == Some(initial_id) {
+                break;
+            }
+        }
+    }
+
+    user_syntaxes
+}
+
+/// Detects how the current buffer's file is indented (unless detection's
+/// been turned off), so that new indentation is added the same way.
+pub fn detect_indentation(workspace: &mut Workspace, preferences: &mut Preferences) {
+    if !preferences.detect_indentation() {
+        return;
+    }
+
+    if let Some(buffer) = workspace.current_buffer() {
+        if let (Some(path), Some(indentation)) = (buffer.path.as_ref(), indentation::detect(&buffer.data())) {
+            preferences.set_indentation(path, indentation);
+        }
+    }
+}
+
+/// Patterns for the paths left out of workspace-wide listings: those configured
+/// for open mode, along with those in the workspace's ignore files (if enabled).
+pub fn workspace_exclusions(app: &Application) -> Result<Option<Vec<ExclusionPattern>>> {
+    let mut exclusions = app.preferences.borrow().open_mode_exclusions()?;
+    if app.preferences.borrow().open_mode_ignore_files() {
+        let ignored = open::exclusions::ignore_file_patterns(&app.workspace.path);
+        exclusions = Some(exclusions.unwrap_or_else(Vec::new).into_iter().chain(ignored).collect());
+    }
+
+    Ok(exclusions)
+}
+
+/// Adds the current buffer's path to the list of recently opened files.
+pub fn record_recent_file(app: &mut Application) -> Result<()> {
+    if let Some(path) = app.workspace.current_buffer().and_then(|buffer| buffer.path.clone()) {
+        app.recent_files.record(path);
+        app.recent_files.save()?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use scribe::Buffer;
+    use crate::models::Application;
+    use crate::models::application::Preferences;
+    use crate::util::tags;
+    use crate::yaml::yaml::YamlLoader;
+    use scribe::{Buffer, Workspace};
     use scribe::buffer::{LineRange, Position, Range};
+    use std::fs;
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn inclusive_range_works_correctly_without_trailing_newline() {
@@ -95,4 +523,62 @@ mod tests {
                                   offset: 0,
                               }));
     }
+
+    #[test]
+    fn apply_syntax_preference_uses_the_configured_syntax() {
+        let data = YamlLoader::load_from_str("types:\n  tpl:\n    syntax: HTML").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("index.tpl"));
+        workspace.add_buffer(buffer);
+
+        super::apply_syntax_preference(&mut workspace, &preferences).unwrap();
+        assert_eq!(
+            workspace.current_buffer().unwrap().syntax_definition.as_ref().unwrap().name,
+            "HTML"
+        );
+    }
+
+    #[test]
+    fn apply_syntax_preference_reports_unknown_syntaxes() {
+        let data = YamlLoader::load_from_str("types:\n  tpl:\n    syntax: Unknown").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("index.tpl"));
+        workspace.add_buffer(buffer);
+
+        assert!(super::apply_syntax_preference(&mut workspace, &preferences).is_err());
+    }
+
+    #[test]
+    fn jump_to_tag_opens_the_file_at_the_definition() {
+        let directory = PathBuf::from(env!("OUT_DIR")).join("jump_to_tag");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("amp.rs"), "// amp\nfn amp() {}\n").unwrap();
+        fs::write(directory.join("tags"), "amp\tamp.rs\t/^fn amp() {}$/;\"\tf\n").unwrap();
+        let tag = tags::all(&directory.join("tags")).unwrap().remove(0);
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        super::jump_to_tag(&tag, &mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.path, Some(directory.join("amp.rs").canonicalize().unwrap()));
+        assert_eq!(buffer.cursor.line, 1);
+    }
+
+    #[test]
+    fn open_remote_file_switches_to_an_open_remote_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let path = PathBuf::from("ssh://example.com/notes.md");
+        let mut buffer = Buffer::new();
+        buffer.path = Some(path.clone());
+        app.workspace.add_buffer(buffer);
+        app.workspace.add_buffer(Buffer::new());
+
+        assert!(super::open_remote_file(&path, &mut app).unwrap());
+        assert_eq!(app.workspace.current_buffer().unwrap().path, Some(path));
+        assert!(!super::open_remote_file(Path::new("notes.md"), &mut app).unwrap());
+    }
 }
@@ -1,5 +1,12 @@
+use crate::util::line_index::LineIndex;
 use luthor::{Tokenizer, StateFunction};
 use luthor::token::{Token, Category};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+
+// Upper bound for cached entries, past which the cache is cleared.
+const MAX_CACHE_ENTRIES: usize = 50_000;
 
 fn initial_state(lexer: &mut Tokenizer) -> Option<StateFunction> {
     if lexer.has_prefix("::") {
@@ -120,6 +127,76 @@ pub fn lex(data: &str) -> Vec<Token> {
     }
 }
 
+/// Remembers lexed content, so that repeated word motions and jump mode
+/// activations don't re-lex lines that haven't changed. Entries are keyed
+/// by content, so edited lines miss the cache rather than going stale.
+pub struct LexCache {
+    entries: HashMap<String, Rc<Vec<Token>>>,
+}
+
+impl LexCache {
+    pub fn new() -> LexCache {
+        LexCache{ entries: HashMap::new() }
+    }
+
+    pub fn lex(&mut self, data: &str) -> Rc<Vec<Token>> {
+        if let Some(tokens) = self.entries.get(data) {
+            return tokens.clone();
+        }
+
+        if self.entries.len() >= MAX_CACHE_ENTRIES {
+            self.entries.clear();
+        }
+        let tokens = Rc::new(lex(data));
+        self.entries.insert(data.to_string(), tokens.clone());
+
+        tokens
+    }
+
+    /// Lexes the data one line at a time, producing the same tokens as `lex`.
+    pub fn lex_lines(&mut self, data: &str) -> Vec<Token> {
+        self.lex_line_range(data, &mut LineIndex::new(), 0..usize::MAX)
+    }
+
+    /// Lexes only the specified range of lines, so that callers interested in
+    /// part of a large buffer don't pay to lex all of it. Whitespace spanning
+    /// the range's boundaries is cut short; tokens are otherwise the same as
+    /// those produced by lexing the entire buffer. The data's line index is used
+    /// to skip straight to the first line, rather than scanning for it.
+    pub fn lex_line_range(&mut self, data: &str, line_index: &mut LineIndex, lines: Range<usize>) -> Vec<Token> {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut line_start = match line_index.line_start(data, lines.start) {
+            Some(line_start) => line_start,
+            None => return tokens,
+        };
+        let mut line = lines.start;
+
+        while line_start < data.len() && line < lines.end {
+            let line_end = data[line_start..]
+                .find('\n')
+                .map(|index| line_start + index + 1)
+                .unwrap_or_else(|| data.len());
+
+            for token in self.lex(&data[line_start..line_end]).iter() {
+                // Whitespace spans lines when the data is lexed all at once.
+                if let Some(previous) = tokens.last_mut() {
+                    if previous.category == Category::Whitespace && token.category == Category::Whitespace {
+                        previous.lexeme.push_str(&token.lexeme);
+                        continue;
+                    }
+                }
+
+                tokens.push(token.clone());
+            }
+
+            line_start = line_end;
+            line += 1;
+        }
+
+        tokens
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,4 +238,34 @@ mod tests {
             assert_eq!(*token, expected_tokens[index]);
         }
     }
+
+    #[test]
+    fn lex_lines_matches_lexing_all_at_once() {
+        let data = "fn main() {\n    let camelCase = ::std::env;\n\n\tCONSTANT\n}";
+        let mut cache = LexCache::new();
+
+        assert_eq!(cache.lex_lines(data), lex(data));
+    }
+
+    #[test]
+    fn lex_line_range_only_lexes_the_specified_lines() {
+        let data = "amp\n  editor\ntext\n";
+        let mut cache = LexCache::new();
+        let lexemes: Vec<String> = cache
+            .lex_line_range(data, &mut LineIndex::new(), 1..2)
+            .into_iter()
+            .map(|token| token.lexeme)
+            .collect();
+
+        assert_eq!(lexemes, vec!["  ", "editor", "\n"]);
+    }
+
+    #[test]
+    fn lex_reuses_results_for_unchanged_content() {
+        let mut cache = LexCache::new();
+        let tokens = cache.lex("amp editor");
+
+        assert!(Rc::ptr_eq(&tokens, &cache.lex("amp editor")));
+        assert!(!Rc::ptr_eq(&tokens, &cache.lex("amp editors")));
+    }
 }
@@ -0,0 +1,142 @@
+use scribe::buffer::{Position, Range};
+use std::time::{Duration, Instant};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How long (in milliseconds) the cursor needs to rest
+/// on a word before its occurrences are highlighted.
+const IDLE_DELAY: u64 = 500;
+
+/// Tracks the word under the cursor, so that its occurrences can
+/// be highlighted once the cursor has settled on it for a moment.
+pub struct Occurrences {
+    word: Option<String>,
+    since: Instant,
+}
+
+impl Occurrences {
+    pub fn new() -> Occurrences {
+        Occurrences {
+            word: None,
+            since: Instant::now(),
+        }
+    }
+
+    /// Notes the word currently under the cursor. Moving to a different
+    /// word clears any highlighting and restarts the idle delay.
+    pub fn update(&mut self, word: Option<String>) {
+        if word != self.word {
+            self.word = word;
+            self.since = Instant::now();
+        }
+    }
+
+    /// The word whose occurrences should be highlighted, if the
+    /// cursor has been resting on it for long enough.
+    pub fn highlighted_word(&self) -> Option<&str> {
+        if self.since.elapsed() >= Duration::from_millis(IDLE_DELAY) {
+            self.word.as_ref().map(|word| word.as_str())
+        } else {
+            None
+        }
+    }
+
+    /// The time left until the current word should be highlighted,
+    /// if there's a word that hasn't been highlighted yet.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        self.word.as_ref()?;
+
+        Duration::from_millis(IDLE_DELAY).checked_sub(self.since.elapsed())
+    }
+}
+
+/// Returns the identifier at the specified (grapheme) offset, if there is one.
+pub fn word_at(line: &str, offset: usize) -> Option<String> {
+    let graphemes: Vec<&str> = line.graphemes(true).collect();
+    if !graphemes.get(offset).map(|grapheme| is_word(grapheme)).unwrap_or(false) {
+        return None;
+    }
+
+    let start = graphemes[..offset]
+        .iter()
+        .rposition(|grapheme| !is_word(grapheme))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let end = graphemes[offset..]
+        .iter()
+        .position(|grapheme| !is_word(grapheme))
+        .map(|index| offset + index)
+        .unwrap_or_else(|| graphemes.len());
+
+    Some(graphemes[start..end].concat())
+}
+
+/// Finds whole-word occurrences of the word in the specified range of lines.
+pub fn find(data: &str, word: &str, start_line: usize, line_count: usize) -> Vec<Range> {
+    let word: Vec<&str> = word.graphemes(true).collect();
+    let length = word.len();
+    let mut ranges = Vec::new();
+    if length == 0 {
+        return ranges;
+    }
+
+    for (line, content) in data.lines().enumerate().skip(start_line).take(line_count) {
+        let graphemes: Vec<&str> = content.graphemes(true).collect();
+        let mut offset = 0;
+
+        while offset + length <= graphemes.len() {
+            let end = offset + length;
+            let whole_word = (offset == 0 || !is_word(graphemes[offset - 1])) &&
+                (end == graphemes.len() || !is_word(graphemes[end]));
+
+            if whole_word && graphemes[offset..end] == word[..] {
+                ranges.push(Range::new(
+                    Position{ line, offset },
+                    Position{ line, offset: end }
+                ));
+                offset = end;
+            } else {
+                offset += 1;
+            }
+        }
+    }
+
+    ranges
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().all(|character| character.is_alphanumeric() || character == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::buffer::{Position, Range};
+    use super::{find, word_at, Occurrences};
+
+    #[test]
+    fn word_at_returns_the_surrounding_identifier() {
+        assert_eq!(word_at("let amp_editor = 1;", 6), Some(String::from("amp_editor")));
+        assert_eq!(word_at("let amp_editor = 1;", 4), Some(String::from("amp_editor")));
+        assert_eq!(word_at("let amp_editor = 1;", 14), None);
+        assert_eq!(word_at("amp", 3), None);
+    }
+
+    #[test]
+    fn find_only_matches_whole_words_within_the_line_range() {
+        let data = "amp\namp ample amp_\n(amp)\namp";
+
+        assert_eq!(find(data, "amp", 1, 2), vec![
+            Range::new(Position{ line: 1, offset: 0 }, Position{ line: 1, offset: 3 }),
+            Range::new(Position{ line: 2, offset: 1 }, Position{ line: 2, offset: 4 }),
+        ]);
+    }
+
+    #[test]
+    fn update_delays_highlighting_of_new_words() {
+        let mut occurrences = Occurrences::new();
+        assert!(occurrences.time_remaining().is_none());
+
+        occurrences.update(Some(String::from("amp")));
+        assert!(occurrences.time_remaining().is_some());
+        assert_eq!(occurrences.highlighted_word(), None);
+    }
+}
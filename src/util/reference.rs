@@ -0,0 +1,105 @@
+use crate::errors::*;
+use regex::Regex;
+use std::process::{Command, Stdio};
+use std::thread;
+use unicode_segmentation::UnicodeSegmentation;
+
+lazy_static! {
+    static ref URL: Regex = Regex::new(r"^(?:[a-zA-Z][a-zA-Z0-9+.-]*://|www\.)\S+$").unwrap();
+    static ref PATH: Regex = Regex::new(r"^(?P<path>[^:]*[./][^:]*?)(?::(?P<line>\d+)(?::\d+)?)?$").unwrap();
+}
+
+// Characters that commonly surround references in prose and code,
+// which aren't considered part of them.
+const DELIMITERS: &str = "\"'`<>()[]{},";
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ':', ';', '!', '?'];
+
+/// Something under the cursor that can be opened.
+#[derive(Debug, PartialEq)]
+pub enum Reference {
+    Url(String),
+    /// A file path, optionally followed by a (one-based) line number.
+    Path { path: String, line: Option<usize> },
+}
+
+/// The URL or file path at the specified (grapheme) offset in the line,
+/// if any. Paths need to include a slash or a dot, to rule out ordinary
+/// words, and can have a line and column number appended to them, like
+/// compiler output does (e.g. "src/main.rs:12:5").
+pub fn reference_at(line: &str, offset: usize) -> Option<Reference> {
+    let graphemes: Vec<&str> = line.graphemes(true).collect();
+    if offset >= graphemes.len() || !is_reference_grapheme(graphemes[offset]) {
+        return None;
+    }
+
+    let start = graphemes[..offset]
+        .iter()
+        .rposition(|grapheme| !is_reference_grapheme(grapheme))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let end = graphemes[offset..]
+        .iter()
+        .position(|grapheme| !is_reference_grapheme(grapheme))
+        .map(|index| offset + index)
+        .unwrap_or_else(|| graphemes.len());
+    let candidate = graphemes[start..end].concat();
+    let candidate = candidate.trim_end_matches(TRAILING_PUNCTUATION);
+
+    if URL.is_match(candidate) {
+        return Some(Reference::Url(candidate.to_string()));
+    }
+
+    let captures = PATH.captures(candidate)?;
+    Some(Reference::Path {
+        path: captures.name("path")?.to_string(),
+        line: captures.name("line").and_then(|line| line.parse().ok()),
+    })
+}
+
+/// Opens the URL in the browser, using the system's default handler.
+pub fn open_url(url: &str) -> Result<()> {
+    let program = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    let mut child = Command::new(program)
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .chain_err(|| format!("Couldn't run {} to open the URL", program))?;
+
+    // Don't hold things up waiting for the browser, but clean up after it.
+    thread::spawn(move || child.wait());
+
+    Ok(())
+}
+
+fn is_reference_grapheme(grapheme: &str) -> bool {
+    grapheme.chars().all(|character| !character.is_whitespace() && !DELIMITERS.contains(character))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reference_at, Reference};
+
+    #[test]
+    fn reference_at_recognizes_urls_without_surrounding_punctuation() {
+        let line = "See (https://amp.rs/docs?page=1).";
+
+        assert_eq!(reference_at(line, 10), Some(Reference::Url(String::from("https://amp.rs/docs?page=1"))));
+        assert_eq!(reference_at(line, 0), None);
+        assert_eq!(reference_at(line, 3), None);
+    }
+
+    #[test]
+    fn reference_at_recognizes_paths_with_line_numbers() {
+        assert_eq!(reference_at("error at src/main.rs:12:5: oops", 12), Some(Reference::Path {
+            path: String::from("src/main.rs"),
+            line: Some(12),
+        }));
+        assert_eq!(reference_at("open \"~/notes.md\"", 8), Some(Reference::Path {
+            path: String::from("~/notes.md"),
+            line: None,
+        }));
+        assert_eq!(reference_at("amp editor", 1), None);
+    }
+}
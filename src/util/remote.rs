@@ -0,0 +1,199 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use scribe::Buffer;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const CONNECTION_DIRECTORY: &str = "ssh";
+
+/// Idle connections to a host are kept open for this many seconds,
+/// so that saving (or opening another file) doesn't reconnect.
+const CONNECTION_PERSISTENCE: u64 = 300;
+
+/// A file on another machine, reached over SSH, named using a URL like
+/// "ssh://user@host:2222/notes.md". As with scp, paths are relative to the
+/// remote user's home directory, unless they start with a second slash
+/// (e.g. "ssh://host//etc/hosts"). "scp://" URLs are accepted, too.
+#[derive(Debug, PartialEq)]
+pub struct RemoteFile {
+    pub host: String,
+    pub port: Option<u16>,
+    pub path: String,
+}
+
+impl RemoteFile {
+    pub fn parse(path: &Path) -> Option<RemoteFile> {
+        let url = path.to_str()?;
+        let location = if url.starts_with("ssh://") || url.starts_with("scp://") {
+            &url[6..]
+        } else {
+            return None;
+        };
+
+        let separator = location.find('/')?;
+        let (authority, path) = (&location[..separator], &location[separator + 1..]);
+        let (host, port) = split_authority(authority)?;
+        if path.is_empty() || path == "/" {
+            return None;
+        }
+
+        Some(RemoteFile {
+            host,
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    /// Fetches the file's content. Files that don't exist yet are empty.
+    pub fn read(&self) -> Result<String> {
+        let quoted_path = quote(&self.path);
+        let script = format!("test ! -e {} || cat -- {}", quoted_path, quoted_path);
+        let output = self.run(&script, None)
+            .chain_err(|| format!("Couldn't read {} from {}", self.path, self.host))?;
+
+        String::from_utf8(output).chain_err(|| format!("{} isn't valid UTF-8", self.path))
+    }
+
+    pub fn write(&self, content: &str) -> Result<()> {
+        let script = format!("cat > {}", quote(&self.path));
+        self.run(&script, Some(content.as_bytes()))
+            .chain_err(|| format!("Couldn't write {} to {}", self.path, self.host))?;
+
+        Ok(())
+    }
+
+    // Runs the shell script on the host, returning its output. There's no
+    // way to answer prompts from within the editor, so ssh is told not to ask
+    // for passwords or confirm host keys; connections are shared, instead.
+    fn run(&self, script: &str, input: Option<&[u8]>) -> Result<Vec<u8>> {
+        let mut command = Command::new("ssh");
+        command.args(&["-o", "BatchMode=yes", "-o", "ControlMaster=auto"]);
+        command.arg("-o").arg(format!("ControlPersist={}", CONNECTION_PERSISTENCE));
+        if let Ok(directory) = connection_directory() {
+            command.arg("-o").arg(format!("ControlPath={}", directory.join("%C").to_string_lossy()));
+        }
+        if let Some(port) = self.port {
+            command.arg("-p").arg(port.to_string());
+        }
+        command.arg("--").arg(&self.host).arg(script);
+
+        let mut child = command
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .chain_err(|| "Couldn't run ssh; is it installed?")?;
+
+        // Errors writing the input (e.g. when the connection fails)
+        // are reported through its exit status, below.
+        if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+            let _ = stdin.write_all(input);
+        }
+
+        let output = child.wait_with_output().chain_err(|| "Couldn't run ssh")?;
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            match output.status.code() {
+                // ssh uses this status for its own errors, rather than the script's.
+                Some(255) => bail!("Couldn't connect: {}", error.trim()),
+                _ => bail!("{}", error.trim()),
+            }
+        }
+
+        Ok(output.stdout)
+    }
+}
+
+/// Whether the buffer holds the content of a remote file,
+/// which is only ever written back to that file.
+pub fn is_remote(buffer: &Buffer) -> bool {
+    buffer.path.as_ref().and_then(|path| RemoteFile::parse(path)).is_some()
+}
+
+// Splits the authority (e.g. "user@host:2222") into the host, as passed
+// to ssh, and the port. IPv6 addresses are bracketed (e.g. "[::1]:2222")
+// since they contain colons of their own; ssh takes them without brackets.
+fn split_authority(authority: &str) -> Option<(String, Option<u16>)> {
+    let (user, address) = match authority.rfind('@') {
+        Some(index) => (&authority[..index + 1], &authority[index + 1..]),
+        None => ("", authority),
+    };
+    let (host, port) = if address.starts_with('[') {
+        let end = address.find(']')?;
+        (&address[1..end], &address[end + 1..])
+    } else {
+        let end = address.find(':').unwrap_or_else(|| address.len());
+        (&address[..end], &address[end..])
+    };
+    let port = match port {
+        "" => None,
+        port if port.starts_with(':') => Some(port[1..].parse().ok()?),
+        _ => return None,
+    };
+    if host.is_empty() {
+        return None;
+    }
+
+    Some((format!("{}{}", user, host), port))
+}
+
+fn connection_directory() -> Result<PathBuf> {
+    let directory = Preferences::state_path()?.join(CONNECTION_DIRECTORY);
+    fs::create_dir_all(&directory).chain_err(|| "Couldn't create the SSH connection directory")?;
+
+    Ok(directory)
+}
+
+// Quotes the argument for use in a shell script.
+fn quote(argument: &str) -> String {
+    format!("'{}'", argument.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::{quote, RemoteFile};
+
+    #[test]
+    fn parse_reads_the_host_port_and_path() {
+        assert_eq!(
+            RemoteFile::parse(Path::new("ssh://amp@example.com:2222/notes.md")),
+            Some(RemoteFile {
+                host: String::from("amp@example.com"),
+                port: Some(2222),
+                path: String::from("notes.md"),
+            })
+        );
+        assert_eq!(
+            RemoteFile::parse(Path::new("scp://example.com//etc/hosts")).map(|file| file.path),
+            Some(String::from("/etc/hosts"))
+        );
+        assert_eq!(RemoteFile::parse(Path::new("ssh://example.com:port/notes.md")), None);
+        assert_eq!(RemoteFile::parse(Path::new("ssh://[::1]x/notes.md")), None);
+        assert_eq!(RemoteFile::parse(Path::new("ssh://example.com/")), None);
+        assert_eq!(RemoteFile::parse(Path::new("notes.md")), None);
+    }
+
+    #[test]
+    fn parse_reads_bracketed_ipv6_hosts() {
+        assert_eq!(
+            RemoteFile::parse(Path::new("ssh://amp@[::1]:2222/notes.md")),
+            Some(RemoteFile {
+                host: String::from("amp@::1"),
+                port: Some(2222),
+                path: String::from("notes.md"),
+            })
+        );
+        assert_eq!(
+            RemoteFile::parse(Path::new("ssh://[fe80::1]/notes.md")).map(|file| (file.host, file.port)),
+            Some((String::from("fe80::1"), None))
+        );
+    }
+
+    #[test]
+    fn quote_escapes_single_quotes() {
+        assert_eq!(quote("amp's notes.md"), "'amp'\\''s notes.md'");
+    }
+}
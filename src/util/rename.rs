@@ -0,0 +1,44 @@
+use bloodhound::ExclusionPattern;
+use crate::util::{occurrences, workspace_replace};
+use crate::util::workspace_replace::FileOccurrences;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Finds whole-word occurrences of the name in the files beneath the workspace
+/// path, grouped by file, to be renamed to the replacement. Files open in buffers
+/// are searched using the buffers' (possibly unsaved) content, provided as a map
+/// of their paths to their data.
+pub fn find(path: &Path, exclusions: Option<&[ExclusionPattern]>, buffers: &HashMap<PathBuf, String>, name: &str, replacement: &str) -> Vec<FileOccurrences> {
+    workspace_replace::find(path, exclusions, buffers, |content| {
+        occurrences::find(content, name, 0, content.lines().count())
+            .into_iter()
+            .map(|range| (range, replacement.to_string()))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use super::find;
+
+    #[test]
+    fn find_groups_whole_word_occurrences_by_file() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/rename_find"));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "fn amp() {}\nfn ample() { amp() }\n").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let mut buffers = HashMap::new();
+        buffers.insert(root.join("README"), String::from("amp"));
+        fs::write(root.join("README"), "saved").unwrap();
+
+        let files = find(&root, None, &buffers, "amp", "editor");
+        let labels: Vec<&str> = files.iter().map(|file| file.label.as_str()).collect();
+        assert_eq!(labels, vec!["README", "src/lib.rs"]);
+        assert_eq!(files[1].occurrences.len(), 2);
+        assert_eq!(files[1].occurrences[1].line_content, "fn ample() { amp() }");
+        assert_eq!(files[1].occurrences[1].replacement, "editor");
+    }
+}
@@ -0,0 +1,157 @@
+use regex::Regex;
+use scribe::Buffer;
+use scribe::buffer::{Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Replaces every match of the pattern in the buffer with the replacement, as
+/// a single undoable operation. When a scope is provided, only matches lying
+/// entirely inside of it are replaced. Capture group references (e.g. `$1`)
+/// in the replacement are expanded if requested, and the replacement can be
+/// adjusted to follow the case of each match (see `match_case`). Returns the
+/// replacement count.
+pub fn replace_all(buffer: &mut Buffer, pattern: &Regex, replacement: &str, expand: bool, preserve_case: bool, scope: Option<&Range>) -> usize {
+    let matches: Vec<(Range, String)> = replacements(&buffer.data(), pattern, replacement, expand, preserve_case)
+        .into_iter()
+        .filter(|&(ref range, _)| {
+            scope.map(|scope| scope.start() <= range.start() && range.end() <= scope.end()).unwrap_or(true)
+        }).collect();
+
+    if matches.is_empty() {
+        return 0;
+    }
+
+    // Work backwards through the buffer, so that changes in length
+    // don't shift the positions of matches that are yet to be replaced.
+    let cursor = *buffer.cursor;
+    buffer.start_operation_group();
+    for &(ref range, ref content) in matches.iter().rev() {
+        buffer.delete_range(range.clone());
+        buffer.cursor.move_to(range.start());
+        if !content.is_empty() {
+            buffer.insert(content.clone());
+        }
+    }
+    buffer.end_operation_group();
+    buffer.cursor.move_to(cursor);
+
+    matches.len()
+}
+
+/// The (non-empty) matches of the pattern in the data, and what each of them
+/// would be replaced with, as described for `replace_all`. Matches don't span
+/// lines, so they can be listed (and replaced) line by line.
+pub fn replacements(data: &str, pattern: &Regex, replacement: &str, expand: bool, preserve_case: bool) -> Vec<(Range, String)> {
+    let mut matches = Vec::new();
+
+    for (line, content) in data.lines().enumerate() {
+        for captures in pattern.captures_iter(content) {
+            let (start, end) = match captures.pos(0) {
+                Some((start, end)) if start < end => (start, end),
+                _ => continue,
+            };
+            let range = Range::new(
+                Position{ line, offset: content[..start].graphemes(true).count() },
+                Position{ line, offset: content[..end].graphemes(true).count() }
+            );
+            let mut replaced = if expand {
+                captures.expand(replacement)
+            } else {
+                replacement.to_string()
+            };
+            if preserve_case {
+                replaced = match_case(&content[start..end], &replaced);
+            }
+            matches.push((range, replaced));
+        }
+    }
+
+    matches
+}
+
+/// Adjusts the replacement to follow the case of the text it's replacing:
+/// uppercase text gets an uppercase replacement, and otherwise, the case of
+/// the first letter is carried over (e.g. replacing "foo" with "barBaz" gives
+/// "BarBaz" for "Foo", and "BARBAZ" for "FOO").
+pub fn match_case(matched: &str, replacement: &str) -> String {
+    let letters: Vec<char> = matched.chars().filter(|c| c.is_alphabetic()).collect();
+    let first_letter = match letters.first() {
+        Some(letter) => *letter,
+        None => return replacement.to_string(),
+    };
+
+    if letters.len() > 1 && letters.iter().all(|letter| letter.is_uppercase()) {
+        return replacement.to_uppercase();
+    }
+
+    // Only the first letter of the replacement needs to change.
+    match replacement.char_indices().find(|&(_, c)| c.is_alphabetic()) {
+        Some((index, letter)) => {
+            let cased: String = if first_letter.is_uppercase() {
+                letter.to_uppercase().collect()
+            } else {
+                letter.to_lowercase().collect()
+            };
+
+            format!("{}{}{}", &replacement[..index], cased, &replacement[index + letter.len_utf8()..])
+        }
+        None => replacement.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use scribe::Buffer;
+    use scribe::buffer::{Position, Range};
+    use super::{match_case, replace_all};
+
+    #[test]
+    fn replace_all_replaces_every_match_as_a_single_operation() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp amp\namp");
+
+        let count = replace_all(&mut buffer, &Regex::new("amp").unwrap(), "editor", false, false, None);
+        assert_eq!(count, 3);
+        assert_eq!(buffer.data(), "editor editor\neditor");
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "amp amp\namp");
+    }
+
+    #[test]
+    fn replace_all_only_replaces_matches_inside_the_scope() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp amp\namp amp");
+        let scope = Range::new(Position{ line: 0, offset: 4 }, Position{ line: 1, offset: 5 });
+
+        let count = replace_all(&mut buffer, &Regex::new("amp").unwrap(), "a", false, false, Some(&scope));
+        assert_eq!(count, 2);
+        assert_eq!(buffer.data(), "amp a\na amp");
+    }
+
+    #[test]
+    fn replace_all_expands_capture_groups() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+
+        replace_all(&mut buffer, &Regex::new(r"(\w+) (\w+)").unwrap(), "$2 $1", true, false, None);
+        assert_eq!(buffer.data(), "editor amp");
+    }
+
+    #[test]
+    fn replace_all_preserves_the_case_of_each_match() {
+        let mut buffer = Buffer::new();
+        buffer.insert("foo Foo FOO");
+
+        replace_all(&mut buffer, &Regex::new("(?i)foo").unwrap(), "bar", false, true, None);
+        assert_eq!(buffer.data(), "bar Bar BAR");
+    }
+
+    #[test]
+    fn match_case_carries_over_the_case_of_the_first_letter() {
+        assert_eq!(match_case("fooBar", "BazQux"), "bazQux");
+        assert_eq!(match_case("FooBar", "bazQux"), "BazQux");
+        assert_eq!(match_case("FOO_BAR", "baz_qux"), "BAZ_QUX");
+        assert_eq!(match_case("_", "baz"), "baz");
+    }
+}
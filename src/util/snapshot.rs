@@ -0,0 +1,177 @@
+use scribe::Buffer;
+use scribe::buffer::{Position, Range};
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::time::{Duration, Instant};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Versions replaced within this many seconds of the last recorded
+/// one are folded into it, rather than being recorded separately.
+const INTERVAL: u64 = 30;
+
+/// The number of earlier versions kept for each buffer
+/// (i.e. at least an hour's worth, given the interval).
+const MAX_VERSIONS: usize = 120;
+
+/// Tracks the content buffers have had over time, so that they can be
+/// compared with or restored to the content they had at an earlier time.
+/// Versions are at most one interval apart, rather than one per change.
+pub struct Snapshots {
+    buffers: HashMap<usize, History>,
+}
+
+struct History {
+    current: Version,
+    versions: VecDeque<Version>,
+}
+
+struct Version {
+    since: Instant,
+    content: String,
+}
+
+impl Snapshots {
+    pub fn new() -> Snapshots {
+        Snapshots {
+            buffers: HashMap::new(),
+        }
+    }
+
+    /// Notes the buffer's content, recording the version it's replacing, if it's changed.
+    pub fn record(&mut self, buffer_id: usize, content: String, now: Instant) {
+        let history = match self.buffers.get_mut(&buffer_id) {
+            Some(history) => history,
+            None => {
+                self.buffers.insert(buffer_id, History {
+                    current: Version{ since: now, content },
+                    versions: VecDeque::new(),
+                });
+                return;
+            }
+        };
+        if history.current.content == content {
+            return;
+        }
+
+        let previous = mem::replace(&mut history.current, Version{ since: now, content });
+        let folded = history.versions
+            .back()
+            .map(|last| previous.since.duration_since(last.since) < Duration::from_secs(INTERVAL))
+            .unwrap_or(false);
+        if folded {
+            if let Some(last) = history.versions.back_mut() {
+                last.content = previous.content;
+            }
+        } else {
+            history.versions.push_back(previous);
+            if history.versions.len() > MAX_VERSIONS {
+                history.versions.pop_front();
+            }
+        }
+    }
+
+    /// Drops the buffer's versions (e.g. when it's closed).
+    pub fn forget(&mut self, buffer_id: usize) {
+        self.buffers.remove(&buffer_id);
+    }
+
+    /// The buffer's content at the specified time, if it was being tracked by then.
+    pub fn content_at(&self, buffer_id: usize, time: Instant) -> Option<&str> {
+        let history = self.buffers.get(&buffer_id)?;
+        if time >= history.current.since {
+            return Some(&history.current.content);
+        }
+
+        history.versions
+            .iter()
+            .rev()
+            .find(|version| version.since <= time)
+            .map(|version| version.content.as_str())
+    }
+}
+
+/// Parses a length of time like "10m", "30s", or "2h";
+/// bare numbers are treated as a number of minutes.
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let (number, unit) = match input.find(|c: char| !c.is_digit(10)) {
+        Some(index) => input.split_at(index),
+        None => (input, "m"),
+    };
+    let number: u64 = number.parse().ok()?;
+    let seconds = match unit {
+        "s" => number,
+        "m" => number * 60,
+        "h" => number * 60 * 60,
+        _ => return None,
+    };
+
+    Some(Duration::from_secs(seconds))
+}
+
+/// Replaces the buffer's content as a single undoable operation.
+pub fn restore(buffer: &mut Buffer, content: &str) {
+    let data = buffer.data();
+    let last_line = data.split('\n').last().unwrap_or("");
+    let end = Position {
+        line: data.split('\n').count() - 1,
+        offset: last_line.graphemes(true).count(),
+    };
+    let cursor = *buffer.cursor;
+
+    buffer.start_operation_group();
+    buffer.delete_range(Range::new(Position{ line: 0, offset: 0 }, end));
+    buffer.cursor.move_to(Position{ line: 0, offset: 0 });
+    buffer.insert(content);
+    buffer.end_operation_group();
+    buffer.cursor.move_to(cursor);
+}
+
+#[cfg(test)]
+mod tests {
+    use scribe::Buffer;
+    use std::time::{Duration, Instant};
+    use super::{parse_duration, restore, Snapshots};
+
+    #[test]
+    fn content_at_returns_the_version_current_at_the_time() {
+        let start = Instant::now();
+        let at = |seconds| start + Duration::from_secs(seconds);
+        let mut snapshots = Snapshots::new();
+        snapshots.record(0, String::from("a"), at(0));
+        snapshots.record(0, String::from("b"), at(60));
+        snapshots.record(0, String::from("c"), at(70));
+        snapshots.record(0, String::from("d"), at(200));
+        snapshots.record(0, String::from("d"), at(300));
+
+        // "b" was replaced shortly after it was recorded,
+        // so it's folded into the version that replaced it.
+        assert_eq!(snapshots.content_at(0, at(30)), Some("a"));
+        assert_eq!(snapshots.content_at(0, at(65)), Some("c"));
+        assert_eq!(snapshots.content_at(0, at(150)), Some("c"));
+        assert_eq!(snapshots.content_at(0, at(250)), Some("d"));
+        assert_eq!(snapshots.content_at(1, at(250)), None);
+
+        snapshots.forget(0);
+        assert_eq!(snapshots.content_at(0, at(250)), None);
+    }
+
+    #[test]
+    fn parse_duration_defaults_to_minutes() {
+        assert_eq!(parse_duration("10"), Some(Duration::from_secs(600)));
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("2h"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("5d"), None);
+    }
+
+    #[test]
+    fn restore_replaces_the_content_as_a_single_operation() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        restore(&mut buffer, "amp");
+        assert_eq!(buffer.data(), "amp");
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "amp\neditor\n");
+    }
+}
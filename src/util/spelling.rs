@@ -0,0 +1,233 @@
+use crate::errors::*;
+use std::cmp;
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The syntax scopes whose words are spell checked: comments and strings in
+/// source code, and everything in prose formats apart from code and links.
+pub const CHECKED_SCOPES: &str = "comment, string, text - markup.raw - markup.underline.link";
+
+// Suggestions further from the misspelled word than this aren't worth offering.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// A set of correctly spelled words, loaded from word lists with one word per
+/// line. Hunspell `.dic` files can be used as-is, though their affix rules
+/// aren't applied (only the listed stems are recognized).
+pub struct Dictionary {
+    words: HashSet<String>,
+    personal_path: Option<PathBuf>,
+}
+
+impl Dictionary {
+    pub fn new() -> Dictionary {
+        Dictionary {
+            words: HashSet::new(),
+            personal_path: None,
+        }
+    }
+
+    /// Loads the words from each of the dictionaries that can be read, along
+    /// with the personal dictionary (if it exists), which words are added to.
+    pub fn load(paths: &[PathBuf], personal_path: PathBuf) -> Result<Dictionary> {
+        let mut dictionary = Dictionary::new();
+        let mut loaded = false;
+        for path in paths {
+            if let Ok(content) = fs::read_to_string(path) {
+                dictionary.insert_words(&content);
+                loaded = true;
+            }
+        }
+        if !loaded {
+            let paths: Vec<String> = paths.iter().map(|path| path.to_string_lossy().into_owned()).collect();
+            bail!("Couldn't read a spelling dictionary from {}", paths.join(", "));
+        }
+
+        if personal_path.exists() {
+            let content = fs::read_to_string(&personal_path)
+                .chain_err(|| "Couldn't read the personal dictionary")?;
+            dictionary.insert_words(&content);
+        }
+        dictionary.personal_path = Some(personal_path);
+
+        Ok(dictionary)
+    }
+
+    pub fn insert(&mut self, word: &str) {
+        self.words.insert(word.to_string());
+    }
+
+    /// Accepts the word from now on, recording it in the personal dictionary.
+    pub fn add(&mut self, word: &str) -> Result<()> {
+        if let Some(ref path) = self.personal_path {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .chain_err(|| "Couldn't open the personal dictionary")?;
+            writeln!(file, "{}", word).chain_err(|| "Couldn't write to the personal dictionary")?;
+        }
+        self.insert(word);
+
+        Ok(())
+    }
+
+    /// Whether the word is spelled correctly. Words are also accepted in
+    /// lowercase, so that capitalized words at the start of sentences are.
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word) || self.words.contains(&word.to_lowercase())
+    }
+
+    /// The misspelled words starting within the specified (grapheme offset)
+    /// ranges of the line, as grapheme offset ranges. Words that look like
+    /// identifiers (e.g. "snake_case", "camelCase", or "HTTP") are skipped.
+    pub fn misspellings(&self, line: &str, ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        words(line)
+            .into_iter()
+            .filter(|&(start, _, _)| {
+                ranges.iter().any(|&(range_start, range_end)| start >= range_start && start < range_end)
+            })
+            .filter(|&(_, _, ref word)| is_checkable(word) && !self.contains(word))
+            .map(|(start, end, _)| (start, end))
+            .collect()
+    }
+
+    /// Known words closest to the specified one, best matches first.
+    pub fn suggestions(&self, word: &str, max_results: usize) -> Vec<String> {
+        let word = word.to_lowercase();
+        let length = word.chars().count();
+        let mut candidates: Vec<(usize, &String)> = self.words
+            .iter()
+            .filter(|candidate| {
+                let candidate_length = candidate.chars().count();
+                cmp::max(length, candidate_length) - cmp::min(length, candidate_length) <= MAX_SUGGESTION_DISTANCE
+            })
+            .map(|candidate| (edit_distance(&word, &candidate.to_lowercase()), candidate))
+            .filter(|&(distance, _)| distance <= MAX_SUGGESTION_DISTANCE)
+            .collect();
+        candidates.sort();
+
+        candidates
+            .into_iter()
+            .take(max_results)
+            .map(|(_, candidate)| candidate.clone())
+            .collect()
+    }
+
+    fn insert_words(&mut self, content: &str) {
+        for line in content.lines() {
+            // Hunspell dictionaries start with a word count, and may follow
+            // words with a slash and affix flags.
+            let word = line.split('/').next().unwrap_or("").trim();
+            if !word.is_empty() && !word.chars().all(|character| character.is_numeric()) {
+                self.words.insert(word.to_string());
+            }
+        }
+    }
+}
+
+/// The word at the specified (grapheme) offset, along with its offset.
+pub fn word_at(line: &str, offset: usize) -> Option<(usize, String)> {
+    words(line)
+        .into_iter()
+        .find(|&(start, end, _)| offset >= start && offset < end)
+        .map(|(start, _, word)| (start, word))
+}
+
+// Runs of letters (including apostrophes within them), along with their
+// starting and ending grapheme offsets. Runs that include digits or
+// underscores are returned as a single word, so that they can be skipped.
+fn words(line: &str) -> Vec<(usize, usize, String)> {
+    let graphemes: Vec<&str> = line.graphemes(true).collect();
+    let mut words = Vec::new();
+    let mut offset = 0;
+
+    while offset < graphemes.len() {
+        if !is_word(graphemes[offset]) {
+            offset += 1;
+            continue;
+        }
+
+        let start = offset;
+        while offset < graphemes.len() && (is_word(graphemes[offset]) || is_inner_apostrophe(&graphemes, offset)) {
+            offset += 1;
+        }
+        words.push((start, offset, graphemes[start..offset].concat()));
+    }
+
+    words
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().all(|character| character.is_alphanumeric() || character == '_')
+}
+
+fn is_inner_apostrophe(graphemes: &[&str], offset: usize) -> bool {
+    (graphemes[offset] == "'" || graphemes[offset] == "’") &&
+        graphemes.get(offset + 1).map(|grapheme| is_word(grapheme)).unwrap_or(false)
+}
+
+fn is_checkable(word: &str) -> bool {
+    let mut characters = word.chars();
+    let first = characters.next();
+
+    word.chars().count() > 1 &&
+        first.map(|character| character.is_alphabetic()).unwrap_or(false) &&
+        characters.all(|character| character.is_lowercase() || character == '\'' || character == '’')
+}
+
+// The number of single-character insertions, deletions,
+// or substitutions needed to turn one word into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..b.len() + 1).collect();
+
+    for (i, a_character) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_character) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_character == *b_character { 0 } else { 1 };
+            current.push(cmp::min(substitution, cmp::min(previous[j + 1], current[j]) + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{word_at, Dictionary};
+
+    fn dictionary() -> Dictionary {
+        let mut dictionary = Dictionary::new();
+        dictionary.insert_words("4\nthe/S\nquick\nbrown\nfox/M\n");
+
+        dictionary
+    }
+
+    #[test]
+    fn misspellings_skips_known_words_and_identifiers() {
+        let line = "The quikc brown fxo, snake_case camelCase HTTP x2";
+
+        assert_eq!(dictionary().misspellings(line, &[(0, line.len())]), vec![(4, 9), (16, 19)]);
+    }
+
+    #[test]
+    fn misspellings_only_checks_words_within_the_ranges() {
+        assert_eq!(dictionary().misspellings("let quikc = 1; // quikc", &[(15, 23)]), vec![(18, 23)]);
+    }
+
+    #[test]
+    fn suggestions_lists_the_closest_words_first() {
+        assert_eq!(dictionary().suggestions("Teh", 5), vec!["the"]);
+        assert_eq!(dictionary().suggestions("brwn", 5), vec!["brown"]);
+    }
+
+    #[test]
+    fn word_at_includes_inner_apostrophes() {
+        assert_eq!(word_at("'don't' do", 3), Some((1, String::from("don't"))));
+        assert_eq!(word_at("'don't' do", 0), None);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::errors::*;
+use crate::models::application::Event;
+use fragment::matching::AsStr;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// The name of the tags file, looked for at the root of the workspace.
+pub const FILE_NAME: &str = "tags";
+
+// Header line recording that a tags file is sorted in byte order, which
+// makes binary searching it possible. Case-folded (or unsorted) files
+// have to be searched from top to bottom.
+const SORTED_HEADER: &str = "!_TAG_FILE_SORTED\t1";
+
+/// Where a tag's definition is found in its file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Address {
+    /// A zero-based line number.
+    Line(usize),
+    /// The content of the definition's line, which is a prefix
+    /// of it, rather than all of it, unless it's anchored.
+    Pattern { content: String, anchored: bool },
+}
+
+/// A definition listed in a tags file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    pub name: String,
+    pub path: PathBuf,
+    pub address: Address,
+    label: String,
+}
+
+impl Tag {
+    /// The line of the content containing the definition, if it can be found.
+    pub fn line(&self, content: &str) -> Option<usize> {
+        match self.address {
+            Address::Line(line) => Some(line),
+            Address::Pattern{ content: ref pattern, anchored } => content.lines().position(|line| {
+                if anchored { line == pattern } else { line.starts_with(pattern.as_str()) }
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl AsStr for Tag {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Finds the definitions of the name in the tags file. Sorted files are
+/// binary searched, without reading any more of them than necessary.
+pub fn find(tags_path: &Path, name: &str) -> Result<Vec<Tag>> {
+    let file = File::open(tags_path).chain_err(|| "Couldn't open the tags file")?;
+    let length = file.metadata().chain_err(|| "Couldn't read the tags file")?.len();
+    let mut reader = BufReader::new(file);
+    let directory = tags_path.parent().unwrap_or_else(|| Path::new(""));
+
+    let sorted = is_sorted(&mut reader)?;
+    let start = if sorted { lower_bound(&mut reader, length, name)? } else { 0 };
+    reader.seek(SeekFrom::Start(start)).chain_err(|| "Couldn't read the tags file")?;
+
+    // In sorted files, the definitions are listed together,
+    // starting where the binary search left off.
+    let mut tags = Vec::new();
+    for line in reader.lines() {
+        let line = line.chain_err(|| "Couldn't read the tags file")?;
+        if sorted && line_name(&line) > name {
+            break;
+        } else if line_name(&line) == name {
+            tags.extend(parse(&line, directory));
+        }
+    }
+
+    Ok(tags)
+}
+
+/// Every definition in the tags file, in the order they're listed.
+pub fn all(tags_path: &Path) -> Result<Vec<Tag>> {
+    let file = File::open(tags_path).chain_err(|| "Couldn't open the tags file")?;
+    let directory = tags_path.parent().unwrap_or_else(|| Path::new(""));
+
+    Ok(BufReader::new(file)
+        .lines()
+        .filter_map(|line| line.ok())
+        .filter_map(|line| parse(&line, directory))
+        .collect())
+}
+
+/// Runs the tags command in the workspace on a background thread,
+/// letting the application know once it's finished.
+pub fn spawn_generate(command: Vec<String>, workspace: PathBuf, events: Sender<Event>) {
+    thread::spawn(move || {
+        let result = generate(&command, &workspace);
+        let _ = events.send(Event::TagsGenerated(result));
+    });
+}
+
+fn generate(command: &[String], workspace: &Path) -> std::result::Result<(), String> {
+    let (program, arguments) = command
+        .split_first()
+        .ok_or_else(|| String::from("The tags command is empty"))?;
+    let output = Command::new(program)
+        .args(arguments)
+        .current_dir(workspace)
+        .output()
+        .map_err(|error| format!("Couldn't run {}: {}", program, error))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+// Headers are listed first, since "!" sorts before anything else.
+fn is_sorted<R: BufRead>(reader: &mut R) -> Result<bool> {
+    for line in reader.lines() {
+        let line = line.chain_err(|| "Couldn't read the tags file")?;
+        if !line.starts_with("!_TAG_") {
+            break;
+        } else if line.starts_with(SORTED_HEADER) {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+// The offset of the first line whose tag name isn't ordered before the name,
+// found by binary searching byte offsets: each probe reads the first line
+// starting at or after its offset, whose name only increases with the offset.
+fn lower_bound<R: BufRead + Seek>(reader: &mut R, length: u64, name: &str) -> Result<u64> {
+    let mut low = 0;
+    let mut high = length;
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match line_at_or_after(reader, middle)? {
+            Some((end, ref line)) if line_name(line) < name => low = end,
+            _ => high = middle,
+        }
+    }
+
+    Ok(low)
+}
+
+// The line starting at or after the offset, along with the offset it ends at (after
+// its newline). Starting one byte early lands on the previous line's newline when
+// the offset is already at the start of a line, which is then all that's skipped.
+fn line_at_or_after<R: BufRead + Seek>(reader: &mut R, offset: u64) -> Result<Option<(u64, String)>> {
+    let mut start = offset;
+    if offset > 0 {
+        reader.seek(SeekFrom::Start(offset - 1)).chain_err(|| "Couldn't read the tags file")?;
+        let mut partial = Vec::new();
+        let skipped = reader.read_until(b'\n', &mut partial).chain_err(|| "Couldn't read the tags file")?;
+        start = offset - 1 + skipped as u64;
+    } else {
+        reader.seek(SeekFrom::Start(0)).chain_err(|| "Couldn't read the tags file")?;
+    }
+
+    let mut line = Vec::new();
+    let length = reader.read_until(b'\n', &mut line).chain_err(|| "Couldn't read the tags file")?;
+    if length == 0 {
+        return Ok(None);
+    }
+
+    let content = String::from_utf8_lossy(&line).trim_end_matches(&['\r', '\n'][..]).to_string();
+    Ok(Some((start + length as u64, content)))
+}
+
+fn line_name(line: &str) -> &str {
+    line.split('\t').next().unwrap_or("")
+}
+
+// Parses a "name<TAB>file<TAB>address;"<TAB>fields" line, with the file
+// relative to the directory containing the tags file.
+fn parse(line: &str, directory: &Path) -> Option<Tag> {
+    if line.starts_with("!_TAG_") {
+        return None;
+    }
+
+    let mut fields = line.splitn(3, '\t');
+    let name = fields.next()?;
+    let file = fields.next()?;
+    let rest = fields.next()?;
+    let address = rest.split(";\"").next()?.trim_end();
+
+    let address = if let Ok(line_number) = address.parse::<usize>() {
+        Address::Line(line_number.saturating_sub(1))
+    } else if address.len() >= 2 && (address.starts_with('/') || address.starts_with('?')) {
+        let pattern = &address[1..address.len() - 1];
+        let pattern = pattern.trim_start_matches('^');
+        let anchored = pattern.ends_with('$') && !pattern.ends_with("\\$");
+        let pattern = if anchored { &pattern[..pattern.len() - 1] } else { pattern };
+
+        Address::Pattern {
+            content: pattern.replace("\\/", "/").replace("\\?", "?").replace("\\\\", "\\"),
+            anchored,
+        }
+    } else {
+        return None;
+    };
+
+    Some(Tag {
+        name: name.to_string(),
+        path: directory.join(file),
+        address,
+        label: format!("{}  {}", name, file),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use super::{all, find, Address};
+
+    const TAGS: &str = "!_TAG_FILE_FORMAT\t2\n!_TAG_FILE_SORTED\t1\t/0=unsorted/\n\
+        amp\tsrc/amp.rs\t/^fn amp() {$/;\"\tf\n\
+        editor\tsrc/editor.rs\t12;\"\tf\n\
+        editor\tsrc/main.rs\t/^struct editor/;\"\ts\n\
+        zebra\tsrc/zoo.rs\t/^fn zebra() {$/;\"\tf\n";
+
+    fn tags_path(name: &str) -> PathBuf {
+        let path = PathBuf::from(env!("OUT_DIR")).join(name);
+        fs::write(&path, TAGS).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn find_binary_searches_sorted_files() {
+        let path = tags_path("sorted_tags");
+        let directory = path.parent().unwrap().to_path_buf();
+
+        let tags = find(&path, "editor").unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].path, directory.join("src/editor.rs"));
+        assert_eq!(tags[0].address, Address::Line(11));
+        assert_eq!(tags[1].address, Address::Pattern{ content: String::from("struct editor"), anchored: false });
+
+        assert_eq!(find(&path, "amp").unwrap().len(), 1);
+        assert_eq!(find(&path, "zebra").unwrap().len(), 1);
+        assert!(find(&path, "missing").unwrap().is_empty());
+        assert!(find(&path, "zzz").unwrap().is_empty());
+    }
+
+    #[test]
+    fn line_finds_pattern_addresses_in_content() {
+        let tags = all(&tags_path("all_tags")).unwrap();
+        assert_eq!(tags.len(), 4);
+
+        let content = "// amp\nfn amp() {\n}\nstruct editor {}";
+        assert_eq!(tags[0].line(content), Some(1));
+        assert_eq!(tags[2].line(content), Some(3));
+        assert_eq!(tags[3].line(content), None);
+    }
+}
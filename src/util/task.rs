@@ -0,0 +1,122 @@
+use bloodhound::ExclusionPattern;
+use crate::models::application::Event;
+use crate::util::files;
+use fragment::matching::AsStr;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::thread;
+
+lazy_static! {
+    static ref TASK: Regex = Regex::new(r"\b(TODO|FIXME|HACK)\b:?\s*(.*)$").unwrap();
+}
+
+/// A TODO, FIXME, or HACK comment, with a zero-based line number.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Task {
+    pub path: PathBuf,
+    pub line: usize,
+    /// The task's location and content, as listed (e.g. "src/main.rs:3: TODO: x").
+    pub label: String,
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl AsStr for Task {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+/// The tasks in the content, labelled with the path they're found at.
+pub fn tasks(path: &Path, label_path: &Path, content: &str) -> Vec<Task> {
+    content.lines().enumerate().filter_map(|(line, text)| {
+        let captures = TASK.captures(text)?;
+        // Block comment terminators aren't part of the note.
+        let note = captures.at(2).map(|note| note.trim().trim_end_matches("*/").trim_end_matches("-->").trim());
+        let label = match note {
+            Some(note) if !note.is_empty() => format!("{}: {}", captures.at(1)?, note),
+            _ => captures.at(1)?.to_string(),
+        };
+
+        Some(Task {
+            path: path.to_path_buf(),
+            line,
+            label: format!("{}:{}: {}", label_path.to_string_lossy(), line + 1, label),
+        })
+    }).collect()
+}
+
+/// Scans the files beneath the workspace path on a background thread, sending
+/// the tasks they contain back to the application, ordered by file and line.
+/// Files open in buffers are scanned using the buffers' (possibly unsaved)
+/// content, provided as a map of their paths to their data.
+pub fn spawn_scan(path: PathBuf, exclusions: Option<Vec<ExclusionPattern>>, buffers: HashMap<PathBuf, String>, events: Sender<Event>) {
+    thread::spawn(move || {
+        let paths = files::workspace_files(&path, exclusions.as_ref().map(|exclusions| exclusions.as_slice()));
+
+        let mut results = Vec::new();
+        for file in paths {
+            let label_path = file.strip_prefix(&path).unwrap_or(&file).to_path_buf();
+            match buffers.get(&file) {
+                Some(content) => results.extend(tasks(&file, &label_path, content)),
+                None => if let Some(content) = files::read_text(&file) {
+                    results.extend(tasks(&file, &label_path, &content));
+                },
+            }
+        }
+
+        let _ = events.send(Event::TaskScanComplete(results));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::Event;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc;
+    use super::{spawn_scan, tasks};
+
+    #[test]
+    fn tasks_finds_todo_fixme_and_hack_comments() {
+        let content = "// TODO: write this\nfn amp() {}\n# FIXME\n/* HACK works around a bug */\n// TODOS";
+        let tasks = tasks(Path::new("/amp/src/main.rs"), Path::new("src/main.rs"), content);
+        let labels: Vec<&str> = tasks.iter().map(|task| task.label.as_str()).collect();
+
+        assert_eq!(labels, vec![
+            "src/main.rs:1: TODO: write this",
+            "src/main.rs:3: FIXME",
+            "src/main.rs:4: HACK: works around a bug",
+        ]);
+        assert_eq!(tasks[1].line, 2);
+        assert_eq!(tasks[1].path, PathBuf::from("/amp/src/main.rs"));
+    }
+
+    #[test]
+    fn spawn_scan_prefers_buffer_content_to_files() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/task_scan"));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "// TODO: saved").unwrap();
+        fs::write(root.join("notes.txt"), "FIXME later").unwrap();
+        let mut buffers = HashMap::new();
+        buffers.insert(root.join("src/lib.rs"), String::from("\n// TODO: unsaved"));
+
+        let (sender, receiver) = mpsc::channel();
+        spawn_scan(root.clone(), None, buffers, sender);
+        let tasks = match receiver.recv().unwrap() {
+            Event::TaskScanComplete(tasks) => tasks,
+            _ => panic!("unexpected event"),
+        };
+
+        let labels: Vec<&str> = tasks.iter().map(|task| task.label.as_str()).collect();
+        assert_eq!(labels, vec!["notes.txt:1: FIXME: later", "src/lib.rs:2: TODO: unsaved"]);
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Measures the phases of a larger operation (e.g. starting the
+/// application), each of which runs until the next is recorded.
+pub struct Timings {
+    started_at: Instant,
+    last_recorded_at: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    pub fn new() -> Timings {
+        let now = Instant::now();
+
+        Timings {
+            started_at: now,
+            last_recorded_at: now,
+            phases: Vec::new(),
+        }
+    }
+
+    /// Records the time elapsed since the previous phase (or the start).
+    pub fn record(&mut self, phase: &'static str) {
+        let now = Instant::now();
+        self.phases.push((phase, now - self.last_recorded_at));
+        self.last_recorded_at = now;
+    }
+
+    /// Adds a span that ran within the phases recorded so far (e.g. highlighting,
+    /// as part of rendering). Spans with the same name are added together, and
+    /// since they overlap with other phases, they don't count towards the total.
+    pub fn add(&mut self, span: &'static str, duration: Duration) {
+        if let Some(&mut (_, ref mut total)) = self.phases.iter_mut().find(|&&mut (name, _)| name == span) {
+            *total += duration;
+            return;
+        }
+
+        self.phases.push((span, duration));
+    }
+
+    pub fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    /// The time elapsed between the start and the most recently recorded phase.
+    pub fn total(&self) -> Duration {
+        self.last_recorded_at - self.started_at
+    }
+
+    /// A single-line overview of the phases, suitable for the status line.
+    pub fn summary(&self) -> String {
+        let phases: Vec<String> = self.phases
+            .iter()
+            .map(|&(phase, duration)| format!("{} {:.1}ms", phase, milliseconds(duration)))
+            .collect();
+
+        phases.join(" ")
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &(phase, duration) in &self.phases {
+            writeln!(f, "{:<20} {:>8.2}ms", phase, milliseconds(duration))?;
+        }
+
+        write!(f, "{:<20} {:>8.2}ms", "total", milliseconds(self.total()))
+    }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + f64::from(duration.subsec_nanos()) / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timings;
+    use std::time::Duration;
+
+    #[test]
+    fn phases_are_recorded_in_order_and_add_up_to_the_total() {
+        let mut timings = Timings::new();
+        timings.record("preferences");
+        timings.record("workspace");
+
+        let names: Vec<&str> = timings.phases().iter().map(|&(name, _)| name).collect();
+        assert_eq!(names, vec!["preferences", "workspace"]);
+
+        let sum = timings.phases().iter().fold(Duration::new(0, 0), |sum, &(_, duration)| sum + duration);
+        assert_eq!(sum, timings.total());
+    }
+
+    #[test]
+    fn add_accumulates_spans_without_affecting_the_total() {
+        let mut timings = Timings::new();
+        timings.record("render");
+        let total = timings.total();
+        timings.add("highlight", Duration::from_millis(2));
+        timings.add("highlight", Duration::from_millis(3));
+
+        assert_eq!(timings.phases()[1], ("highlight", Duration::from_millis(5)));
+        assert_eq!(timings.total(), total);
+    }
+}
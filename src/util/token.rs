@@ -1,6 +1,8 @@
-use crate::util::movement_lexer;
+use crate::util::line_index::LineIndex;
+use crate::util::movement_lexer::LexCache;
 use scribe::buffer::{Buffer, Position};
 use luthor::token::Category;
+use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum Direction {
@@ -8,76 +10,143 @@ pub enum Direction {
     Backward,
 }
 
+// Lines are lexed this many at a time, moving away from the cursor until
+// a token's found, so that motions don't lex the rest of a large buffer.
+const CHUNK_LINES: usize = 100;
+
+/// Finds the start of the token before or after the cursor. The buffer's line
+/// index (which must be up to date with its content) is used to find lines,
+/// so that only those near the cursor, on the relevant side of it, are lexed.
 pub fn adjacent_token_position(buffer: &mut Buffer,
+                           lex_cache: &mut LexCache,
+                           line_index: &mut LineIndex,
                            whitespace: bool,
                            direction: Direction)
                            -> Option<(Position)> {
-    let mut line = 0;
-    let mut offset = 0;
-    let mut previous_position = Position {
-        line: 0,
-        offset: 0,
-    };
-    let tokens = movement_lexer::lex(&buffer.data());
-    for token in tokens {
-        let position = Position {
-            line,
-            offset,
-        };
-        if position > *buffer.cursor && direction == Direction::Forward {
-            // We've found the next token!
-            if whitespace {
-                // We're allowing whitespace, so return the token.
+    let data = buffer.data();
+
+    // Token lengths are measured in bytes; compare against the cursor in those
+    // terms, converting back to grapheme offsets once a position's been found.
+    let cursor = byte_position(line_content(&data, line_index, buffer.cursor.line), *buffer.cursor);
+    let position = match direction {
+        Direction::Forward => next_token_position(&data, lex_cache, line_index, cursor, whitespace),
+        Direction::Backward => previous_token_position(&data, lex_cache, line_index, cursor),
+    }?;
+
+    Some(grapheme_position(line_content(&data, line_index, position.line), position))
+}
+
+// Finds the first token starting after the cursor, skipping whitespace unless
+// it's allowed, lexing the lines from the cursor onwards a chunk at a time.
+fn next_token_position(data: &str, lex_cache: &mut LexCache, line_index: &mut LineIndex, cursor: Position, whitespace: bool) -> Option<Position> {
+    let mut start = cursor.line;
+    let mut continued_whitespace = false;
+
+    loop {
+        let tokens = lex_cache.lex_line_range(data, line_index, start..start + CHUNK_LINES);
+        if tokens.is_empty() {
+            return None;
+        }
+
+        let mut position = Position{ line: start, offset: 0 };
+        for (index, token) in tokens.iter().enumerate() {
+            // Whitespace carried over from the previous chunk doesn't start a new token.
+            let continuation = index == 0 && continued_whitespace && token.category == Category::Whitespace;
+            if position > cursor && !continuation && (whitespace || token.category != Category::Whitespace) {
                 return Some(position);
-            } else {
-                // We're not allowing whitespace; skip this token if that's what it is.
-                match token.category {
-                    Category::Whitespace => (),
-                    _ => {
-                        return Some(position);
-                    }
-                }
             }
+
+            advance(&mut position, &token.lexeme);
         }
 
-        // We've not yet found it; advance to the next token.
-        match token.lexeme.split('\n').count() {
-            1 => {
-                // There's only one line in this token, so
-                // only advance the offset by its size.
-                offset += token.lexeme.len()
-            }
-            n => {
-                // There are multiple lines, so advance the
-                // line count and set the offset to the last
-                // line's length
-                line += n - 1;
-                offset = token.lexeme.split('\n').last().unwrap().len();
-            }
-        };
-
-        // If we're looking backwards and the next iteration will pass the
-        // cursor, return the current position, or the previous if it's whitespace.
-        let next_position = Position {
-            line,
-            offset,
-        };
-        if next_position >= *buffer.cursor && direction == Direction::Backward {
-            match token.category {
-                Category::Whitespace => {
-                    return Some(previous_position);
+        continued_whitespace = tokens.last().map(|token| token.category == Category::Whitespace).unwrap_or(false);
+        start += CHUNK_LINES;
+    }
+}
+
+// Finds the start of the token before the cursor, or the one before that if
+// it's whitespace, lexing the lines up to the cursor a chunk at a time.
+fn previous_token_position(data: &str, lex_cache: &mut LexCache, line_index: &mut LineIndex, cursor: Position) -> Option<Position> {
+    let mut cursor = cursor;
+
+    loop {
+        let start = cursor.line.saturating_sub(CHUNK_LINES);
+        let tokens = lex_cache.lex_line_range(data, line_index, start..cursor.line + 1);
+        let mut position = Position{ line: start, offset: 0 };
+        let mut previous_position = None;
+
+        for token in tokens.iter() {
+            let token_position = position;
+            advance(&mut position, &token.lexeme);
+
+            // Stop at the token that reaches the cursor.
+            if position >= cursor {
+                if token.category != Category::Whitespace {
+                    return Some(token_position);
                 }
-                _ => {
-                    return Some(position);
+
+                match previous_position {
+                    Some(previous_position) => return Some(previous_position),
+                    None if start == 0 => return Some(token_position),
+                    // The whitespace may have started in an earlier chunk;
+                    // look for the token before it, from its first line.
+                    None => break,
                 }
             }
+
+            previous_position = Some(token_position);
         }
 
-        // Keep a reference to the current position in case the next
-        // token is whitespace, and we need to return this instead.
-        previous_position = position;
+        if position < cursor || start == 0 {
+            return None;
+        }
+        cursor = Position{ line: start, offset: 0 };
     }
+}
 
-    None
+// Moves the position past the lexeme, which may span multiple lines.
+fn advance(position: &mut Position, lexeme: &str) {
+    match lexeme.split('\n').count() {
+        // There's only one line in this token, so
+        // only advance the offset by its size.
+        1 => position.offset += lexeme.len(),
+        // There are multiple lines, so advance the line
+        // count and set the offset to the last line's length.
+        n => {
+            position.line += n - 1;
+            position.offset = lexeme.split('\n').last().unwrap_or("").len();
+        }
+    }
 }
 
+// The content of the line, excluding its line ending.
+fn line_content<'a>(data: &'a str, line_index: &mut LineIndex, line: usize) -> &'a str {
+    let start = match line_index.line_start(data, line) {
+        Some(start) => start,
+        None => return "",
+    };
+    let content = data[start..].split('\n').next().unwrap_or("");
+
+    content.trim_end_matches('\r')
+}
+
+// Converts a position's grapheme offset into a byte offset within its line.
+fn byte_position(line: &str, position: Position) -> Position {
+    let offset = line
+        .grapheme_indices(true)
+        .nth(position.offset)
+        .map(|(index, _)| index)
+        .unwrap_or_else(|| line.len());
+
+    Position { line: position.line, offset }
+}
+
+// Converts a position's byte offset within its line into a grapheme offset.
+fn grapheme_position(line: &str, position: Position) -> Position {
+    let offset = line
+        .get(..position.offset)
+        .map(|prefix| prefix.graphemes(true).count())
+        .unwrap_or(position.offset);
+
+    Position { line: position.line, offset }
+}
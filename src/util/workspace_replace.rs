@@ -0,0 +1,182 @@
+use bloodhound::ExclusionPattern;
+use crate::util::files;
+use scribe::Buffer;
+use scribe::buffer::Range;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// An occurrence found in the workspace, and what it'll be replaced
+/// with, if it's still selected when the replacements are applied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Occurrence {
+    pub range: Range,
+    pub line_content: String,
+    pub replacement: String,
+    pub selected: bool,
+}
+
+impl Occurrence {
+    /// The occurrence's line, as it'll read once the occurrence is replaced.
+    pub fn replaced_line(&self) -> String {
+        let mut line = self.line_content.clone();
+        let start = byte_offset(&line, self.range.start().offset);
+        let end = byte_offset(&line, self.range.end().offset);
+        line.replace_range(start..end, &self.replacement);
+
+        line
+    }
+}
+
+/// The occurrences found in a single file, along with the content they were
+/// found in, which is compared against the file's content before replacing
+/// them to make sure they haven't gone stale.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileOccurrences {
+    pub path: PathBuf,
+    pub label: String,
+    pub content: String,
+    pub occurrences: Vec<Occurrence>,
+}
+
+impl FileOccurrences {
+    pub fn selected_count(&self) -> usize {
+        self.occurrences.iter().filter(|occurrence| occurrence.selected).count()
+    }
+
+    /// The file's content, with the selected occurrences replaced.
+    pub fn replaced(&self) -> String {
+        let mut lines: Vec<String> = self.content.split('\n').map(String::from).collect();
+
+        // Work backwards, so that changes in length don't shift
+        // the offsets of occurrences that are yet to be replaced.
+        for occurrence in self.occurrences.iter().rev().filter(|occurrence| occurrence.selected) {
+            if let Some(line) = lines.get_mut(occurrence.range.start().line) {
+                let start = byte_offset(line, occurrence.range.start().offset);
+                let end = byte_offset(line, occurrence.range.end().offset);
+                line.replace_range(start..end, &occurrence.replacement);
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Finds occurrences in the files beneath the workspace path, grouped by file,
+/// using the provided function to find them (and their replacements) in each
+/// file's content; they're expected to be in order, and not to span lines.
+/// Files open in buffers are searched using the buffers' (possibly unsaved)
+/// content, provided as a map of their paths to their data.
+pub fn find<F>(path: &Path, exclusions: Option<&[ExclusionPattern]>, buffers: &HashMap<PathBuf, String>, matches: F) -> Vec<FileOccurrences>
+    where F: Fn(&str) -> Vec<(Range, String)>
+{
+    files::workspace_files(path, exclusions).into_iter().filter_map(|file| {
+        let content = match buffers.get(&file) {
+            Some(content) => content.clone(),
+            None => files::read_text(&file)?,
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        let occurrences: Vec<Occurrence> = matches(&content)
+            .into_iter()
+            .map(|(range, replacement)| Occurrence {
+                line_content: lines[range.start().line].to_string(),
+                range,
+                replacement,
+                selected: true,
+            }).collect();
+
+        if occurrences.is_empty() {
+            return None;
+        }
+
+        Some(FileOccurrences {
+            label: file.strip_prefix(path).unwrap_or(&file).to_string_lossy().into_owned(),
+            path: file,
+            content,
+            occurrences,
+        })
+    }).collect()
+}
+
+/// Replaces the selected occurrences in the buffer as a single undoable operation.
+pub fn replace_in_buffer(buffer: &mut Buffer, occurrences: &[Occurrence]) {
+    let cursor = *buffer.cursor;
+    buffer.start_operation_group();
+    for occurrence in occurrences.iter().rev().filter(|occurrence| occurrence.selected) {
+        buffer.delete_range(occurrence.range.clone());
+        buffer.cursor.move_to(occurrence.range.start());
+        if !occurrence.replacement.is_empty() {
+            buffer.insert(occurrence.replacement.clone());
+        }
+    }
+    buffer.end_operation_group();
+    buffer.cursor.move_to(cursor);
+}
+
+fn byte_offset(line: &str, grapheme_offset: usize) -> usize {
+    line.graphemes(true).take(grapheme_offset).map(|grapheme| grapheme.len()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::replace;
+    use regex::Regex;
+    use scribe::Buffer;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use super::{find, replace_in_buffer};
+
+    #[test]
+    fn find_groups_occurrences_by_file() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/workspace_replace_find"));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "fn amp() {}\nfn main() { amp() }\n").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let mut buffers = HashMap::new();
+        buffers.insert(root.join("README"), String::from("amp"));
+        fs::write(root.join("README"), "saved").unwrap();
+        let pattern = Regex::new("amp").unwrap();
+
+        let files = find(&root, None, &buffers, |content| replace::replacements(content, &pattern, "editor", false, false));
+        let labels: Vec<&str> = files.iter().map(|file| file.label.as_str()).collect();
+        assert_eq!(labels, vec!["README", "src/lib.rs"]);
+        assert_eq!(files[1].occurrences.len(), 2);
+        assert_eq!(files[1].occurrences[1].line_content, "fn main() { amp() }");
+    }
+
+    #[test]
+    fn find_uses_the_replacement_for_each_occurrence() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/workspace_replace_replacements"));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("lib.rs"), "amp_editor\nämp_text\n").unwrap();
+        let pattern = Regex::new(r"(\w+)_(\w+)").unwrap();
+
+        let files = find(&root, None, &HashMap::new(), |content| {
+            replace::replacements(content, &pattern, "$2_$1", true, false)
+        });
+        assert_eq!(files[0].occurrences[1].replaced_line(), "text_ämp");
+        assert_eq!(files[0].replaced(), "editor_amp\ntext_ämp\n");
+    }
+
+    #[test]
+    fn replaced_only_replaces_selected_occurrences() {
+        let root = PathBuf::from(concat!(env!("OUT_DIR"), "/workspace_replace_replaced"));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("lib.rs"), "amp(amp);\n\"ämp\" amp\n").unwrap();
+        let pattern = Regex::new("amp").unwrap();
+
+        let mut files = find(&root, None, &HashMap::new(), |content| {
+            replace::replacements(content, &pattern, "editor", false, false)
+        });
+        files[0].occurrences[1].selected = false;
+        assert_eq!(files[0].replaced(), "editor(amp);\n\"ämp\" editor\n");
+
+        let mut buffer = Buffer::new();
+        buffer.insert(files[0].content.clone());
+        replace_in_buffer(&mut buffer, &files[0].occurrences);
+        assert_eq!(buffer.data(), "editor(amp);\n\"ämp\" editor\n");
+        buffer.undo();
+        assert_eq!(buffer.data(), files[0].content);
+    }
+}
@@ -0,0 +1,77 @@
+/// Visible stand-ins for bidirectional formatting characters. Terminals draw
+/// these with no width (or act on them, reordering the text around them),
+/// which would leave the rest of the line out of step with the cursor.
+const CONTROL_MARKERS: [(char, &str); 12] = [
+    ('\u{200E}', "→"), // left-to-right mark
+    ('\u{200F}', "←"), // right-to-left mark
+    ('\u{061C}', "←"), // arabic letter mark
+    ('\u{202A}', "⇉"), // left-to-right embedding
+    ('\u{202B}', "⇇"), // right-to-left embedding
+    ('\u{202D}', "⇉"), // left-to-right override
+    ('\u{202E}', "⇇"), // right-to-left override
+    ('\u{202C}', "⇤"), // pop directional formatting
+    ('\u{2066}', "⇉"), // left-to-right isolate
+    ('\u{2067}', "⇇"), // right-to-left isolate
+    ('\u{2068}', "⇆"), // first strong isolate
+    ('\u{2069}', "⇤"), // pop directional isolate
+];
+
+/// Codepoints belonging to right-to-left scripts (inclusive).
+const RIGHT_TO_LEFT_RANGES: [(u32, u32); 6] = [
+    // Hebrew, Arabic, Syriac, Thaana, NKo, Samaritan, and Mandaic.
+    (0x0590, 0x085F),
+    (0x08A0, 0x08FF),
+    // Hebrew and Arabic presentation forms.
+    (0xFB1D, 0xFDFF),
+    (0xFE70, 0xFEFF),
+    // Historic scripts, Adlam, and Arabic mathematical symbols.
+    (0x10800, 0x10FFF),
+    (0x1E800, 0x1EFFF),
+];
+
+/// The marker drawn in place of a grapheme, if it's a bidirectional control.
+pub fn control_marker(grapheme: &str) -> Option<&'static str> {
+    let mut chars = grapheme.chars();
+    let character = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+
+    CONTROL_MARKERS
+        .iter()
+        .find(|(control, _)| *control == character)
+        .map(|(_, marker)| *marker)
+}
+
+/// Whether a grapheme is written right-to-left, based on its first character.
+pub fn is_right_to_left(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .next()
+        .map(|character| {
+            let codepoint = character as u32;
+
+            RIGHT_TO_LEFT_RANGES
+                .iter()
+                .any(|&(start, end)| start <= codepoint && codepoint <= end)
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn control_marker_replaces_bidi_formatting_characters() {
+        assert_eq!(super::control_marker("\u{202E}"), Some("⇇"));
+        assert_eq!(super::control_marker("\u{2069}"), Some("⇤"));
+        assert_eq!(super::control_marker("a"), None);
+    }
+
+    #[test]
+    fn is_right_to_left_detects_hebrew_and_arabic() {
+        assert!(super::is_right_to_left("ש"));
+        assert!(super::is_right_to_left("ع"));
+        assert!(!super::is_right_to_left("a"));
+        assert!(!super::is_right_to_left(""));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::models::application::Preferences;
+use std::path::PathBuf;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Controls how lines too long for the screen are broken into rows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WrapOptions {
+    pub tab_width: usize,
+    /// Break rows between words, rather than at the last column.
+    pub words: bool,
+    /// Indent continuation rows to match the line's own indentation.
+    pub hanging_indent: bool,
+}
+
+impl Default for WrapOptions {
+    fn default() -> WrapOptions {
+        WrapOptions {
+            tab_width: 2,
+            words: false,
+            hanging_indent: false,
+        }
+    }
+}
+
+impl WrapOptions {
+    /// Builds the options configured for a buffer with the specified path.
+    pub fn new(preferences: &Preferences, path: Option<&PathBuf>) -> WrapOptions {
+        WrapOptions {
+            tab_width: preferences.tab_width(path),
+            words: preferences.wrap_at_words(),
+            hanging_indent: preferences.wrap_hanging_indent(),
+        }
+    }
+}
+
+/// The rows a line occupies when wrapped to a particular width.
+#[derive(Debug, PartialEq)]
+pub struct LineLayout {
+    /// Grapheme offsets at which continuation rows start.
+    pub breaks: Vec<usize>,
+    /// Columns of indentation at the start of each continuation row.
+    pub indent: usize,
+}
+
+impl LineLayout {
+    pub fn row_count(&self) -> usize {
+        self.breaks.len() + 1
+    }
+}
+
+/// Lays out a line for the specified content width. This needs to mirror the
+/// buffer renderer: a row is broken once it's full, and tabs are expanded
+/// relative to the start of the row's content (and clipped at its end).
+pub fn layout(line: &str, width: usize, options: WrapOptions) -> LineLayout {
+    let graphemes: Vec<&str> = line.trim_end_matches('\n').graphemes(true).collect();
+    let indent = if options.hanging_indent {
+        // Keep at least half of the row for content.
+        indentation_width(&graphemes, options.tab_width).min(width / 2)
+    } else {
+        0
+    };
+    let mut breaks = Vec::new();
+    if width == 0 {
+        return LineLayout{ breaks, indent };
+    }
+
+    let mut column = 0;
+    for (offset, grapheme) in graphemes.iter().enumerate() {
+        let word_start = options.words && !is_whitespace(grapheme) &&
+            offset > 0 && is_whitespace(graphemes[offset - 1]);
+
+        if column >= width {
+            breaks.push(offset);
+            column = indent;
+        } else if word_start && column > indent {
+            // Move words that don't fit onto the next row, unless
+            // they're too long to fit on a row of their own, anyway.
+            let word_length = graphemes[offset..]
+                .iter()
+                .take_while(|grapheme| !is_whitespace(grapheme))
+                .count();
+            if column + word_length > width && indent + word_length <= width {
+                breaks.push(offset);
+                column = indent;
+            }
+        }
+
+        column = if *grapheme == "\t" {
+            (next_tab_stop(column, options.tab_width)).min(width)
+        } else {
+            column + 1
+        };
+    }
+
+    LineLayout{ breaks, indent }
+}
+
+fn indentation_width(graphemes: &[&str], tab_width: usize) -> usize {
+    graphemes
+        .iter()
+        .take_while(|grapheme| is_whitespace(grapheme))
+        .fold(0, |column, grapheme| {
+            if *grapheme == "\t" {
+                next_tab_stop(column, tab_width)
+            } else {
+                column + 1
+            }
+        })
+}
+
+fn next_tab_stop(column: usize, tab_width: usize) -> usize {
+    if tab_width == 0 {
+        return column;
+    }
+
+    (column / tab_width + 1) * tab_width
+}
+
+fn is_whitespace(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{layout, WrapOptions};
+
+    #[test]
+    fn layout_breaks_full_rows_at_any_character_by_default() {
+        let line_layout = layout("amp editor", 6, WrapOptions::default());
+
+        assert_eq!(line_layout.breaks, vec![6]);
+        assert_eq!(line_layout.row_count(), 2);
+    }
+
+    #[test]
+    fn layout_breaks_rows_between_words() {
+        let options = WrapOptions{ words: true, ..WrapOptions::default() };
+
+        assert_eq!(layout("amp editor", 6, options).breaks, vec![4]);
+        assert_eq!(layout("amp text editor", 10, options).breaks, vec![9]);
+    }
+
+    #[test]
+    fn layout_breaks_words_too_long_for_a_row_at_the_last_column() {
+        let options = WrapOptions{ words: true, ..WrapOptions::default() };
+
+        assert_eq!(layout("a editorial", 6, options).breaks, vec![6]);
+    }
+
+    #[test]
+    fn layout_indents_continuation_rows_when_hanging() {
+        let options = WrapOptions{ hanging_indent: true, ..WrapOptions::default() };
+        let line_layout = layout("  amp editor", 8, options);
+
+        assert_eq!(line_layout.indent, 2);
+        assert_eq!(line_layout.breaks, vec![8]);
+    }
+
+    #[test]
+    fn layout_limits_hanging_indent_to_half_the_width() {
+        let options = WrapOptions{ hanging_indent: true, ..WrapOptions::default() };
+
+        assert_eq!(layout("        amp", 8, options).indent, 4);
+    }
+}
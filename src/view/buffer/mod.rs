@@ -1,8 +1,10 @@
+mod bidi;
 mod renderer;
 mod render_cache;
 mod render_state;
 mod lexeme_mapper;
 mod line_numbers;
+mod line_wrap;
 mod scrollable_region;
 
 pub use self::renderer::BufferRenderer;
@@ -10,4 +12,5 @@ pub use self::render_cache::RenderCache;
 pub use self::render_state::RenderState;
 pub use self::lexeme_mapper::{LexemeMapper, MappedLexeme};
 pub use self::line_numbers::LineNumbers;
+pub use self::line_wrap::{layout, LineLayout, WrapOptions};
 pub use self::scrollable_region::ScrollableRegion;
@@ -1,7 +1,12 @@
 use crate::models::application::Preferences;
+use crate::util::color_value;
+use crate::util::conflict::{self, Conflict, ConflictSection};
+use crate::util::lint::Diagnostic;
+use crate::util::spelling::{self, Dictionary};
 use scribe::buffer::{Buffer, Position, Range};
 use scribe::util::LineIterator;
-use crate::view::buffer::{LexemeMapper, MappedLexeme, RenderState};
+use crate::view::buffer::bidi;
+use crate::view::buffer::{layout, LexemeMapper, MappedLexeme, RenderState, WrapOptions};
 use crate::view::buffer::line_numbers::*;
 use crate::view::{Colors, RENDER_CACHE_FREQUENCY, RGBColor, Style};
 use crate::view::color::to_rgb_color;
@@ -9,22 +14,40 @@ use crate::view::terminal::{Cell, Terminal, TerminalBuffer};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::mem;
 use std::rc::Rc;
 use std::str::FromStr;
-use syntect::highlighting::{Highlighter, HighlightIterator, Theme};
+use std::time::{Duration, Instant};
+use syntect::highlighting::{Highlighter, HighlightIterator, ScopeSelectors, Theme};
 use syntect::highlighting::Style as ThemeStyle;
-use syntect::parsing::ScopeStack;
+use syntect::parsing::{ScopeStack, ScopeStackOp};
 use unicode_segmentation::UnicodeSegmentation;
 use crate::errors::*;
 
+// Drawn in place of leading whitespace at each indentation level.
+const INDENT_GUIDE: &str = "│";
+
 /// A one-time-use type that encapsulates all of the
 /// details involved in rendering a buffer to the screen.
 pub struct BufferRenderer<'a, 'p> {
     buffer: &'a Buffer,
     buffer_position: Position,
+    color_swatches: bool,
+    column_offset: usize,
+    conflicts: Vec<Conflict>,
+    content_column: usize,
     cursor_position: Option<Position>,
+    diagnostics: &'a [Diagnostic],
+    dictionary: Option<&'a Dictionary>,
+    checked_scopes: ScopeSelectors,
+    misspellings: Vec<(usize, usize)>,
+    swatches: Vec<RGBColor>,
     gutter_width: usize,
     highlights: Option<&'a [Range]>,
+    highlight_duration: Duration,
+    indent_guide_color: Option<RGBColor>,
+    in_indentation: bool,
+    large_file: bool,
     stylist: Highlighter<'a>,
     current_style: ThemeStyle,
     line_numbers: LineNumbers,
@@ -35,6 +58,13 @@ pub struct BufferRenderer<'a, 'p> {
     terminal: &'a Terminal,
     terminal_buffer: &'a mut TerminalBuffer<'p>,
     theme: &'a Theme,
+    truncated_left: bool,
+    truncated_right: bool,
+    wrap_breaks: Vec<usize>,
+    wrap_indent: usize,
+    wrap_options: WrapOptions,
+    length_guides: Vec<usize>,
+    occurrences: &'a [Range],
 }
 
 impl<'a, 'p> BufferRenderer<'a, 'p> {
@@ -51,11 +81,36 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         let stylist = Highlighter::new(theme);
         let current_style = stylist.get_default();
 
+        // Guides use the theme's color for them, if it has one.
+        let indent_guide_color = if preferences.indent_guides(buffer.path.as_ref()) {
+            Some(theme.settings.guide.map(to_rgb_color).unwrap_or_else(|| {
+                tint(
+                    theme.settings.background.map(to_rgb_color).unwrap_or(RGBColor(0, 0, 0)),
+                    to_rgb_color(current_style.foreground)
+                )
+            }))
+        } else {
+            None
+        };
+
         BufferRenderer{
             buffer,
+            color_swatches: false,
+            column_offset: 0,
+            conflicts: Vec::new(),
+            content_column: 0,
             cursor_position: None,
+            diagnostics: &[],
+            dictionary: None,
+            checked_scopes: ScopeSelectors::from_str(spelling::CHECKED_SCOPES).unwrap_or_default(),
+            misspellings: Vec::new(),
+            swatches: Vec::new(),
             gutter_width,
             highlights,
+            highlight_duration: Duration::new(0, 0),
+            indent_guide_color,
+            in_indentation: true,
+            large_file: false,
             stylist,
             current_style,
             line_numbers,
@@ -67,6 +122,156 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
             terminal,
             terminal_buffer,
             theme,
+            truncated_left: false,
+            truncated_right: false,
+            wrap_breaks: Vec::new(),
+            wrap_indent: 0,
+            wrap_options: WrapOptions::new(preferences, buffer.path.as_ref()),
+            length_guides: preferences.line_length_guides(buffer.path.as_ref()),
+            occurrences: &[],
+        }
+    }
+
+    /// Time spent parsing lines for syntax highlighting during the last render.
+    pub fn highlight_duration(&self) -> Duration {
+        self.highlight_duration
+    }
+
+    /// Sets the number of columns to skip at the start of each line
+    /// when rendering without line wrapping (i.e. horizontal scrolling).
+    pub fn set_column_offset(&mut self, column_offset: usize) {
+        self.column_offset = column_offset;
+    }
+
+    /// Ranges to be subtly highlighted, such as
+    /// occurrences of the word under the cursor.
+    pub fn set_occurrences(&mut self, occurrences: &'a [Range]) {
+        self.occurrences = occurrences;
+    }
+
+    /// Renders the buffer as a large file: highlighting starts fresh at the
+    /// first visible line, rather than parsing everything before it, and
+    /// merge conflicts aren't looked for.
+    pub fn set_large_file(&mut self) {
+        self.large_file = true;
+    }
+
+    /// Merge conflicts in the buffer, whose lines are tinted by section.
+    pub fn set_conflicts(&mut self, conflicts: Vec<Conflict>) {
+        self.conflicts = conflicts;
+    }
+
+    /// Linter findings, shown after the lines they refer to.
+    pub fn set_diagnostics(&mut self, diagnostics: &'a [Diagnostic]) {
+        self.diagnostics = diagnostics;
+    }
+
+    /// Previews colors written in the buffer (e.g. "#ff8800")
+    /// as swatches following the lines they're written on.
+    pub fn set_color_swatches(&mut self) {
+        self.color_swatches = true;
+    }
+
+    /// Underlines words missing from the dictionary, in the
+    /// parts of the buffer that are spell checked (e.g. comments).
+    pub fn set_dictionary(&mut self, dictionary: &'a Dictionary) {
+        self.dictionary = Some(dictionary);
+    }
+
+    // Misspelled words in the parts of the line that are spell checked,
+    // found by following its syntax scopes from the start of the line.
+    fn find_misspellings(&self, line: &str, scopes: &ScopeStack, events: &[(usize, ScopeStackOp)]) -> Vec<(usize, usize)> {
+        let dictionary = match self.dictionary {
+            Some(dictionary) => dictionary,
+            None => return Vec::new(),
+        };
+        let mut stack = scopes.clone();
+        let mut checked_ranges = Vec::new();
+        let mut start = 0;
+
+        for &(position, ref operation) in events {
+            self.add_checked_range(&mut checked_ranges, &stack, line, start, position);
+            stack.apply(operation);
+            start = position;
+        }
+        self.add_checked_range(&mut checked_ranges, &stack, line, start, line.len());
+
+        dictionary.misspellings(line, &checked_ranges)
+    }
+
+    // Records a (byte) range of the line as a (grapheme offset) range
+    // to be checked, if the scopes applied to it are spell checked.
+    fn add_checked_range(&self, ranges: &mut Vec<(usize, usize)>, stack: &ScopeStack, line: &str, start: usize, end: usize) {
+        if end > start && self.checked_scopes.does_match(stack.as_slice()).is_some() {
+            let start_offset = line[..start].graphemes(true).count();
+            let end_offset = start_offset + line[start..end].graphemes(true).count();
+            ranges.push((start_offset, end_offset));
+        }
+    }
+
+    // Misspelled words are underlined, unless they're already styled (e.g. highlighted).
+    fn spelling_style(&self, style: Style) -> Style {
+        let offset = self.buffer_position.offset;
+        let misspelled = self.misspellings.iter().any(|&(start, end)| offset >= start && offset < end);
+
+        if misspelled && style == Style::Default {
+            Style::Underlined
+        } else {
+            style
+        }
+    }
+
+    fn line_diagnostics(&self) -> Vec<&'a Diagnostic> {
+        let line = self.buffer_position.line;
+        let diagnostics: &'a [Diagnostic] = self.diagnostics;
+
+        diagnostics.iter().filter(|diagnostic| diagnostic.line == line).collect()
+    }
+
+    // Follows the current line's content with its diagnostics' messages,
+    // dimmed, and as much of them as will fit on the row. Like swatches,
+    // they're drawn outside of the content, leaving the cursor unaffected.
+    fn print_diagnostics(&mut self) {
+        if !self.preferences.inline_diagnostics() {
+            return;
+        }
+
+        let messages: Vec<&str> = self.line_diagnostics()
+            .into_iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        if messages.is_empty() {
+            return;
+        }
+
+        let content = format!("  {} ", messages.join("; "));
+        let colors = if self.on_cursor_line() { Colors::Focused } else { Colors::Default };
+        for grapheme in content.graphemes(true) {
+            if self.screen_position.offset >= self.terminal.width() {
+                break;
+            }
+
+            self.print(self.screen_position, Style::Dim, colors, grapheme.to_string());
+            self.screen_position.offset += 1;
+        }
+    }
+
+    // Follows the current line's content with a cell for each of its colors,
+    // filled with the color itself. These are drawn outside of the content,
+    // so that they don't shift it (or the cursor) out of place.
+    fn print_swatches(&mut self) {
+        let swatches = mem::replace(&mut self.swatches, Vec::new());
+        let colors = if self.on_cursor_line() { Colors::Focused } else { Colors::Default };
+
+        for swatch in swatches {
+            if self.screen_position.offset + 1 >= self.terminal.width() {
+                break;
+            }
+
+            self.print(self.screen_position, Style::Default, colors, " ");
+            self.screen_position.offset += 1;
+            self.print(self.screen_position, Style::Default, Colors::Custom(swatch, swatch), " ");
+            self.screen_position.offset += 1;
         }
     }
 
@@ -76,13 +281,15 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
 
     fn print_rest_of_line(&mut self) {
         let on_cursor_line = self.on_cursor_line();
-        let guide_offset = self.length_guide_offset();
+        let conflict_colors = self.conflict_colors(RGBColor(255, 255, 255));
 
         for offset in self.screen_position.offset..self.terminal.width() {
-            let colors = if on_cursor_line || guide_offset.map(|go| go == offset).unwrap_or(false) {
+            let colors = if on_cursor_line {
                 Colors::Focused
+            } else if let Some((_, colors)) = conflict_colors {
+                colors
             } else {
-                Colors::Default
+                self.guide_colors(offset, Colors::Default)
             };
 
             self.print(Position{ line: self.screen_position.line, offset },
@@ -90,15 +297,77 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
                        colors,
                        " ");
         }
+
+        self.print_truncation_indicators();
     }
 
-    fn length_guide_offset(&self) -> Option<usize> {
-        self.preferences.line_length_guide().map(|offset| self.gutter_width + offset)
+    // Marks the edges of lines with content hidden by horizontal scrolling.
+    fn print_truncation_indicators(&mut self) {
+        let line = self.screen_position.line;
+
+        if self.truncated_left {
+            self.print(Position{ line, offset: self.gutter_width }, Style::Default, Colors::Focused, "<");
+        }
+        if self.truncated_right {
+            if let Some(offset) = self.terminal.width().checked_sub(1) {
+                self.print(Position{ line, offset }, Style::Default, Colors::Focused, ">");
+            }
+        }
+    }
+
+    fn on_length_guide(&self, screen_offset: usize) -> bool {
+        let visible_column_offset = self.visible_column_offset();
+
+        self.length_guides.iter().any(|guide| {
+            guide.checked_sub(visible_column_offset)
+                .map(|offset| self.gutter_width + offset == screen_offset)
+                .unwrap_or(false)
+        })
+    }
+
+    // Tints the background of columns with a line length guide, leaving
+    // colors that already have a distinct background (e.g. highlights) as-is.
+    fn guide_colors(&self, screen_offset: usize, colors: Colors) -> Colors {
+        if !self.on_length_guide(screen_offset) {
+            return colors;
+        }
+
+        match colors {
+            Colors::Default => Colors::Focused,
+            Colors::CustomForeground(color) => Colors::CustomFocusedForeground(color),
+            _ => colors,
+        }
+    }
+
+    // Leading whitespace at each indentation level (i.e. every tab stop)
+    // is drawn as a guide, unless it's highlighted (e.g. selected).
+    fn indent_guide(&self, column: usize, colors: Colors) -> Option<Colors> {
+        let guide_color = self.indent_guide_color?;
+        if !self.in_indentation || column % self.preferences.tab_width(self.buffer.path.as_ref()) != 0 {
+            return None;
+        }
+
+        match colors {
+            Colors::CustomForeground(_) => Some(Colors::CustomForeground(guide_color)),
+            Colors::CustomFocusedForeground(_) => Some(Colors::CustomFocusedForeground(guide_color)),
+            _ => None,
+        }
+    }
+
+    // The column offset only applies when lines aren't wrapped.
+    fn visible_column_offset(&self) -> usize {
+        if self.preferences.line_wrapping() {
+            0
+        } else {
+            self.column_offset
+        }
     }
 
     fn advance_to_next_line(&mut self) {
         if self.inside_visible_content() {
             self.set_cursor();
+            self.print_swatches();
+            self.print_diagnostics();
             self.print_rest_of_line();
 
             // It's important to only increase this once we've entered the
@@ -110,6 +379,10 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         // Move the buffer position to the next line.
         self.buffer_position.line += 1;
         self.buffer_position.offset = 0;
+        self.content_column = 0;
+        self.in_indentation = true;
+        self.truncated_left = false;
+        self.truncated_right = false;
 
         // Print this on the brand new line.
         self.print_line_number();
@@ -119,11 +392,22 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
     // at which point we can set it relative to the screen,
     // which will compensate for scrolling, tab expansion, etc.
     fn set_cursor(&mut self) {
-        if self.inside_visible_content() && *self.buffer.cursor == self.buffer_position {
+        if self.inside_visible_content() && *self.buffer.cursor == self.buffer_position && self.content_column_visible() {
             self.cursor_position = Some(self.screen_position);
         }
     }
 
+    // Whether or not the current content column is on-screen, which is only
+    // a concern when lines aren't wrapped and may be horizontally scrolled.
+    fn content_column_visible(&self) -> bool {
+        if self.preferences.line_wrapping() {
+            return true;
+        }
+
+        self.content_column >= self.column_offset &&
+            self.gutter_width + self.content_column - self.column_offset < self.terminal.width()
+    }
+
     fn current_char_style(&self, token_color: RGBColor) -> (Style, Colors) {
         let (style, colors) = match self.highlights {
             Some(highlight_ranges) => {
@@ -141,14 +425,22 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
 
                 // We aren't inside one of the highlighted areas.
                 // Fall back to other styling considerations.
-                if self.on_cursor_line() {
+                if let Some(conflict_style) = self.conflict_colors(token_color) {
+                    conflict_style
+                } else if let Some(occurrence_style) = self.occurrence_colors(token_color) {
+                    occurrence_style
+                } else if self.on_cursor_line() {
                     (Style::Default, Colors::CustomFocusedForeground(token_color))
                 } else {
                     (Style::Default, Colors::CustomForeground(token_color))
                 }
             }
             None => {
-                if self.on_cursor_line() {
+                if let Some(conflict_style) = self.conflict_colors(token_color) {
+                    conflict_style
+                } else if let Some(occurrence_style) = self.occurrence_colors(token_color) {
+                    occurrence_style
+                } else if self.on_cursor_line() {
                     (Style::Default, Colors::CustomFocusedForeground(token_color))
                 } else {
                     (Style::Default, Colors::CustomForeground(token_color))
@@ -160,48 +452,211 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
     }
 
     pub fn print_lexeme<L: Into<Cow<'p, str>>>(&mut self, lexeme: L) {
-        for character in lexeme.into().graphemes(true) {
-            // Ignore newline characters.
-            if character == "\n" { continue; }
+        match lexeme.into() {
+            // Graphemes borrowed from the buffer can be printed without copying them.
+            Cow::Borrowed(lexeme) => {
+                for character in lexeme.graphemes(true) {
+                    self.print_grapheme(Cow::Borrowed(character));
+                }
+            },
+            Cow::Owned(lexeme) => self.print_mapped_lexeme(&lexeme),
+        }
+    }
 
-            self.set_cursor();
+    // Mapped lexemes don't live as long as the terminal buffer,
+    // so each of their graphemes needs to be copied to be printed.
+    fn print_mapped_lexeme(&mut self, lexeme: &str) {
+        for character in lexeme.graphemes(true) {
+            self.print_grapheme(Cow::Owned(character.to_string()));
+        }
+    }
 
-            // Determine the style we'll use to print.
-            let token_color = to_rgb_color(self.current_style.foreground);
-            let (style, color) = self.current_char_style(token_color);
+    fn print_grapheme(&mut self, character: Cow<'p, str>) {
+        // Ignore newline characters.
+        if character == "\n" { return; }
 
-            if self.preferences.line_wrapping() && self.screen_position.offset == self.terminal.width() {
-                self.screen_position.line += 1;
-                self.screen_position.offset = self.gutter_width;
-                self.print(self.screen_position, style, color, character.to_string());
-                self.screen_position.offset += 1;
-                self.buffer_position.offset += 1;
-            } else if character == "\t" {
-                // Calculate the next tab stop using the tab-aware offset,
-                // *without considering the line number gutter*, and then
-                // re-add the gutter width to get the actual/screen offset.
-                let buffer_tab_stop = self.next_tab_stop(self.screen_position.offset - self.gutter_width);
-                let mut screen_tab_stop = buffer_tab_stop + self.gutter_width;
-
-                // Now that we know where we'd like to go, prevent it from being off-screen.
-                if screen_tab_stop > self.terminal.width() {
-                    screen_tab_stop = self.terminal.width();
-                }
+        if self.preferences.line_wrapping() && self.at_wrap_point() {
+            self.wrap_line();
+        }
 
-                // Print the sequence of spaces and move the offset accordingly.
-                for _ in self.screen_position.offset..screen_tab_stop {
-                    self.print(self.screen_position, style, color, " ");
-                    self.screen_position.offset += 1;
-                }
-                self.buffer_position.offset += 1;
-            } else {
-                self.print(self.screen_position, style, color, character.to_string());
+        self.set_cursor();
+
+        // Determine the style we'll use to print.
+        let token_color = to_rgb_color(self.current_style.foreground);
+        let (style, color) = self.current_char_style(token_color);
+        let style = self.spelling_style(style);
+        let (character, style) = self.bidi_safe(character, style);
+        if character != " " && character != "\t" {
+            self.in_indentation = false;
+        }
+
+        if !self.preferences.line_wrapping() {
+            self.print_unwrapped(character, style, color);
+        } else if character == "\t" {
+            // Calculate the next tab stop using the tab-aware offset,
+            // *without considering the line number gutter*, and then
+            // re-add the gutter width to get the actual/screen offset.
+            let buffer_tab_stop = self.next_tab_stop(self.screen_position.offset - self.gutter_width);
+            let mut screen_tab_stop = buffer_tab_stop + self.gutter_width;
+
+            // Now that we know where we'd like to go, prevent it from being off-screen.
+            if screen_tab_stop > self.terminal.width() {
+                screen_tab_stop = self.terminal.width();
+            }
+
+            // Print the sequence of spaces and move the offset accordingly.
+            for _ in self.screen_position.offset..screen_tab_stop {
+                let column = self.screen_position.offset - self.gutter_width;
+                let (content, color) = match self.indent_guide(column, color) {
+                    Some(guide_color) => (INDENT_GUIDE, guide_color),
+                    None => (" ", color),
+                };
+                let color = self.guide_colors(self.screen_position.offset, color);
+                self.print(self.screen_position, style, color, content);
                 self.screen_position.offset += 1;
-                self.buffer_position.offset += 1;
             }
+            self.buffer_position.offset += 1;
+        } else {
+            let column = self.screen_position.offset - self.gutter_width;
+            let (character, color) = match self.indent_guide(column, color) {
+                Some(guide_color) if character == " " => (Cow::Borrowed(INDENT_GUIDE), guide_color),
+                _ => (character, color),
+            };
+            let color = self.guide_colors(self.screen_position.offset, color);
+            self.print(self.screen_position, style, color, character);
+            self.screen_position.offset += 1;
+            self.buffer_position.offset += 1;
+        }
 
-            self.set_cursor();
+        self.set_cursor();
+    }
+
+    // Terminals draw bidirectional formatting characters with no width, or
+    // act on them, which would put the content and cursor out of step. Show
+    // them as visible markers (each in its own column), instead, and italicize
+    // right-to-left text so that it's clear where direction changes occur.
+    fn bidi_safe(&self, character: Cow<'p, str>, style: Style) -> (Cow<'p, str>, Style) {
+        if !self.preferences.bidi_markers() {
+            return (character, style);
+        }
+
+        if let Some(marker) = bidi::control_marker(&character) {
+            let style = if style == Style::Default { Style::Inverted } else { style };
+
+            (Cow::Borrowed(marker), style)
+        } else if style == Style::Default && bidi::is_right_to_left(&character) {
+            (character, Style::Italic)
+        } else {
+            (character, style)
+        }
+    }
+
+    // Lays out the line about to be printed, so that we know where to wrap it.
+    fn start_line(&mut self, line: &str) {
+        if !self.preferences.line_wrapping() || self.before_visible_content() {
+            return;
         }
+
+        let width = self.terminal.width().checked_sub(self.gutter_width).unwrap_or(0);
+        let line_layout = layout(line, width, self.wrap_options);
+        self.wrap_breaks = line_layout.breaks;
+        self.wrap_indent = line_layout.indent;
+    }
+
+    // Whether the next grapheme belongs on a new row. Rows that are full are
+    // always wrapped, in case the layout didn't anticipate the printed content
+    // (e.g. lexemes that have been mapped to something else).
+    fn at_wrap_point(&mut self) -> bool {
+        let offset = self.buffer_position.offset;
+        while self.wrap_breaks.first().map(|&point| point < offset).unwrap_or(false) {
+            self.wrap_breaks.remove(0);
+        }
+        let at_break = self.wrap_breaks.first() == Some(&offset);
+        if at_break {
+            self.wrap_breaks.remove(0);
+        }
+
+        at_break || self.screen_position.offset >= self.terminal.width()
+    }
+
+    // Continues the current line on the next row. Guides
+    // aren't drawn in whitespace carried over to the new row.
+    fn wrap_line(&mut self) {
+        self.in_indentation = false;
+        self.print_rest_of_line();
+        self.screen_position.line += 1;
+        self.print_wrap_gutter();
+        self.screen_position.offset = self.gutter_width;
+
+        let colors = if self.on_cursor_line() {
+            Colors::Focused
+        } else {
+            Colors::Default
+        };
+        for _ in 0..self.wrap_indent {
+            self.print(self.screen_position, Style::Default, colors, " ");
+            self.screen_position.offset += 1;
+        }
+    }
+
+    // Fills the gutter alongside a wrapped line's continuation row,
+    // aligning the wrap indicator (if there is one) with line numbers.
+    fn print_wrap_gutter(&mut self) {
+        let line = self.screen_position.line;
+        let number_width = self.line_numbers.width();
+        let indicator_width = number_width - PADDING_WIDTH;
+        let indicator: String = self.preferences
+            .wrap_indicator()
+            .unwrap_or_default()
+            .graphemes(true)
+            .take(indicator_width)
+            .collect();
+        let padding = indicator_width - indicator.graphemes(true).count();
+        let gap_color = if self.on_cursor_line() {
+            Colors::Focused
+        } else {
+            Colors::Default
+        };
+
+        self.print(
+            Position{ line, offset: 0 },
+            Style::Default,
+            Colors::Focused,
+            format!(" {}{} ", " ".repeat(padding), indicator)
+        );
+        self.print(Position{ line, offset: number_width }, Style::Default, gap_color, " ");
+    }
+
+    // Prints a grapheme on a line that isn't wrapped, skipping any columns
+    // scrolled off either side of the screen, and noting that they were.
+    fn print_unwrapped(&mut self, character: Cow<'p, str>, style: Style, colors: Colors) {
+        let (content, width) = if character == "\t" {
+            (Cow::Borrowed(" "), self.next_tab_stop(self.content_column) - self.content_column)
+        } else {
+            (character, 1)
+        };
+
+        for _ in 0..width {
+            if self.content_column < self.column_offset {
+                self.truncated_left = true;
+            } else {
+                let offset = self.gutter_width + self.content_column - self.column_offset;
+
+                if offset < self.terminal.width() {
+                    let (content, colors) = match self.indent_guide(self.content_column, colors) {
+                        Some(guide_colors) if content == " " => (Cow::Borrowed(INDENT_GUIDE), guide_colors),
+                        _ => (content.clone(), colors),
+                    };
+                    let colors = self.guide_colors(offset, colors);
+                    self.print(Position{ line: self.screen_position.line, offset }, style, colors, content);
+                    self.screen_position.offset = offset + 1;
+                } else {
+                    self.truncated_right = true;
+                }
+            }
+            self.content_column += 1;
+        }
+        self.buffer_position.offset += 1;
     }
 
     fn before_visible_content(&mut self) -> bool {
@@ -226,19 +681,42 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         let syntax_definition = self.buffer.syntax_definition.as_ref().ok_or("Buffer has no syntax definition")?;
 
         // Start or resume state from a previous cache point, if available.
-        let (cached_line_no, mut state) = self
-            .cached_render_state()
-            .unwrap_or((0, RenderState::new(&highlighter, syntax_definition)));
+        let (cached_line_no, mut state) = if self.large_file {
+            (0, RenderState::new(&highlighter, syntax_definition))
+        } else {
+            self.cached_render_state()
+                .unwrap_or((0, RenderState::new(&highlighter, syntax_definition)))
+        };
         let (focused_style, blurred_style) = self.mapper_styles();
 
         'print: for (line_no, line) in lines {
+            // Large files aren't parsed until we've reached visible content.
+            if self.large_file && self.before_visible_content() {
+                if has_trailing_newline(line) {
+                    self.advance_to_next_line();
+                }
+                continue;
+            }
+
             // Skip past lines that precede the cached render state.
             if line_no >= cached_line_no {
-                if line_no % RENDER_CACHE_FREQUENCY == 0 && line_no > 0 {
+                if line_no % RENDER_CACHE_FREQUENCY == 0 && line_no > 0 && !self.large_file {
                     self.render_cache.borrow_mut().insert(line_no, state.clone());
                 }
 
+                self.start_line(line);
+                let parse_started_at = Instant::now();
                 let events = state.parse.parse_line(line);
+                self.highlight_duration += parse_started_at.elapsed();
+                if self.dictionary.is_some() && !self.before_visible_content() {
+                    self.misspellings = self.find_misspellings(line, &state.highlight.path, &events);
+                }
+                if self.color_swatches && !self.before_visible_content() {
+                    self.swatches = color_value::color_values(line)
+                        .into_iter()
+                        .map(|(red, green, blue)| RGBColor(red, green, blue))
+                        .collect();
+                }
                 let styled_lexemes = HighlightIterator::new(
                     &mut state.highlight,
                     &events,
@@ -264,11 +742,11 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
                             match mapped_lexeme {
                                 MappedLexeme::Focused(value) => {
                                     self.current_style = focused_style;
-                                    self.print_lexeme(value.to_string());
+                                    self.print_mapped_lexeme(value);
                                 },
                                 MappedLexeme::Blurred(value) => {
                                     self.current_style = blurred_style;
-                                    self.print_lexeme(value.to_string());
+                                    self.print_mapped_lexeme(value);
                                 }
                             }
 
@@ -288,6 +766,10 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         self.set_cursor();
 
         // One last call to this for the last line.
+        if self.inside_visible_content() {
+            self.print_swatches();
+            self.print_diagnostics();
+        }
         self.print_rest_of_line();
 
         // Return the cursor location. If it occurred somewhere in the buffer, it
@@ -296,6 +778,34 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         Ok(self.cursor_position)
     }
 
+    /// Tints the background of lines inside merge conflicts,
+    /// using a distinct color for each side of the conflict.
+    fn conflict_colors(&self, token_color: RGBColor) -> Option<(Style, Colors)> {
+        let section = conflict::conflict_at(&self.conflicts, self.buffer_position.line)
+            .and_then(|conflict| conflict.section(self.buffer_position.line))?;
+        let background = self.theme.settings.background
+            .map(to_rgb_color)
+            .unwrap_or(RGBColor(0, 0, 0));
+
+        Some(match section {
+            ConflictSection::Marker => (Style::Bold, Colors::CustomFocusedForeground(token_color)),
+            ConflictSection::Ours => (Style::Default, Colors::Custom(token_color, tint(background, RGBColor(0, 180, 0)))),
+            ConflictSection::Base => (Style::Default, Colors::Custom(token_color, tint(background, RGBColor(180, 180, 180)))),
+            ConflictSection::Theirs => (Style::Default, Colors::Custom(token_color, tint(background, RGBColor(0, 120, 220)))),
+        })
+    }
+
+    fn occurrence_colors(&self, token_color: RGBColor) -> Option<(Style, Colors)> {
+        if !self.occurrences.iter().any(|range| range.includes(&self.buffer_position)) {
+            return None;
+        }
+        let background = self.theme.settings.background
+            .map(to_rgb_color)
+            .unwrap_or(RGBColor(0, 0, 0));
+
+        Some((Style::Default, Colors::Custom(token_color, tint(background, RGBColor(220, 200, 0)))))
+    }
+
     fn print_line_number(&mut self) {
         if !self.inside_visible_content() { return };
 
@@ -308,10 +818,17 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
             Style::Default
         };
 
+        // Lines with diagnostics are flagged in the gutter.
+        let colors = if self.line_diagnostics().is_empty() {
+            Colors::Focused
+        } else {
+            Colors::Warning
+        };
+
         self.print(
             Position{ line: self.screen_position.line, offset: 0 },
             weight,
-            Colors::Focused,
+            colors,
             line_number
         );
 
@@ -376,6 +893,17 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
     }
 }
 
+// Blends a small amount of the specified color into the background.
+fn tint(background: RGBColor, color: RGBColor) -> RGBColor {
+    let blend = |base: u8, tint: u8| (base as f32 * 0.8 + tint as f32 * 0.2) as u8;
+
+    RGBColor(
+        blend(background.0, color.0),
+        blend(background.1, color.1),
+        blend(background.2, color.2)
+    )
+}
+
 fn has_trailing_newline(line: &str) -> bool {
     line.chars()
         .last()
@@ -386,6 +914,8 @@ fn has_trailing_newline(line: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use crate::models::application::Preferences;
+    use crate::util::lint::Diagnostic;
+    use crate::util::spelling::Dictionary;
     use scribe::{Buffer, Workspace};
     use scribe::buffer::Position;
     use scribe::util::LineIterator;
@@ -395,6 +925,7 @@ mod tests {
     use std::rc::Rc;
     use super::{BufferRenderer, LexemeMapper, MappedLexeme};
     use syntect::highlighting::ThemeSet;
+    use crate::view::{Colors, RGBColor, Style};
     use crate::view::terminal::*;
     use crate::yaml::yaml::YamlLoader;
 
@@ -537,6 +1068,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_wraps_lines_between_words_with_indicator() {
+        // Set up a workspace and buffer; the workspace will
+        // handle setting up the buffer's syntax definition.
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\n");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let data = YamlLoader::load_from_str("wrap_at_words: true\nwrap_indicator: \">\"")
+            .unwrap().into_iter().nth(0).unwrap();
+        let preferences = Preferences::new(Some(data));
+
+        BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        ).render(lines, None).unwrap();
+
+        let expected_content = " 1  amp   \n >  editor\n 2        ";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+    }
+
+    #[test]
+    fn render_tints_line_length_guide_columns() {
+        // Set up a workspace and buffer; the workspace will
+        // handle setting up the buffer's syntax definition.
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("\nsecond\n");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let data = YamlLoader::load_from_str("line_length_guide: [2, 4]").unwrap().into_iter().nth(0).unwrap();
+        let preferences = Preferences::new(Some(data));
+
+        BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        ).render(lines, None).unwrap();
+
+        // Guides are drawn behind content, as well as past the end of lines.
+        let tinted_offsets: Vec<usize> = terminal_buffer
+            .iter()
+            .filter(|(position, cell)| {
+                position.line != 0 && position.offset >= 4 && match cell.colors {
+                    Colors::Focused | Colors::CustomFocusedForeground(_) => true,
+                    _ => false,
+                }
+            })
+            .map(|(position, _)| position.offset)
+            .collect();
+        assert_eq!(&tinted_offsets[0..4], &[6, 8, 6, 8]);
+    }
+
     // Used to test lexeme mapper usage.
     struct TestMapper {}
     impl LexemeMapper for TestMapper {
@@ -579,6 +1188,111 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_scrolls_unwrapped_lines_horizontally_with_truncation_indicators() {
+        // Set up a workspace and buffer; the workspace will
+        // handle setting up the buffer's syntax definition.
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let data = YamlLoader::load_from_str("line_wrapping: false").unwrap().into_iter().nth(0).unwrap();
+        let preferences = Preferences::new(Some(data));
+
+        let mut renderer = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        );
+        renderer.set_column_offset(2);
+        let cursor_position = renderer.render(lines, None).unwrap();
+
+        // The cursor is scrolled out of view, at the start of the line.
+        assert_eq!(cursor_position, None);
+
+        let expected_content = " 1  < edi>";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+    }
+
+    #[test]
+    fn render_draws_indent_guides_in_leading_whitespace() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fn\n    x\n\tx");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let data = YamlLoader::load_from_str("indent_guides: true").unwrap().into_iter().nth(0).unwrap();
+        let preferences = Preferences::new(Some(data));
+
+        BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        ).render(lines, None).unwrap();
+
+        let content = terminal_buffer.content();
+        let rows: Vec<&str> = content.lines().collect();
+        assert_eq!(rows[0].trim_end(), " 1  fn");
+        assert_eq!(rows[1].trim_end(), " 2  │ │ x");
+        assert_eq!(rows[2].trim_end(), " 3  │ x");
+    }
+
+    #[test]
+    fn render_draws_indent_guides_in_horizontally_scrolled_lines() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fn\n    x");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let data = YamlLoader::load_from_str("indent_guides: true\nline_wrapping: false").unwrap().into_iter().nth(0).unwrap();
+        let preferences = Preferences::new(Some(data));
+
+        let mut renderer = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        );
+        renderer.set_column_offset(1);
+        renderer.render(lines, None).unwrap();
+
+        let content = terminal_buffer.content();
+        assert_eq!(content.lines().nth(1).unwrap().trim_end(), " 2  <│ x");
+    }
+
     #[test]
     fn render_returns_cursor_position_when_at_the_start_of_an_empty_line() {
         // Set up a workspace and buffer; the workspace will
@@ -609,6 +1323,183 @@ mod tests {
         assert_eq!(cursor_position, Some(Position{ line: 0, offset: 4 }));
     }
 
+    #[test]
+    fn render_shows_bidi_controls_as_markers_aligned_with_the_cursor() {
+        // Set up a workspace and buffer; the workspace will
+        // handle setting up the buffer's syntax definition.
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("a\u{202E}ש");
+        buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+
+        let cursor_position = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        ).render(lines, None).unwrap();
+
+        assert!(terminal_buffer.content().starts_with(" 1  a⇇ש"));
+        assert_eq!(cursor_position, Some(Position{ line: 0, offset: 7 }));
+    }
+
+    #[test]
+    fn render_shows_diagnostics_after_their_lines() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one\ntwo\nthree");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let diagnostics = vec![
+            Diagnostic{ line: 1, offset: 0, message: String::from("error: bad") },
+            Diagnostic{ line: 2, offset: 0, message: String::from("note: worse") },
+        ];
+
+        let mut renderer = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        );
+        renderer.set_diagnostics(&diagnostics);
+        renderer.render(lines, None).unwrap();
+
+        let content = terminal_buffer.content();
+        let rows: Vec<&str> = content.lines().collect();
+        assert_eq!(rows[0].trim_end(), " 1  one");
+        assert_eq!(rows[1].trim_end(), " 2  two  error: bad");
+        assert_eq!(rows[2].trim_end(), " 3  three  note: worse");
+    }
+
+    #[test]
+    fn render_hides_inline_diagnostics_when_disabled() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("one");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let data = YamlLoader::load_from_str("inline_diagnostics: false").unwrap().into_iter().nth(0).unwrap();
+        let preferences = Preferences::new(Some(data));
+        let diagnostics = vec![Diagnostic{ line: 0, offset: 0, message: String::from("error: bad") }];
+
+        let mut renderer = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        );
+        renderer.set_diagnostics(&diagnostics);
+        renderer.render(lines, None).unwrap();
+
+        let content = terminal_buffer.content();
+        assert_eq!(content.lines().nth(0).unwrap().trim_end(), " 1  one");
+    }
+
+    #[test]
+    fn render_shows_color_swatches_after_their_lines() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("red: #f00\nnone");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+
+        let mut renderer = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        );
+        renderer.set_color_swatches();
+        renderer.render(lines, None).unwrap();
+
+        let swatches: Vec<Position> = terminal_buffer
+            .iter()
+            .filter(|(_, cell)| match cell.colors {
+                Colors::Custom(RGBColor(255, 0, 0), RGBColor(255, 0, 0)) => true,
+                _ => false,
+            })
+            .map(|(position, _)| position)
+            .collect();
+        assert_eq!(swatches, vec![Position{ line: 0, offset: 14 }]);
+    }
+
+    #[test]
+    fn render_underlines_misspelled_words() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("the quikc fox");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let mut dictionary = Dictionary::new();
+        dictionary.insert("the");
+        dictionary.insert("fox");
+
+        let mut renderer = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        );
+        renderer.set_dictionary(&dictionary);
+        renderer.render(lines, None).unwrap();
+
+        let styles: Vec<Style> = terminal_buffer.iter().take(17).map(|(_, cell)| cell.style).collect();
+        assert_eq!(&styles[4..8], &[Style::Default; 4]);
+        assert_eq!(&styles[8..13], &[Style::Underlined; 5]);
+        assert_eq!(&styles[13..17], &[Style::Default; 4]);
+    }
+
     #[test]
     fn render_caches_state_using_correct_frequency_excluding_first_line() {
         // Set up a workspace and buffer; the workspace will
@@ -643,6 +1534,40 @@ mod tests {
         assert_eq!(render_cache.borrow().keys().count(), 5);
     }
 
+    #[test]
+    fn render_skips_caching_and_hidden_lines_for_large_files() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        for line in 0..500 {
+            buffer.insert(format!("line {}\n", 499 - line));
+        }
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let render_cache = Rc::new(RefCell::new(HashMap::new()));
+
+        let mut renderer = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            495,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &render_cache,
+            &mut terminal_buffer
+        );
+        renderer.set_large_file();
+        renderer.render(lines, None).unwrap();
+
+        assert!(render_cache.borrow().is_empty());
+        assert_eq!(terminal_buffer.content().lines().nth(0).unwrap().trim_end(), " 496  line 495");
+    }
+
     #[test]
     fn render_uses_cached_state() {
         let mut workspace = Workspace::new(Path::new(".")).unwrap();
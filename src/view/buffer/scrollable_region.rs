@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use scribe::buffer::Buffer;
 use unicode_segmentation::UnicodeSegmentation;
-use crate::view::buffer::LineNumbers;
+use crate::view::buffer::{layout, LineNumbers, WrapOptions};
 use crate::view::terminal::Terminal;
 
 /// Abstract representation of a fixed-height section of the screen.
@@ -10,6 +10,8 @@ use crate::view::terminal::Terminal;
 pub struct ScrollableRegion {
     terminal: Arc<Box<Terminal + Sync + Send + 'static>>,
     line_offset: usize,
+    column_offset: usize,
+    wrap_options: WrapOptions,
 }
 
 impl ScrollableRegion {
@@ -17,9 +19,17 @@ impl ScrollableRegion {
         ScrollableRegion {
             terminal,
             line_offset: 0,
+            column_offset: 0,
+            wrap_options: WrapOptions::default(),
         }
     }
 
+    /// Updates the options used to determine how many
+    /// rows wrapped lines occupy when scrolling.
+    pub fn set_wrap_options(&mut self, wrap_options: WrapOptions) {
+        self.wrap_options = wrap_options;
+    }
+
     /// If necessary, moves the line offset such that the specified line is
     /// visible, using previous state to determine whether said line is at
     /// the top or bottom of the new visible range.
@@ -40,6 +50,28 @@ impl ScrollableRegion {
         }
     }
 
+    /// If necessary, moves the column offset such that the cursor is visible
+    /// on its (unwrapped) line, leaving a column on either side of it free
+    /// for the indicators used to signal content hidden by the scroll.
+    pub fn scroll_columns_into_view(&mut self, buffer: &Buffer, tab_width: usize) {
+        let column = cursor_column(buffer, tab_width);
+        let gutter_width = LineNumbers::new(&buffer, None).width() + 1;
+        let content_width = self.terminal.width().checked_sub(gutter_width).unwrap_or(0);
+
+        if self.column_offset > 0 && column <= self.column_offset {
+            // Cursor is at or before the start of the visible range.
+            self.column_offset = column.checked_sub(1).unwrap_or(0);
+        } else if column + 1 >= self.column_offset + content_width {
+            // Cursor is at or beyond the end of the visible range.
+            self.column_offset = (column + 2).checked_sub(content_width).unwrap_or(0);
+        }
+    }
+
+    /// Resets the column offset, used when lines are wrapped.
+    pub fn reset_column_offset(&mut self) {
+        self.column_offset = 0;
+    }
+
     /// Moves the line offset such that the specified line is centered vertically.
     pub fn scroll_to_center(&mut self, buffer: &Buffer) {
         let limit = (self.height() as f32 / 2.0).ceil() as usize;
@@ -49,12 +81,36 @@ impl ScrollableRegion {
         ).unwrap_or(0);
     }
 
+    /// Moves the line offset such that the cursor line is at the top of the region.
+    pub fn scroll_to_top(&mut self, buffer: &Buffer) {
+        self.line_offset = buffer.cursor.line;
+    }
+
+    /// Moves the line offset such that the cursor line is at the bottom of the
+    /// region, taking line wrapping into consideration.
+    pub fn scroll_to_bottom(&mut self, buffer: &Buffer) {
+        self.line_offset = buffer.cursor.line.checked_sub(
+            self.preceding_line_count(&buffer, self.height())
+        ).unwrap_or(0);
+    }
+
     /// The number of lines the region has scrolled over.
     /// A value of zero represents an unscrolled region.
     pub fn line_offset(&self) -> usize {
         self.line_offset
     }
 
+    /// The number of (tab-expanded) columns the region has scrolled over
+    /// horizontally. Only relevant when line wrapping is disabled.
+    pub fn column_offset(&self) -> usize {
+        self.column_offset
+    }
+
+    /// Moves the line offset to an absolute value.
+    pub fn scroll_to(&mut self, line_offset: usize) {
+        self.line_offset = line_offset;
+    }
+
     pub fn scroll_up(&mut self, amount: usize) {
         self.line_offset = match self.line_offset.checked_sub(amount) {
             Some(amount) => amount,
@@ -82,6 +138,8 @@ impl ScrollableRegion {
         // to the right-hand side of the line number columns.
         let gutter_width = LineNumbers::new(&buffer, None).width() + 1;
 
+        let content_width = self.terminal.width().checked_sub(gutter_width).unwrap_or(0);
+
         let end = buffer.cursor.line + 1;
         let start = end.checked_sub(limit).unwrap_or(0);
         let line_count = end - start;
@@ -91,13 +149,7 @@ impl ScrollableRegion {
             .lines()
             .skip(start)
             .take(line_count)
-            .map(|line| {
-                let grapheme_count = line.graphemes(true).count().max(1) as f32;
-                let buffer_content_width = (self.terminal.width() - gutter_width) as f32;
-                let wrapped_line_count = grapheme_count / buffer_content_width;
-
-                wrapped_line_count.ceil() as usize
-            })
+            .map(|line| layout(line, content_width, self.wrap_options).row_count())
             .collect();
 
         // Figure out how many lines we can fit
@@ -124,6 +176,24 @@ impl ScrollableRegion {
     }
 }
 
+/// Determines the on-screen column of the buffer's cursor,
+/// relative to the start of its line, with tabs expanded.
+fn cursor_column(buffer: &Buffer, tab_width: usize) -> usize {
+    let data = buffer.data();
+    let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+    let tab_width = tab_width.max(1);
+
+    line.graphemes(true)
+        .take(buffer.cursor.offset)
+        .fold(0, |column, grapheme| {
+            if grapheme == "\t" {
+                (column / tab_width + 1) * tab_width
+            } else {
+                column + 1
+            }
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::ScrollableRegion;
@@ -286,6 +356,83 @@ mod tests {
         assert_eq!(region.line_offset(), 1);
     }
 
+    #[test]
+    fn scroll_to_top_sets_line_offset_to_cursor_line() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        for _ in 0..20 {
+            buffer.insert("\n");
+        }
+        buffer.cursor.move_to(Position{ line: 15, offset: 0 });
+        region.scroll_to_top(&buffer);
+        assert_eq!(region.line_offset(), 15);
+    }
+
+    #[test]
+    fn scroll_to_bottom_sets_correct_line_offset() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        for _ in 0..20 {
+            buffer.insert("\n");
+        }
+        buffer.cursor.move_to(Position{ line: 15, offset: 0 });
+        region.scroll_to_bottom(&buffer);
+        assert_eq!(region.line_offset(), 7);
+    }
+
+    #[test]
+    fn scroll_to_bottom_does_not_set_negative_offset() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        buffer.insert("\n\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        region.scroll_to_bottom(&buffer);
+        assert_eq!(region.line_offset(), 0);
+    }
+
+    #[test]
+    fn scroll_columns_into_view_follows_cursor_beyond_right_edge() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        buffer.insert("amp editor");
+        buffer.cursor.move_to(Position{ line: 0, offset: 8 });
+        region.scroll_columns_into_view(&buffer, 2);
+
+        // The terminal is 10 columns wide, 4 of which are used by the gutter.
+        // The last column is reserved for the truncation indicator.
+        assert_eq!(region.column_offset(), 4);
+    }
+
+    #[test]
+    fn scroll_columns_into_view_follows_cursor_beyond_left_edge() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        buffer.insert("amp editor");
+        buffer.cursor.move_to(Position{ line: 0, offset: 8 });
+        region.scroll_columns_into_view(&buffer, 2);
+        buffer.cursor.move_to(Position{ line: 0, offset: 2 });
+        region.scroll_columns_into_view(&buffer, 2);
+
+        assert_eq!(region.column_offset(), 1);
+    }
+
+    #[test]
+    fn scroll_columns_into_view_expands_tabs() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        buffer.insert("\t\tamp");
+        buffer.cursor.move_to(Position{ line: 0, offset: 2 });
+        region.scroll_columns_into_view(&buffer, 4);
+
+        assert_eq!(region.column_offset(), 4);
+    }
+
     #[test]
     fn scroll_down_increases_line_offset_by_amount() {
         let terminal = build_terminal().unwrap();
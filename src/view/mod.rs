@@ -3,6 +3,7 @@ pub mod terminal;
 mod buffer;
 mod data;
 mod event_listener;
+mod popup;
 mod presenter;
 mod style;
 mod theme_loader;
@@ -12,24 +13,33 @@ pub use self::data::StatusLineData;
 pub use self::buffer::{LexemeMapper, MappedLexeme};
 pub use self::style::Style;
 pub use self::color::{Colors, RGBColor};
+pub use self::popup::{Popup, PopupAnchor};
 pub use self::presenter::Presenter;
 pub use self::terminal::*;
 
 use crate::errors::*;
 use crate::input::Key;
 use crate::models::application::{Event, Preferences};
+use crate::util::conflict::{self, Conflict};
+use crate::util::line_index::LineIndex;
+use crate::util::lint::Diagnostic;
+use crate::util::spelling::Dictionary;
+use crate::util::timings::Timings;
 use self::buffer::{RenderCache, RenderState};
-use self::buffer::ScrollableRegion;
+use self::buffer::{ScrollableRegion, WrapOptions};
 use self::event_listener::EventListener;
 use scribe::buffer::Buffer;
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::ops::Drop;
+use std::path::PathBuf;
+use std::mem;
 use std::sync::mpsc::{self, Sender, SyncSender};
 use std::sync::Arc;
-use self::theme_loader::ThemeLoader;
+use std::thread::JoinHandle;
+use self::theme_loader::{LoadedThemes, ThemeLoader};
 use syntect::highlighting::ThemeSet;
 
 const RENDER_CACHE_FREQUENCY: usize = 100;
@@ -38,9 +48,30 @@ pub struct View {
     terminal: Arc<Box<Terminal + Sync + Send + 'static>>,
     scrollable_regions: HashMap<usize, ScrollableRegion>,
     render_caches: HashMap<usize, Rc<RefCell<HashMap<usize, RenderState>>>>,
-    pub theme_set: ThemeSet,
+    line_indices: HashMap<usize, Rc<RefCell<LineIndex>>>,
+    conflict_caches: HashMap<usize, Rc<RefCell<Option<Vec<Conflict>>>>>,
+    // Buffers changed since they were last checked (see take_change).
+    changed_buffers: Rc<RefCell<HashSet<usize>>>,
+    theme_set: Option<ThemeSet>,
+    theme_loader: Option<JoinHandle<Result<LoadedThemes>>>,
+    // User themes that couldn't be loaded, waiting to be reported.
+    theme_errors: Vec<Error>,
+    // The theme buffers were last rendered with.
+    rendered_theme: Option<String>,
     preferences: Rc<RefCell<Preferences>>,
+    // Loaded the first time spelling is checked.
+    dictionary: Option<Dictionary>,
     pub last_key: Option<Key>,
+    /// Keys that have been typed but haven't run anything yet (e.g. counts).
+    pub pending_input: Option<String>,
+    /// Timings for the frame being handled and rendered, collected only
+    /// while enabled, along with a summary of the previous frame's.
+    pub frame_timings: Option<Timings>,
+    pub last_frame_timings: Option<String>,
+    /// The latest linter findings for each file, shown alongside its lines.
+    pub diagnostics: HashMap<PathBuf, Vec<Diagnostic>>,
+    // Large buffers that have been opted back into every feature.
+    full_feature_buffers: HashSet<usize>,
     event_channel: Sender<Event>,
     event_listener_killswitch: SyncSender<()>
 }
@@ -48,8 +79,19 @@ pub struct View {
 impl View {
     pub fn new(preferences: Rc<RefCell<Preferences>>, event_channel: Sender<Event>) -> Result<View> {
         let terminal = build_terminal().chain_err(|| "Failed to initialize terminal")?;
+
+        View::with_terminal(terminal, preferences, event_channel)
+    }
+
+    /// Builds a view that doesn't draw anything or listen for input, leaving
+    /// the terminal untouched, for running commands without a UI.
+    pub fn new_headless(preferences: Rc<RefCell<Preferences>>, event_channel: Sender<Event>) -> Result<View> {
+        View::with_terminal(Arc::new(Box::new(HeadlessTerminal)), preferences, event_channel)
+    }
+
+    fn with_terminal(terminal: Arc<Box<Terminal + Sync + Send + 'static>>, preferences: Rc<RefCell<Preferences>>, event_channel: Sender<Event>) -> Result<View> {
         let theme_path = preferences.borrow().theme_path()?;
-        let theme_set = ThemeLoader::new(theme_path).load()?;
+        let theme_loader = ThemeLoader::new(theme_path).spawn(event_channel.clone());
 
         let (killswitch_tx, killswitch_rx) = mpsc::sync_channel(0);
         EventListener::start(terminal.clone(), event_channel.clone(), killswitch_rx);
@@ -57,15 +99,120 @@ impl View {
         Ok(View {
             terminal,
             last_key: None,
+            pending_input: None,
+            frame_timings: None,
+            last_frame_timings: None,
+            diagnostics: HashMap::new(),
+            full_feature_buffers: HashSet::new(),
             preferences,
+            dictionary: None,
             scrollable_regions: HashMap::new(),
             render_caches: HashMap::new(),
-            theme_set,
+            line_indices: HashMap::new(),
+            conflict_caches: HashMap::new(),
+            changed_buffers: Rc::new(RefCell::new(HashSet::new())),
+            theme_set: None,
+            theme_loader: Some(theme_loader),
+            theme_errors: Vec::new(),
+            rendered_theme: None,
             event_channel,
             event_listener_killswitch: killswitch_tx
         })
     }
 
+    /// Starts or stops collecting frame timings, which are shown in the status line.
+    pub fn toggle_frame_timings(&mut self) {
+        if self.frame_timings.take().is_none() {
+            self.frame_timings = Some(Timings::new());
+        }
+        self.last_frame_timings = None;
+    }
+
+    /// Starts timing a new frame, if frame timings are enabled.
+    pub fn start_frame(&mut self) {
+        if let Some(ref mut timings) = self.frame_timings {
+            *timings = Timings::new();
+        }
+    }
+
+    /// Records the end of a phase of the current frame, if frame timings are enabled.
+    pub fn record_frame_phase(&mut self, phase: &'static str) {
+        if let Some(ref mut timings) = self.frame_timings {
+            timings.record(phase);
+        }
+    }
+
+    /// Wraps up the current frame once it's been rendered,
+    /// making its timings available to the next one.
+    pub fn finish_frame(&mut self) {
+        self.record_frame_phase("render");
+        self.last_frame_timings = self.frame_timings.as_ref().map(|timings| timings.summary());
+    }
+
+    /// Themes are loaded in the background; this waits for them to finish
+    /// loading the first time it's called. Until then (i.e. until the loader's
+    /// event arrives), buffers are rendered using the terminal's colors.
+    pub fn theme_set(&mut self) -> Result<&ThemeSet> {
+        if let Some(loader) = self.theme_loader.take() {
+            let loaded_themes = loader
+                .join()
+                .map_err(|_| Error::from("Theme loader panicked"))??;
+            self.use_loaded_themes(loaded_themes);
+        }
+
+        self.theme_set.as_ref().ok_or_else(|| "Failed to load themes".into())
+    }
+
+    /// Loads the themes again, picking up any that
+    /// have been added to or changed in the themes directory.
+    pub fn reload_themes(&mut self) -> Result<()> {
+        let theme_path = self.preferences.borrow().theme_path()?;
+        let loaded_themes = ThemeLoader::new(theme_path).load()?;
+        self.theme_loader = None;
+        self.use_loaded_themes(loaded_themes);
+
+        // The current theme may have changed, even if its name hasn't.
+        self.rendered_theme = None;
+
+        Ok(())
+    }
+
+    /// Problems loading user themes since this was last called.
+    pub fn take_theme_errors(&mut self) -> Vec<Error> {
+        mem::replace(&mut self.theme_errors, Vec::new())
+    }
+
+    fn use_loaded_themes(&mut self, loaded_themes: LoadedThemes) {
+        self.theme_set = Some(loaded_themes.theme_set);
+        self.theme_errors = loaded_themes.errors;
+    }
+
+    /// The dictionary that spelling is checked against,
+    /// which is loaded the first time it's needed.
+    pub fn dictionary(&mut self) -> Result<&mut Dictionary> {
+        if self.dictionary.is_none() {
+            let paths = self.preferences.borrow().dictionaries();
+            self.dictionary = Some(Dictionary::load(&paths, Preferences::dictionary_path()?)?);
+        }
+
+        self.dictionary.as_mut().ok_or_else(|| "Failed to load the spelling dictionary".into())
+    }
+
+    /// Whether the buffer's content is over the large file threshold, in which
+    /// case only the visible lines are highlighted, and features that scan the
+    /// whole buffer (e.g. spell checking) are skipped, unless forced back on.
+    pub fn is_large_buffer(&self, buffer: &Buffer, data: &str) -> bool {
+        data.len() > self.preferences.borrow().large_file_threshold() &&
+            !buffer.id.map(|id| self.full_feature_buffers.contains(&id)).unwrap_or(false)
+    }
+
+    /// Turns every feature back on for a large buffer.
+    pub fn enable_full_features(&mut self, buffer: &Buffer) -> Result<()> {
+        self.full_feature_buffers.insert(buffer_key(buffer)?);
+
+        Ok(())
+    }
+
     pub fn build_presenter<'a>(&'a mut self) -> Result<Presenter<'a>> {
         Presenter::new(self)
     }
@@ -75,7 +222,19 @@ impl View {
     ///
 
     pub fn scroll_to_cursor(&mut self, buffer: &Buffer) -> Result<()> {
-        self.get_region(buffer)?.scroll_into_view(&buffer);
+        let (line_wrapping, tab_width) = {
+            let preferences = self.preferences.borrow();
+            (preferences.line_wrapping(), preferences.tab_width(buffer.path.as_ref()))
+        };
+        let region = self.get_region(buffer)?;
+        region.scroll_into_view(&buffer);
+
+        // Long lines are cut off rather than wrapped; follow the cursor horizontally.
+        if line_wrapping {
+            region.reset_column_offset();
+        } else {
+            region.scroll_columns_into_view(&buffer, tab_width);
+        }
 
         Ok(())
     }
@@ -86,6 +245,24 @@ impl View {
         Ok(())
     }
 
+    pub fn scroll_to_top(&mut self, buffer: &Buffer) -> Result<()> {
+        self.get_region(buffer)?.scroll_to_top(&buffer);
+
+        Ok(())
+    }
+
+    pub fn scroll_to_bottom(&mut self, buffer: &Buffer) -> Result<()> {
+        self.get_region(buffer)?.scroll_to_bottom(&buffer);
+
+        Ok(())
+    }
+
+    /// The number of lines available to buffer content,
+    /// excluding the line reserved for the status bar.
+    pub fn page_height(&self) -> usize {
+        self.terminal.height().checked_sub(1).unwrap_or(0)
+    }
+
     pub fn scroll_up(&mut self, buffer: &Buffer, amount: usize) -> Result<()> {
         self.get_region(buffer)?.scroll_up(amount);
 
@@ -116,24 +293,47 @@ impl View {
         Ok(())
     }
 
+    pub fn line_offset(&mut self, buffer: &Buffer) -> Result<usize> {
+        Ok(self.get_region(buffer)?.line_offset())
+    }
+
+    pub fn set_line_offset(&mut self, buffer: &Buffer, line_offset: usize) -> Result<()> {
+        self.get_region(buffer)?.scroll_to(line_offset);
+
+        Ok(())
+    }
+
     /// Cleans up buffer-related view data. This method
     /// should be called whenever a buffer is closed.
     pub fn forget_buffer(&mut self, buffer: &Buffer) -> Result<()> {
         self.scrollable_regions.remove(&buffer_key(buffer)?);
         self.render_caches.remove(&buffer_key(buffer)?);
+        self.line_indices.remove(&buffer_key(buffer)?);
+        self.conflict_caches.remove(&buffer_key(buffer)?);
+        self.full_feature_buffers.remove(&buffer_key(buffer)?);
+        self.changed_buffers.borrow_mut().remove(&buffer_key(buffer)?);
 
         Ok(())
     }
 
+    /// Whether the buffer has changed since this was last called for it, which
+    /// is cheaper than comparing its content. New buffers count as changed.
+    pub fn take_change(&mut self, buffer: &Buffer) -> bool {
+        buffer.id.map(|id| self.changed_buffers.borrow_mut().remove(&id)).unwrap_or(false)
+    }
+
     // Tries to fetch a scrollable region for the specified buffer,
     // inserting (and returning a reference to) a new one if not.
     fn get_region(&mut self, buffer: &Buffer) -> Result<&mut ScrollableRegion> {
-        Ok(self.scrollable_regions
+        let wrap_options = WrapOptions::new(&self.preferences.borrow(), buffer.path.as_ref());
+        let region = self.scrollable_regions
             .entry(buffer_key(buffer)?)
             .or_insert(
                 ScrollableRegion::new(self.terminal.clone())
-            )
-        )
+            );
+        region.set_wrap_options(wrap_options);
+
+        Ok(region)
     }
 
     fn get_render_cache(&self, buffer: &Buffer) -> Result<&Rc<RefCell<HashMap<usize, RenderState>>>> {
@@ -144,6 +344,24 @@ impl View {
         Ok(cache)
     }
 
+    // Cached render state includes highlighting, which depends on the theme.
+    // Discard it when the theme changes, so that buffers are fully redrawn.
+    fn use_theme(&mut self, theme: &str) {
+        if self.rendered_theme.as_ref().map(|name| name.as_str()) == Some(theme) {
+            return;
+        }
+
+        self.clear_render_caches();
+        self.rendered_theme = Some(theme.to_string());
+    }
+
+    /// Drops every buffer's cached highlighting (e.g. after their syntaxes change).
+    pub fn clear_render_caches(&mut self) {
+        for render_cache in self.render_caches.values() {
+            render_cache.borrow_mut().clear();
+        }
+    }
+
     pub fn suspend(&mut self) {
         let _ = self.event_listener_killswitch.send(());
         self.terminal.suspend();
@@ -152,23 +370,81 @@ impl View {
         self.event_listener_killswitch = killswitch_tx;
     }
 
+    /// Stops listening for input and hands the terminal back to the shell.
+    pub fn restore_terminal(&mut self) {
+        let _ = self.event_listener_killswitch.send(());
+        self.terminal.restore();
+    }
+
+    /// The buffer's line index, which is kept up to date as the buffer changes.
+    /// Buffers that haven't been initialized get a new index, good for one use.
+    pub fn line_index(&self, buffer: &Buffer) -> Rc<RefCell<LineIndex>> {
+        buffer.id
+            .and_then(|id| self.line_indices.get(&id))
+            .cloned()
+            .unwrap_or_else(|| Rc::new(RefCell::new(LineIndex::new())))
+    }
+
+    /// Merge conflicts in the buffer, which are only looked for again once
+    /// it's changed, rather than rescanning its content on every render.
+    pub fn conflicts(&self, buffer: &Buffer, data: &str) -> Vec<Conflict> {
+        let cache = match buffer.id.and_then(|id| self.conflict_caches.get(&id)) {
+            Some(cache) => cache,
+            None => return conflict::conflicts(data),
+        };
+
+        cache
+            .borrow_mut()
+            .get_or_insert_with(|| conflict::conflicts(data))
+            .clone()
+    }
+
+    /// Switches to the cursor shape configured for the specified mode.
+    pub fn set_cursor_shape(&self, mode: &str) {
+        self.terminal.set_cursor_shape(self.preferences.borrow().cursor_shape(mode));
+    }
+
+    /// Applies the configured input timeout, which may have changed.
+    pub fn set_input_timeout(&self) {
+        self.terminal.set_input_timeout(self.preferences.borrow().input_timeout());
+    }
+
     pub fn last_key(&self) -> &Option<Key> {
         &self.last_key
     }
 
-    /// Sets up new buffers with render caches and cache invalidation callbacks.
+    /// Sets up new buffers with render caches, line indices, conflict
+    /// caches, and the callbacks that invalidate them (and note changes).
     pub fn initialize_buffer(&mut self, buffer: &mut Buffer) -> Result<()> {
-        // Build and store a new render cache for the buffer.
+        // Build and store a new render cache and line index for the buffer.
         let render_cache = Rc::new(RefCell::new(HashMap::new()));
         self.render_caches.insert(
             buffer_key(buffer)?,
             render_cache.clone()
         );
+        let line_index = Rc::new(RefCell::new(LineIndex::new()));
+        self.line_indices.insert(
+            buffer_key(buffer)?,
+            line_index.clone()
+        );
 
-        // Wire up the buffer's change callback to invalidate the render cache.
+        let conflict_cache = Rc::new(RefCell::new(None));
+        self.conflict_caches.insert(
+            buffer_key(buffer)?,
+            conflict_cache.clone()
+        );
+
+        let id = buffer_key(buffer)?;
+        let changed_buffers = self.changed_buffers.clone();
+        changed_buffers.borrow_mut().insert(id);
+
+        // Wire up the buffer's change callback to invalidate all of them.
         buffer.change_callback = Some(
             Box::new(move |change_position| {
                 render_cache.borrow_mut().invalidate_from(change_position.line);
+                line_index.borrow_mut().invalidate_from(change_position.line);
+                *conflict_cache.borrow_mut() = None;
+                changed_buffers.borrow_mut().insert(id);
             })
         );
 
@@ -199,6 +475,22 @@ mod tests {
     use syntect::highlighting::{Highlighter, ThemeSet};
     use crate::view::buffer::RenderState;
 
+    #[test]
+    fn is_large_buffer_uses_the_threshold_until_full_features_are_enabled() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        preferences.borrow_mut().set("large_file_threshold", "3").unwrap();
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(0);
+
+        assert!(!view.is_large_buffer(&buffer, "amp"));
+        assert!(view.is_large_buffer(&buffer, "amp\n"));
+
+        view.enable_full_features(&buffer).unwrap();
+        assert!(!view.is_large_buffer(&buffer, "amp\n"));
+    }
+
     #[test]
     fn scroll_down_prevents_scrolling_completely_beyond_buffer() {
         let preferences = Rc::new(RefCell::new(Preferences::new(None)));
@@ -252,6 +544,32 @@ mod tests {
         assert!(view.render_caches.get(&buffer.id.unwrap()).is_some());
     }
 
+    #[test]
+    fn use_theme_clears_render_caches_when_the_theme_changes() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+
+        // Set up a buffer with a syntax definition and id.
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        workspace.add_buffer(Buffer::new());
+        let mut buffer = workspace.current_buffer().unwrap();
+        view.initialize_buffer(&mut buffer).unwrap();
+
+        let theme_set = ThemeSet::load_defaults();
+        let highlighter = Highlighter::new(&theme_set.themes["base16-ocean.dark"]);
+        let render_state = RenderState::new(&highlighter, buffer.syntax_definition.as_ref().unwrap());
+        let render_cache = view.render_caches.get(&buffer.id.unwrap()).unwrap().clone();
+
+        view.use_theme("base16-ocean.dark");
+        render_cache.borrow_mut().insert(100, render_state);
+        view.use_theme("base16-ocean.dark");
+        assert_eq!(render_cache.borrow().len(), 1);
+
+        view.use_theme("base16-eighties.dark");
+        assert!(render_cache.borrow().is_empty());
+    }
+
     #[test]
     fn initialize_buffer_sets_change_callback_to_clear_render_cache() {
         let preferences = Rc::new(RefCell::new(Preferences::new(None)));
@@ -307,5 +625,42 @@ mod tests {
             vec![&0]
         );
     }
-}
 
+    #[test]
+    fn take_change_reports_new_and_changed_buffers_once() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        workspace.add_buffer(Buffer::new());
+        let buffer = workspace.current_buffer().unwrap();
+
+        view.initialize_buffer(buffer).unwrap();
+        assert!(view.take_change(buffer));
+        assert!(!view.take_change(buffer));
+
+        buffer.insert("amp");
+        assert!(view.take_change(buffer));
+        assert!(!view.take_change(buffer));
+    }
+
+    #[test]
+    fn conflicts_are_cached_until_the_buffer_changes() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        workspace.add_buffer(Buffer::new());
+        let mut buffer = workspace.current_buffer().unwrap();
+        buffer.insert("<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n");
+        view.initialize_buffer(&mut buffer).unwrap();
+        assert_eq!(view.conflicts(&buffer, &buffer.data()).len(), 1);
+
+        // Content isn't rescanned while the cache is valid.
+        assert_eq!(view.conflicts(&buffer, "").len(), 1);
+
+        buffer.insert("amp");
+        assert!(view.conflicts(&buffer, "").is_empty());
+    }
+}
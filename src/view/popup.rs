@@ -0,0 +1,247 @@
+use crate::view::{Colors, Style};
+use crate::view::terminal::{Cell, TerminalBuffer};
+use scribe::buffer::Position;
+use std::borrow::Cow;
+use std::cmp;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Keeps long popups from covering the entire buffer.
+const DEFAULT_MAX_HEIGHT: usize = 10;
+
+/// Where a popup is placed on the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PopupAnchor {
+    /// Below the position and aligned with its column,
+    /// or above it, if there isn't enough room below.
+    Below(Position),
+    /// Above the position and aligned with its column,
+    /// or below it, if there isn't enough room above.
+    Above(Position),
+    /// Spanning the top of the screen.
+    Top,
+    /// Spanning the bottom of the screen, just above the status line.
+    Bottom,
+}
+
+/// A bordered, scrollable panel drawn over buffer content. Popups are added
+/// to a presenter and drawn once everything else has been, in the order that
+/// they were added (so later popups cover earlier ones), leaving the status
+/// line uncovered.
+pub struct Popup {
+    pub anchor: PopupAnchor,
+    pub title: Option<String>,
+    pub lines: Vec<String>,
+    /// A line that's highlighted, and kept visible regardless of scrolling.
+    pub selected: Option<usize>,
+    /// The number of lines scrolled past.
+    pub scroll_offset: usize,
+    /// The most lines shown at once, excluding the border.
+    pub max_height: usize,
+}
+
+/// The part of the screen covered by a popup, including its border.
+#[derive(Debug, PartialEq)]
+pub struct PopupArea {
+    pub position: Position,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Popup {
+    pub fn new(anchor: PopupAnchor, lines: Vec<String>) -> Popup {
+        Popup {
+            anchor,
+            title: None,
+            lines,
+            selected: None,
+            scroll_offset: 0,
+            max_height: DEFAULT_MAX_HEIGHT,
+        }
+    }
+
+    /// Where the popup is drawn on a screen with the specified dimensions
+    /// (excluding the status line), or None if there's nothing to show or no
+    /// room to show it.
+    pub fn area(&self, screen_width: usize, screen_height: usize) -> Option<PopupArea> {
+        if self.lines.is_empty() || screen_width < 3 || screen_height < 3 {
+            return None;
+        }
+
+        let height = cmp::min(cmp::min(self.lines.len(), self.max_height) + 2, screen_height);
+        let width = match self.anchor {
+            PopupAnchor::Top | PopupAnchor::Bottom => screen_width,
+            _ => {
+                let content_width = self.lines
+                    .iter()
+                    .chain(self.title.iter())
+                    .map(|line| line.graphemes(true).count())
+                    .max()
+                    .unwrap_or(0);
+
+                cmp::min(content_width + 2, screen_width)
+            },
+        };
+        let fits_below = |line: usize| line + 1 + height <= screen_height;
+        let position = match self.anchor {
+            PopupAnchor::Top => Position{ line: 0, offset: 0 },
+            PopupAnchor::Bottom => Position{ line: screen_height - height, offset: 0 },
+            PopupAnchor::Below(anchor) => {
+                let line = if fits_below(anchor.line) {
+                    anchor.line + 1
+                } else {
+                    anchor.line.checked_sub(height).unwrap_or(screen_height - height)
+                };
+
+                Position{ line, offset: cmp::min(anchor.offset, screen_width - width) }
+            },
+            PopupAnchor::Above(anchor) => {
+                let line = match anchor.line.checked_sub(height) {
+                    Some(line) => line,
+                    None if fits_below(anchor.line) => anchor.line + 1,
+                    None => 0,
+                };
+
+                Position{ line, offset: cmp::min(anchor.offset, screen_width - width) }
+            },
+        };
+
+        Some(PopupArea{ position, width, height })
+    }
+
+    /// Draws the popup's border and visible lines over the specified area.
+    pub fn draw<'c>(&self, terminal_buffer: &mut TerminalBuffer<'c>, area: &PopupArea) {
+        let inner_width = area.width - 2;
+        let visible_lines = area.height - 2;
+        let first_line = self.first_visible_line(visible_lines);
+        let Position{ line: top, offset: left } = area.position;
+        let bottom = top + area.height - 1;
+
+        // The top border includes the title, if there is one.
+        let title: String = self.title
+            .as_ref()
+            .map(|title| format!(" {} ", title))
+            .unwrap_or_default()
+            .graphemes(true)
+            .take(inner_width)
+            .collect();
+        let title_width = title.graphemes(true).count();
+        let top_border = format!("┌{}{}┐", title, "─".repeat(inner_width - title_width));
+        print_row(terminal_buffer, area.position, area.width, &top_border, Style::Default, Colors::Focused);
+        let bottom_border = format!("└{}┘", "─".repeat(inner_width));
+        print_row(terminal_buffer, Position{ line: bottom, offset: left }, area.width, &bottom_border, Style::Default, Colors::Focused);
+
+        // Mark the borders when there are lines scrolled out of view.
+        if inner_width > 0 {
+            let indicator_offset = left + area.width - 2;
+            if first_line > 0 {
+                print_row(terminal_buffer, Position{ line: top, offset: indicator_offset }, 1, "↑", Style::Default, Colors::Focused);
+            }
+            if first_line + visible_lines < self.lines.len() {
+                print_row(terminal_buffer, Position{ line: bottom, offset: indicator_offset }, 1, "↓", Style::Default, Colors::Focused);
+            }
+        }
+
+        for row in 0..visible_lines {
+            let index = first_line + row;
+            let line = top + 1 + row;
+            let (style, colors) = if self.selected == Some(index) {
+                (Style::Default, Colors::Inverted)
+            } else {
+                (Style::Default, Colors::Focused)
+            };
+            let content = self.lines.get(index).map(|line| line.as_str()).unwrap_or("");
+
+            print_row(terminal_buffer, Position{ line, offset: left }, 1, "│", Style::Default, Colors::Focused);
+            print_row(terminal_buffer, Position{ line, offset: left + 1 }, inner_width, content, style, colors);
+            print_row(terminal_buffer, Position{ line, offset: left + area.width - 1 }, 1, "│", Style::Default, Colors::Focused);
+        }
+    }
+
+    // The scroll offset, limited to the lines available,
+    // and adjusted to keep the selected line (if any) in view.
+    fn first_visible_line(&self, visible_lines: usize) -> usize {
+        let mut first_line = cmp::min(self.scroll_offset, self.lines.len().saturating_sub(visible_lines));
+
+        if let Some(selected) = self.selected {
+            if selected < first_line {
+                first_line = selected;
+            } else if selected >= first_line + visible_lines {
+                first_line = selected + 1 - visible_lines;
+            }
+        }
+
+        first_line
+    }
+}
+
+// Prints content one grapheme per cell, truncated or padded to the width.
+fn print_row<'c>(terminal_buffer: &mut TerminalBuffer<'c>, position: Position, width: usize, content: &str, style: Style, colors: Colors) {
+    let mut graphemes = content.graphemes(true);
+
+    for column in 0..width {
+        let content = graphemes.next().unwrap_or(" ");
+
+        terminal_buffer.set_cell(
+            Position{ line: position.line, offset: position.offset + column },
+            Cell{ content: Cow::Owned(content.to_string()), style, colors }
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::view::terminal::TerminalBuffer;
+    use scribe::buffer::Position;
+    use super::{Popup, PopupAnchor, PopupArea};
+
+    fn lines(count: usize) -> Vec<String> {
+        (0..count).map(|line| format!("item {}", line)).collect()
+    }
+
+    #[test]
+    fn area_places_popups_below_the_anchor_when_they_fit() {
+        let popup = Popup::new(PopupAnchor::Below(Position{ line: 2, offset: 4 }), lines(2));
+
+        assert_eq!(
+            popup.area(20, 10),
+            Some(PopupArea{ position: Position{ line: 3, offset: 4 }, width: 8, height: 4 })
+        );
+    }
+
+    #[test]
+    fn area_flips_popups_above_the_anchor_and_keeps_them_on_screen() {
+        let popup = Popup::new(PopupAnchor::Below(Position{ line: 8, offset: 18 }), lines(2));
+
+        assert_eq!(
+            popup.area(20, 10),
+            Some(PopupArea{ position: Position{ line: 4, offset: 12 }, width: 8, height: 4 })
+        );
+    }
+
+    #[test]
+    fn area_spans_the_screen_for_edge_anchors() {
+        let popup = Popup::new(PopupAnchor::Bottom, lines(3));
+
+        assert_eq!(
+            popup.area(20, 10),
+            Some(PopupArea{ position: Position{ line: 5, offset: 0 }, width: 20, height: 5 })
+        );
+        assert_eq!(Popup::new(PopupAnchor::Top, Vec::new()).area(20, 10), None);
+    }
+
+    #[test]
+    fn draw_scrolls_to_keep_the_selected_line_visible() {
+        let mut popup = Popup::new(PopupAnchor::Top, lines(5));
+        popup.title = Some(String::from("list"));
+        popup.max_height = 2;
+        popup.selected = Some(3);
+        let area = popup.area(10, 6).unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(10, 6);
+        popup.draw(&mut terminal_buffer, &area);
+
+        assert_eq!(
+            terminal_buffer.content(),
+            "┌ list ─↑┐\n│item 2  │\n│item 3  │\n└───────↓┘\n          \n          "
+        );
+    }
+}
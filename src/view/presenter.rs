@@ -1,7 +1,8 @@
 use crate::errors::*;
+use crate::util::occurrences;
 use crate::view::buffer::{BufferRenderer, LexemeMapper};
-use crate::view::color::{ColorMap, Colors};
-use crate::view::StatusLineData;
+use crate::view::color::{to_rgb_color, ColorMap, Colors};
+use crate::view::{Popup, StatusLineData};
 use crate::view::style::Style;
 use crate::view::terminal::{Cell, TerminalBuffer};
 use crate::view::View;
@@ -9,24 +10,35 @@ use pad::PadStr;
 use scribe::buffer::{Buffer, Position, Range};
 use scribe::util::LineIterator;
 use std::borrow::Cow;
+use syntect::easy::HighlightLines;
 use syntect::highlighting::Theme;
+use syntect::parsing::SyntaxDefinition;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub struct Presenter<'p> {
     cursor_position: Option<Position>,
     terminal_buffer: TerminalBuffer<'p>,
     theme: Theme,
+    occurrence: Option<String>,
+    popups: Vec<Popup>,
     pub view: &'p mut View,
 }
 
 impl<'p> Presenter<'p> {
     pub fn new(view: &mut View) -> Result<Presenter> {
-        let theme = {
-            let preferences = view.preferences.borrow();
-            let theme_name = preferences.theme();
-            let theme = view.theme_set.themes
-                .get(theme_name)
-                .ok_or_else(|| format!("Couldn't find \"{}\" theme", theme_name))?;
-            theme.clone()
+        let theme_name = view.preferences.borrow().theme().to_string();
+        let theme = if view.theme_loader.is_some() {
+            // Don't hold up rendering while themes are loading
+            // in the background; they're swapped in once ready.
+            Theme::default()
+        } else {
+            let theme = view.theme_set()?.themes
+                .get(&theme_name)
+                .ok_or_else(|| format!("Couldn't find \"{}\" theme", theme_name))?
+                .clone();
+            view.use_theme(&theme_name);
+
+            theme
         };
 
         Ok(Presenter{
@@ -36,6 +48,8 @@ impl<'p> Presenter<'p> {
                 view.terminal.height(),
             ),
             theme,
+            occurrence: None,
+            popups: Vec::new(),
             view
         })
     }
@@ -56,7 +70,20 @@ impl<'p> Presenter<'p> {
         self.cursor_position = position;
     }
 
+    /// Adds a popup, drawn over everything except the status line
+    /// (and any popups added after it) when the screen is presented.
+    pub fn add_popup(&mut self, popup: Popup) {
+        self.popups.push(popup);
+    }
+
     pub fn present(&mut self) {
+        let (width, height) = (self.width(), self.height().saturating_sub(1));
+        for popup in self.popups.drain(..) {
+            if let Some(area) = popup.area(width, height) {
+                popup.draw(&mut self.terminal_buffer, &area);
+            }
+        }
+
         for (position, cell) in self.terminal_buffer.iter() {
             self.view.terminal.print(
                 &position,
@@ -69,47 +96,159 @@ impl<'p> Presenter<'p> {
         self.view.terminal.present();
     }
 
+    /// Highlights occurrences of the word within the visible
+    /// portion of buffers subsequently printed to the screen.
+    pub fn highlight_occurrences(&mut self, word: &str) {
+        self.occurrence = Some(word.to_string());
+    }
+
     pub fn print_buffer(&mut self, buffer: &Buffer, buffer_data: &'p str, highlights: Option<&[Range]>, lexeme_mapper: Option<&'p mut LexemeMapper>) -> Result<()> {
         let scroll_offset = self.view.get_region(buffer)?.line_offset();
+        let column_offset = self.view.get_region(buffer)?.column_offset();
         let lines = LineIterator::new(buffer_data);
+        let large_file = self.view.is_large_buffer(buffer, buffer_data);
+        let occurrences = match self.occurrence {
+            Some(ref word) if !large_file => occurrences::find(buffer_data, word, scroll_offset, self.height()),
+            _ => Vec::new(),
+        };
 
-        self.cursor_position = BufferRenderer::new(
+        let spell_check = !large_file && self.view.preferences.borrow().spell_check(buffer.path.as_ref());
+        if spell_check {
+            self.view.dictionary()?;
+        }
+
+        let preferences = self.view.preferences.borrow();
+        let mut renderer = BufferRenderer::new(
             buffer,
             highlights,
             scroll_offset,
             &**self.view.terminal,
             &self.theme,
-            &self.view.preferences.borrow(),
+            &preferences,
             self.view.get_render_cache(buffer)?,
             &mut self.terminal_buffer
-        ).render(lines, lexeme_mapper)?;
+        );
+        renderer.set_column_offset(column_offset);
+        if large_file {
+            renderer.set_large_file();
+        }
+        renderer.set_occurrences(&occurrences);
+        if !large_file {
+            renderer.set_conflicts(self.view.conflicts(buffer, buffer_data));
+        }
+        if !large_file && preferences.color_swatches() {
+            renderer.set_color_swatches();
+        }
+        if let Some(diagnostics) = buffer.path.as_ref().and_then(|path| self.view.diagnostics.get(path)) {
+            renderer.set_diagnostics(diagnostics);
+        }
+        if let (true, Some(dictionary)) = (spell_check, self.view.dictionary.as_ref()) {
+            renderer.set_dictionary(dictionary);
+        }
+        self.cursor_position = renderer.render(lines, lexeme_mapper)?;
+        let highlight_duration = renderer.highlight_duration();
+        if let Some(ref mut timings) = self.view.frame_timings {
+            timings.add("highlight", highlight_duration);
+        }
 
         Ok(())
     }
 
+    /// Draws read-only content in a pane extending from the specified column
+    /// to the right edge of the screen, under a title, leaving the status line
+    /// uncovered. Only the lines that fit in the pane are highlighted.
+    /// The theme content is highlighted with, once themes have loaded.
+    pub fn theme_name(&self) -> Option<&str> {
+        self.view.rendered_theme.as_ref().map(|name| name.as_str())
+    }
+
+    /// Highlights the content's lines, for printing in a pane.
+    pub fn highlight(&self, content: &str, syntax: &SyntaxDefinition) -> Vec<Vec<(Colors, String)>> {
+        let mut highlighter = HighlightLines::new(syntax, &self.theme);
+
+        content
+            .lines()
+            .map(|line| {
+                highlighter
+                    .highlight(line)
+                    .into_iter()
+                    .map(|(style, text)| {
+                        (Colors::CustomForeground(to_rgb_color(style.foreground)), text.replace('\t', "  "))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn print_pane(&mut self, offset: usize, title: &str, rows: &[Vec<(Colors, String)>]) {
+        let height = self.height().saturating_sub(1);
+        let width = self.width().saturating_sub(offset + 1);
+
+        for line in 0..height {
+            self.print(&Position{ line, offset }, Style::Default, Colors::Focused, "│");
+
+            let mut cells: Vec<(Style, Colors, String)> = if line == 0 {
+                title
+                    .graphemes(true)
+                    .map(|grapheme| (Style::Bold, Colors::Focused, grapheme.to_string()))
+                    .collect()
+            } else {
+                rows.get(line - 1)
+                    .into_iter()
+                    .flat_map(|row| row.iter())
+                    .flat_map(|&(colors, ref text)| {
+                        text.graphemes(true).map(move |grapheme| (Style::Default, colors, grapheme.to_string()))
+                    })
+                    .collect()
+            };
+            cells.truncate(width);
+
+            for column in 0..width {
+                let (style, colors, content) = cells
+                    .get(column)
+                    .cloned()
+                    .unwrap_or_else(|| (Style::Default, Colors::Default, String::from(" ")));
+                let colors = if line == 0 { Colors::Focused } else { colors };
+
+                self.print(&Position{ line, offset: offset + 1 + column }, style, colors, content);
+            }
+        }
+    }
+
     pub fn print_status_line(&mut self, entries: &[StatusLineData]) {
         let line = self.view.terminal.height() - 1;
 
+        // Pending keys are shown alongside the mode, which always comes first.
+        let mut contents: Vec<String> = entries.iter().map(|entry| entry.content.clone()).collect();
+        if let (Some(pending), Some(mode)) = (self.view.pending_input.as_ref(), contents.first_mut()) {
+            mode.push_str(&format!("{} ", pending));
+        }
+        if let (Some(timings), Some(mode)) = (self.view.last_frame_timings.as_ref(), contents.first_mut()) {
+            mode.push_str(&format!("{} ", timings));
+        }
+
         entries.iter().enumerate().fold(0, |offset, (index, element)| {
             let content = match entries.len() {
                 1 => {
                     // There's only one element; have it fill the line.
-                    element.content.pad_to_width(self.view.terminal.width())
+                    contents[index].pad_to_width(self.view.terminal.width())
                 },
                 2 => {
                     if index == entries.len() - 1 {
                         // Expand the last element to fill the remaining width.
-                        element.content.pad_to_width(self.view.terminal.width() - offset)
+                        contents[index].pad_to_width(self.view.terminal.width().saturating_sub(offset))
                     } else {
-                        element.content.clone()
+                        contents[index].clone()
                     }
                 },
                 _ => {
                     if index == entries.len() - 2 {
                         // Before-last element extends to fill unused space.
-                        element.content.pad_to_width(self.view.terminal.width() - offset - entries[index+1].content.len())
+                        contents[index].pad_to_width(
+                            self.view.terminal.width().saturating_sub(offset + contents[index+1].len())
+                        )
                     } else {
-                        element.content.clone()
+                        contents[index].clone()
                     }
                 }
             };
@@ -2,8 +2,10 @@
 pub enum Style {
     Default,
     Bold,
+    Dim,
     Inverted,
     Italic,
+    Underlined,
 }
 
 impl Default for Style {
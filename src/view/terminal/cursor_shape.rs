@@ -0,0 +1,25 @@
+use crate::errors::*;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CursorShape {
+    /// Whatever the terminal uses when left to its own devices.
+    Default,
+    Block,
+    Bar,
+    Underline,
+}
+
+impl FromStr for CursorShape {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<CursorShape> {
+        match name {
+            "default" => Ok(CursorShape::Default),
+            "block" => Ok(CursorShape::Block),
+            "bar" => Ok(CursorShape::Bar),
+            "underline" => Ok(CursorShape::Underline),
+            _ => bail!("Unknown cursor shape \"{}\" (expected block, bar, underline, or default)", name),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::models::application::Event;
+use scribe::buffer::Position;
+use std::thread;
+use std::time::Duration;
+use super::{CursorShape, Terminal};
+use crate::view::{Colors, Style};
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 24;
+
+/// A terminal that discards output and never receives input,
+/// for running commands without a UI (e.g. in batch mode).
+pub struct HeadlessTerminal;
+
+impl Terminal for HeadlessTerminal {
+    fn listen(&self) -> Option<Event> {
+        // Wait like a real terminal would, rather than
+        // having the event listener spin on nothing.
+        thread::sleep(Duration::from_millis(100));
+
+        None
+    }
+    fn clear(&self) { }
+    fn present(&self) { }
+    fn width(&self) -> usize { WIDTH }
+    fn height(&self) -> usize { HEIGHT }
+    fn set_cursor(&self, _: Option<Position>) { }
+    fn set_cursor_shape(&self, _: CursorShape) { }
+    fn set_input_timeout(&self, _: Duration) { }
+    fn print(&self, _: &Position, _: Style, _: Colors, _: &str) { }
+    fn restore(&self) { }
+    fn suspend(&self) { }
+}
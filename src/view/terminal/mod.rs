@@ -1,6 +1,8 @@
 mod buffer;
 mod buffer_iterator;
 mod cell;
+mod cursor_shape;
+mod headless_terminal;
 mod termion_terminal;
 
 #[cfg(any(test, feature = "bench"))]
@@ -11,10 +13,13 @@ use crate::models::application::Event;
 use scribe::buffer::Position;
 use crate::view::{Colors, Style};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub use self::buffer::TerminalBuffer;
 pub use self::buffer_iterator::TerminalBufferIterator;
 pub use self::cell::Cell;
+pub use self::cursor_shape::CursorShape;
+pub use self::headless_terminal::HeadlessTerminal;
 pub use self::termion_terminal::TermionTerminal;
 
 #[cfg(any(test, feature = "bench"))]
@@ -27,7 +32,10 @@ pub trait Terminal {
     fn width(&self) -> usize;
     fn height(&self) -> usize;
     fn set_cursor(&self, _: Option<Position>);
+    fn set_cursor_shape(&self, _: CursorShape);
+    fn set_input_timeout(&self, _: Duration);
     fn print<'a>(&self, _: &Position, _: Style, _: Colors, _: &str);
+    fn restore(&self);
     fn suspend(&self);
 }
 
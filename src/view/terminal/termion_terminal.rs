@@ -4,54 +4,73 @@ extern crate termion;
 use crate::errors::*;
 use mio::{Events, Poll, PollOpt, Ready, Token};
 use mio::unix::EventedFd;
-use super::Terminal;
+use super::{CursorShape, Terminal};
+use std::env;
 use std::io::Stdout;
 use std::os::unix::io::AsRawFd;
 use scribe::buffer::{Distance, Position};
 use self::termion::color::{Bg, Fg};
 use self::termion::{color, cursor};
-use self::termion::input::{Keys, TermRead};
 use self::termion::raw::{IntoRawMode, RawTerminal};
 use self::termion::style;
-use std::io::{BufWriter, Stdin, stdin, stdout, Write};
+use std::io::{BufWriter, stdin, stdout, Write};
 use std::fmt::Display;
 use std::ops::Drop;
-use std::sync::Mutex;
+use std::panic;
+use std::sync::{Mutex, Once};
 use std::time::Duration;
 use crate::view::{Colors, Style};
 use unicode_segmentation::UnicodeSegmentation;
 use signal_hook::iterator::Signals;
 
-use self::termion::event::Key as TermionKey;
-use crate::input::Key;
+use crate::input::KeyDecoder;
 use crate::models::application::Event;
 
 const STDIN_INPUT: Token = Token(0);
-const RESIZE: Token = Token(1);
+const SIGNALS: Token = Token(1);
+const INPUT_TIMEOUT_DEFAULT: u64 = 50;
+static PANIC_HOOK: Once = Once::new();
+
+// Bi-directional support mode (BDSM) sequences. Content is laid out cell by
+// cell, so terminals that reorder right-to-left text themselves are asked not
+// to while running, which would otherwise put it out of line with the cursor.
+const EXPLICIT_BIDI: &str = "\x1b[8l";
+const IMPLICIT_BIDI: &str = "\x1b[8h";
 
 pub struct TermionTerminal {
     event_listener: Poll,
     signals: Signals,
-    input: Mutex<Option<Keys<Stdin>>>,
+    input: Mutex<Option<KeyDecoder>>,
+    // How long to wait for the rest of an escape sequence
+    // before treating its escape byte as the escape key.
+    input_timeout: Mutex<Duration>,
     output: Mutex<Option<BufWriter<RawTerminal<Stdout>>>>,
     current_style: Mutex<Option<Style>>,
     current_colors: Mutex<Option<Colors>>,
     current_position: Mutex<Option<Position>>,
+    // Left unset until a shape is requested, so that
+    // the terminal's own is untouched unless configured.
+    current_cursor_shape: Mutex<Option<CursorShape>>,
+    cursor_shapes_supported: bool,
 }
 
 impl TermionTerminal {
     #[allow(dead_code)]
     pub fn new() -> Result<TermionTerminal> {
         let (event_listener, signals) = create_event_listener()?;
+        install_panic_hook();
 
         Ok(TermionTerminal {
             event_listener,
             signals,
-            input: Mutex::new(Some(stdin().keys())),
-            output: Mutex::new(Some(create_output_instance())),
+            input: Mutex::new(Some(KeyDecoder::new())),
+            input_timeout: Mutex::new(Duration::from_millis(INPUT_TIMEOUT_DEFAULT)),
+            output: Mutex::new(Some(create_output_instance()?)),
             current_style: Mutex::new(None),
             current_colors: Mutex::new(None),
             current_position: Mutex::new(None),
+            current_cursor_shape: Mutex::new(None),
+            cursor_shapes_supported: supports_cursor_shapes(),
         })
     }
 
@@ -130,45 +149,51 @@ impl TermionTerminal {
 
 impl Terminal for TermionTerminal {
     fn listen(&self) -> Option<Event> {
-        // Check for events on stdin.
+        // Keys decoded from an earlier read (e.g. a paste) go out first.
+        let waiting = {
+            let mut guard = self.input.lock().ok()?;
+            if let Some(ref mut decoder) = *guard {
+                if let Some(key) = decoder.next_key() {
+                    return Some(Event::Key(key));
+                }
+            }
+
+            guard.as_ref().map(|decoder| decoder.is_waiting()).unwrap_or(false)
+        };
+
+        // Check for events on stdin. Input that's waiting on the rest of an
+        // escape sequence is only given until the input timeout to arrive.
+        let timeout = if waiting {
+            self.input_timeout.lock().map(|timeout| *timeout).ok()?
+        } else {
+            Duration::from_millis(100)
+        };
         let mut events = Events::with_capacity(1);
-        self.event_listener.poll(&mut events, Some(Duration::from_millis(100))).ok()?;
+        self.event_listener.poll(&mut events, Some(timeout)).ok()?;
         if let Some(event) = events.iter().next() {
             match event.token() {
                 STDIN_INPUT => {
                     let mut guard = self.input.lock().ok()?;
-                    let input_handle = guard.as_mut()?;
-                    let input_data = input_handle.next()?;
-                    let key = input_data.ok()?;
-
-                    match key {
-                        TermionKey::Backspace => Some(Event::Key(Key::Backspace)),
-                        TermionKey::Left => Some(Event::Key(Key::Left)),
-                        TermionKey::Right => Some(Event::Key(Key::Right)),
-                        TermionKey::Up => Some(Event::Key(Key::Up)),
-                        TermionKey::Down => Some(Event::Key(Key::Down)),
-                        TermionKey::Home => Some(Event::Key(Key::Home)),
-                        TermionKey::End => Some(Event::Key(Key::End)),
-                        TermionKey::PageUp => Some(Event::Key(Key::PageUp)),
-                        TermionKey::PageDown => Some(Event::Key(Key::PageDown)),
-                        TermionKey::Delete => Some(Event::Key(Key::Delete)),
-                        TermionKey::Insert => Some(Event::Key(Key::Insert)),
-                        TermionKey::Esc => Some(Event::Key(Key::Esc)),
-                        TermionKey::Char('\n') => Some(Event::Key(Key::Enter)),
-                        TermionKey::Char('\t') => Some(Event::Key(Key::Tab)),
-                        TermionKey::Char(c) => Some(Event::Key(Key::Char(c))),
-                        TermionKey::Ctrl(c) => Some(Event::Key(Key::Ctrl(c))),
-                        _ => None,
-                    }
-                },
-                RESIZE => {
-                    // Consume the resize signal so it doesn't trigger again.
-                    self.signals.into_iter().next();
+                    let decoder = guard.as_mut()?;
+                    decoder.push(&read_input()?);
 
-                    Some(Event::Resize)
+                    decoder.next_key().map(Event::Key)
+                },
+                SIGNALS => {
+                    // Consume the signal so it doesn't trigger again.
+                    match self.signals.into_iter().next() {
+                        Some(libc::SIGTSTP) => Some(Event::Suspend),
+                        Some(libc::SIGTERM) | Some(libc::SIGHUP) => Some(Event::Terminate),
+                        // Redraw after being resized or resumed.
+                        _ => Some(Event::Resize),
+                    }
                 }
                 _ => None,
             }
+        } else if waiting {
+            let mut guard = self.input.lock().ok()?;
+
+            guard.as_mut()?.flush().map(Event::Key)
         } else {
             None
         }
@@ -258,7 +283,46 @@ impl Terminal for TermionTerminal {
         }
     }
 
-    fn suspend(&self) {
+    fn set_cursor_shape(&self, shape: CursorShape) {
+        if !self.cursor_shapes_supported {
+            return;
+        }
+
+        if let Ok(mut current_shape) = self.current_cursor_shape.lock() {
+            if *current_shape == Some(shape) || (current_shape.is_none() && shape == CursorShape::Default) {
+                return;
+            }
+
+            if let Ok(mut output) = self.output.lock() {
+                if let Some(ref mut output) = *output {
+                    let _ = write!(output, "{}", cursor_shape_sequence(shape));
+                    current_shape.replace(shape);
+                }
+            }
+        }
+    }
+
+    fn set_input_timeout(&self, timeout: Duration) {
+        if let Ok(mut input_timeout) = self.input_timeout.lock() {
+            *input_timeout = timeout;
+        }
+    }
+
+    fn restore(&self) {
+        // Put the terminal's own cursor shape back; the current shape is
+        // cleared so that it's re-applied after resuming from a suspension.
+        let changed_shape = self.current_cursor_shape
+            .lock()
+            .ok()
+            .and_then(|mut shape| shape.take());
+        if changed_shape.is_some() {
+            if let Ok(mut output) = self.output.lock() {
+                if let Some(ref mut output) = *output {
+                    let _ = write!(output, "{}", cursor_shape_sequence(CursorShape::Default));
+                }
+            }
+        }
+
         self.restore_cursor();
         self.set_cursor(Some(Position{ line: 0, offset: 0 }));
         self.present();
@@ -269,11 +333,19 @@ impl Terminal for TermionTerminal {
 
         // Terminal destructor cleans up for us.
         if let Ok(mut guard) = self.output.lock() {
+            if let Some(ref mut output) = *guard {
+                let _ = write!(output, "{}", IMPLICIT_BIDI);
+                let _ = output.flush();
+            }
             guard.take();
         }
         if let Ok(mut guard) = self.input.lock() {
             guard.take();
         }
+    }
+
+    fn suspend(&self) {
+        self.restore();
 
         unsafe {
             // Stop the amp process.
@@ -281,10 +353,10 @@ impl Terminal for TermionTerminal {
         }
 
         if let Ok(mut guard) = self.output.lock() {
-            guard.replace(create_output_instance());
+            *guard = create_output_instance().ok();
         }
         if let Ok(mut guard) = self.input.lock() {
-            guard.replace(stdin().keys());
+            guard.replace(KeyDecoder::new());
         }
     }
 }
@@ -303,6 +375,40 @@ fn cursor_position(position: &Position) -> cursor::Goto {
     )
 }
 
+// Cursor shapes are set using DECSCUSR, which the Linux console
+// and "dumb" terminals don't understand (they'd print it as-is).
+fn supports_cursor_shapes() -> bool {
+    match env::var("TERM") {
+        Ok(term) => !(term.is_empty() || term == "dumb" || term.starts_with("linux")),
+        Err(_) => false,
+    }
+}
+
+// The steady (non-blinking) variants are used; the
+// default shape is whatever the user has configured.
+fn cursor_shape_sequence(shape: CursorShape) -> &'static str {
+    match shape {
+        CursorShape::Default => "\x1b[0 q",
+        CursorShape::Block => "\x1b[2 q",
+        CursorShape::Underline => "\x1b[4 q",
+        CursorShape::Bar => "\x1b[6 q",
+    }
+}
+
+// Reads whatever input is available. This goes straight to the file
+// descriptor, since input held in a buffer wouldn't wake up the event listener.
+fn read_input() -> Option<Vec<u8>> {
+    let mut data = [0; 1024];
+    let length = unsafe {
+        libc::read(stdin().as_raw_fd(), data.as_mut_ptr() as *mut libc::c_void, data.len())
+    };
+    if length <= 0 {
+        return None;
+    }
+
+    Some(data[..length as usize].to_vec())
+}
+
 fn terminal_size() -> (usize, usize) {
     termion::terminal_size()
         .map(|(x,y)| (x as usize, y as usize))
@@ -310,8 +416,13 @@ fn terminal_size() -> (usize, usize) {
 }
 
 fn create_event_listener() -> Result<(Poll, Signals)> {
-    let signals = Signals::new(&[signal_hook::SIGWINCH])
-        .chain_err(|| "Failed to initialize event listener signal")?;
+    let signals = Signals::new(&[
+        signal_hook::SIGWINCH,
+        libc::SIGTSTP,
+        libc::SIGCONT,
+        libc::SIGTERM,
+        libc::SIGHUP,
+    ]).chain_err(|| "Failed to initialize event listener signals")?;
     let event_listener = Poll::new().chain_err(|| "Failed to establish polling")?;
     event_listener.register(
         &EventedFd(&stdin().as_raw_fd()),
@@ -321,24 +432,59 @@ fn create_event_listener() -> Result<(Poll, Signals)> {
     ).chain_err(|| "Failed to register stdin to event listener")?;
     event_listener.register(
         &signals,
-        RESIZE,
+        SIGNALS,
         Ready::readable(),
         PollOpt::level()
-    ).chain_err(|| "Failed to register resize signal to event listener")?;
+    ).chain_err(|| "Failed to register signals to event listener")?;
 
     Ok((event_listener, signals))
 }
 
-fn create_output_instance() -> BufWriter<RawTerminal<Stdout>> {
+/// Panics leave the terminal in raw mode with the cursor hidden until the
+/// output instance is dropped, which is too late for the panic message to be
+/// legible. Capture the terminal's original settings, and put them back (along
+/// with the cursor and default styles) before the panic is reported.
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let fd = stdout().as_raw_fd();
+        let mut original_settings: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(fd, &mut original_settings) } != 0 {
+            return;
+        }
+
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            unsafe { libc::tcsetattr(fd, libc::TCSANOW, &original_settings); }
+            print!("{}{}{}{}{}", style::Reset, termion::clear::All, cursor::Goto(1, 1), cursor::Show, IMPLICIT_BIDI);
+            if supports_cursor_shapes() {
+                print!("{}", cursor_shape_sequence(CursorShape::Default));
+            }
+            let _ = stdout().flush();
+
+            default_hook(info);
+        }));
+    });
+}
+
+fn create_output_instance() -> Result<BufWriter<RawTerminal<Stdout>>> {
+    let terminal = stdout()
+        .into_raw_mode()
+        .chain_err(|| "Failed to switch the terminal to raw mode")?;
+
     // Use a 1MB buffered writer for stdout.
-    BufWriter::with_capacity(1_048_576, stdout().into_raw_mode().unwrap())
+    let mut output = BufWriter::with_capacity(1_048_576, terminal);
+    let _ = write!(output, "{}", EXPLICIT_BIDI);
+
+    Ok(output)
 }
 
 fn map_style(style: Style) -> Option<Box<Display>> {
     match style {
         Style::Default => None,
         Style::Bold => Some(Box::new(style::Bold)),
+        Style::Dim => Some(Box::new(style::Faint)),
         Style::Inverted => Some(Box::new(style::Invert)),
         Style::Italic => Some(Box::new(style::Italic)),
+        Style::Underlined => Some(Box::new(style::Underline)),
     }
 }
@@ -2,7 +2,8 @@ use crate::input::Key;
 use crate::models::application::Event;
 use scribe::buffer::Position;
 use std::sync::Mutex;
-use super::Terminal;
+use std::time::Duration;
+use super::{CursorShape, Terminal};
 use crate::view::{Colors, Style};
 
 const WIDTH: usize = 10;
@@ -86,6 +87,9 @@ impl Terminal for TestTerminal {
         let mut cursor = self.cursor.lock().unwrap();
         *cursor = position;
     }
+    fn set_cursor_shape(&self, _: CursorShape) { }
+    fn set_input_timeout(&self, _: Duration) { }
+    fn restore(&self) { }
     fn suspend(&self) { }
     fn print(&self, position: &Position, _: Style, colors: Colors, content: &str) {
         // Ignore lines beyond visible height.
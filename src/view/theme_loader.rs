@@ -1,54 +1,91 @@
 use crate::errors::*;
+use crate::models::application::Event;
 use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::fs::File;
 use std::io::{BufReader, Cursor, Read, Seek};
 use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread::{self, JoinHandle};
 use syntect::highlighting::{Theme, ThemeSet};
 
 pub struct ThemeLoader {
     path: PathBuf,
-    themes: BTreeMap<String, Theme>
+    themes: BTreeMap<String, Theme>,
+    errors: Vec<Error>
+}
+
+/// The themes that were loaded, along with problems loading user themes,
+/// which are reported rather than preventing the rest from being used.
+pub struct LoadedThemes {
+    pub theme_set: ThemeSet,
+    pub errors: Vec<Error>
 }
 
 impl ThemeLoader {
     pub fn new(path: PathBuf) -> ThemeLoader {
         ThemeLoader{
             path,
-            themes: BTreeMap::new()
+            themes: BTreeMap::new(),
+            errors: Vec::new()
         }
 
     }
 
     /// Consumes the ThemeLoader to produce a ThemeSet.
-    pub fn load(mut self) -> Result<ThemeSet> {
+    pub fn load(mut self) -> Result<LoadedThemes> {
         self.load_defaults()?;
-        self.load_user()?;
+        self.load_user();
+
+        Ok(LoadedThemes {
+            theme_set: ThemeSet { themes: self.themes },
+            errors: self.errors
+        })
+    }
+
+    /// Loads themes on a background thread, so that parsing them can overlap
+    /// with the rest of the application's startup, sending an event when done.
+    pub fn spawn(self, event_channel: Sender<Event>) -> JoinHandle<Result<LoadedThemes>> {
+        thread::spawn(move || {
+            let loaded_themes = self.load();
+            let _ = event_channel.send(Event::ThemesLoaded);
 
-        Ok(ThemeSet { themes: self.themes })
+            loaded_themes
+        })
     }
 
-    fn load_user(&mut self) -> Result<()> {
-        let theme_dir_entries =
-            self.path.read_dir().chain_err(|| "Failed to read themes directory")?;
+    // User themes that can't be read or parsed are skipped, with
+    // the problem recorded so that it can be reported once loaded.
+    fn load_user(&mut self) {
+        let theme_dir_entries = match self.path.read_dir() {
+            Ok(entries) => entries,
+            Err(error) => {
+                self.errors.push(Error::with_chain(error, "Failed to read themes directory"));
+                return;
+            }
+        };
 
-        let theme_paths = theme_dir_entries
+        let mut theme_paths: Vec<PathBuf> = theme_dir_entries
             .filter_map(|dir| dir.ok())
             .map(|theme| theme.path())
             .filter(|path| path.is_file())
-            .filter(|path| path.extension() == Some(OsStr::new("tmTheme")));
+            .filter(|path| path.extension() == Some(OsStr::new("tmTheme")))
+            .collect();
+        theme_paths.sort();
 
         for theme_path in theme_paths {
-            if let Ok(theme) = File::open(&theme_path) {
-                if let Some(file_stem) = theme_path.file_stem() {
-                    if let Some(theme_name) = file_stem.to_str() {
-                        self.insert_theme(theme_name, theme)?
-                    }
-                }
+            let theme_name = match theme_path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            let result = File::open(&theme_path)
+                .chain_err(|| format!("Couldn't open {} theme", theme_name))
+                .and_then(|theme| self.insert_theme(&theme_name, theme));
+
+            if let Err(error) = result {
+                self.errors.push(error);
             }
         }
-
-        Ok(())
     }
 
     fn load_defaults(&mut self) -> Result<()> {
@@ -70,12 +107,56 @@ impl ThemeLoader {
 
     fn insert_theme<D: Read + Seek>(&mut self, theme_name: &str, theme_data: D) -> Result<()> {
         let mut reader = BufReader::new(theme_data);
-        if let Ok(theme_set) = ThemeSet::load_from_reader(&mut reader) {
-            self.themes.insert(String::from(theme_name), theme_set);
-        } else {
-            bail!("Failed to load {} theme", theme_name);
+        match ThemeSet::load_from_reader(&mut reader) {
+            Ok(theme_set) => {
+                self.themes.insert(String::from(theme_name), theme_set);
+            },
+            Err(error) => bail!("Failed to load {} theme: {:?}", theme_name, error),
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ThemeLoader;
+    use crate::models::application::Event;
+    use std::env;
+    use std::fs;
+    use std::sync::mpsc;
+
+    #[test]
+    fn load_skips_and_reports_user_themes_that_fail_to_parse() {
+        let path = env::temp_dir().join(format!("amp_theme_loader_{}", std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("broken.tmTheme"), "not a theme").unwrap();
+        fs::write(
+            path.join("custom.tmTheme"),
+            include_str!("../themes/solarized_light.tmTheme")
+        ).unwrap();
+
+        let loaded = ThemeLoader::new(path.clone()).load().unwrap();
+        fs::remove_dir_all(&path).unwrap();
+
+        assert!(loaded.theme_set.themes.contains_key("custom"));
+        assert!(loaded.theme_set.themes.contains_key("solarized_dark"));
+        assert!(!loaded.theme_set.themes.contains_key("broken"));
+        assert_eq!(loaded.errors.len(), 1);
+        assert!(loaded.errors[0].to_string().starts_with("Failed to load broken theme"));
+    }
+
+    #[test]
+    fn spawn_sends_an_event_once_themes_have_loaded() {
+        let path = env::temp_dir().join(format!("amp_theme_spawn_{}", std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        let (tx, rx) = mpsc::channel();
+
+        let loader = ThemeLoader::new(path.clone()).spawn(tx);
+        assert_eq!(rx.recv().unwrap(), Event::ThemesLoaded);
+        let loaded = loader.join().unwrap().unwrap();
+        fs::remove_dir_all(&path).unwrap();
+
+        assert!(loaded.theme_set.themes.contains_key("solarized_dark"));
+    }
+}